@@ -1,11 +1,12 @@
 use cosmwasm_std::{entry_point, to_binary, Addr, Binary, Deps, DepsMut, Env, MessageInfo, Response, StdResult};
-use cw2::set_contract_version;
+use cw2::{get_contract_version, set_contract_version};
 use cw20::{Cw20ExecuteMsg, Cw20QueryMsg};
 use cosmwasm_std::Uint128;
 use cw20_base::{contract as cw20_base, msg::InstantiateMsg as Cw20InstantiateMsg};
 use cw20_base::state::TokenInfo;
 use cw20_base::state::TOKEN_INFO;
 use cw_storage_plus::Item;
+use semver::Version;
 
 const CONTRACT_NAME: &str = "aln-csp";
 const CONTRACT_VERSION: &str = "0.1.0";
@@ -110,6 +111,24 @@ pub fn execute(
     }
 }
 
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug, PartialEq)]
+pub struct MigrateMsg {}
+
+#[entry_point]
+pub fn migrate(deps: DepsMut, _env: Env, _msg: MigrateMsg) -> Result<Response, cw20_base::ContractError> {
+    let stored = get_contract_version(deps.storage)?;
+    if stored.contract != CONTRACT_NAME {
+        return Err(cw20_base::ContractError::Std(cosmwasm_std::StdError::generic_err(format!("cannot migrate from contract {}", stored.contract))));
+    }
+    let prev_v: Version = stored.version.parse().map_err(|_| cosmwasm_std::StdError::generic_err("invalid stored contract version"))?;
+    let new_v: Version = CONTRACT_VERSION.parse().map_err(|_| cosmwasm_std::StdError::generic_err("invalid contract version"))?;
+    if prev_v > new_v {
+        return Err(cw20_base::ContractError::Std(cosmwasm_std::StdError::generic_err(format!("cannot migrate from newer version {}", stored.version))));
+    }
+    set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+    Ok(Response::new().add_attribute("action", "migrate").add_attribute("from_version", stored.version).add_attribute("to_version", CONTRACT_VERSION))
+}
+
 #[entry_point]
 pub fn query(deps: Deps, env: Env, msg: Cw20QueryMsg) -> StdResult<Binary> {
     cw20_base::query(deps, env, msg)
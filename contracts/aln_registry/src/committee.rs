@@ -0,0 +1,215 @@
+use cosmwasm_std::{Addr, Deps, DepsMut, Env, MessageInfo, Order, Response, StdResult, Storage};
+use cw_storage_plus::{Bound, Item, Map, PrimaryKey};
+use serde::{Deserialize, Serialize};
+
+use crate::epochs::{approve_epoch, unapproved_epoch};
+use crate::lifecycle::{approve, at_height, lifecycle_response, load_for, save_version};
+use crate::{assets, AssetState, ContractError, RegisteredAsset, GOVERNANCE};
+
+const DEFAULT_LIMIT: u32 = 10;
+const MAX_LIMIT: u32 = 30;
+/// Attestations are kept per asset epoch in one entry, so the committee stays small.
+pub const MAX_COMMITTEE_MEMBERS: usize = 20;
+
+/// Approvers of sanitization: once configured, `ApproveSanitized` takes effect only when
+/// `threshold` members attest the same `ubs_report_hash`.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct SanitizationCommittee {
+    pub members: Vec<Addr>,
+    pub threshold: u32,
+}
+
+/// One member's `ApproveSanitized` of an asset epoch still waiting for the threshold.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Attestation {
+    pub approver: Addr,
+    pub ubs_report_hash: String,
+    pub height: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct PendingAttestationsResponse {
+    pub id: String,
+    /// Snapshot epoch the round approves; 0 is the asset itself.
+    pub epoch: u32,
+    /// The asset's at the current height for epoch 0, otherwise the epoch's (`proposed`,
+    /// `approved` or `disputed`); `disputed` once members attested different hashes.
+    pub state: AssetState,
+    /// Of the current committee; `None` without one.
+    pub threshold: Option<u32>,
+    pub attestations: Vec<Attestation>,
+}
+
+pub const SANITIZATION_COMMITTEE: Item<SanitizationCommittee> = Item::new("reg_sanitization_committee");
+/// (asset id, snapshot epoch) -> attestations of the epoch's open approval round, in order.
+pub const ATTESTATIONS: Map<(&str, u32), Vec<Attestation>> = Map::new("reg_attestations");
+
+/// Drop the open approval round of epoch `epoch` of `id`, e.g. when its root changes.
+pub(crate) fn clear_attestations(storage: &mut dyn Storage, id: &str, epoch: u32) {
+    ATTESTATIONS.remove(storage, (id, epoch));
+}
+
+/// Drop the open approval rounds of every epoch of `a`.
+pub(crate) fn clear_all_attestations(storage: &mut dyn Storage, a: &RegisteredAsset) {
+    for e in a.snapshot_epochs() {
+        clear_attestations(storage, a.id.as_str(), e.epoch);
+    }
+}
+
+/// `id` for `action` on the approval round of `epoch`: epoch 0 follows the asset's state, later
+/// epochs need a live asset and an epoch neither approved nor disputed.
+fn load_round(storage: &dyn Storage, env: &Env, id: &str, epoch: u32, action: &str) -> Result<RegisteredAsset, ContractError> {
+    if epoch == 0 {
+        let refused = [AssetState::Approved, AssetState::Active, AssetState::Deprecated, AssetState::Revoked, AssetState::Disputed];
+        return load_for(storage, env, id, action, &refused);
+    }
+    let a = load_for(storage, env, id, action, &[AssetState::Deprecated, AssetState::Revoked])?;
+    unapproved_epoch(&a, epoch)?;
+    Ok(a)
+}
+
+/// Close the round of `epoch` of `a` as approved by `approvers`.
+fn approve_round(storage: &mut dyn Storage, env: &Env, a: &mut RegisteredAsset, epoch: u32, ubs_report_hash: String, approvers: Vec<Addr>) -> StdResult<()> {
+    clear_attestations(storage, a.id.as_str(), epoch);
+    if epoch == 0 { approve(storage, env, a, ubs_report_hash, approvers) } else { approve_epoch(storage, env, a, epoch, ubs_report_hash, approvers) }
+}
+
+/// Governance-only: (re)configure the committee. Attestations of members no longer on it stop
+/// counting; a lower threshold applies from the next attestation on, or `FinalizeAttestations`
+/// for rounds that already meet it.
+pub fn set_committee(deps: DepsMut, info: MessageInfo, members: Vec<String>, threshold: u32) -> Result<Response, ContractError> {
+    let gov = GOVERNANCE.load(deps.storage)?;
+    if info.sender != gov { return Err(ContractError::Unauthorized { action: "set the sanitization committee".to_string() }); }
+    let mut addrs: Vec<Addr> = vec![];
+    for m in members {
+        let a = deps.api.addr_validate(&m)?;
+        if addrs.contains(&a) { return Err(ContractError::InvalidCommittee(format!("{} is listed twice", a))); }
+        addrs.push(a);
+    }
+    if addrs.is_empty() || addrs.len() > MAX_COMMITTEE_MEMBERS {
+        return Err(ContractError::InvalidCommittee(format!("needs 1 to {} members", MAX_COMMITTEE_MEMBERS)));
+    }
+    if threshold == 0 || threshold as usize > addrs.len() {
+        return Err(ContractError::InvalidCommittee(format!("threshold must be between 1 and {}", addrs.len())));
+    }
+    let committee = SanitizationCommittee { members: addrs, threshold };
+    SANITIZATION_COMMITTEE.save(deps.storage, &committee)?;
+    Ok(Response::new()
+        .add_attribute("action", "set_sanitization_committee")
+        .add_attribute("members", committee.members.len().to_string())
+        .add_attribute("threshold", threshold.to_string()))
+}
+
+/// Governance-only: go back to single governance approvals.
+pub fn remove_committee(deps: DepsMut, info: MessageInfo) -> Result<Response, ContractError> {
+    let gov = GOVERNANCE.load(deps.storage)?;
+    if info.sender != gov { return Err(ContractError::Unauthorized { action: "remove the sanitization committee".to_string() }); }
+    SANITIZATION_COMMITTEE.remove(deps.storage);
+    Ok(Response::new().add_attribute("action", "remove_sanitization_committee"))
+}
+
+/// `ApproveSanitized` under a committee: record the sender's attestation of epoch `epoch` of a
+/// proposed asset. A hash differing from an earlier attestation puts the asset (or the epoch) in
+/// dispute; `threshold` matching attestations approve it.
+pub(crate) fn attest(deps: DepsMut, env: Env, info: MessageInfo, committee: SanitizationCommittee, id: String, epoch: u32, ubs_report_hash: String) -> Result<Response, ContractError> {
+    if !committee.members.contains(&info.sender) { return Err(ContractError::NotCommitteeMember { address: info.sender.to_string() }); }
+    let mut a = load_round(deps.storage, &env, &id, epoch, "attest sanitization")?;
+    let mut pending = ATTESTATIONS.may_load(deps.storage, (id.as_str(), epoch))?.unwrap_or_default();
+    pending.retain(|p| committee.members.contains(&p.approver));
+    if pending.iter().any(|p| p.approver == info.sender) { return Err(ContractError::AlreadyAttested { id, approver: info.sender.to_string() }); }
+    pending.push(Attestation { approver: info.sender.clone(), ubs_report_hash: ubs_report_hash.clone(), height: env.block.height });
+
+    if pending.iter().any(|p| p.ubs_report_hash != ubs_report_hash) {
+        // kept for the record until governance resolves the dispute
+        ATTESTATIONS.save(deps.storage, (id.as_str(), epoch), &pending)?;
+        match a.epochs.iter_mut().find(|e| e.epoch == epoch) {
+            Some(e) => e.disputed = true,
+            None => a.lifecycle.state = AssetState::Disputed,
+        }
+        save_version(deps.storage, &env, &mut a)?;
+        return Ok(lifecycle_response("sanitization_disputed", &a, &env).add_attribute("epoch", epoch.to_string()).add_attribute("approver", info.sender));
+    }
+    if pending.len() as u32 >= committee.threshold {
+        let approvers: Vec<Addr> = pending.into_iter().map(|p| p.approver).collect();
+        let names = approvers.iter().map(|a| a.as_str()).collect::<Vec<_>>().join(",");
+        approve_round(deps.storage, &env, &mut a, epoch, ubs_report_hash, approvers)?;
+        return Ok(lifecycle_response("approve_sanitized", &a, &env).add_attribute("epoch", epoch.to_string()).add_attribute("approvers", names));
+    }
+    ATTESTATIONS.save(deps.storage, (id.as_str(), epoch), &pending)?;
+    Ok(Response::new()
+        .add_attribute("action", "attest_sanitized")
+        .add_attribute("id", id)
+        .add_attribute("epoch", epoch.to_string())
+        .add_attribute("approver", info.sender)
+        .add_attribute("attestations", pending.len().to_string())
+        .add_attribute("threshold", committee.threshold.to_string()))
+}
+
+/// Approve epoch `epoch` of proposed asset `id` once the attestations of current members meet
+/// the current threshold. Needed when the committee changed after the last attestation, since
+/// only a new attestation re-checks the round otherwise.
+pub fn finalize_attestations(deps: DepsMut, env: Env, id: String, epoch: u32) -> Result<Response, ContractError> {
+    let committee = SANITIZATION_COMMITTEE.may_load(deps.storage)?.ok_or(ContractError::NoCommittee)?;
+    let mut a = load_round(deps.storage, &env, &id, epoch, "finalize attestations")?;
+    let mut pending = ATTESTATIONS.may_load(deps.storage, (id.as_str(), epoch))?.unwrap_or_default();
+    pending.retain(|p| committee.members.contains(&p.approver));
+    if (pending.len() as u32) < committee.threshold {
+        return Err(ContractError::AttestationThresholdNotMet { id, epoch, attestations: pending.len() as u32, threshold: committee.threshold });
+    }
+    // a round with differing hashes is disputed, so every attestation carries the same one
+    let ubs_report_hash = pending[0].ubs_report_hash.clone();
+    let approvers: Vec<Addr> = pending.into_iter().map(|p| p.approver).collect();
+    let names = approvers.iter().map(|a| a.as_str()).collect::<Vec<_>>().join(",");
+    approve_round(deps.storage, &env, &mut a, epoch, ubs_report_hash, approvers)?;
+    Ok(lifecycle_response("finalize_attestations", &a, &env).add_attribute("epoch", epoch.to_string()).add_attribute("approvers", names))
+}
+
+/// Governance-only: close the disputed round of epoch `epoch` of `id`; the asset (or the epoch)
+/// goes back to `Proposed` and the committee attests again.
+pub fn resolve_dispute(deps: DepsMut, env: Env, info: MessageInfo, id: String, epoch: u32) -> Result<Response, ContractError> {
+    let gov = GOVERNANCE.load(deps.storage)?;
+    if info.sender != gov { return Err(ContractError::Unauthorized { action: "resolve sanitization disputes".to_string() }); }
+    let mut a = assets().load(deps.storage, id.clone())?;
+    if epoch == 0 && a.lifecycle.state != AssetState::Disputed {
+        let state = at_height(a, env.block.height).lifecycle.state;
+        return Err(ContractError::InvalidAssetTransition { id, state, action: "resolve dispute".to_string() });
+    }
+    if epoch > 0 && !a.snapshot_epoch(epoch).is_some_and(|e| e.disputed) {
+        return Err(ContractError::InvalidSnapshotEpoch(format!("epoch {} of asset {} is not disputed", epoch, id)));
+    }
+    clear_attestations(deps.storage, id.as_str(), epoch);
+    match a.epochs.iter_mut().find(|e| e.epoch == epoch) {
+        Some(e) => e.disputed = false,
+        None => a.lifecycle.state = AssetState::Proposed,
+    }
+    save_version(deps.storage, &env, &mut a)?;
+    Ok(lifecycle_response("resolve_sanitization_dispute", &a, &env).add_attribute("epoch", epoch.to_string()))
+}
+
+fn pending_response(storage: &dyn Storage, env: &Env, id: String, epoch: u32, attestations: Vec<Attestation>) -> StdResult<PendingAttestationsResponse> {
+    let asset = assets().may_load(storage, id.clone())?.map(|a| at_height(a, env.block.height));
+    let state = match asset.as_ref().and_then(|a| a.epochs.iter().find(|e| e.epoch == epoch)) {
+        Some(e) if e.disputed => AssetState::Disputed,
+        Some(e) if e.sanitized_approved => AssetState::Approved,
+        Some(_) => AssetState::Proposed,
+        None => asset.map_or(AssetState::default(), |a| a.lifecycle.state),
+    };
+    let threshold = SANITIZATION_COMMITTEE.may_load(storage)?.map(|c| c.threshold);
+    Ok(PendingAttestationsResponse { id, epoch, state, threshold, attestations })
+}
+
+pub fn query_pending_attestations(deps: Deps, env: Env, id: String, epoch: u32) -> StdResult<PendingAttestationsResponse> {
+    let attestations = ATTESTATIONS.may_load(deps.storage, (id.as_str(), epoch))?.unwrap_or_default();
+    pending_response(deps.storage, &env, id, epoch, attestations)
+}
+
+/// Open (or disputed) approval rounds by (asset id, epoch) after `start_after`.
+pub fn query_assets_pending_approval(deps: Deps, env: Env, start_after: Option<(String, u32)>, limit: Option<u32>) -> StdResult<Vec<PendingAttestationsResponse>> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.as_ref().map(|(id, epoch)| Bound::exclusive((id.as_str(), *epoch).joined_key()));
+    ATTESTATIONS
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| item.and_then(|((id, epoch), attestations)| pending_response(deps.storage, &env, id, epoch, attestations)))
+        .collect()
+}
@@ -0,0 +1,113 @@
+use cosmwasm_std::{Addr, Deps, DepsMut, Env, MessageInfo, Response, StdResult, Storage, Uint128};
+use serde::{Deserialize, Serialize};
+
+use crate::lifecycle::{at_height, save_version};
+use crate::{assets, AssetState, ContractError, RegisteredAsset, GOVERNANCE};
+
+/// The asset is loaded on every bridge claim, so its epoch list stays short.
+pub const MAX_SNAPSHOT_EPOCHS: usize = 32;
+
+/// One snapshot wave of an asset, claimable on its own: a leaf can be claimed once per epoch.
+/// Epoch 0 is the snapshot the asset was registered with.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Default)]
+pub struct SnapshotEpoch {
+    pub epoch: u32,
+    pub merkle_root: String,
+    pub snapshot_height: u64,
+    pub activation_height: u64,
+    pub claim_deadline: Option<u64>,
+    /// Sum of the snapshot balances under `merkle_root`, in source units.
+    pub total_supply: Option<Uint128>,
+    /// Sanitization of this epoch's snapshot, approved on its own with `ApproveSanitized` for the
+    /// epoch; the bridge refuses claims of an unapproved epoch. Epoch 0 mirrors the asset.
+    /// Ignored on `AddSnapshotEpoch`.
+    #[serde(default)]
+    pub sanitized_approved: bool,
+    #[serde(default)]
+    pub ubs_report_hash: Option<String>,
+    #[serde(default)]
+    pub approved_by: Vec<Addr>,
+    /// Committee members attested different hashes for this epoch.
+    #[serde(default)]
+    pub disputed: bool,
+}
+
+impl RegisteredAsset {
+    /// Every epoch in order, starting with the registration snapshot.
+    pub fn snapshot_epochs(&self) -> Vec<SnapshotEpoch> {
+        let first = SnapshotEpoch {
+            epoch: 0,
+            merkle_root: self.merkle_root.clone(),
+            snapshot_height: self.snapshot_height,
+            activation_height: self.activation_height,
+            claim_deadline: self.claim_deadline,
+            total_supply: self.total_supply,
+            sanitized_approved: self.sanitized_approved,
+            ubs_report_hash: self.ubs_report_hash.clone(),
+            approved_by: self.lifecycle.approved_by.clone(),
+            disputed: self.lifecycle.state == AssetState::Disputed,
+        };
+        std::iter::once(first).chain(self.epochs.iter().cloned()).collect()
+    }
+
+    pub fn snapshot_epoch(&self, epoch: u32) -> Option<SnapshotEpoch> {
+        self.snapshot_epochs().into_iter().find(|e| e.epoch == epoch)
+    }
+}
+
+/// Governance-only: append `epoch` to `id`. It must be numbered as the next epoch, its snapshot
+/// must follow the previous one and its root must be new, so no leaf set is claimable twice
+/// through a repeated root. The epoch starts unapproved, whatever the asset's state.
+pub fn add_snapshot_epoch(deps: DepsMut, env: Env, info: MessageInfo, id: String, mut epoch: SnapshotEpoch) -> Result<Response, ContractError> {
+    let gov = GOVERNANCE.load(deps.storage)?;
+    if info.sender != gov { return Err(ContractError::Unauthorized { action: "add snapshot epochs".to_string() }); }
+    let mut a = assets().load(deps.storage, id.clone())?;
+    let state = at_height(a.clone(), env.block.height).lifecycle.state;
+    if matches!(state, AssetState::Deprecated | AssetState::Revoked) {
+        return Err(ContractError::InvalidAssetTransition { id, state, action: "add snapshot epoch".to_string() });
+    }
+    let existing = a.snapshot_epochs();
+    if existing.len() >= MAX_SNAPSHOT_EPOCHS { return Err(ContractError::InvalidSnapshotEpoch(format!("at most {} epochs per asset", MAX_SNAPSHOT_EPOCHS))); }
+    if epoch.epoch as usize != existing.len() { return Err(ContractError::InvalidSnapshotEpoch(format!("next epoch is {}", existing.len()))); }
+    if epoch.merkle_root.is_empty() { return Err(ContractError::InvalidSnapshotEpoch("merkle_root must not be empty".to_string())); }
+    if existing.iter().any(|e| e.merkle_root.trim_start_matches("0x").eq_ignore_ascii_case(epoch.merkle_root.trim_start_matches("0x"))) {
+        return Err(ContractError::InvalidSnapshotEpoch("merkle_root is already used by an epoch of this asset".to_string()));
+    }
+    let last = existing.last().map_or(0, |e| e.snapshot_height);
+    if epoch.snapshot_height <= last { return Err(ContractError::InvalidSnapshotEpoch(format!("snapshot_height must be after {}", last))); }
+    if epoch.claim_deadline.is_some_and(|d| d <= epoch.activation_height) { return Err(ContractError::InvalidClaimDeadline); }
+    epoch.sanitized_approved = false;
+    epoch.ubs_report_hash = None;
+    epoch.approved_by = vec![];
+    epoch.disputed = false;
+    a.epochs.push(epoch.clone());
+    save_version(deps.storage, &env, &mut a)?;
+    Ok(Response::new()
+        .add_attribute("action", "add_snapshot_epoch")
+        .add_attribute("id", a.id)
+        .add_attribute("epoch", epoch.epoch.to_string())
+        .add_attribute("merkle_root", epoch.merkle_root)
+        .add_attribute("version", a.lifecycle.version.to_string()))
+}
+
+/// Epoch `epoch` (1 or later) of `a`, still open for approval.
+pub(crate) fn unapproved_epoch(a: &RegisteredAsset, epoch: u32) -> Result<&SnapshotEpoch, ContractError> {
+    let e = a.epochs.iter().find(|e| e.epoch == epoch).ok_or_else(|| ContractError::InvalidSnapshotEpoch(format!("asset {} has no epoch {}", a.id, epoch)))?;
+    if e.sanitized_approved { return Err(ContractError::InvalidSnapshotEpoch(format!("epoch {} is already approved", epoch))); }
+    if e.disputed { return Err(ContractError::InvalidSnapshotEpoch(format!("epoch {} is disputed", epoch))); }
+    Ok(e)
+}
+
+/// Record the sanitization of epoch `epoch` (1 or later) of `a` as approved by `approvers`.
+pub(crate) fn approve_epoch(storage: &mut dyn Storage, env: &Env, a: &mut RegisteredAsset, epoch: u32, ubs_report_hash: String, approvers: Vec<Addr>) -> StdResult<()> {
+    if let Some(e) = a.epochs.iter_mut().find(|e| e.epoch == epoch) {
+        e.sanitized_approved = true;
+        e.ubs_report_hash = Some(ubs_report_hash);
+        e.approved_by = approvers;
+    }
+    save_version(storage, env, a)
+}
+
+pub fn query_snapshot_epochs(deps: Deps, id: String) -> StdResult<Vec<SnapshotEpoch>> {
+    Ok(assets().may_load(deps.storage, id)?.map(|a| a.snapshot_epochs()).unwrap_or_default())
+}
@@ -0,0 +1,80 @@
+use cosmwasm_std::StdError;
+use thiserror::Error;
+
+#[derive(Error, Debug, PartialEq)]
+pub enum ContractError {
+    #[error("{0}")]
+    Std(#[from] StdError),
+
+    #[error("only governance can {action}")]
+    Unauthorized { action: String },
+
+    #[error("ubs_report_hash is required for asset registration")]
+    UbsReportRequired,
+
+    #[error("asset {id} is already registered")]
+    AssetExists { id: String },
+
+    #[error("asset {id} is {state}, cannot {action}")]
+    InvalidAssetTransition { id: String, state: crate::AssetState, action: String },
+
+    #[error("asset {id} is active, {field} can no longer change")]
+    AssetActivated { id: String, field: String },
+
+    #[error("unknown scaling profile: {id}")]
+    UnknownScalingProfile { id: String },
+
+    #[error("scaling profile {id} is used by asset {asset_id}")]
+    ScalingProfileInUse { id: String, asset_id: String },
+
+    #[error("invalid scaling profile: {0}")]
+    InvalidScalingProfile(String),
+
+    #[error("a revocation needs a reason")]
+    RevocationReasonRequired,
+
+    #[error("claim_deadline must be after activation_height")]
+    InvalidClaimDeadline,
+
+    #[error("invalid snapshot epoch: {0}")]
+    InvalidSnapshotEpoch(String),
+
+    #[error("invalid sanitization committee: {0}")]
+    InvalidCommittee(String),
+
+    #[error("{address} is not on the sanitization committee")]
+    NotCommitteeMember { address: String },
+
+    #[error("{approver} already attested asset {id}")]
+    AlreadyAttested { id: String, approver: String },
+
+    #[error("sanitization is approved by the committee, register the asset unapproved")]
+    CommitteeApprovalRequired,
+
+    #[error("no sanitization committee is configured")]
+    NoCommittee,
+
+    #[error("epoch {epoch} of asset {id} has {attestations} of {threshold} attestations")]
+    AttestationThresholdNotMet { id: String, epoch: u32, attestations: u32, threshold: u32 },
+
+    #[error("no pending governance proposal")]
+    NoPendingGovernance,
+
+    #[error("only the pending governance can accept")]
+    NotPendingGovernance,
+
+    #[error("governance proposal expired")]
+    GovernanceProposalExpired,
+
+    #[error("cannot migrate from contract {previous_contract}")]
+    CannotMigrate { previous_contract: String },
+
+    #[error("cannot migrate from newer version {previous_version}")]
+    CannotMigrateVersion { previous_version: String },
+
+    #[error("asset migration in progress; run MigrateBatch until it completes")]
+    MigrationPending,
+
+    #[error("no asset migration pending")]
+    NoPendingMigration,
+}
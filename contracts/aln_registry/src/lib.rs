@@ -1,9 +1,32 @@
-use cosmwasm_std::{entry_point, to_binary, Addr, Binary, Deps, DepsMut, Env, MessageInfo, Response, StdResult};
-use cw_storage_plus::{Map, Item};
+use cosmwasm_std::{entry_point, to_binary, Addr, Binary, Deps, DepsMut, Env, MessageInfo, Response, StdError, StdResult, Uint128};
+use cw2::{set_contract_version, CONTRACT};
+use cw_storage_plus::{Index, IndexList, IndexedMap, Item, MultiIndex};
+use semver::Version;
 use serde::{Deserialize, Serialize};
 
+mod committee;
+mod epochs;
+mod error;
+mod governance;
+mod lifecycle;
+mod listing;
+mod migrations;
+mod profiles;
+pub use committee::{Attestation, PendingAttestationsResponse, SanitizationCommittee, ATTESTATIONS, MAX_COMMITTEE_MEMBERS, SANITIZATION_COMMITTEE};
+pub use epochs::{SnapshotEpoch, MAX_SNAPSHOT_EPOCHS};
+pub use error::ContractError;
+pub use governance::{PendingGovernance, PENDING_GOVERNANCE};
+pub use lifecycle::{AssetLifecycle, AssetState, AssetUpdate, ASSET_VERSIONS};
+pub use listing::{AssetFilter, ListAssetsResponse};
+pub use migrations::ASSET_MIGRATION_CURSOR;
+pub use profiles::{ScalingProfile, MAX_PROFILE_DECIMALS, SCALING_PROFILES};
+pub use aln_merkle::MerkleVersion;
+use cw_utils::Expiration;
+
 const CONTRACT_NAME: &str = "aln-registry";
-const CONTRACT_VERSION: &str = "0.1.0";
+const CONTRACT_VERSION: &str = "0.7.0";
+/// Version of instances deployed before cw2 tracking.
+const LEGACY_VERSION: &str = "0.1.0";
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
 pub struct RegisteredAsset {
@@ -16,9 +39,49 @@ pub struct RegisteredAsset {
     pub scaling_profile_id: String,
     pub activation_height: u64,
     pub sanitized_approved: bool,
+    /// Leaf/node encoding `merkle_root` was built with; assets registered before v2 read as v1.
+    #[serde(default)]
+    pub merkle_version: MerkleVersion,
+    /// Block height from which claims are refused and the bridge may sweep what is left unclaimed.
+    #[serde(default)]
+    pub claim_deadline: Option<u64>,
+    /// Sum of the snapshot balances under `merkle_root`, in source units; the sweep records
+    /// `total_supply` minus the claimed balances.
+    #[serde(default)]
+    pub total_supply: Option<Uint128>,
+    /// State and version, kept by the registry; ignored on `RegisterAsset`.
+    #[serde(default)]
+    pub lifecycle: AssetLifecycle,
+    /// Snapshot epochs 1, 2, ... added with `AddSnapshotEpoch`; the fields above are epoch 0.
+    /// Ignored on `RegisterAsset`.
+    #[serde(default)]
+    pub epochs: Vec<SnapshotEpoch>,
+}
+
+/// Secondary indexes of `assets()`.
+pub struct AssetIndexes<'a> {
+    /// AssetsBySourceDenom: origin denom -> the ALN asset ids registered for it.
+    pub source_denom: MultiIndex<'a, String, RegisteredAsset, String>,
+    /// Scaling profile id -> the assets scaled by it.
+    pub scaling_profile_id: MultiIndex<'a, String, RegisteredAsset, String>,
+}
+
+impl<'a> IndexList<RegisteredAsset> for AssetIndexes<'a> {
+    fn get_indexes(&'_ self) -> Box<dyn Iterator<Item = &'_ dyn Index<RegisteredAsset>> + '_> {
+        let v: Vec<&dyn Index<RegisteredAsset>> = vec![&self.source_denom, &self.scaling_profile_id];
+        Box::new(v.into_iter())
+    }
+}
+
+/// Registered assets by id.
+pub fn assets<'a>() -> IndexedMap<'a, String, RegisteredAsset, AssetIndexes<'a>> {
+    let indexes = AssetIndexes {
+        source_denom: MultiIndex::new(|a: &RegisteredAsset| a.source_denom.clone(), "reg_assets", "reg_assets__source_denom"),
+        scaling_profile_id: MultiIndex::new(|a: &RegisteredAsset| a.scaling_profile_id.clone(), "reg_assets", "reg_assets__scaling_profile_id"),
+    };
+    IndexedMap::new("reg_assets", indexes)
 }
 
-pub const ASSETS: Map<String, RegisteredAsset> = Map::new("reg_assets");
 pub const GOVERNANCE: Item<Addr> = Item::new("governance_addr");
 pub const ALLOW_MISSING_UBS: Item<bool> = Item::new("allow_missing_ubs");
 
@@ -26,65 +89,192 @@ pub const ALLOW_MISSING_UBS: Item<bool> = Item::new("allow_missing_ubs");
 pub struct InstantiateMsg {
     pub governance_addr: String,
     pub allow_missing_ubs: Option<bool>,
+    /// Profiles available to the first registrations.
+    pub scaling_profiles: Option<Vec<ScalingProfile>>,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
 #[serde(rename_all = "snake_case")]
 pub enum ExecuteMsg {
+    /// Fails for an id that is already registered; use `UpdateAsset` to change an asset.
     RegisterAsset { asset: RegisteredAsset },
-    ApproveSanitized { id: String, ubs_report_hash: String },
+    /// Governance-only without a sanitization committee. With one, an attestation by a member:
+    /// the approval applies once `threshold` members attest the same hash. `epoch` (default 0,
+    /// the asset itself) approves a snapshot epoch added with `AddSnapshotEpoch`.
+    ApproveSanitized { id: String, ubs_report_hash: String, epoch: Option<u32> },
+    /// Governance-only: change the given fields. Snapshot fields (`merkle_root` and co.) only
+    /// change before the asset is active, and changing one needs a new `ApproveSanitized`.
+    UpdateAsset { id: String, update: AssetUpdate },
+    /// Governance-only: withdraw the sanitization approval for good; the bridge refuses claims.
+    RevokeSanitized { id: String, reason: String },
+    /// Governance-only: retire the asset. Claims stay open until its claim deadline.
+    DeprecateAsset { id: String },
+    /// Governance-only: append the asset's next snapshot epoch (e.g. late-discovered holders or
+    /// a second migration wave), claimable under its own root, activation and deadline once
+    /// `ApproveSanitized` approves it.
+    AddSnapshotEpoch { id: String, epoch: SnapshotEpoch },
+    /// Governance-only: require `threshold` of `members` to attest a sanitization approval.
+    SetSanitizationCommittee { members: Vec<String>, threshold: u32 },
+    /// Governance-only: go back to single governance approvals.
+    RemoveSanitizationCommittee {},
+    /// Governance-only: drop the conflicting attestations of a disputed asset, or of its
+    /// disputed `epoch` (default 0); it goes back to `Proposed` for a new round.
+    ResolveSanitizationDispute { id: String, epoch: Option<u32> },
+    /// Approve a proposed asset (or its `epoch`, default 0) whose open round already meets the
+    /// current committee's threshold, e.g. after `SetSanitizationCommittee` lowered it. Anyone
+    /// can call it.
+    FinalizeAttestations { id: String, epoch: Option<u32> },
+    /// Governance-only: create or replace a scaling profile. Profiles of approved, active or
+    /// deprecated assets cannot change.
+    SetScalingProfile { profile: ScalingProfile },
+    /// Governance-only: delete a profile no asset refers to.
+    RemoveScalingProfile { id: String },
+    /// Governance-only: nominate a successor (e.g. a multisig or DAO). Takes effect on `AcceptGovernance`.
+    ProposeGovernance { new_governance: String, expires: Option<Expiration> },
+    /// Sent by the pending governance before expiry to complete the handover.
+    AcceptGovernance {},
+    /// Governance-only: withdraw the outstanding proposal.
+    CancelGovernanceProposal {},
+    /// Continue an asset migration left pending by `migrate` with at most `limit` assets
+    /// (default 50, max 200). Anyone can call it; other messages are refused until it completes.
+    MigrateBatch { limit: Option<u32> },
 }
 
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct MigrateMsg {}
+
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
 #[serde(rename_all = "snake_case")]
 pub enum QueryMsg {
+    /// The asset with its state at the current height (`Option<RegisteredAsset>`).
     GetAsset { id: String },
+    /// Assets by id after `start_after` (`ListAssetsResponse`), optionally filtered; continue from
+    /// its `next_start_after` until that is `None`.
+    ListAssets { start_after: Option<String>, limit: Option<u32>, filter: Option<AssetFilter> },
+    /// The asset as `version` left it (`Option<RegisteredAsset>`), with its state as recorded.
+    AssetVersion { id: String, version: u64 },
+    /// Snapshot epochs of the asset in order, epoch 0 first (`Vec<SnapshotEpoch>`).
+    SnapshotEpochs { id: String },
+    /// `Option<SanitizationCommittee>`.
+    SanitizationCommittee {},
+    /// Attestations of the open approval round of the asset's `epoch`, default 0
+    /// (`PendingAttestationsResponse`).
+    PendingAttestations { id: String, epoch: Option<u32> },
+    /// Open or disputed approval rounds, by (asset id, epoch) after `start_after`
+    /// (`Vec<PendingAttestationsResponse>`).
+    AssetsPendingApproval { start_after: Option<(String, u32)>, limit: Option<u32> },
+    /// `Option<ScalingProfile>`.
+    ScalingProfile { id: String },
+    /// Profiles by id after `start_after` (`Vec<ScalingProfile>`).
+    ScalingProfiles { start_after: Option<String>, limit: Option<u32> },
+    /// Outstanding governance handover, if any (`Option<PendingGovernance>`).
+    PendingGovernance {},
 }
 
 #[entry_point]
-pub fn instantiate(deps: DepsMut, _env: Env, info: MessageInfo, msg: InstantiateMsg) -> StdResult<Response> {
+pub fn instantiate(deps: DepsMut, _env: Env, info: MessageInfo, msg: InstantiateMsg) -> Result<Response, ContractError> {
+    set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
     let gov = deps.api.addr_validate(&msg.governance_addr)?;
     GOVERNANCE.save(deps.storage, &gov)?;
     let allow = msg.allow_missing_ubs.unwrap_or(false);
     ALLOW_MISSING_UBS.save(deps.storage, &allow)?;
+    profiles::save_profiles(deps.storage, msg.scaling_profiles.unwrap_or_default())?;
     Ok(Response::new().add_attribute("action", "instantiate"))
 }
 
 #[entry_point]
-pub fn execute(deps: DepsMut, _env: Env, info: MessageInfo, msg: ExecuteMsg) -> StdResult<Response> {
+pub fn execute(deps: DepsMut, env: Env, info: MessageInfo, msg: ExecuteMsg) -> Result<Response, ContractError> {
+    if !matches!(msg, ExecuteMsg::MigrateBatch { .. }) {
+        migrations::ensure_migrated(deps.storage)?;
+    }
     match msg {
-        ExecuteMsg::RegisterAsset { asset } => register_asset(deps, info, asset),
-        ExecuteMsg::ApproveSanitized { id, ubs_report_hash } => approve_sanitized(deps, info, id, ubs_report_hash),
+        ExecuteMsg::RegisterAsset { asset } => register_asset(deps, env, info, asset),
+        ExecuteMsg::ApproveSanitized { id, ubs_report_hash, epoch } => lifecycle::approve_sanitized(deps, env, info, id, epoch.unwrap_or(0), ubs_report_hash),
+        ExecuteMsg::UpdateAsset { id, update } => lifecycle::update_asset(deps, env, info, id, update),
+        ExecuteMsg::RevokeSanitized { id, reason } => lifecycle::revoke_sanitized(deps, env, info, id, reason),
+        ExecuteMsg::DeprecateAsset { id } => lifecycle::deprecate_asset(deps, env, info, id),
+        ExecuteMsg::AddSnapshotEpoch { id, epoch } => epochs::add_snapshot_epoch(deps, env, info, id, epoch),
+        ExecuteMsg::SetSanitizationCommittee { members, threshold } => committee::set_committee(deps, info, members, threshold),
+        ExecuteMsg::RemoveSanitizationCommittee {} => committee::remove_committee(deps, info),
+        ExecuteMsg::ResolveSanitizationDispute { id, epoch } => committee::resolve_dispute(deps, env, info, id, epoch.unwrap_or(0)),
+        ExecuteMsg::FinalizeAttestations { id, epoch } => committee::finalize_attestations(deps, env, id, epoch.unwrap_or(0)),
+        ExecuteMsg::SetScalingProfile { profile } => profiles::set_scaling_profile(deps, env, info, profile),
+        ExecuteMsg::RemoveScalingProfile { id } => profiles::remove_scaling_profile(deps, env, info, id),
+        ExecuteMsg::ProposeGovernance { new_governance, expires } => governance::propose_governance(deps, env, info, new_governance, expires),
+        ExecuteMsg::AcceptGovernance {} => governance::accept_governance(deps, env, info),
+        ExecuteMsg::CancelGovernanceProposal {} => governance::cancel_governance_proposal(deps, info),
+        ExecuteMsg::MigrateBatch { limit } => migrations::migrate_batch(deps.storage, env, info, limit),
     }
 }
 
-fn register_asset(deps: DepsMut, info: MessageInfo, asset: RegisteredAsset) -> StdResult<Response> {
+fn register_asset(deps: DepsMut, env: Env, info: MessageInfo, mut asset: RegisteredAsset) -> Result<Response, ContractError> {
     let gov = GOVERNANCE.load(deps.storage)?;
-    if info.sender != gov { return Err(cosmwasm_std::StdError::generic_err("only governance can register assets")); }
+    if info.sender != gov { return Err(ContractError::Unauthorized { action: "register assets".to_string() }); }
+    if assets().has(deps.storage, asset.id.clone()) { return Err(ContractError::AssetExists { id: asset.id }); }
     let allow_missing = ALLOW_MISSING_UBS.load(deps.storage)?;
     if asset.ubs_report_hash.is_none() && !allow_missing {
-        return Err(cosmwasm_std::StdError::generic_err("ubs_report_hash is required for asset registration"));
+        return Err(ContractError::UbsReportRequired);
+    }
+    if asset.claim_deadline.is_some_and(|d| d <= asset.activation_height) {
+        return Err(ContractError::InvalidClaimDeadline);
     }
-    ASSETS.save(deps.storage, asset.id.clone(), &asset)?;
-    Ok(Response::new().add_attribute("action", "register_asset"))
+    profiles::ensure_profile(deps.storage, asset.scaling_profile_id.as_str())?;
+    if asset.sanitized_approved && SANITIZATION_COMMITTEE.may_load(deps.storage)?.is_some() {
+        return Err(ContractError::CommitteeApprovalRequired);
+    }
+    let state = if asset.sanitized_approved { AssetState::Approved } else { AssetState::Proposed };
+    asset.lifecycle = AssetLifecycle { state, ..AssetLifecycle::default() };
+    asset.epochs = vec![];
+    lifecycle::save_version(deps.storage, &env, &mut asset)?;
+    Ok(Response::new().add_attribute("action", "register_asset").add_attribute("id", asset.id).add_attribute("version", asset.lifecycle.version.to_string()))
 }
 
-fn approve_sanitized(deps: DepsMut, info: MessageInfo, id: String, ubs_report_hash: String) -> StdResult<Response> {
-    let gov = GOVERNANCE.load(deps.storage)?;
-    if info.sender != gov { return Err(cosmwasm_std::StdError::generic_err("only governance can approve sanitized")); }
-    let mut a = ASSETS.load(deps.storage, id.clone())?;
-    a.ubs_report_hash = Some(ubs_report_hash);
-    a.sanitized_approved = true;
-    ASSETS.save(deps.storage, id.clone(), &a)?;
-    Ok(Response::new().add_attribute("action", "approve_sanitized").add_attribute("id", id))
+#[entry_point]
+pub fn migrate(deps: DepsMut, env: Env, _msg: MigrateMsg) -> Result<Response, ContractError> {
+    // instances deployed before cw2 tracking carry no version
+    let previous = match CONTRACT.may_load(deps.storage)? {
+        Some(stored) => {
+            if stored.contract != CONTRACT_NAME {
+                return Err(ContractError::CannotMigrate { previous_contract: stored.contract });
+            }
+            stored.version
+        }
+        None => LEGACY_VERSION.to_string(),
+    };
+    let prev_v: Version = previous.parse().map_err(|_| StdError::generic_err("invalid stored contract version"))?;
+    let new_v: Version = CONTRACT_VERSION.parse().map_err(|_| StdError::generic_err("invalid contract version"))?;
+    if prev_v > new_v {
+        return Err(ContractError::CannotMigrateVersion { previous_version: previous });
+    }
+    // 0.2.0 (lifecycle backfill), 0.3.0 (source denom index) and 0.7.0 (scaling profile index)
+    // rewrite every asset, in batches
+    if prev_v < Version::new(0, 7, 0) {
+        migrations::schedule_asset_migration(deps.storage)?;
+    }
+    let done = migrations::run_batch(deps.storage, &env, migrations::DEFAULT_MIGRATION_BATCH as usize)?;
+    set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+    Ok(Response::new()
+        .add_attribute("action", "migrate")
+        .add_attribute("from_version", previous)
+        .add_attribute("to_version", CONTRACT_VERSION)
+        .add_attribute("migration_done", done.to_string()))
 }
 
 #[entry_point]
-pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
+pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
     match msg {
         QueryMsg::GetAsset { id } => {
-            let a = ASSETS.load(deps.storage, id.clone())?;
-            Ok(to_binary(&a)?)
+            let a = assets().may_load(deps.storage, id)?;
+            to_binary(&a.map(|a| lifecycle::at_height(a, env.block.height)))
         }
+        QueryMsg::ListAssets { start_after, limit, filter } => to_binary(&listing::list_assets(deps, env, start_after, limit, filter)?),
+        QueryMsg::AssetVersion { id, version } => to_binary(&ASSET_VERSIONS.may_load(deps.storage, (id.as_str(), version))?),
+        QueryMsg::SnapshotEpochs { id } => to_binary(&epochs::query_snapshot_epochs(deps, id)?),
+        QueryMsg::SanitizationCommittee {} => to_binary(&SANITIZATION_COMMITTEE.may_load(deps.storage)?),
+        QueryMsg::PendingAttestations { id, epoch } => to_binary(&committee::query_pending_attestations(deps, env, id, epoch.unwrap_or(0))?),
+        QueryMsg::AssetsPendingApproval { start_after, limit } => to_binary(&committee::query_assets_pending_approval(deps, env, start_after, limit)?),
+        QueryMsg::ScalingProfile { id } => to_binary(&SCALING_PROFILES.may_load(deps.storage, id.as_str())?),
+        QueryMsg::ScalingProfiles { start_after, limit } => to_binary(&profiles::query_scaling_profiles(deps, start_after, limit)?),
+        QueryMsg::PendingGovernance {} => to_binary(&PENDING_GOVERNANCE.may_load(deps.storage)?),
     }
 }
@@ -0,0 +1,213 @@
+use std::fmt;
+
+use cosmwasm_std::{Addr, DepsMut, Env, MessageInfo, Response, StdResult, Storage, Uint128};
+use cw_storage_plus::Map;
+use serde::{Deserialize, Serialize};
+
+use crate::committee::{attest, clear_all_attestations, clear_attestations, SANITIZATION_COMMITTEE};
+use crate::epochs::{approve_epoch, unapproved_epoch};
+use crate::{assets, ContractError, MerkleVersion, RegisteredAsset, GOVERNANCE};
+
+/// Where an asset is in its lifecycle. `Approved` assets read as `Active` from their
+/// `activation_height` on; `Revoked` is final.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum AssetState {
+    /// Registered, sanitization not approved yet.
+    #[default]
+    Proposed,
+    Approved,
+    Active,
+    /// Retired by governance: claims stay open until the claim deadline, but the asset can no
+    /// longer be updated or re-approved.
+    Deprecated,
+    /// Sanitization withdrawn; the bridge refuses its claims.
+    Revoked,
+    /// Committee members attested different UBS report hashes; governance has to resolve it.
+    Disputed,
+}
+
+impl fmt::Display for AssetState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = match self {
+            AssetState::Proposed => "proposed",
+            AssetState::Approved => "approved",
+            AssetState::Active => "active",
+            AssetState::Deprecated => "deprecated",
+            AssetState::Revoked => "revoked",
+            AssetState::Disputed => "disputed",
+        };
+        f.write_str(s)
+    }
+}
+
+/// Registry-managed part of an asset; whatever `RegisterAsset` carries here is ignored.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Default)]
+pub struct AssetLifecycle {
+    pub state: AssetState,
+    /// Starts at 1 and is bumped on every change; each version stays queryable.
+    pub version: u64,
+    pub revoked_reason: Option<String>,
+    /// Block height of the change that produced this version.
+    pub updated_height: u64,
+    /// Governance, or the committee members whose attestations approved the current
+    /// sanitization.
+    #[serde(default)]
+    pub approved_by: Vec<Addr>,
+}
+
+/// Fields governance may change with `UpdateAsset`. Everything but `claim_deadline` describes
+/// the snapshot and is frozen once the asset is active. A different root, merkle version,
+/// snapshot height, scaling profile or activation height needs a new sanitization approval: the
+/// asset goes back to `Proposed`.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Default)]
+pub struct AssetUpdate {
+    pub merkle_root: Option<String>,
+    pub merkle_version: Option<MerkleVersion>,
+    pub snapshot_height: Option<u64>,
+    pub total_supply: Option<Uint128>,
+    pub scaling_profile_id: Option<String>,
+    pub activation_height: Option<u64>,
+    pub claim_deadline: Option<u64>,
+}
+
+/// (asset id, version) -> the asset as that version left it.
+pub const ASSET_VERSIONS: Map<(&str, u64), RegisteredAsset> = Map::new("reg_asset_versions");
+
+/// The asset as seen at `height`: `Approved` turns `Active` at `activation_height`.
+pub fn at_height(mut asset: RegisteredAsset, height: u64) -> RegisteredAsset {
+    if asset.lifecycle.state == AssetState::Approved && height >= asset.activation_height {
+        asset.lifecycle.state = AssetState::Active;
+    }
+    asset
+}
+
+/// Store `asset` as its next version.
+pub(crate) fn save_version(storage: &mut dyn Storage, env: &Env, asset: &mut RegisteredAsset) -> StdResult<()> {
+    asset.lifecycle.version += 1;
+    asset.lifecycle.updated_height = env.block.height;
+    assets().save(storage, asset.id.clone(), asset)?;
+    ASSET_VERSIONS.save(storage, (asset.id.as_str(), asset.lifecycle.version), asset)
+}
+
+fn ensure_governance(deps: &DepsMut, info: &MessageInfo, action: &str) -> Result<(), ContractError> {
+    let gov = GOVERNANCE.load(deps.storage)?;
+    if info.sender != gov { return Err(ContractError::Unauthorized { action: action.to_string() }); }
+    Ok(())
+}
+
+/// Load `id` for `action`, refusing the states in `refused` (checked at the current height).
+pub(crate) fn load_for(storage: &dyn Storage, env: &Env, id: &str, action: &str, refused: &[AssetState]) -> Result<RegisteredAsset, ContractError> {
+    let asset = assets().load(storage, id.to_string())?;
+    let state = at_height(asset.clone(), env.block.height).lifecycle.state;
+    if refused.contains(&state) {
+        return Err(ContractError::InvalidAssetTransition { id: id.to_string(), state, action: action.to_string() });
+    }
+    Ok(asset)
+}
+
+pub(crate) fn lifecycle_response(action: &str, asset: &RegisteredAsset, env: &Env) -> Response {
+    Response::new()
+        .add_attribute("action", action)
+        .add_attribute("id", asset.id.as_str())
+        .add_attribute("state", at_height(asset.clone(), env.block.height).lifecycle.state.to_string())
+        .add_attribute("version", asset.lifecycle.version.to_string())
+}
+
+/// Record the sanitization of `asset` as approved by `approvers`.
+pub(crate) fn approve(storage: &mut dyn Storage, env: &Env, asset: &mut RegisteredAsset, ubs_report_hash: String, approvers: Vec<Addr>) -> StdResult<()> {
+    asset.ubs_report_hash = Some(ubs_report_hash);
+    asset.sanitized_approved = true;
+    asset.lifecycle.state = AssetState::Approved;
+    asset.lifecycle.approved_by = approvers;
+    save_version(storage, env, asset)
+}
+
+/// Governance-only while no sanitization committee is configured; with one, an attestation by
+/// a member (see `committee::attest`). `epoch` 0 approves the asset, a later one only that
+/// snapshot epoch.
+pub fn approve_sanitized(deps: DepsMut, env: Env, info: MessageInfo, id: String, epoch: u32, ubs_report_hash: String) -> Result<Response, ContractError> {
+    if let Some(committee) = SANITIZATION_COMMITTEE.may_load(deps.storage)? {
+        return attest(deps, env, info, committee, id, epoch, ubs_report_hash);
+    }
+    ensure_governance(&deps, &info, "approve sanitized")?;
+    if epoch > 0 {
+        let mut a = load_for(deps.storage, &env, &id, "approve sanitized", &[AssetState::Deprecated, AssetState::Revoked])?;
+        unapproved_epoch(&a, epoch)?;
+        approve_epoch(deps.storage, &env, &mut a, epoch, ubs_report_hash, vec![info.sender])?;
+        return Ok(lifecycle_response("approve_sanitized", &a, &env).add_attribute("epoch", epoch.to_string()));
+    }
+    let mut a = load_for(deps.storage, &env, &id, "approve sanitized", &[AssetState::Deprecated, AssetState::Revoked, AssetState::Disputed])?;
+    approve(deps.storage, &env, &mut a, ubs_report_hash, vec![info.sender])?;
+    Ok(lifecycle_response("approve_sanitized", &a, &env))
+}
+
+pub fn update_asset(deps: DepsMut, env: Env, info: MessageInfo, id: String, update: AssetUpdate) -> Result<Response, ContractError> {
+    ensure_governance(&deps, &info, "update assets")?;
+    let mut a = load_for(deps.storage, &env, &id, "update", &[AssetState::Deprecated, AssetState::Revoked])?;
+    if at_height(a.clone(), env.block.height).lifecycle.state == AssetState::Active {
+        let frozen = [
+            ("merkle_root", update.merkle_root.is_some()),
+            ("merkle_version", update.merkle_version.is_some()),
+            ("snapshot_height", update.snapshot_height.is_some()),
+            ("total_supply", update.total_supply.is_some()),
+            ("scaling_profile_id", update.scaling_profile_id.is_some()),
+            ("activation_height", update.activation_height.is_some()),
+        ];
+        if let Some((field, _)) = frozen.iter().find(|(_, set)| *set) {
+            return Err(ContractError::AssetActivated { id, field: field.to_string() });
+        }
+    }
+    // anything the sanitization report was attested against sends the asset back for approval
+    let mut changed = false;
+    if let Some(root) = update.merkle_root { changed |= root != a.merkle_root; a.merkle_root = root; }
+    if let Some(v) = update.merkle_version { changed |= v != a.merkle_version; a.merkle_version = v; }
+    if let Some(h) = update.snapshot_height { changed |= h != a.snapshot_height; a.snapshot_height = h; }
+    if let Some(s) = update.total_supply { a.total_supply = Some(s); }
+    if let Some(p) = update.scaling_profile_id {
+        crate::profiles::ensure_profile(deps.storage, p.as_str())?;
+        changed |= p != a.scaling_profile_id;
+        a.scaling_profile_id = p;
+    }
+    if let Some(h) = update.activation_height { changed |= h != a.activation_height; a.activation_height = h; }
+    if changed {
+        a.sanitized_approved = false;
+        a.lifecycle.state = AssetState::Proposed;
+        a.lifecycle.approved_by.clear();
+        clear_attestations(deps.storage, id.as_str(), 0);
+    }
+    if let Some(d) = update.claim_deadline { a.claim_deadline = Some(d); }
+    if a.claim_deadline.is_some_and(|d| d <= a.activation_height) {
+        return Err(ContractError::InvalidClaimDeadline);
+    }
+    save_version(deps.storage, &env, &mut a)?;
+    Ok(lifecycle_response("update_asset", &a, &env))
+}
+
+pub fn revoke_sanitized(deps: DepsMut, env: Env, info: MessageInfo, id: String, reason: String) -> Result<Response, ContractError> {
+    ensure_governance(&deps, &info, "revoke sanitized")?;
+    if reason.trim().is_empty() { return Err(ContractError::RevocationReasonRequired); }
+    let mut a = load_for(deps.storage, &env, &id, "revoke", &[AssetState::Revoked])?;
+    a.sanitized_approved = false;
+    a.lifecycle.state = AssetState::Revoked;
+    a.lifecycle.revoked_reason = Some(reason.clone());
+    clear_all_attestations(deps.storage, &a);
+    save_version(deps.storage, &env, &mut a)?;
+    Ok(lifecycle_response("revoke_sanitized", &a, &env).add_attribute("reason", reason))
+}
+
+pub fn deprecate_asset(deps: DepsMut, env: Env, info: MessageInfo, id: String) -> Result<Response, ContractError> {
+    ensure_governance(&deps, &info, "deprecate assets")?;
+    let mut a = load_for(deps.storage, &env, &id, "deprecate", &[AssetState::Deprecated, AssetState::Revoked])?;
+    a.lifecycle.state = AssetState::Deprecated;
+    clear_all_attestations(deps.storage, &a);
+    save_version(deps.storage, &env, &mut a)?;
+    Ok(lifecycle_response("deprecate_asset", &a, &env))
+}
+
+/// 0.2.0: an asset registered before lifecycle tracking becomes version 1, `Approved` when its
+/// sanitization was approved and `Proposed` otherwise.
+pub(crate) fn backfill_lifecycle_v0_2_0(storage: &mut dyn Storage, env: &Env, a: &mut RegisteredAsset) -> StdResult<()> {
+    a.lifecycle.state = if a.sanitized_approved { AssetState::Approved } else { AssetState::Proposed };
+    save_version(storage, env, a)
+}
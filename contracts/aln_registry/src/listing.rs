@@ -0,0 +1,69 @@
+use cosmwasm_std::{Deps, Env, Order, StdResult};
+use cw_storage_plus::Bound;
+use serde::{Deserialize, Serialize};
+
+use crate::lifecycle::at_height;
+use crate::{assets, AssetState, RegisteredAsset};
+
+const DEFAULT_LIMIT: u32 = 10;
+const MAX_LIMIT: u32 = 30;
+
+/// `ListAssets` filters; unset fields match every asset.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Default)]
+pub struct AssetFilter {
+    pub source_chain: Option<String>,
+    /// Served from the `AssetsBySourceDenom` index.
+    pub source_denom: Option<String>,
+    pub sanitized_approved: Option<bool>,
+    pub scaling_profile_id: Option<String>,
+    /// Lifecycle state at the current height (`active` once an approved asset reaches its
+    /// `activation_height`).
+    pub state: Option<AssetState>,
+}
+
+impl AssetFilter {
+    fn matches(&self, a: &RegisteredAsset) -> bool {
+        self.source_chain.as_ref().map_or(true, |c| *c == a.source_chain)
+            && self.source_denom.as_ref().map_or(true, |d| *d == a.source_denom)
+            && self.sanitized_approved.map_or(true, |s| s == a.sanitized_approved)
+            && self.scaling_profile_id.as_ref().map_or(true, |p| *p == a.scaling_profile_id)
+            && self.state.map_or(true, |s| s == a.lifecycle.state)
+    }
+}
+
+/// Entries read per requested asset, so a sparse filter cannot walk the whole map in one query.
+const SCAN_PER_RESULT: usize = 10;
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct ListAssetsResponse {
+    pub assets: Vec<RegisteredAsset>,
+    /// `start_after` for the next page; `None` once every candidate was read. Set whenever the
+    /// page is full or the scan stopped early, so fewer than `limit` assets does not mean the end.
+    pub next_start_after: Option<String>,
+}
+
+/// Assets by id after `start_after`, as seen at the current height. Reads at most
+/// `limit * SCAN_PER_RESULT` candidates.
+pub fn list_assets(deps: Deps, env: Env, start_after: Option<String>, limit: Option<u32>, filter: Option<AssetFilter>) -> StdResult<ListAssetsResponse> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let filter = filter.unwrap_or_default();
+    let start = start_after.as_ref().map(|s| Bound::exclusive(s.as_bytes()));
+    let candidates: Box<dyn Iterator<Item = StdResult<(String, RegisteredAsset)>> + '_> = match filter.source_denom.clone() {
+        Some(denom) => Box::new(assets().idx.source_denom.prefix(denom).range(deps.storage, start, None, Order::Ascending)),
+        None => Box::new(assets().range(deps.storage, start, None, Order::Ascending)),
+    };
+    let mut out = vec![];
+    let mut scanned = 0;
+    let mut last = None;
+    for item in candidates {
+        if out.len() == limit || scanned == limit.max(1) * SCAN_PER_RESULT {
+            return Ok(ListAssetsResponse { assets: out, next_start_after: last });
+        }
+        let (id, a) = item?;
+        scanned += 1;
+        let a = at_height(a, env.block.height);
+        if filter.matches(&a) { out.push(a); }
+        last = Some(id);
+    }
+    Ok(ListAssetsResponse { assets: out, next_start_after: None })
+}
@@ -0,0 +1,62 @@
+use cosmwasm_std::{Env, MessageInfo, Order, Response, StdResult, Storage};
+use cw_storage_plus::{Bound, Item};
+
+use crate::lifecycle::backfill_lifecycle_v0_2_0;
+use crate::{assets, ContractError, RegisteredAsset};
+
+/// Assets rewritten by `migrate` itself and by a `MigrateBatch` without a limit.
+pub const DEFAULT_MIGRATION_BATCH: u32 = 50;
+pub const MAX_MIGRATION_BATCH: u32 = 200;
+
+/// Id of the last asset rewritten by the pending migration ("" before the first); removed once
+/// every asset is. Governance actions are refused meanwhile.
+pub const ASSET_MIGRATION_CURSOR: Item<String> = Item::new("reg_asset_migration_cursor");
+
+pub(crate) fn ensure_migrated(storage: &dyn Storage) -> Result<(), ContractError> {
+    if ASSET_MIGRATION_CURSOR.may_load(storage)?.is_some() { return Err(ContractError::MigrationPending); }
+    Ok(())
+}
+
+/// Rewrite every asset for the 0.2.0 lifecycle backfill and the 0.3.0/0.7.0 asset indexes.
+pub(crate) fn schedule_asset_migration(storage: &mut dyn Storage) -> StdResult<()> {
+    if ASSET_MIGRATION_CURSOR.may_load(storage)?.is_none() {
+        ASSET_MIGRATION_CURSOR.save(storage, &String::new())?;
+    }
+    Ok(())
+}
+
+/// Continue the pending asset migration with at most `limit` assets. Anyone may call it.
+pub fn migrate_batch(storage: &mut dyn Storage, env: Env, _info: MessageInfo, limit: Option<u32>) -> Result<Response, ContractError> {
+    if ASSET_MIGRATION_CURSOR.may_load(storage)?.is_none() { return Err(ContractError::NoPendingMigration); }
+    let limit = limit.unwrap_or(DEFAULT_MIGRATION_BATCH).clamp(1, MAX_MIGRATION_BATCH);
+    let done = run_batch(storage, &env, limit as usize)?;
+    Ok(Response::new().add_attribute("action", "migrate_batch").add_attribute("migration_done", done.to_string()))
+}
+
+/// Rewrite up to `limit` assets after the cursor; returns whether the migration completed.
+pub(crate) fn run_batch(storage: &mut dyn Storage, env: &Env, limit: usize) -> StdResult<bool> {
+    let cursor = match ASSET_MIGRATION_CURSOR.may_load(storage)? {
+        Some(c) => c,
+        None => return Ok(true),
+    };
+    let start = if cursor.is_empty() { None } else { Some(Bound::exclusive(cursor.as_bytes())) };
+    let batch: Vec<(String, RegisteredAsset)> = assets().range(storage, start, None, Order::Ascending).take(limit).collect::<StdResult<_>>()?;
+    for (id, a) in batch.iter_mut() {
+        if a.lifecycle.version == 0 {
+            backfill_lifecycle_v0_2_0(storage, env, a)?;
+        } else {
+            // 0.3.0/0.7.0: saving through `assets()` adds the source denom and scaling profile entries
+            assets().save(storage, id.clone(), a)?;
+        }
+    }
+    match batch.last() {
+        Some((last, _)) if batch.len() == limit => {
+            ASSET_MIGRATION_CURSOR.save(storage, last)?;
+            Ok(false)
+        }
+        _ => {
+            ASSET_MIGRATION_CURSOR.remove(storage);
+            Ok(true)
+        }
+    }
+}
@@ -0,0 +1,110 @@
+use cosmwasm_std::{Decimal, Deps, DepsMut, Env, MessageInfo, Order, Response, StdResult, Storage};
+use cw_storage_plus::{Bound, Map};
+use serde::{Deserialize, Serialize};
+
+use crate::lifecycle::at_height;
+use crate::{assets, AssetState, ContractError, GOVERNANCE};
+
+const DEFAULT_LIMIT: u32 = 10;
+const MAX_LIMIT: u32 = 30;
+/// Keeps `10^decimals` and the scaled balances well inside `u128`.
+pub const MAX_PROFILE_DECIMALS: u32 = 18;
+
+/// How a source balance converts into ALN energy, as `aln_tools allocations` computes it from
+/// `config/scaling.yaml`: floor(B / 10^source_decimals * c * 10^aln_decimals).
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct ScalingProfile {
+    pub id: String,
+    /// AU.ET compression factor.
+    pub c_e: Decimal,
+    /// CSP compression factor.
+    pub c_s: Decimal,
+    pub source_decimals: u32,
+    pub aln_decimals: u32,
+    /// Assets of this profile count as toxic: their energy is capped and needs a toxic sink.
+    #[serde(default)]
+    pub toxic: bool,
+    /// Fraction of the scaled AU.ET and CSP withheld from every claim.
+    #[serde(default)]
+    pub haircut: Option<Decimal>,
+}
+
+impl ScalingProfile {
+    fn validate(&self) -> Result<(), ContractError> {
+        if self.id.is_empty() { return Err(ContractError::InvalidScalingProfile("id must not be empty".to_string())); }
+        if self.source_decimals > MAX_PROFILE_DECIMALS || self.aln_decimals > MAX_PROFILE_DECIMALS {
+            return Err(ContractError::InvalidScalingProfile(format!("decimals must not exceed {}", MAX_PROFILE_DECIMALS)));
+        }
+        if self.haircut.is_some_and(|h| h > Decimal::one()) { return Err(ContractError::InvalidScalingProfile("haircut must not exceed 1".to_string())); }
+        Ok(())
+    }
+}
+
+pub const SCALING_PROFILES: Map<&str, ScalingProfile> = Map::new("reg_scaling_profiles");
+
+/// Registration and updates only accept known profiles.
+pub(crate) fn ensure_profile(storage: &dyn Storage, id: &str) -> Result<(), ContractError> {
+    if !SCALING_PROFILES.has(storage, id) { return Err(ContractError::UnknownScalingProfile { id: id.to_string() }); }
+    Ok(())
+}
+
+/// First asset on `id` (read from the `scaling_profile_id` index), limited to assets whose
+/// sanitization is approved when `approved_only`.
+fn asset_using(storage: &dyn Storage, env: &Env, id: &str, approved_only: bool) -> StdResult<Option<String>> {
+    for item in assets().idx.scaling_profile_id.prefix(id.to_string()).range(storage, None, None, Order::Ascending) {
+        let (_, a) = item?;
+        let state = at_height(a.clone(), env.block.height).lifecycle.state;
+        if !approved_only || matches!(state, AssetState::Approved | AssetState::Active | AssetState::Deprecated) { return Ok(Some(a.id)); }
+    }
+    Ok(None)
+}
+
+pub(crate) fn save_profiles(storage: &mut dyn Storage, profiles: Vec<ScalingProfile>) -> Result<(), ContractError> {
+    for p in profiles {
+        p.validate()?;
+        SCALING_PROFILES.save(storage, p.id.as_str(), &p)?;
+    }
+    Ok(())
+}
+
+/// Governance-only: create or replace a profile. A profile behind approved assets is frozen, so
+/// an approval always covers the scaling it was given for and every claim of an asset is scaled
+/// the same way.
+pub fn set_scaling_profile(deps: DepsMut, env: Env, info: MessageInfo, profile: ScalingProfile) -> Result<Response, ContractError> {
+    let gov = GOVERNANCE.load(deps.storage)?;
+    if info.sender != gov { return Err(ContractError::Unauthorized { action: "set scaling profiles".to_string() }); }
+    profile.validate()?;
+    let existing = SCALING_PROFILES.may_load(deps.storage, profile.id.as_str())?;
+    if existing.as_ref().is_some_and(|p| *p != profile) {
+        if let Some(asset_id) = asset_using(deps.storage, &env, profile.id.as_str(), true)? {
+            return Err(ContractError::ScalingProfileInUse { id: profile.id, asset_id });
+        }
+    }
+    SCALING_PROFILES.save(deps.storage, profile.id.as_str(), &profile)?;
+    Ok(Response::new()
+        .add_attribute("action", if existing.is_some() { "update_scaling_profile" } else { "create_scaling_profile" })
+        .add_attribute("id", profile.id)
+        .add_attribute("toxic", profile.toxic.to_string()))
+}
+
+/// Governance-only: delete a profile no asset refers to.
+pub fn remove_scaling_profile(deps: DepsMut, env: Env, info: MessageInfo, id: String) -> Result<Response, ContractError> {
+    let gov = GOVERNANCE.load(deps.storage)?;
+    if info.sender != gov { return Err(ContractError::Unauthorized { action: "remove scaling profiles".to_string() }); }
+    ensure_profile(deps.storage, id.as_str())?;
+    if let Some(asset_id) = asset_using(deps.storage, &env, id.as_str(), false)? {
+        return Err(ContractError::ScalingProfileInUse { id, asset_id });
+    }
+    SCALING_PROFILES.remove(deps.storage, id.as_str());
+    Ok(Response::new().add_attribute("action", "remove_scaling_profile").add_attribute("id", id))
+}
+
+pub fn query_scaling_profiles(deps: Deps, start_after: Option<String>, limit: Option<u32>) -> StdResult<Vec<ScalingProfile>> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.as_ref().map(|s| Bound::exclusive(s.as_bytes()));
+    SCALING_PROFILES
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| item.map(|(_, p)| p))
+        .collect()
+}
@@ -1,14 +1,18 @@
 #[cfg(test)]
 mod tests {
-    use super::super::{instantiate, execute, query, InstantiateMsg, ExecuteMsg, RegisteredAsset};
+    use super::super::{instantiate, execute, query, InstantiateMsg, ExecuteMsg, MerkleVersion, RegisteredAsset, ScalingProfile};
     use cosmwasm_std::testing::{mock_dependencies, mock_env, mock_info};
-    use cosmwasm_std::Addr;
+    use cosmwasm_std::{Addr, Decimal};
+
+    fn test_profiles() -> Vec<ScalingProfile> {
+        ["clean", "safe", "malicious_cleanup"].iter().map(|id| ScalingProfile { id: id.to_string(), c_e: Decimal::one(), c_s: Decimal::percent(50), source_decimals: 6, aln_decimals: 6, toxic: id.starts_with("malicious"), haircut: None }).collect()
+    }
 
     #[test]
     fn register_and_approve_sanitized() {
         let mut deps = mock_dependencies();
         let gov = "gov".to_string();
-        let msg = InstantiateMsg { governance_addr: gov.clone(), allow_missing_ubs: Some(true) };
+        let msg = InstantiateMsg { governance_addr: gov.clone(), allow_missing_ubs: Some(true), scaling_profiles: Some(test_profiles()) };
         instantiate(deps.as_mut(), mock_env(), mock_info(&gov, &[]), msg).unwrap();
 
         let asset = RegisteredAsset {
@@ -21,17 +25,22 @@ mod tests {
             scaling_profile_id: "malicious_cleanup".to_string(),
             activation_height: 0,
             sanitized_approved: false,
+            merkle_version: MerkleVersion::V1,
+            claim_deadline: None,
+            total_supply: None,
+            lifecycle: Default::default(),
+            epochs: vec![],
         };
 
         let res = execute(deps.as_mut(), mock_env(), mock_info(&gov, &[]), ExecuteMsg::RegisterAsset { asset: asset.clone() }).unwrap();
         assert_eq!(res.attributes[0].value, "register_asset");
 
         // non gov cannot approve
-        let err = execute(deps.as_mut(), mock_env(), mock_info("notgov", &[]), ExecuteMsg::ApproveSanitized { id: "a1".to_string(), ubs_report_hash: "h1".to_string() });
-        assert!(err.is_err());
+        let err = execute(deps.as_mut(), mock_env(), mock_info("notgov", &[]), ExecuteMsg::ApproveSanitized { id: "a1".to_string(), ubs_report_hash: "h1".to_string(), epoch: None }).unwrap_err();
+        assert!(matches!(err, crate::ContractError::Unauthorized { .. }));
 
         // gov approves
-        let res2 = execute(deps.as_mut(), mock_env(), mock_info(&gov, &[]), ExecuteMsg::ApproveSanitized { id: "a1".to_string(), ubs_report_hash: "h1".to_string() }).unwrap();
+        let res2 = execute(deps.as_mut(), mock_env(), mock_info(&gov, &[]), ExecuteMsg::ApproveSanitized { id: "a1".to_string(), ubs_report_hash: "h1".to_string(), epoch: None }).unwrap();
         assert_eq!(res2.attributes[0].value, "approve_sanitized");
     }
 
@@ -39,7 +48,7 @@ mod tests {
     fn register_requires_ubs_if_disallowed() {
         let mut deps = mock_dependencies();
         let gov = "gov".to_string();
-        let msg = InstantiateMsg { governance_addr: gov.clone(), allow_missing_ubs: Some(false) };
+        let msg = InstantiateMsg { governance_addr: gov.clone(), allow_missing_ubs: Some(false), scaling_profiles: Some(test_profiles()) };
         instantiate(deps.as_mut(), mock_env(), mock_info(&gov, &[]), msg).unwrap();
 
         let asset = RegisteredAsset {
@@ -52,10 +61,450 @@ mod tests {
             scaling_profile_id: "safe".to_string(),
             activation_height: 0,
             sanitized_approved: false,
+            merkle_version: MerkleVersion::V1,
+            claim_deadline: None,
+            total_supply: None,
+            lifecycle: Default::default(),
+            epochs: vec![],
         };
 
         // registering without UBS should fail when allow_missing_ubs = false
-        let err = execute(deps.as_mut(), mock_env(), mock_info(&gov, &[]), ExecuteMsg::RegisterAsset { asset: asset.clone() });
-        assert!(err.is_err());
+        let err = execute(deps.as_mut(), mock_env(), mock_info(&gov, &[]), ExecuteMsg::RegisterAsset { asset: asset.clone() }).unwrap_err();
+        assert_eq!(err, crate::ContractError::UbsReportRequired);
+
+        // a claim deadline must fall after activation
+        let asset = RegisteredAsset { ubs_report_hash: Some("h".to_string()), activation_height: 100, claim_deadline: Some(100), ..asset };
+        let err = execute(deps.as_mut(), mock_env(), mock_info(&gov, &[]), ExecuteMsg::RegisterAsset { asset: asset.clone() }).unwrap_err();
+        assert_eq!(err, crate::ContractError::InvalidClaimDeadline);
+        execute(deps.as_mut(), mock_env(), mock_info(&gov, &[]), ExecuteMsg::RegisterAsset { asset: RegisteredAsset { claim_deadline: Some(101), ..asset } }).unwrap();
+    }
+
+    #[test]
+    fn two_step_governance_handover() {
+        let mut deps = mock_dependencies();
+        let gov = "gov".to_string();
+        instantiate(deps.as_mut(), mock_env(), mock_info(&gov, &[]), InstantiateMsg { governance_addr: gov.clone(), allow_missing_ubs: Some(true), scaling_profiles: Some(test_profiles()) }).unwrap();
+
+        // only governance proposes; an already expired proposal is refused
+        let propose = ExecuteMsg::ProposeGovernance { new_governance: "dao".to_string(), expires: Some(cw_utils::Expiration::AtHeight(mock_env().block.height + 10)) };
+        let err = execute(deps.as_mut(), mock_env(), mock_info("dao", &[]), propose.clone()).unwrap_err();
+        assert!(matches!(err, crate::ContractError::Unauthorized { .. }));
+        let stale = ExecuteMsg::ProposeGovernance { new_governance: "dao".to_string(), expires: Some(cw_utils::Expiration::AtHeight(1)) };
+        assert_eq!(execute(deps.as_mut(), mock_env(), mock_info(&gov, &[]), stale).unwrap_err(), crate::ContractError::GovernanceProposalExpired);
+        execute(deps.as_mut(), mock_env(), mock_info(&gov, &[]), propose).unwrap();
+
+        let pending: Option<crate::PendingGovernance> = cosmwasm_std::from_binary(&query(deps.as_ref(), mock_env(), crate::QueryMsg::PendingGovernance {}).unwrap()).unwrap();
+        assert_eq!(pending.unwrap().new_governance, Addr::unchecked("dao"));
+
+        // nobody but the nominee accepts, and not after expiry
+        assert_eq!(execute(deps.as_mut(), mock_env(), mock_info("other", &[]), ExecuteMsg::AcceptGovernance {}).unwrap_err(), crate::ContractError::NotPendingGovernance);
+        let mut late = mock_env();
+        late.block.height += 11;
+        assert_eq!(execute(deps.as_mut(), late, mock_info("dao", &[]), ExecuteMsg::AcceptGovernance {}).unwrap_err(), crate::ContractError::GovernanceProposalExpired);
+
+        execute(deps.as_mut(), mock_env(), mock_info("dao", &[]), ExecuteMsg::AcceptGovernance {}).unwrap();
+        assert_eq!(crate::GOVERNANCE.load(deps.as_ref().storage).unwrap(), Addr::unchecked("dao"));
+        // the old governance lost its rights, the proposal is consumed
+        let err = execute(deps.as_mut(), mock_env(), mock_info(&gov, &[]), ExecuteMsg::CancelGovernanceProposal {}).unwrap_err();
+        assert!(matches!(err, crate::ContractError::Unauthorized { .. }));
+        assert_eq!(execute(deps.as_mut(), mock_env(), mock_info("dao", &[]), ExecuteMsg::CancelGovernanceProposal {}).unwrap_err(), crate::ContractError::NoPendingGovernance);
+    }
+
+    #[test]
+    fn asset_lifecycle_and_versions() {
+        use crate::{AssetState, AssetUpdate, ContractError, QueryMsg};
+        let mut deps = mock_dependencies();
+        let gov = mock_info("gov", &[]);
+        instantiate(deps.as_mut(), mock_env(), gov.clone(), InstantiateMsg { governance_addr: "gov".to_string(), allow_missing_ubs: Some(true), scaling_profiles: Some(test_profiles()) }).unwrap();
+        let height = mock_env().block.height;
+        let asset = RegisteredAsset {
+            id: "l1".to_string(),
+            source_chain: "kaiyo-1".to_string(),
+            source_denom: "ibc/lll".to_string(),
+            snapshot_height: 1,
+            merkle_root: "root1".to_string(),
+            ubs_report_hash: None,
+            scaling_profile_id: "clean".to_string(),
+            activation_height: height + 10,
+            sanitized_approved: false,
+            merkle_version: MerkleVersion::V1,
+            claim_deadline: None,
+            total_supply: None,
+            lifecycle: Default::default(),
+            epochs: vec![],
+        };
+        let get = |deps: &cosmwasm_std::OwnedDeps<_, _, _>, env: cosmwasm_std::Env| -> RegisteredAsset { cosmwasm_std::from_binary(&query(deps.as_ref(), env, QueryMsg::GetAsset { id: "l1".to_string() }).unwrap()).unwrap() };
+        execute(deps.as_mut(), mock_env(), gov.clone(), ExecuteMsg::RegisterAsset { asset: asset.clone() }).unwrap();
+        assert_eq!((get(&deps, mock_env()).lifecycle.state, get(&deps, mock_env()).lifecycle.version), (AssetState::Proposed, 1));
+
+        // re-registering the id is an error, not an overwrite
+        let err = execute(deps.as_mut(), mock_env(), gov.clone(), ExecuteMsg::RegisterAsset { asset: RegisteredAsset { merkle_root: "other".to_string(), ..asset.clone() } }).unwrap_err();
+        assert_eq!(err, ContractError::AssetExists { id: "l1".to_string() });
+
+        // approved before activation: the root may still change, which drops the approval
+        execute(deps.as_mut(), mock_env(), gov.clone(), ExecuteMsg::ApproveSanitized { id: "l1".to_string(), ubs_report_hash: "h1".to_string(), epoch: None }).unwrap();
+        assert_eq!(get(&deps, mock_env()).lifecycle.state, AssetState::Approved);
+        let new_root = ExecuteMsg::UpdateAsset { id: "l1".to_string(), update: AssetUpdate { merkle_root: Some("root2".to_string()), ..AssetUpdate::default() } };
+        assert!(matches!(execute(deps.as_mut(), mock_env(), mock_info("notgov", &[]), new_root.clone()).unwrap_err(), ContractError::Unauthorized { .. }));
+        execute(deps.as_mut(), mock_env(), gov.clone(), new_root).unwrap();
+        let a = get(&deps, mock_env());
+        assert_eq!((a.merkle_root.as_str(), a.sanitized_approved, a.lifecycle.state, a.lifecycle.version), ("root2", false, AssetState::Proposed, 3));
+        execute(deps.as_mut(), mock_env(), gov.clone(), ExecuteMsg::ApproveSanitized { id: "l1".to_string(), ubs_report_hash: "h2".to_string(), epoch: None }).unwrap();
+        // so do the other snapshot fields, unless the value is unchanged
+        let set_version = |v: MerkleVersion| ExecuteMsg::UpdateAsset { id: "l1".to_string(), update: AssetUpdate { merkle_version: Some(v), ..AssetUpdate::default() } };
+        execute(deps.as_mut(), mock_env(), gov.clone(), set_version(MerkleVersion::V1)).unwrap();
+        assert_eq!(get(&deps, mock_env()).lifecycle.state, AssetState::Approved);
+        execute(deps.as_mut(), mock_env(), gov.clone(), set_version(MerkleVersion::V2)).unwrap();
+        let a = get(&deps, mock_env());
+        assert_eq!((a.sanitized_approved, a.lifecycle.state, a.lifecycle.approved_by.len()), (false, AssetState::Proposed, 0));
+        execute(deps.as_mut(), mock_env(), gov.clone(), ExecuteMsg::ApproveSanitized { id: "l1".to_string(), ubs_report_hash: "h2".to_string(), epoch: None }).unwrap();
+
+        // active from activation_height on: snapshot fields are frozen, the deadline is not
+        let mut active = mock_env();
+        active.block.height = height + 10;
+        assert_eq!(get(&deps, active.clone()).lifecycle.state, AssetState::Active);
+        let err = execute(deps.as_mut(), active.clone(), gov.clone(), ExecuteMsg::UpdateAsset { id: "l1".to_string(), update: AssetUpdate { merkle_root: Some("root3".to_string()), ..AssetUpdate::default() } }).unwrap_err();
+        assert_eq!(err, ContractError::AssetActivated { id: "l1".to_string(), field: "merkle_root".to_string() });
+        let err = execute(deps.as_mut(), active.clone(), gov.clone(), ExecuteMsg::UpdateAsset { id: "l1".to_string(), update: AssetUpdate { claim_deadline: Some(height + 10), ..AssetUpdate::default() } }).unwrap_err();
+        assert_eq!(err, ContractError::InvalidClaimDeadline);
+        execute(deps.as_mut(), active.clone(), gov.clone(), ExecuteMsg::UpdateAsset { id: "l1".to_string(), update: AssetUpdate { claim_deadline: Some(height + 100), ..AssetUpdate::default() } }).unwrap();
+
+        // revocation needs a reason and is final
+        let revoke = |reason: &str| ExecuteMsg::RevokeSanitized { id: "l1".to_string(), reason: reason.to_string() };
+        assert_eq!(execute(deps.as_mut(), active.clone(), gov.clone(), revoke(" ")).unwrap_err(), ContractError::RevocationReasonRequired);
+        execute(deps.as_mut(), active.clone(), gov.clone(), revoke("report withdrawn")).unwrap();
+        let a = get(&deps, active.clone());
+        assert_eq!((a.sanitized_approved, a.lifecycle.state, a.lifecycle.revoked_reason.as_deref()), (false, AssetState::Revoked, Some("report withdrawn")));
+        let err = execute(deps.as_mut(), active.clone(), gov.clone(), ExecuteMsg::ApproveSanitized { id: "l1".to_string(), ubs_report_hash: "h3".to_string(), epoch: None }).unwrap_err();
+        assert_eq!(err, ContractError::InvalidAssetTransition { id: "l1".to_string(), state: AssetState::Revoked, action: "approve sanitized".to_string() });
+        assert!(matches!(execute(deps.as_mut(), active.clone(), gov.clone(), ExecuteMsg::DeprecateAsset { id: "l1".to_string() }).unwrap_err(), ContractError::InvalidAssetTransition { .. }));
+
+        // every version stays queryable
+        let version = |v: u64| -> Option<RegisteredAsset> { cosmwasm_std::from_binary(&query(deps.as_ref(), mock_env(), QueryMsg::AssetVersion { id: "l1".to_string(), version: v }).unwrap()).unwrap() };
+        assert_eq!(version(1).unwrap().merkle_root, "root1");
+        assert_eq!(version(2).unwrap().lifecycle.state, AssetState::Approved);
+        assert_eq!(version(3).unwrap().merkle_root, "root2");
+        assert_eq!(version(9).unwrap().lifecycle.state, AssetState::Revoked);
+        assert!(version(10).is_none());
+    }
+
+    #[test]
+    fn list_assets_with_filters() {
+        use crate::{AssetFilter, AssetState, QueryMsg};
+        let mut deps = mock_dependencies();
+        let gov = mock_info("gov", &[]);
+        instantiate(deps.as_mut(), mock_env(), gov.clone(), InstantiateMsg { governance_addr: "gov".to_string(), allow_missing_ubs: Some(true), scaling_profiles: Some(test_profiles()) }).unwrap();
+        let height = mock_env().block.height;
+        let asset = |id: &str, chain: &str, denom: &str, approved: bool, activation: u64| RegisteredAsset {
+            id: id.to_string(),
+            source_chain: chain.to_string(),
+            source_denom: denom.to_string(),
+            snapshot_height: 1,
+            merkle_root: "root".to_string(),
+            ubs_report_hash: Some("h".to_string()),
+            scaling_profile_id: if approved { "clean" } else { "malicious_cleanup" }.to_string(),
+            activation_height: activation,
+            sanitized_approved: approved,
+            merkle_version: MerkleVersion::V1,
+            claim_deadline: None,
+            total_supply: None,
+            lifecycle: Default::default(),
+            epochs: vec![],
+        };
+        for a in [asset("a1", "kaiyo-1", "ibc/usk", true, 0), asset("a2", "kaiyo-1", "ukuji", false, 0), asset("a3", "osmosis-1", "ibc/usk", true, height + 5), asset("a4", "kaiyo-1", "ibc/usk", true, 0)] {
+            execute(deps.as_mut(), mock_env(), gov.clone(), ExecuteMsg::RegisterAsset { asset: a }).unwrap();
+        }
+        let page = |start_after: Option<&str>, limit: Option<u32>, filter: Option<AssetFilter>| -> crate::ListAssetsResponse { cosmwasm_std::from_binary(&query(deps.as_ref(), mock_env(), QueryMsg::ListAssets { start_after: start_after.map(|s| s.to_string()), limit, filter }).unwrap()).unwrap() };
+        let list = |start_after: Option<&str>, limit: Option<u32>, filter: Option<AssetFilter>| -> Vec<String> { page(start_after, limit, filter).assets.into_iter().map(|a| a.id).collect() };
+
+        assert_eq!(list(None, None, None), vec!["a1", "a2", "a3", "a4"]);
+        assert_eq!(list(Some("a1"), Some(2), None), vec!["a2", "a3"]);
+        // AssetsBySourceDenom, narrowed by chain and paginated within the denom
+        let usk = AssetFilter { source_denom: Some("ibc/usk".to_string()), ..AssetFilter::default() };
+        assert_eq!(list(None, None, Some(usk.clone())), vec!["a1", "a3", "a4"]);
+        assert_eq!(list(Some("a1"), Some(1), Some(usk.clone())), vec!["a3"]);
+        assert_eq!(list(None, None, Some(AssetFilter { source_chain: Some("kaiyo-1".to_string()), ..usk })), vec!["a1", "a4"]);
+        assert_eq!(list(None, None, Some(AssetFilter { sanitized_approved: Some(false), ..AssetFilter::default() })), vec!["a2"]);
+        assert_eq!(list(None, None, Some(AssetFilter { scaling_profile_id: Some("clean".to_string()), ..AssetFilter::default() })), vec!["a1", "a3", "a4"]);
+        // state is read at the current height: a3 is approved but not active yet
+        assert_eq!(list(None, None, Some(AssetFilter { state: Some(AssetState::Active), ..AssetFilter::default() })), vec!["a1", "a4"]);
+        assert_eq!(list(None, None, Some(AssetFilter { state: Some(AssetState::Approved), ..AssetFilter::default() })), vec!["a3"]);
+        // the cursor is set while candidates remain, and only then
+        assert_eq!(page(None, None, None).next_start_after, None);
+        assert_eq!(page(Some("a1"), Some(2), None).next_start_after.as_deref(), Some("a3"));
+        assert_eq!(page(Some("a3"), Some(1), None).next_start_after, None);
+
+        // a missing id is None, not an error
+        let missing: Option<RegisteredAsset> = cosmwasm_std::from_binary(&query(deps.as_ref(), mock_env(), QueryMsg::GetAsset { id: "nope".to_string() }).unwrap()).unwrap();
+        assert!(missing.is_none());
+    }
+
+    #[test]
+    fn scaling_profiles_crud_and_registration() {
+        use crate::{AssetUpdate, ContractError, QueryMsg};
+        let mut deps = mock_dependencies();
+        let gov = mock_info("gov", &[]);
+        instantiate(deps.as_mut(), mock_env(), gov.clone(), InstantiateMsg { governance_addr: "gov".to_string(), allow_missing_ubs: Some(true), scaling_profiles: None }).unwrap();
+        let profile = ScalingProfile { id: "bluechip".to_string(), c_e: Decimal::from_ratio(1u128, 1_000_000u128), c_s: Decimal::from_ratio(5u128, 10_000_000u128), source_decimals: 6, aln_decimals: 6, toxic: false, haircut: Some(Decimal::percent(10)) };
+        let asset = RegisteredAsset {
+            id: "b1".to_string(),
+            source_chain: "kaiyo-1".to_string(),
+            source_denom: "ibc/blue".to_string(),
+            snapshot_height: 1,
+            merkle_root: "root".to_string(),
+            ubs_report_hash: Some("h".to_string()),
+            scaling_profile_id: "bluechip".to_string(),
+            activation_height: 0,
+            sanitized_approved: true,
+            merkle_version: MerkleVersion::V1,
+            claim_deadline: None,
+            total_supply: None,
+            lifecycle: Default::default(),
+            epochs: vec![],
+        };
+
+        // unknown profiles are refused at registration
+        let err = execute(deps.as_mut(), mock_env(), gov.clone(), ExecuteMsg::RegisterAsset { asset: asset.clone() }).unwrap_err();
+        assert_eq!(err, ContractError::UnknownScalingProfile { id: "bluechip".to_string() });
+
+        // governance creates profiles; parameters are validated
+        let set = |p: ScalingProfile| ExecuteMsg::SetScalingProfile { profile: p };
+        assert!(matches!(execute(deps.as_mut(), mock_env(), mock_info("notgov", &[]), set(profile.clone())).unwrap_err(), ContractError::Unauthorized { .. }));
+        let err = execute(deps.as_mut(), mock_env(), gov.clone(), set(ScalingProfile { haircut: Some(Decimal::percent(101)), ..profile.clone() })).unwrap_err();
+        assert!(matches!(err, ContractError::InvalidScalingProfile(_)));
+        let err = execute(deps.as_mut(), mock_env(), gov.clone(), set(ScalingProfile { aln_decimals: 19, ..profile.clone() })).unwrap_err();
+        assert!(matches!(err, ContractError::InvalidScalingProfile(_)));
+        let res = execute(deps.as_mut(), mock_env(), gov.clone(), set(profile.clone())).unwrap();
+        assert_eq!(res.attributes[0].value, "create_scaling_profile");
+        execute(deps.as_mut(), mock_env(), gov.clone(), set(ScalingProfile { id: "toxic".to_string(), toxic: true, haircut: None, ..profile.clone() })).unwrap();
+        let loaded: Option<ScalingProfile> = cosmwasm_std::from_binary(&query(deps.as_ref(), mock_env(), QueryMsg::ScalingProfile { id: "bluechip".to_string() }).unwrap()).unwrap();
+        assert_eq!(loaded, Some(profile.clone()));
+        let all: Vec<ScalingProfile> = cosmwasm_std::from_binary(&query(deps.as_ref(), mock_env(), QueryMsg::ScalingProfiles { start_after: None, limit: None }).unwrap()).unwrap();
+        assert_eq!(all.iter().map(|p| (p.id.as_str(), p.toxic)).collect::<Vec<_>>(), vec![("bluechip", false), ("toxic", true)]);
+
+        // a proposed asset leaves its profile open; an approved one freezes it before activation
+        let later = ScalingProfile { id: "later".to_string(), ..profile.clone() };
+        execute(deps.as_mut(), mock_env(), gov.clone(), set(later.clone())).unwrap();
+        let proposed = RegisteredAsset { id: "p1".to_string(), scaling_profile_id: "later".to_string(), sanitized_approved: false, activation_height: 1_000_000, ..asset.clone() };
+        execute(deps.as_mut(), mock_env(), gov.clone(), ExecuteMsg::RegisterAsset { asset: proposed.clone() }).unwrap();
+        execute(deps.as_mut(), mock_env(), gov.clone(), set(ScalingProfile { c_e: Decimal::one(), ..later.clone() })).unwrap();
+        execute(deps.as_mut(), mock_env(), gov.clone(), ExecuteMsg::RegisterAsset { asset: RegisteredAsset { id: "p2".to_string(), sanitized_approved: true, ..proposed } }).unwrap();
+        let err = execute(deps.as_mut(), mock_env(), gov.clone(), set(later)).unwrap_err();
+        assert_eq!(err, ContractError::ScalingProfileInUse { id: "later".to_string(), asset_id: "p2".to_string() });
+
+        // once an asset on it is active, the profile is frozen and cannot be removed
+        execute(deps.as_mut(), mock_env(), gov.clone(), ExecuteMsg::RegisterAsset { asset }).unwrap();
+        let err = execute(deps.as_mut(), mock_env(), gov.clone(), set(ScalingProfile { c_e: Decimal::one(), ..profile.clone() })).unwrap_err();
+        assert_eq!(err, ContractError::ScalingProfileInUse { id: "bluechip".to_string(), asset_id: "b1".to_string() });
+        let err = execute(deps.as_mut(), mock_env(), gov.clone(), ExecuteMsg::RemoveScalingProfile { id: "bluechip".to_string() }).unwrap_err();
+        assert_eq!(err, ContractError::ScalingProfileInUse { id: "bluechip".to_string(), asset_id: "b1".to_string() });
+        let err = execute(deps.as_mut(), mock_env(), gov.clone(), ExecuteMsg::UpdateAsset { id: "b1".to_string(), update: AssetUpdate { claim_deadline: Some(10), scaling_profile_id: Some("nope".to_string()), ..AssetUpdate::default() } }).unwrap_err();
+        assert!(matches!(err, ContractError::AssetActivated { .. }));
+
+        // unused profiles can go
+        execute(deps.as_mut(), mock_env(), gov.clone(), ExecuteMsg::RemoveScalingProfile { id: "toxic".to_string() }).unwrap();
+        let err = execute(deps.as_mut(), mock_env(), gov, ExecuteMsg::RemoveScalingProfile { id: "toxic".to_string() }).unwrap_err();
+        assert_eq!(err, ContractError::UnknownScalingProfile { id: "toxic".to_string() });
+    }
+
+    #[test]
+    fn snapshot_epochs_follow_registration() {
+        use crate::{ContractError, QueryMsg, SnapshotEpoch};
+        let mut deps = mock_dependencies();
+        let gov = mock_info("gov", &[]);
+        instantiate(deps.as_mut(), mock_env(), gov.clone(), InstantiateMsg { governance_addr: "gov".to_string(), allow_missing_ubs: Some(true), scaling_profiles: Some(test_profiles()) }).unwrap();
+        let late = SnapshotEpoch { epoch: 1, merkle_root: "root1".to_string(), snapshot_height: 20, activation_height: 30, claim_deadline: Some(40), total_supply: None, ..Default::default() };
+        let asset = RegisteredAsset {
+            id: "e1".to_string(),
+            source_chain: "kaiyo-1".to_string(),
+            source_denom: "ibc/ep".to_string(),
+            snapshot_height: 10,
+            merkle_root: "root0".to_string(),
+            ubs_report_hash: Some("h".to_string()),
+            scaling_profile_id: "clean".to_string(),
+            activation_height: 0,
+            sanitized_approved: true,
+            merkle_version: MerkleVersion::V1,
+            claim_deadline: None,
+            total_supply: None,
+            lifecycle: Default::default(),
+            epochs: vec![late.clone()],
+        };
+        // epochs only come from AddSnapshotEpoch
+        execute(deps.as_mut(), mock_env(), gov.clone(), ExecuteMsg::RegisterAsset { asset }).unwrap();
+        let epochs = |deps: &cosmwasm_std::OwnedDeps<_, _, _>| -> Vec<SnapshotEpoch> { cosmwasm_std::from_binary(&query(deps.as_ref(), mock_env(), QueryMsg::SnapshotEpochs { id: "e1".to_string() }).unwrap()).unwrap() };
+        assert_eq!(epochs(&deps).iter().map(|e| e.epoch).collect::<Vec<_>>(), vec![0]);
+
+        let add = |e: SnapshotEpoch| ExecuteMsg::AddSnapshotEpoch { id: "e1".to_string(), epoch: e };
+        assert!(matches!(execute(deps.as_mut(), mock_env(), mock_info("notgov", &[]), add(late.clone())).unwrap_err(), ContractError::Unauthorized { .. }));
+        let err = execute(deps.as_mut(), mock_env(), gov.clone(), add(SnapshotEpoch { claim_deadline: Some(30), ..late.clone() })).unwrap_err();
+        assert_eq!(err, ContractError::InvalidClaimDeadline);
+        let err = execute(deps.as_mut(), mock_env(), gov.clone(), add(SnapshotEpoch { snapshot_height: 10, ..late.clone() })).unwrap_err();
+        assert!(matches!(err, ContractError::InvalidSnapshotEpoch(_)));
+        let res = execute(deps.as_mut(), mock_env(), gov.clone(), add(late.clone())).unwrap();
+        assert_eq!(res.attributes.iter().find(|a| a.key == "version").unwrap().value, "2");
+        assert_eq!(epochs(&deps)[1], late);
+
+        // each later epoch is approved on its own, once
+        assert!(epochs(&deps)[0].sanitized_approved);
+        let approve = |epoch: u32| ExecuteMsg::ApproveSanitized { id: "e1".to_string(), ubs_report_hash: "h1".to_string(), epoch: Some(epoch) };
+        assert!(matches!(execute(deps.as_mut(), mock_env(), mock_info("notgov", &[]), approve(1)).unwrap_err(), ContractError::Unauthorized { .. }));
+        assert!(matches!(execute(deps.as_mut(), mock_env(), gov.clone(), approve(2)).unwrap_err(), ContractError::InvalidSnapshotEpoch(_)));
+        let res = execute(deps.as_mut(), mock_env(), gov.clone(), approve(1)).unwrap();
+        assert_eq!(res.attributes.iter().find(|a| a.key == "epoch").unwrap().value, "1");
+        let e1 = epochs(&deps)[1].clone();
+        assert_eq!((e1.sanitized_approved, e1.ubs_report_hash.as_deref(), e1.approved_by.len()), (true, Some("h1"), 1));
+        assert!(matches!(execute(deps.as_mut(), mock_env(), gov.clone(), approve(1)).unwrap_err(), ContractError::InvalidSnapshotEpoch(_)));
+
+        // retired assets take no new epochs
+        execute(deps.as_mut(), mock_env(), gov.clone(), ExecuteMsg::DeprecateAsset { id: "e1".to_string() }).unwrap();
+        let err = execute(deps.as_mut(), mock_env(), gov, add(SnapshotEpoch { epoch: 2, merkle_root: "root2".to_string(), snapshot_height: 50, ..late })).unwrap_err();
+        assert!(matches!(err, ContractError::InvalidAssetTransition { .. }));
+    }
+
+    #[test]
+    fn committee_approves_at_threshold_and_disputes_conflicts() {
+        use crate::{AssetState, ContractError, PendingAttestationsResponse, QueryMsg, SnapshotEpoch};
+        let mut deps = mock_dependencies();
+        let gov = mock_info("gov", &[]);
+        instantiate(deps.as_mut(), mock_env(), gov.clone(), InstantiateMsg { governance_addr: "gov".to_string(), allow_missing_ubs: Some(true), scaling_profiles: Some(test_profiles()) }).unwrap();
+        let asset = |id: &str, approved: bool| RegisteredAsset {
+            id: id.to_string(),
+            source_chain: "kaiyo-1".to_string(),
+            source_denom: format!("ibc/{}", id),
+            snapshot_height: 10,
+            merkle_root: format!("root-{}", id),
+            ubs_report_hash: None,
+            scaling_profile_id: "clean".to_string(),
+            activation_height: 0,
+            sanitized_approved: approved,
+            merkle_version: MerkleVersion::V1,
+            claim_deadline: None,
+            total_supply: None,
+            lifecycle: Default::default(),
+            epochs: vec![],
+        };
+        let members = vec!["member1".to_string(), "member2".to_string(), "member3".to_string()];
+        let err = execute(deps.as_mut(), mock_env(), gov.clone(), ExecuteMsg::SetSanitizationCommittee { members: members.clone(), threshold: 4 }).unwrap_err();
+        assert!(matches!(err, ContractError::InvalidCommittee(_)));
+        execute(deps.as_mut(), mock_env(), gov.clone(), ExecuteMsg::SetSanitizationCommittee { members, threshold: 2 }).unwrap();
+
+        // registration can no longer skip the committee
+        let err = execute(deps.as_mut(), mock_env(), gov.clone(), ExecuteMsg::RegisterAsset { asset: asset("c1", true) }).unwrap_err();
+        assert_eq!(err, ContractError::CommitteeApprovalRequired);
+        execute(deps.as_mut(), mock_env(), gov.clone(), ExecuteMsg::RegisterAsset { asset: asset("c1", false) }).unwrap();
+        execute(deps.as_mut(), mock_env(), gov.clone(), ExecuteMsg::RegisterAsset { asset: asset("c2", false) }).unwrap();
+
+        let get_asset = |deps: &cosmwasm_std::OwnedDeps<_, _, _>, id: &str| -> RegisteredAsset { cosmwasm_std::from_binary(&query(deps.as_ref(), mock_env(), QueryMsg::GetAsset { id: id.to_string() }).unwrap()).unwrap() };
+        let approve = |id: &str, hash: &str| ExecuteMsg::ApproveSanitized { id: id.to_string(), ubs_report_hash: hash.to_string(), epoch: None };
+        let err = execute(deps.as_mut(), mock_env(), gov.clone(), approve("c1", "h")).unwrap_err();
+        assert_eq!(err, ContractError::NotCommitteeMember { address: "gov".to_string() });
+        execute(deps.as_mut(), mock_env(), mock_info("member1", &[]), approve("c1", "h")).unwrap();
+        let err = execute(deps.as_mut(), mock_env(), mock_info("member1", &[]), approve("c1", "h")).unwrap_err();
+        assert!(matches!(err, ContractError::AlreadyAttested { .. }));
+        let pending = |deps: &cosmwasm_std::OwnedDeps<_, _, _>, id: &str| -> PendingAttestationsResponse { cosmwasm_std::from_binary(&query(deps.as_ref(), mock_env(), QueryMsg::PendingAttestations { id: id.to_string(), epoch: None }).unwrap()).unwrap() };
+        let p = pending(&deps, "c1");
+        assert_eq!((p.state, p.threshold, p.attestations.len()), (AssetState::Proposed, Some(2), 1));
+        assert!(!get_asset(&deps, "c1").sanitized_approved);
+
+        // the second matching attestation approves
+        let res = execute(deps.as_mut(), mock_env(), mock_info("member2", &[]), approve("c1", "h")).unwrap();
+        assert_eq!(res.attributes.iter().find(|a| a.key == "approvers").unwrap().value, "member1,member2");
+        let a = get_asset(&deps, "c1");
+        assert!(a.sanitized_approved);
+        assert_eq!(a.lifecycle.approved_by.len(), 2);
+        assert!(pending(&deps, "c1").attestations.is_empty());
+
+        // a later epoch of the active asset gets its own round, disputes included
+        let epoch = SnapshotEpoch { epoch: 1, merkle_root: "root-c1-1".to_string(), snapshot_height: 20, activation_height: 30, ..Default::default() };
+        execute(deps.as_mut(), mock_env(), gov.clone(), ExecuteMsg::AddSnapshotEpoch { id: "c1".to_string(), epoch }).unwrap();
+        let approve_epoch = |hash: &str| ExecuteMsg::ApproveSanitized { id: "c1".to_string(), ubs_report_hash: hash.to_string(), epoch: Some(1) };
+        let epoch_round = |deps: &cosmwasm_std::OwnedDeps<_, _, _>| -> PendingAttestationsResponse { cosmwasm_std::from_binary(&query(deps.as_ref(), mock_env(), QueryMsg::PendingAttestations { id: "c1".to_string(), epoch: Some(1) }).unwrap()).unwrap() };
+        execute(deps.as_mut(), mock_env(), mock_info("member1", &[]), approve_epoch("h1")).unwrap();
+        execute(deps.as_mut(), mock_env(), mock_info("member2", &[]), approve_epoch("other")).unwrap();
+        let p = epoch_round(&deps);
+        assert_eq!((p.epoch, p.state, p.attestations.len()), (1, AssetState::Disputed, 2));
+        assert_eq!(get_asset(&deps, "c1").lifecycle.state, AssetState::Active);
+        assert!(matches!(execute(deps.as_mut(), mock_env(), mock_info("member3", &[]), approve_epoch("h1")).unwrap_err(), ContractError::InvalidSnapshotEpoch(_)));
+        execute(deps.as_mut(), mock_env(), gov.clone(), ExecuteMsg::ResolveSanitizationDispute { id: "c1".to_string(), epoch: Some(1) }).unwrap();
+        execute(deps.as_mut(), mock_env(), mock_info("member1", &[]), approve_epoch("h1")).unwrap();
+        let res = execute(deps.as_mut(), mock_env(), mock_info("member3", &[]), approve_epoch("h1")).unwrap();
+        assert_eq!(res.attributes.iter().find(|a| a.key == "approvers").unwrap().value, "member1,member3");
+        let a = get_asset(&deps, "c1");
+        assert_eq!((a.epochs[0].sanitized_approved, a.epochs[0].ubs_report_hash.as_deref(), a.lifecycle.approved_by.len()), (true, Some("h1"), 2));
+        assert_eq!(epoch_round(&deps).state, AssetState::Approved);
+
+        // a conflicting hash disputes the asset until governance resolves it
+        execute(deps.as_mut(), mock_env(), mock_info("member1", &[]), approve("c2", "h")).unwrap();
+        execute(deps.as_mut(), mock_env(), mock_info("member3", &[]), approve("c2", "other")).unwrap();
+        let open: Vec<PendingAttestationsResponse> = cosmwasm_std::from_binary(&query(deps.as_ref(), mock_env(), QueryMsg::AssetsPendingApproval { start_after: None, limit: None }).unwrap()).unwrap();
+        assert_eq!(open.iter().map(|p| (p.id.as_str(), p.epoch, p.state, p.attestations.len())).collect::<Vec<_>>(), vec![("c2", 0, AssetState::Disputed, 2)]);
+        let err = execute(deps.as_mut(), mock_env(), mock_info("member2", &[]), approve("c2", "h")).unwrap_err();
+        assert!(matches!(err, ContractError::InvalidAssetTransition { state: AssetState::Disputed, .. }));
+        assert!(!get_asset(&deps, "c2").sanitized_approved);
+        execute(deps.as_mut(), mock_env(), gov.clone(), ExecuteMsg::ResolveSanitizationDispute { id: "c2".to_string(), epoch: None }).unwrap();
+        let p = pending(&deps, "c2");
+        assert_eq!((p.state, p.attestations.len()), (AssetState::Proposed, 0));
+
+        // shrinking the committee under a round's attestation count is finalized by anyone
+        let all = vec!["member1".to_string(), "member2".to_string(), "member3".to_string()];
+        execute(deps.as_mut(), mock_env(), gov.clone(), ExecuteMsg::SetSanitizationCommittee { members: all, threshold: 3 }).unwrap();
+        execute(deps.as_mut(), mock_env(), mock_info("member1", &[]), approve("c2", "h")).unwrap();
+        execute(deps.as_mut(), mock_env(), mock_info("member2", &[]), approve("c2", "h")).unwrap();
+        let finalize = ExecuteMsg::FinalizeAttestations { id: "c2".to_string(), epoch: None };
+        let err = execute(deps.as_mut(), mock_env(), mock_info("anyone", &[]), finalize.clone()).unwrap_err();
+        assert_eq!(err, ContractError::AttestationThresholdNotMet { id: "c2".to_string(), epoch: 0, attestations: 2, threshold: 3 });
+        execute(deps.as_mut(), mock_env(), gov, ExecuteMsg::SetSanitizationCommittee { members: vec!["member1".to_string(), "member2".to_string()], threshold: 2 }).unwrap();
+        let res = execute(deps.as_mut(), mock_env(), mock_info("anyone", &[]), finalize.clone()).unwrap();
+        assert_eq!(res.attributes.iter().find(|a| a.key == "approvers").unwrap().value, "member1,member2");
+        let a = get_asset(&deps, "c2");
+        assert_eq!((a.sanitized_approved, a.lifecycle.state, a.ubs_report_hash.as_deref()), (true, AssetState::Active, Some("h")));
+        assert!(matches!(execute(deps.as_mut(), mock_env(), mock_info("anyone", &[]), finalize).unwrap_err(), ContractError::InvalidAssetTransition { .. }));
+    }
+
+    #[test]
+    fn migration_backfills_and_indexes_in_batches() {
+        use crate::{AssetFilter, AssetState, MigrateMsg, QueryMsg};
+        let mut deps = mock_dependencies();
+        // pre-cw2 instance: assets stored without lifecycle or asset indexes
+        crate::GOVERNANCE.save(deps.as_mut().storage, &Addr::unchecked("gov")).unwrap();
+        let legacy: cw_storage_plus::Map<&str, RegisteredAsset> = cw_storage_plus::Map::new("reg_assets");
+        for i in 0..60 {
+            let id = format!("a{:02}", i);
+            let asset = RegisteredAsset { id: id.clone(), source_chain: "kaiyo-1".to_string(), source_denom: "ibc/usk".to_string(), snapshot_height: 1, merkle_root: "root".to_string(), ubs_report_hash: Some("h".to_string()), scaling_profile_id: "clean".to_string(), activation_height: 0, sanitized_approved: i % 2 == 0, merkle_version: MerkleVersion::V1, claim_deadline: None, total_supply: None, lifecycle: Default::default(), epochs: vec![] };
+            legacy.save(deps.as_mut().storage, id.as_str(), &asset).unwrap();
+        }
+
+        let res = crate::migrate(deps.as_mut(), mock_env(), MigrateMsg {}).unwrap();
+        assert!(res.attributes.iter().any(|a| a.key == "migration_done" && a.value == "false"));
+        let err = execute(deps.as_mut(), mock_env(), mock_info("gov", &[]), ExecuteMsg::DeprecateAsset { id: "a00".to_string() }).unwrap_err();
+        assert_eq!(err, crate::ContractError::MigrationPending);
+
+        let res = execute(deps.as_mut(), mock_env(), mock_info("anyone", &[]), ExecuteMsg::MigrateBatch { limit: None }).unwrap();
+        assert!(res.attributes.iter().any(|a| a.key == "migration_done" && a.value == "true"));
+        let err = execute(deps.as_mut(), mock_env(), mock_info("anyone", &[]), ExecuteMsg::MigrateBatch { limit: None }).unwrap_err();
+        assert_eq!(err, crate::ContractError::NoPendingMigration);
+
+        let usk = AssetFilter { source_denom: Some("ibc/usk".to_string()), ..AssetFilter::default() };
+        let listed: crate::ListAssetsResponse = cosmwasm_std::from_binary(&query(deps.as_ref(), mock_env(), QueryMsg::ListAssets { start_after: Some("a55".to_string()), limit: None, filter: Some(usk) }).unwrap()).unwrap();
+        let states: Vec<(String, u64, AssetState)> = listed.assets.into_iter().map(|a| (a.id, a.lifecycle.version, a.lifecycle.state)).collect();
+        assert_eq!(states, vec![("a56".to_string(), 1, AssetState::Active), ("a57".to_string(), 1, AssetState::Proposed), ("a58".to_string(), 1, AssetState::Active), ("a59".to_string(), 1, AssetState::Proposed)]);
+        let by_profile = crate::assets().idx.scaling_profile_id.prefix("clean".to_string()).range(deps.as_ref().storage, None, None, cosmwasm_std::Order::Ascending).count();
+        assert_eq!(by_profile, 60);
+
+        // a filter matching nothing stops after `limit * 10` candidates and hands back a cursor
+        let none = AssetFilter { scaling_profile_id: Some("nope".to_string()), ..AssetFilter::default() };
+        let listed: crate::ListAssetsResponse = cosmwasm_std::from_binary(&query(deps.as_ref(), mock_env(), QueryMsg::ListAssets { start_after: None, limit: Some(2), filter: Some(none) }).unwrap()).unwrap();
+        assert_eq!((listed.assets.len(), listed.next_start_after.as_deref()), (0, Some("a19")));
+    }
+
+    #[test]
+    fn assets_stored_before_merkle_version_read_as_v1() {
+        let legacy = r#"{"id":"a0","source_chain":"kaiyo-1","source_denom":"ibc/old","snapshot_height":1,"merkle_root":"0x00","ubs_report_hash":null,"scaling_profile_id":"clean","activation_height":0,"sanitized_approved":true}"#;
+        let asset: RegisteredAsset = cosmwasm_std::from_slice(legacy.as_bytes()).unwrap();
+        assert_eq!(asset.merkle_version, MerkleVersion::V1);
+        assert!(asset.epochs.is_empty());
+        let v2: RegisteredAsset = cosmwasm_std::from_slice(legacy.replace("}", r#","merkle_version":"v2"}"#).as_bytes()).unwrap();
+        assert_eq!(v2.merkle_version, MerkleVersion::V2);
     }
 }
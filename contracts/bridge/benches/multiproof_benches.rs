@@ -0,0 +1,132 @@
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use sha2::{Sha256, Digest};
+
+// Compares k single-leaf proofs against one multiproof for the same k leaves of a 1024-leaf tree.
+// Each sha256 over two nodes is what the contract pays gas for, so hash counts are printed too.
+
+fn build_leaf(i: u64) -> [u8;32] {
+    let mut hasher = Sha256::new();
+    hasher.update(&i.to_be_bytes());
+    let res = hasher.finalize();
+    let mut arr = [0u8;32]; arr.copy_from_slice(&res); arr
+}
+
+fn hash_pair(left: &[u8;32], right: &[u8;32], count: &mut usize) -> [u8;32] {
+    *count += 1;
+    let mut h = Sha256::new(); h.update(left); h.update(right);
+    let mut arr = [0u8;32]; arr.copy_from_slice(&h.finalize()); arr
+}
+
+fn build_levels(leaves: &[[u8;32]]) -> Vec<Vec<[u8;32]>> {
+    let mut levels: Vec<Vec<[u8;32]>> = vec![leaves.to_vec()];
+    let mut unused = 0usize;
+    while levels.last().unwrap().len() > 1 {
+        let prev = levels.last().unwrap();
+        let next = prev.chunks(2).map(|p| hash_pair(&p[0], p.get(1).unwrap_or(&p[0]), &mut unused)).collect();
+        levels.push(next);
+    }
+    levels
+}
+
+fn single_proof(levels: &[Vec<[u8;32]>], idx: usize) -> Vec<([u8;32], bool)> {
+    let mut proof = vec![];
+    let mut index = idx;
+    for level_nodes in &levels[..levels.len() - 1] {
+        let pair_index = if index % 2 == 0 { index + 1 } else { index - 1 };
+        let sibling = if pair_index < level_nodes.len() { level_nodes[pair_index] } else { level_nodes[index] };
+        proof.push((sibling, pair_index < index));
+        index /= 2;
+    }
+    proof
+}
+
+fn verify_single(leaf: &[u8;32], proof: &[([u8;32], bool)], root: &[u8;32], count: &mut usize) -> bool {
+    let mut cur = *leaf;
+    for (sib, is_left) in proof.iter() {
+        cur = if *is_left { hash_pair(sib, &cur, count) } else { hash_pair(&cur, sib, count) };
+    }
+    &cur == root
+}
+
+fn build_multiproof(levels: &[Vec<[u8;32]>], indices: &[usize]) -> Vec<[u8;32]> {
+    let mut known = indices.to_vec();
+    let mut hashes = vec![];
+    for level in &levels[..levels.len() - 1] {
+        let mut next = vec![];
+        let mut i = 0;
+        while i < known.len() {
+            let idx = known[i];
+            if idx % 2 == 0 {
+                if idx + 1 == level.len() {
+                } else if i + 1 < known.len() && known[i + 1] == idx + 1 {
+                    i += 1;
+                } else {
+                    hashes.push(level[idx + 1]);
+                }
+            } else {
+                hashes.push(level[idx - 1]);
+            }
+            next.push(idx / 2);
+            i += 1;
+        }
+        known = next;
+    }
+    hashes
+}
+
+// same algorithm as `core::multiproof::multiproof_root` in the bridge
+fn verify_multi(leaves: &[(u64, [u8;32])], leaf_count: u64, proof: &[[u8;32]], root: &[u8;32], count: &mut usize) -> bool {
+    let mut layer = leaves.to_vec();
+    let mut width = leaf_count;
+    let mut used = 0usize;
+    while width > 1 {
+        let mut next = Vec::with_capacity(layer.len());
+        let mut i = 0;
+        while i < layer.len() {
+            let (idx, h) = layer[i];
+            let node = if idx % 2 == 0 {
+                if idx + 1 == width { hash_pair(&h, &h, count) }
+                else if i + 1 < layer.len() && layer[i + 1].0 == idx + 1 { i += 1; hash_pair(&h, &layer[i].1, count) }
+                else { used += 1; hash_pair(&h, &proof[used - 1], count) }
+            } else { used += 1; hash_pair(&proof[used - 1], &h, count) };
+            next.push((idx / 2, node));
+            i += 1;
+        }
+        layer = next;
+        width = (width + 1) / 2;
+    }
+    used == proof.len() && &layer[0].1 == root
+}
+
+fn bench_multiproof(c: &mut Criterion) {
+    let n = 1024u64;
+    let leaves: Vec<[u8;32]> = (0..n).map(build_leaf).collect();
+    let levels = build_levels(&leaves);
+    let root = levels.last().unwrap()[0];
+
+    let mut group = c.benchmark_group("batch_claim_proofs");
+    for k in [8usize, 64, 256] {
+        // a consolidating operator's accounts are spread across the tree
+        let indices: Vec<usize> = (0..k).map(|i| i * (n as usize / k)).collect();
+        let singles: Vec<Vec<([u8;32], bool)>> = indices.iter().map(|i| single_proof(&levels, *i)).collect();
+        let multi = build_multiproof(&levels, &indices);
+        let multi_leaves: Vec<(u64, [u8;32])> = indices.iter().map(|i| (*i as u64, leaves[*i])).collect();
+
+        let mut single_hashes = 0usize;
+        for (i, p) in indices.iter().zip(singles.iter()) { assert!(verify_single(&leaves[*i], p, &root, &mut single_hashes)); }
+        let mut multi_hashes = 0usize;
+        assert!(verify_multi(&multi_leaves, n, &multi, &root, &mut multi_hashes));
+        println!("k={}: single proofs {} hashes / {} siblings, multiproof {} hashes / {} siblings", k, single_hashes, singles.iter().map(|p| p.len()).sum::<usize>(), multi_hashes, multi.len());
+
+        group.bench_with_input(BenchmarkId::new("single_proofs", k), &k, |b, _| {
+            b.iter(|| { let mut cnt = 0; for (i, p) in indices.iter().zip(singles.iter()) { assert!(verify_single(&leaves[*i], p, &root, &mut cnt)); } });
+        });
+        group.bench_with_input(BenchmarkId::new("multiproof", k), &k, |b, _| {
+            b.iter(|| { let mut cnt = 0; assert!(verify_multi(&multi_leaves, n, &multi, &root, &mut cnt)); });
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_multiproof);
+criterion_main!(benches);
@@ -1,5 +1,5 @@
 use serde::{Deserialize, Serialize};
-use cosmwasm_std::{Addr, Uint128};
+use cosmwasm_std::{StdError, Uint128};
 use thiserror::Error;
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -34,8 +34,12 @@ pub struct SanitizationResult {
     pub report_hash: Option<String>,
 }
 
-#[derive(Debug, Error)]
+/// Bridge error type; also the contract's `ContractError`. Variants are stable so relayers
+/// and frontends can match on them instead of message strings.
+#[derive(Debug, Error, PartialEq)]
 pub enum BridgeError {
+    #[error("{0}")]
+    Std(#[from] StdError),
     #[error("lightclient-error: {0}")]
     LightClient(String),
     #[error("ubs-error: {0}")]
@@ -44,6 +48,114 @@ pub enum BridgeError {
     Proof(String),
     #[error("invariant-violation: {0}")]
     Invariant(String),
+    #[error("unauthorized: {action}")]
+    Unauthorized { action: String },
+    #[error("already claimed")]
+    AlreadyClaimed,
+    #[error("origin event already processed")]
+    OriginAlreadyProcessed,
+    #[error("invalid balance in snapshot")]
+    InvalidSnapshotBalance,
+    #[error("snapshot hash mismatch")]
+    SnapshotHashMismatch,
+    #[error("invalid snapshot_hash")]
+    InvalidSnapshotHash,
+    #[error("claimant does not control snapshot address")]
+    ClaimantNotOwner,
+    #[error("invalid claim authorization: {0}")]
+    InvalidClaimAuthorization(String),
+    #[error("asset not sanitized")]
+    AssetNotSanitized,
+    #[error("ubs report hash missing on registered asset")]
+    UbsReportHashMissing,
+    #[error("ubs report hash mismatch")]
+    UbsReportHashMismatch,
+    #[error("asset claim not activated yet")]
+    NotActivated,
+    #[error("claim deadline passed")]
+    ClaimDeadlinePassed,
+    #[error("claim deadline has not passed")]
+    ClaimDeadlineNotPassed,
+    #[error("unclaimed supply of {asset_id} already swept")]
+    AlreadySwept { asset_id: String },
+    #[error("quarantine case {id} not found")]
+    QuarantineCaseNotFound { id: u64 },
+    #[error("quarantine case {id} is already closed")]
+    QuarantineCaseClosed { id: u64 },
+    #[error("no light client for origin chain {chain_id}")]
+    LightClientNotFound { chain_id: String },
+    #[error("no verified root for {chain_id} at height {height}")]
+    ConsensusRootNotFound { chain_id: String, height: u64 },
+    #[error("origin chain {chain_id} requires an origin proof")]
+    OriginProofRequired { chain_id: String },
+    #[error("asset {asset_id} is not registered")]
+    AssetNotRegistered { asset_id: String },
+    #[error("asset {asset_id} has no snapshot epoch {epoch}")]
+    UnknownSnapshotEpoch { asset_id: String, epoch: u32 },
+    #[error("snapshot epoch {epoch} of {asset_id} is not sanitized")]
+    EpochNotSanitized { asset_id: String, epoch: u32 },
+    #[error("ledger hook {contract} already registered")]
+    LedgerHookExists { contract: String },
+    #[error("ledger hook {contract} not found")]
+    LedgerHookNotFound { contract: String },
+    #[error("invalid merkle proof")]
+    InvalidMerkleProof,
+    #[error("unknown scaling profile: {id}")]
+    UnknownScalingProfile { id: String },
+    #[error("claimed {denom} amount does not match entitlement")]
+    EntitlementMismatch { denom: String },
+    #[error("toxic cap exceeded")]
+    ToxicCapExceeded,
+    #[error("toxic asset requires sink")]
+    ToxicSinkRequired,
+    #[error("anomaly threshold exceeded and no sink configured")]
+    AnomalyWithoutSink,
+    #[error("state migration in progress; run MigrateBatch until it completes")]
+    MigrationPending,
+    #[error("no state migration pending")]
+    NoPendingMigration,
+    #[error("ledger checkpoint in progress; run ContinueCheckpoint until it completes")]
+    CheckpointPending,
+    #[error("no ledger checkpoint pending")]
+    NoPendingCheckpoint,
+    #[error("ubs oracle report not available")]
+    UbsReportUnavailable,
+    #[error("ubs decision rejected: {report_hash}")]
+    UbsRejected { report_hash: String },
+    #[error("caller not system-allowed")]
+    NotSystemAllowed,
+    #[error("insufficient energy")]
+    InsufficientEnergy,
+    #[error("energy is locked by a vesting schedule")]
+    EnergyLocked,
+    #[error("owner has not granted the caller an energy allowance")]
+    NoEnergyAllowance,
+    #[error("energy allowance expired")]
+    EnergyAllowanceExpired,
+    #[error("energy allowance exceeded for this period")]
+    EnergyAllowanceExceeded,
+    #[error("system contract epoch cap exceeded")]
+    SystemCapExceeded,
+    #[error("no pending governance proposal")]
+    NoPendingGovernance,
+    #[error("only the pending governance can accept")]
+    NotPendingGovernance,
+    #[error("governance proposal expired")]
+    GovernanceProposalExpired,
+    #[error("{action} is paused")]
+    Paused { action: String },
+    #[error("{action} is paused for asset {asset_id}")]
+    AssetPaused { asset_id: String, action: String },
+    #[error("queued claim {id} not found")]
+    QueuedClaimNotFound { id: u64 },
+    #[error("batch claim is empty")]
+    EmptyBatch,
+    #[error("invalid config: {0}")]
+    InvalidConfig(String),
+    #[error("cannot migrate from contract {previous_contract}")]
+    CannotMigrate { previous_contract: String },
+    #[error("cannot migrate from newer version {previous_version}")]
+    CannotMigrateVersion { previous_version: String },
 }
 
 pub trait LightClient {
@@ -1,2 +1,5 @@
 pub mod bridge_architecture;
+pub mod multiproof;
+pub mod ownership;
 pub mod refactor_state;
+pub mod scaling;
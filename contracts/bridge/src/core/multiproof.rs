@@ -0,0 +1,28 @@
+use aln_merkle::MerkleVersion;
+use cosmwasm_std::Binary;
+use serde::{Deserialize, Serialize};
+
+/// Compact proof for several leaves of one snapshot tree (see `aln_tools multiproof`).
+/// `indices` are the proven leaf positions, strictly ascending; `hashes` are the sibling nodes
+/// that cannot be derived from the proven leaves, in the order they are consumed (level by
+/// level, left to right). An odd node at the end of a level needs no sibling: v1 pairs it with
+/// itself, v2 promotes it.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct MultiProof {
+    pub leaf_count: u64,
+    pub indices: Vec<u64>,
+    pub hashes: Vec<Binary>,
+}
+
+/// Recompute the root from `leaves` (one per `mp.indices`, same order). Returns `None` when the
+/// proof is malformed: bad lengths, unsorted or out-of-range indices, missing or unused hashes.
+pub fn multiproof_root(version: MerkleVersion, leaves: &[[u8; 32]], mp: &MultiProof) -> Option<[u8; 32]> {
+    let mut proof: Vec<[u8; 32]> = Vec::with_capacity(mp.hashes.len());
+    for h in mp.hashes.iter() {
+        if h.len() != 32 { return None; }
+        let mut arr = [0u8; 32];
+        arr.copy_from_slice(h.as_slice());
+        proof.push(arr);
+    }
+    aln_merkle::multiproof_root(version, mp.leaf_count, &mp.indices, leaves, &proof).map(|(root, _)| root)
+}
@@ -0,0 +1,70 @@
+use bech32::FromBase32;
+use cosmwasm_std::{Addr, Api, Binary};
+use ripemd::Ripemd160;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use super::bridge_architecture::BridgeError;
+
+const CLAIM_AUTH_DOMAIN: &[u8] = b"aln-bridge-claim-v1";
+
+/// Signature by the origin-chain key controlling `snapshot.address`, authorizing a credit
+/// to a specific ALN recipient. `pubkey` is a 33-byte compressed secp256k1 key and
+/// `signature` the 64-byte (r || s) signature over `claim_auth_digest`.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct ClaimAuthorization {
+    pub pubkey: Binary,
+    pub signature: Binary,
+}
+
+fn bech32_payload(addr: &str) -> Option<Vec<u8>> {
+    let (_hrp, data, _variant) = bech32::decode(addr).ok()?;
+    Vec::<u8>::from_base32(&data).ok()
+}
+
+/// True when both addresses are the same account: identical strings, or bech32 addresses
+/// with the same payload under different prefixes (e.g. `kujira1…` and its ALN re-prefix).
+pub fn addresses_equivalent(origin_address: &str, aln_address: &str) -> bool {
+    if origin_address == aln_address {
+        return true;
+    }
+    match (bech32_payload(origin_address), bech32_payload(aln_address)) {
+        (Some(a), Some(b)) => a == b,
+        _ => false,
+    }
+}
+
+/// Cosmos SDK account id of a secp256k1 key: ripemd160(sha256(pubkey)).
+fn pubkey_account_id(pubkey: &[u8]) -> Vec<u8> {
+    let sha = Sha256::digest(pubkey);
+    Ripemd160::digest(sha).to_vec()
+}
+
+/// Message signed by the origin key. Binds the ALN chain, this bridge instance, the leaf
+/// and the recipient so an authorization cannot be replayed elsewhere or redirected.
+pub fn claim_auth_digest(chain_id: &str, bridge: &Addr, asset_id: &str, snapshot_hash: &str, recipient: &Addr) -> [u8; 32] {
+    let mut h = Sha256::new();
+    h.update(CLAIM_AUTH_DOMAIN);
+    for part in [chain_id, bridge.as_str(), asset_id, snapshot_hash, recipient.as_str()] {
+        h.update([0u8]);
+        h.update(part.as_bytes());
+    }
+    let mut out = [0u8; 32];
+    out.copy_from_slice(&h.finalize());
+    out
+}
+
+/// Check that `auth` was produced by the key behind `origin_address` over `digest`.
+pub fn verify_claim_authorization(api: &dyn Api, digest: &[u8; 32], auth: &ClaimAuthorization, origin_address: &str) -> Result<(), BridgeError> {
+    let origin_id = bech32_payload(origin_address).ok_or_else(|| BridgeError::InvalidClaimAuthorization("snapshot address is not bech32".to_string()))?;
+    if pubkey_account_id(auth.pubkey.as_slice()) != origin_id {
+        return Err(BridgeError::InvalidClaimAuthorization("key does not control snapshot address".to_string()));
+    }
+    let ok = api
+        .secp256k1_verify(digest, auth.signature.as_slice(), auth.pubkey.as_slice())
+        .map_err(|e| BridgeError::InvalidClaimAuthorization(e.to_string()))?;
+    if !ok {
+        return Err(BridgeError::InvalidClaimAuthorization("bad signature".to_string()));
+    }
+    Ok(())
+}
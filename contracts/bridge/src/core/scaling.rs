@@ -0,0 +1,41 @@
+use cosmwasm_std::{Decimal, StdResult, Uint128};
+use serde::{Deserialize, Serialize};
+
+/// Registry profile of an asset: `c_e`/`c_s` are the AU.ET and CSP compression factors, decimals
+/// convert source minimal units into ALN minimal units.
+pub use aln_registry::ScalingProfile;
+
+/// AU.ET / CSP amounts a proven snapshot balance is entitled to.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Entitlement {
+    pub auet: Uint128,
+    pub csp: Uint128,
+}
+
+impl Entitlement {
+    pub fn total(&self) -> u128 {
+        self.auet.u128() + self.csp.u128()
+    }
+}
+
+/// Same formula as `aln_tools allocations`: floor(B / 10^d_src * c * 10^d_aln), computed in integers.
+pub fn scale_balance(balance: Uint128, c: Decimal, source_decimals: u32, aln_decimals: u32) -> StdResult<Uint128> {
+    let up = Uint128::new(10u128.pow(aln_decimals));
+    let down = Uint128::new(10u128.pow(source_decimals));
+    let scaled = balance.checked_mul(up)?;
+    Ok((scaled * c) / down)
+}
+
+/// Scaled AU.ET and CSP, less the profile's haircut (rounded in the holder's favour).
+pub fn compute_entitlement(profile: &ScalingProfile, balance: Uint128) -> StdResult<Entitlement> {
+    let after_haircut = |scaled: Uint128| -> StdResult<Uint128> {
+        match profile.haircut {
+            Some(h) => Ok(scaled.checked_sub(scaled * h)?),
+            None => Ok(scaled),
+        }
+    };
+    Ok(Entitlement {
+        auet: after_haircut(scale_balance(balance, profile.c_e, profile.source_decimals, profile.aln_decimals)?)?,
+        csp: after_haircut(scale_balance(balance, profile.c_s, profile.source_decimals, profile.aln_decimals)?)?,
+    })
+}
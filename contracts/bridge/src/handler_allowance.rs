@@ -0,0 +1,196 @@
+use cosmwasm_std::{Addr, Deps, DepsMut, Empty, Env, MessageInfo, Order, Response, StdResult, Storage, Uint128};
+use cw_storage_plus::{Bound, Map};
+use cw_utils::Expiration;
+use serde::{Deserialize, Serialize};
+use crate::{ContractError, EnergyVector, GOVERNANCE};
+
+const DEFAULT_LIMIT: u32 = 10;
+const MAX_LIMIT: u32 = 30;
+
+/// What `spender` (a system contract) may debit from `owner` through `SystemConsume`: up to
+/// `amount` per `period_seconds` window counted from `period_start`, or `amount` in total when
+/// there is no period. Granted by the owner, like a cw20 allowance.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct EnergyAllowance {
+    pub owner: Addr,
+    pub spender: Addr,
+    pub amount: EnergyVector,
+    pub period_seconds: Option<u64>,
+    pub expires: Expiration,
+    pub period_start: u64,
+    /// Debited in the current period.
+    pub spent: EnergyVector,
+}
+
+/// Governance ceiling on what one system contract may debit across all owners per epoch.
+/// Epochs are fixed `epoch_seconds` windows of block time.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct SystemCap {
+    pub amount: EnergyVector,
+    pub epoch_seconds: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct SystemCapResponse {
+    pub addr: Addr,
+    pub cap: SystemCap,
+    pub epoch: u64,
+    /// Debited by `addr` in `epoch`.
+    pub used: EnergyVector,
+}
+
+pub const ENERGY_ALLOWANCES: Map<(&Addr, &Addr), EnergyAllowance> = Map::new("energy_allowances");
+/// (spender, owner) index over `ENERGY_ALLOWANCES`.
+pub const ENERGY_ALLOWANCES_BY_SPENDER: Map<(&Addr, &Addr), Empty> = Map::new("energy_allowances_by_spender");
+pub const SYSTEM_CAPS: Map<&Addr, SystemCap> = Map::new("system_caps");
+/// (epoch, debited) of the last epoch a system contract consumed in.
+const SYSTEM_CAP_USAGE: Map<&Addr, (u64, EnergyVector)> = Map::new("system_cap_usage");
+
+fn zero() -> EnergyVector {
+    EnergyVector { auet: Uint128::zero(), csp: Uint128::zero(), erp: Uint128::zero() }
+}
+
+fn add(a: &EnergyVector, b: &EnergyVector) -> EnergyVector {
+    EnergyVector { auet: a.auet + b.auet, csp: a.csp + b.csp, erp: a.erp + b.erp }
+}
+
+fn exceeds(v: &EnergyVector, max: &EnergyVector) -> bool {
+    v.auet > max.auet || v.csp > max.csp || v.erp > max.erp
+}
+
+/// Allowance as of `now`: a new period starts with nothing spent.
+fn current(mut a: EnergyAllowance, now: u64) -> EnergyAllowance {
+    if let Some(p) = a.period_seconds {
+        if now >= a.period_start + p {
+            a.period_start += (now - a.period_start) / p * p;
+            a.spent = zero();
+        }
+    }
+    a
+}
+
+fn epoch_usage(storage: &dyn Storage, addr: &Addr, epoch: u64) -> StdResult<EnergyVector> {
+    Ok(match SYSTEM_CAP_USAGE.may_load(storage, addr)? {
+        Some((e, used)) if e == epoch => used,
+        _ => zero(),
+    })
+}
+
+/// Charge a `SystemConsume` debit of `delta` against the owner's allowance for `spender` and
+/// the spender's epoch cap.
+pub fn spend(storage: &mut dyn Storage, env: &Env, owner: &Addr, spender: &Addr, delta: &EnergyVector) -> Result<(), ContractError> {
+    let now = env.block.time.seconds();
+    let allowance = ENERGY_ALLOWANCES.may_load(storage, (owner, spender))?.ok_or(ContractError::NoEnergyAllowance)?;
+    if allowance.expires.is_expired(&env.block) { return Err(ContractError::EnergyAllowanceExpired); }
+    let mut allowance = current(allowance, now);
+    allowance.spent = add(&allowance.spent, delta);
+    if exceeds(&allowance.spent, &allowance.amount) { return Err(ContractError::EnergyAllowanceExceeded); }
+    ENERGY_ALLOWANCES.save(storage, (owner, spender), &allowance)?;
+
+    if let Some(cap) = SYSTEM_CAPS.may_load(storage, spender)? {
+        let epoch = now / cap.epoch_seconds;
+        let used = add(&epoch_usage(storage, spender, epoch)?, delta);
+        if exceeds(&used, &cap.amount) { return Err(ContractError::SystemCapExceeded); }
+        SYSTEM_CAP_USAGE.save(storage, spender, &(epoch, used))?;
+    }
+    Ok(())
+}
+
+/// Owner grants (or replaces) `spender`'s allowance; the new allowance starts a fresh period.
+pub fn grant_energy_allowance(deps: DepsMut, env: Env, info: MessageInfo, spender: String, amount: EnergyVector, period_seconds: Option<u64>, expires: Option<Expiration>) -> Result<Response, ContractError> {
+    let spender = deps.api.addr_validate(&spender)?;
+    if spender == info.sender { return Err(ContractError::InvalidConfig("cannot grant an allowance to yourself".to_string())); }
+    if period_seconds == Some(0) { return Err(ContractError::InvalidConfig("allowance period must be non-zero".to_string())); }
+    let expires = expires.unwrap_or_default();
+    if expires.is_expired(&env.block) { return Err(ContractError::EnergyAllowanceExpired); }
+    let allowance = EnergyAllowance { owner: info.sender.clone(), spender: spender.clone(), amount, period_seconds, expires, period_start: env.block.time.seconds(), spent: zero() };
+    ENERGY_ALLOWANCES.save(deps.storage, (&info.sender, &spender), &allowance)?;
+    ENERGY_ALLOWANCES_BY_SPENDER.save(deps.storage, (&spender, &info.sender), &Empty {})?;
+    Ok(Response::new().add_attribute("action", "grant_energy_allowance").add_attribute("owner", info.sender).add_attribute("spender", spender))
+}
+
+pub fn revoke_energy_allowance(deps: DepsMut, info: MessageInfo, spender: String) -> Result<Response, ContractError> {
+    let spender = deps.api.addr_validate(&spender)?;
+    if !ENERGY_ALLOWANCES.has(deps.storage, (&info.sender, &spender)) { return Err(ContractError::NoEnergyAllowance); }
+    ENERGY_ALLOWANCES.remove(deps.storage, (&info.sender, &spender));
+    ENERGY_ALLOWANCES_BY_SPENDER.remove(deps.storage, (&spender, &info.sender));
+    Ok(Response::new().add_attribute("action", "revoke_energy_allowance").add_attribute("owner", info.sender).add_attribute("spender", spender))
+}
+
+/// Governance-only: set (or remove with `None`) a system contract's epoch cap.
+pub fn set_system_cap(deps: DepsMut, info: MessageInfo, addr: String, cap: Option<SystemCap>) -> Result<Response, ContractError> {
+    let gov = GOVERNANCE.load(deps.storage)?;
+    if info.sender != gov { return Err(ContractError::Unauthorized { action: "set_system_cap".to_string() }); }
+    let a = deps.api.addr_validate(&addr)?;
+    match &cap {
+        Some(c) => {
+            if c.epoch_seconds == 0 { return Err(ContractError::InvalidConfig("system cap epoch must be non-zero".to_string())); }
+            SYSTEM_CAPS.save(deps.storage, &a, c)?;
+        }
+        None => SYSTEM_CAPS.remove(deps.storage, &a),
+    }
+    Ok(Response::new().add_attribute("action", "set_system_cap").add_attribute("addr", addr).add_attribute("enabled", cap.is_some().to_string()))
+}
+
+/// Allowance with the current period applied, so `spent` reads zero once a period has rolled over.
+pub fn query_energy_allowance(deps: Deps, env: Env, owner: String, spender: String) -> StdResult<Option<EnergyAllowance>> {
+    let owner = deps.api.addr_validate(&owner)?;
+    let spender = deps.api.addr_validate(&spender)?;
+    Ok(ENERGY_ALLOWANCES.may_load(deps.storage, (&owner, &spender))?.map(|a| current(a, env.block.time.seconds())))
+}
+
+pub fn query_allowances_by_owner(deps: Deps, env: Env, owner: String, start_after: Option<String>, limit: Option<u32>) -> StdResult<Vec<EnergyAllowance>> {
+    let owner = deps.api.addr_validate(&owner)?;
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = match start_after {
+        Some(s) => Some(Bound::exclusive(deps.api.addr_validate(&s)?.as_bytes())),
+        None => None,
+    };
+    let now = env.block.time.seconds();
+    ENERGY_ALLOWANCES
+        .prefix(&owner)
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| item.map(|(_, a)| current(a, now)))
+        .collect()
+}
+
+pub fn query_allowances_by_spender(deps: Deps, env: Env, spender: String, start_after: Option<String>, limit: Option<u32>) -> StdResult<Vec<EnergyAllowance>> {
+    let spender = deps.api.addr_validate(&spender)?;
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = match start_after {
+        Some(s) => Some(Bound::exclusive(deps.api.addr_validate(&s)?.as_bytes())),
+        None => None,
+    };
+    let now = env.block.time.seconds();
+    ENERGY_ALLOWANCES_BY_SPENDER
+        .prefix(&spender)
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| {
+            let (owner, _) = item?;
+            Ok(current(ENERGY_ALLOWANCES.load(deps.storage, (&owner, &spender))?, now))
+        })
+        .collect()
+}
+
+pub fn query_system_cap(deps: Deps, env: Env, addr: String) -> StdResult<Option<SystemCapResponse>> {
+    let a = deps.api.addr_validate(&addr)?;
+    match SYSTEM_CAPS.may_load(deps.storage, &a)? {
+        Some(cap) => {
+            let epoch = env.block.time.seconds() / cap.epoch_seconds;
+            let used = epoch_usage(deps.storage, &a, epoch)?;
+            Ok(Some(SystemCapResponse { addr: a, cap, epoch, used }))
+        }
+        None => Ok(None),
+    }
+}
+
+pub fn query_system_caps(deps: Deps, start_after: Option<String>, limit: Option<u32>) -> StdResult<Vec<(Addr, SystemCap)>> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = match start_after {
+        Some(s) => Some(Bound::exclusive(deps.api.addr_validate(&s)?.as_bytes())),
+        None => None,
+    };
+    SYSTEM_CAPS.range(deps.storage, start, None, Order::Ascending).take(limit).collect()
+}
@@ -0,0 +1,50 @@
+use cosmwasm_std::{DepsMut, Env, Event, MessageInfo, Response};
+use serde::{Deserialize, Serialize};
+use crate::core::multiproof::{multiproof_root, MultiProof};
+use crate::{registry_asset, snapshot_epoch, snapshot_leaf, ClaimAuthorization, ContractError, LeafProof, SnapshotEntry};
+
+/// One leaf of a `BatchClaim`, in the same position as its index in `MultiProof::indices`.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct BatchClaimEntry {
+    pub snapshot: SnapshotEntry,
+    pub snapshot_hash: String,
+    /// Only honored together with `authorization`; defaults to the sender.
+    pub recipient: Option<String>,
+    pub authorization: Option<ClaimAuthorization>,
+    pub origin_tx_hash: Option<String>,
+    pub origin_nonce: Option<u64>,
+}
+
+pub fn batch_claim(mut deps: DepsMut, env: Env, info: MessageInfo, asset_id: String, epoch: u32, claims: Vec<BatchClaimEntry>, multiproof: MultiProof, ubs_report_hash: Option<String>) -> Result<Response, ContractError> {
+    if claims.is_empty() { return Err(ContractError::EmptyBatch); }
+
+    let asset = registry_asset(deps.as_ref(), asset_id.as_str())?;
+    let snap = snapshot_epoch(&asset, epoch)?;
+
+    // recompute every H_i, then check them all against the epoch root in one pass
+    let mut leaves: Vec<[u8; 32]> = Vec::with_capacity(claims.len());
+    for c in claims.iter() {
+        let leaf = snapshot_leaf(asset.merkle_version, &c.snapshot)?;
+        if format!("0x{}", hex::encode(leaf)) != c.snapshot_hash { return Err(ContractError::SnapshotHashMismatch); }
+        leaves.push(leaf);
+    }
+    let root = hex::decode(snap.merkle_root.trim_start_matches("0x")).map_err(|_| ContractError::InvalidMerkleProof)?;
+    match multiproof_root(asset.merkle_version, &leaves, &multiproof) {
+        Some(computed) if computed.as_slice() == root.as_slice() => {}
+        _ => return Err(ContractError::InvalidMerkleProof),
+    }
+
+    // each leaf then goes through the regular claim checks; any failure reverts the batch
+    let mut resp = Response::new().add_attribute("action", "batch_claim").add_attribute("asset_id", asset_id.clone()).add_attribute("epoch", epoch.to_string()).add_attribute("count", claims.len().to_string());
+    for c in claims {
+        let recipient = match (&c.authorization, &c.recipient) {
+            (Some(_), Some(r)) => deps.api.addr_validate(r)?,
+            (None, Some(_)) => return Err(ContractError::InvalidClaimAuthorization("recipient requires an authorization".to_string())),
+            _ => info.sender.clone(),
+        };
+        let res = crate::claim(deps.branch(), env.clone(), recipient, c.authorization, asset_id.clone(), epoch, c.snapshot, c.snapshot_hash, LeafProof::InMultiproof, None, None, c.origin_tx_hash, c.origin_nonce, ubs_report_hash.clone())?;
+        // per-leaf events (e.g. `aln_quarantine`) are forwarded as they are
+        resp = resp.add_submessages(res.messages).add_event(Event::new("aln_batch_claim_leaf").add_attributes(res.attributes)).add_events(res.events);
+    }
+    Ok(resp)
+}
@@ -0,0 +1,201 @@
+use cosmwasm_std::{Addr, Deps, Env, StdResult, Uint128};
+use crate::core::ownership::verify_claim_authorization;
+use crate::{addresses_equivalent, claim_auth_digest, compute_entitlement, decode_proof, energy_totals_after, handler_downgrade, handler_rate_limit, migrations, refactor_is_processed, sanitize_claim, snapshot_leaf, verify_merkle_proof};
+use crate::{ClaimAuthorization, ContractError, DowngradeTreatment, Entitlement, LeafProof, QuarantineReason, SnapshotEntry};
+use crate::{registry_asset, registry_profile, snapshot_epoch, ANOMALY_THRESHOLD_AMOUNT, CLAIMED_LEAVES, TOXIC_SINK};
+use aln_registry::RegisteredAsset;
+
+/// One leaf claim as submitted.
+pub(crate) struct ClaimInput {
+    pub recipient: Addr,
+    pub authorization: Option<ClaimAuthorization>,
+    pub asset_id: String,
+    pub epoch: u32,
+    pub snapshot: SnapshotEntry,
+    /// `None` for origin events, whose H_i is derived from the asset's merkle version.
+    pub snapshot_hash: Option<String>,
+    pub proof: LeafProof,
+    pub amount_auet: Option<Uint128>,
+    pub amount_csp: Option<Uint128>,
+    pub origin_tx_hash: Option<String>,
+    pub origin_nonce: Option<u64>,
+    pub ubs_report_hash: Option<String>,
+}
+
+/// Checks the claim failed, in claim-flow order and named after their step. `claim` returns the
+/// first; `SimulateClaim` lists them all.
+#[derive(Default)]
+pub(crate) struct ClaimChecks(pub Vec<(&'static str, ContractError)>);
+
+impl ClaimChecks {
+    pub fn push(&mut self, check: &'static str, err: ContractError) {
+        self.0.push((check, err));
+    }
+
+    /// `Some(value)` on success, otherwise records the failure under `check`.
+    pub fn check<T>(&mut self, check: &'static str, res: Result<T, ContractError>) -> Option<T> {
+        match res {
+            Ok(v) => Some(v),
+            Err(e) => {
+                self.push(check, e);
+                None
+            }
+        }
+    }
+}
+
+/// How the claim settles.
+pub(crate) enum ClaimOutcome {
+    /// Rejected by UBS: the leaf is spent and nothing is credited.
+    Rejected,
+    /// Held in a quarantine case for governance.
+    Quarantine(QuarantineReason),
+    /// Over the named rate limit: queued for governance release.
+    Queued(&'static str),
+    /// Credited to the recipient.
+    Credit,
+}
+
+/// What a claim would do, from the read-only checks of the claim flow.
+pub(crate) struct ClaimPlan {
+    pub asset: RegisteredAsset,
+    pub snapshot_hash: String,
+    pub balance: u128,
+    pub entitled: Entitlement,
+    pub toxic: bool,
+    /// The epoch's UBS report hash in the registry.
+    pub ubs_report_hash: Option<String>,
+    pub sanitization: aln_ubs::SanitizationResult,
+    pub downgrade: Option<DowngradeTreatment>,
+    /// TOTAL_ENERGY and TOXIC_ENERGY once the claim counts; `None` for a rejected claim.
+    pub totals: Option<(Uint128, Option<Uint128>)>,
+    pub sink: Option<Addr>,
+    pub outcome: ClaimOutcome,
+}
+
+/// Run the checks of the claim flow for `input` against current state, recording every failure
+/// in `checks`. Checks that depend on an earlier failed step are skipped. Returns `None` when a
+/// failure leaves nothing to plan; a plan next to failures is not applied.
+pub(crate) fn plan_claim(deps: Deps, env: &Env, input: &ClaimInput, checks: &mut ClaimChecks) -> StdResult<Option<ClaimPlan>> {
+    checks.check("migration", migrations::ensure_migrated(deps.storage));
+    let already_claimed = |hash: &str| -> StdResult<bool> { Ok(CLAIMED_LEAVES.may_load(deps.storage, (input.asset_id.as_str(), input.epoch, hash))?.is_some()) };
+    if let Some(given) = input.snapshot_hash.as_deref() {
+        if already_claimed(given)? { checks.push("already_claimed", ContractError::AlreadyClaimed); }
+    }
+    // origin tx metadata must not have been refactored before, on any chain
+    if let (Some(txh), Some(n)) = (input.origin_tx_hash.as_ref(), input.origin_nonce) {
+        if refactor_is_processed(deps, input.snapshot.chain_id.as_str(), input.snapshot.denom.as_str(), txh.as_str(), n)? {
+            checks.push("origin_replay", ContractError::OriginAlreadyProcessed);
+        }
+    }
+
+    // the registry's asset and epoch; the asset's merkle version decides how H_i is encoded
+    let asset = match checks.check("registry", registry_asset(deps, input.asset_id.as_str())) {
+        Some(a) => a,
+        None => return Ok(None),
+    };
+    let snap = match checks.check("snapshot_epoch", snapshot_epoch(&asset, input.epoch)) {
+        Some(e) => e,
+        None => return Ok(None),
+    };
+    let leaf = checks.check("snapshot_hash", snapshot_leaf(asset.merkle_version, &input.snapshot));
+    let snapshot_hash = match (leaf, input.snapshot_hash.clone()) {
+        (Some(l), Some(given)) => {
+            if format!("0x{}", hex::encode(l)) != given { checks.push("snapshot_hash", ContractError::SnapshotHashMismatch); }
+            given
+        }
+        (Some(l), None) => {
+            let derived = format!("0x{}", hex::encode(l));
+            if already_claimed(derived.as_str())? { checks.push("already_claimed", ContractError::AlreadyClaimed); }
+            derived
+        }
+        (None, given) => given.unwrap_or_default(),
+    };
+
+    // bind the recipient to the snapshot owner: either the same account (bech32 re-prefix)
+    // or an authorization signed by the origin key for this exact recipient
+    match input.authorization.as_ref() {
+        Some(auth) => {
+            let digest = claim_auth_digest(env.block.chain_id.as_str(), &env.contract.address, input.asset_id.as_str(), snapshot_hash.as_str(), &input.recipient);
+            checks.check("ownership", verify_claim_authorization(deps.api, &digest, auth, input.snapshot.address.as_str()));
+        }
+        None => {
+            if !addresses_equivalent(input.snapshot.address.as_str(), input.recipient.as_str()) { checks.push("ownership", ContractError::ClaimantNotOwner); }
+        }
+    }
+
+    // sanitization approval, the epoch's UBS report hash, activation and deadline
+    if !asset.sanitized_approved { checks.push("sanitization", ContractError::AssetNotSanitized); }
+    match (&snap.ubs_report_hash, &input.ubs_report_hash) {
+        (None, _) => checks.push("sanitization", ContractError::UbsReportHashMissing),
+        (Some(reg), Some(given)) if reg != given => checks.push("sanitization", ContractError::UbsReportHashMismatch),
+        _ => {}
+    }
+    if env.block.height < snap.activation_height { checks.push("activation_height", ContractError::NotActivated); }
+    if snap.claim_deadline.is_some_and(|d| env.block.height >= d) { checks.push("claim_deadline", ContractError::ClaimDeadlinePassed); }
+
+    // the leaf against the epoch's merkle root
+    if let (Some(l), LeafProof::Path(merkle_proof)) = (leaf, &input.proof) {
+        let proven = decode_proof(merkle_proof).map(|steps| verify_merkle_proof(asset.merkle_version, &l, &steps, snap.merkle_root.trim_start_matches("0x")));
+        if !matches!(proven, Ok(true)) { checks.push("merkle_proof", ContractError::InvalidMerkleProof); }
+    }
+
+    // Entitlement is derived from the proven balance and the asset's scaling profile; caller
+    // supplied amounts are only accepted as an assertion and must match exactly.
+    let balance: Option<u128> = input.snapshot.balance.parse().ok();
+    let profile = checks.check("scaling_profile", registry_profile(deps, asset.scaling_profile_id.as_str()));
+    let (balance, toxic, entitled) = match (balance, profile) {
+        (Some(b), Some(p)) => match checks.check("entitlement", compute_entitlement(&p, Uint128::new(b)).map_err(ContractError::from)) {
+            Some(e) => (b, p.toxic, e),
+            None => return Ok(None),
+        },
+        _ => return Ok(None),
+    };
+    if input.amount_auet.is_some_and(|a| a != entitled.auet) { checks.push("entitlement", ContractError::EntitlementMismatch { denom: "auet".to_string() }); }
+    if input.amount_csp.is_some_and(|c| c != entitled.csp) { checks.push("entitlement", ContractError::EntitlementMismatch { denom: "csp".to_string() }); }
+
+    // UBS sanitization decides before the claim is counted, quarantined or credited
+    let sanitization = match checks.check("ubs_oracle", sanitize_claim(deps, &input.snapshot, input.origin_tx_hash.as_deref(), input.origin_nonce, entitled.total())) {
+        Some(s) => s,
+        None => return Ok(None),
+    };
+    // a downgraded claim is credited according to the governance-configured treatment
+    let downgrade = match sanitization.decision {
+        aln_ubs::SanitizationDecision::Downgraded => Some(handler_downgrade::treatment_for(deps.storage, input.asset_id.as_str())?),
+        _ => None,
+    };
+    if matches!(sanitization.decision, aln_ubs::SanitizationDecision::Rejected) {
+        return Ok(Some(ClaimPlan { asset, snapshot_hash, balance, entitled, toxic, ubs_report_hash: snap.ubs_report_hash, sanitization, downgrade, totals: None, sink: None, outcome: ClaimOutcome::Rejected }));
+    }
+
+    // toxic cap, then the sink that toxic-profile and anomalous claims need
+    let totals = match checks.check("toxic_cap", energy_totals_after(deps.storage, toxic, entitled.total())) {
+        Some(t) => t,
+        None => return Ok(None),
+    };
+    let sink = TOXIC_SINK.may_load(deps.storage)?.flatten();
+    if toxic && sink.is_none() { checks.push("toxic_sink", ContractError::ToxicSinkRequired); }
+
+    // toxic-profile claims and anomalies for large amounts wait in quarantine for governance,
+    // which releases them through the rate limits and the downgrade treatment
+    let quarantine = if toxic {
+        Some(QuarantineReason::ToxicProfile)
+    } else {
+        ANOMALY_THRESHOLD_AMOUNT.may_load(deps.storage)?.flatten().filter(|th| entitled.auet > *th).map(|threshold| QuarantineReason::AnomalyThreshold { threshold })
+    };
+    let outcome = match quarantine {
+        Some(reason) => {
+            if matches!(reason, QuarantineReason::AnomalyThreshold { .. }) && sink.is_none() { checks.push("anomaly_threshold", ContractError::AnomalyWithoutSink); }
+            ClaimOutcome::Quarantine(reason)
+        }
+        None => match handler_rate_limit::exceeded_limit(deps.storage, env, input.asset_id.as_str(), &input.recipient, Uint128::new(entitled.total()))? {
+            Some(limit) => ClaimOutcome::Queued(limit),
+            None => {
+                // a toxic share is paid to the sink when the claim is credited
+                if matches!(downgrade, Some(DowngradeTreatment::ToxicShare { .. })) && sink.is_none() { checks.push("toxic_sink", ContractError::ToxicSinkRequired); }
+                ClaimOutcome::Credit
+            }
+        },
+    };
+    Ok(Some(ClaimPlan { asset, snapshot_hash, balance, entitled, toxic, ubs_report_hash: snap.ubs_report_hash, sanitization, downgrade, totals: Some(totals), sink, outcome }))
+}
@@ -0,0 +1,134 @@
+use cosmwasm_std::{Deps, DepsMut, Env, MessageInfo, Order, Response, StdError, StdResult, Storage, Timestamp, Uint128};
+use cw_storage_plus::{Bound, Map, PrimaryKey};
+use serde::{Deserialize, Serialize};
+use aln_registry::{QueryMsg as RegQueryMsg, RegisteredAsset, SnapshotEpoch};
+use crate::{ContractError, GOVERNANCE, REGISTRY_CONTRACT};
+
+const DEFAULT_LIMIT: u32 = 10;
+const MAX_LIMIT: u32 = 30;
+
+/// Claims of one asset so far, across its snapshot epochs. `claimed` sums the proven snapshot balances (source units), so it
+/// is comparable with the registry's `total_supply`; toxic-diverted, queued and quarantined claims count too, rejected ones do not.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Default)]
+pub struct AssetClaimTotals {
+    pub claims: u64,
+    pub claimed: Uint128,
+}
+
+/// Treasury accounting entry written by `SweepUnclaimed` for the snapshot epochs
+/// `first_epoch..=last_epoch` once their claim deadlines passed. `total_supply`, `claimed` and
+/// `unclaimed` cover those epochs only.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct TreasurySweep {
+    pub asset_id: String,
+    pub first_epoch: u32,
+    pub last_epoch: u32,
+    pub height: u64,
+    pub time: Timestamp,
+    pub total_supply: Uint128,
+    pub claimed: Uint128,
+    pub unclaimed: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct AssetClaimStatsResponse {
+    pub asset_id: String,
+    pub claims: u64,
+    pub claimed: Uint128,
+    /// Summed over the snapshot epochs, when the registry knows every epoch's supply.
+    pub total_supply: Option<Uint128>,
+    /// `total_supply - claimed`, when the registry knows the supply.
+    pub unclaimed: Option<Uint128>,
+    /// Deadline of the last snapshot epoch to close.
+    pub claim_deadline: Option<u64>,
+    /// Latest sweep of the asset.
+    pub sweep: Option<TreasurySweep>,
+}
+
+pub const ASSET_CLAIM_TOTALS: Map<&str, AssetClaimTotals> = Map::new("asset_claim_totals");
+/// (asset id, last epoch swept) -> sweep. Epochs added after a sweep are swept by the next one.
+pub const TREASURY_SWEEPS: Map<(&str, u32), TreasurySweep> = Map::new("treasury_sweeps");
+
+pub fn record_claim(storage: &mut dyn Storage, asset_id: &str, balance: Uint128) -> StdResult<()> {
+    let mut t = ASSET_CLAIM_TOTALS.may_load(storage, asset_id)?.unwrap_or_default();
+    t.claims += 1;
+    t.claimed += balance;
+    ASSET_CLAIM_TOTALS.save(storage, asset_id, &t)
+}
+
+fn load_asset(deps: Deps, asset_id: &str) -> StdResult<RegisteredAsset> {
+    let reg_addr = REGISTRY_CONTRACT.load(deps.storage)?;
+    let asset: Option<RegisteredAsset> = deps.querier.query_wasm_smart(reg_addr, &RegQueryMsg::GetAsset { id: asset_id.to_string() })?;
+    asset.ok_or_else(|| StdError::not_found(format!("registered asset {}", asset_id)))
+}
+
+/// Supply of `epochs` and the deadline of the last of them to close; `None` when an epoch has
+/// no supply or stays open indefinitely.
+fn epochs_supply_and_deadline(epochs: &[SnapshotEpoch]) -> (Option<Uint128>, Option<u64>) {
+    let supply = epochs.iter().map(|e| e.total_supply).sum::<Option<Uint128>>();
+    let deadline = epochs.iter().map(|e| e.claim_deadline).collect::<Option<Vec<u64>>>().and_then(|d| d.into_iter().max());
+    (supply, deadline)
+}
+
+/// Sweeps of `asset_id` so far, ascending by epoch.
+fn asset_sweeps(storage: &dyn Storage, asset_id: &str) -> StdResult<Vec<TreasurySweep>> {
+    TREASURY_SWEEPS.prefix(asset_id).range(storage, None, None, Order::Ascending).map(|item| item.map(|(_, s)| s)).collect()
+}
+
+/// Governance-only: once the claim deadline of every snapshot epoch has passed, record what was
+/// never claimed in the epochs no earlier sweep covered.
+pub fn sweep_unclaimed(deps: DepsMut, env: Env, info: MessageInfo, asset_id: String) -> Result<Response, ContractError> {
+    let gov = GOVERNANCE.load(deps.storage)?;
+    if info.sender != gov { return Err(ContractError::Unauthorized { action: "sweep_unclaimed".to_string() }); }
+    let asset = load_asset(deps.as_ref(), asset_id.as_str())?;
+    let swept = asset_sweeps(deps.storage, asset_id.as_str())?;
+    let first_epoch = swept.last().map_or(0, |s| s.last_epoch + 1);
+    let epochs: Vec<SnapshotEpoch> = asset.snapshot_epochs().into_iter().filter(|e| e.epoch >= first_epoch).collect();
+    let last_epoch = match epochs.last() {
+        Some(e) => e.epoch,
+        None => return Err(ContractError::AlreadySwept { asset_id }),
+    };
+    let (total_supply, deadline) = epochs_supply_and_deadline(&epochs);
+    match deadline {
+        Some(d) if env.block.height >= d => {}
+        _ => return Err(ContractError::ClaimDeadlineNotPassed),
+    }
+    let total_supply = total_supply.ok_or_else(|| ContractError::InvalidConfig("asset has no total_supply to sweep against".to_string()))?;
+    // every epoch was closed at the earlier sweeps, so claims since then belong to these epochs
+    let claimed = ASSET_CLAIM_TOTALS.may_load(deps.storage, asset_id.as_str())?.unwrap_or_default().claimed.saturating_sub(swept.iter().map(|s| s.claimed).sum());
+    let sweep = TreasurySweep { asset_id: asset_id.clone(), first_epoch, last_epoch, height: env.block.height, time: env.block.time, total_supply, claimed, unclaimed: total_supply.saturating_sub(claimed) };
+    TREASURY_SWEEPS.save(deps.storage, (asset_id.as_str(), last_epoch), &sweep)?;
+    Ok(Response::new()
+        .add_attribute("action", "sweep_unclaimed")
+        .add_attribute("asset_id", asset_id)
+        .add_attribute("first_epoch", first_epoch.to_string())
+        .add_attribute("last_epoch", last_epoch.to_string())
+        .add_attribute("claimed", claimed.to_string())
+        .add_attribute("unclaimed", sweep.unclaimed.to_string()))
+}
+
+pub fn query_asset_claim_stats(deps: Deps, asset_id: String) -> StdResult<AssetClaimStatsResponse> {
+    let asset = load_asset(deps, asset_id.as_str())?;
+    let t = ASSET_CLAIM_TOTALS.may_load(deps.storage, asset_id.as_str())?.unwrap_or_default();
+    let sweep = TREASURY_SWEEPS.prefix(asset_id.as_str()).range(deps.storage, None, None, Order::Descending).next().transpose()?.map(|(_, s)| s);
+    let (total_supply, claim_deadline) = epochs_supply_and_deadline(&asset.snapshot_epochs());
+    Ok(AssetClaimStatsResponse {
+        unclaimed: total_supply.map(|s| s.saturating_sub(t.claimed)),
+        asset_id,
+        claims: t.claims,
+        claimed: t.claimed,
+        total_supply,
+        claim_deadline,
+        sweep,
+    })
+}
+
+pub fn query_treasury_sweeps(deps: Deps, start_after: Option<(String, u32)>, limit: Option<u32>) -> StdResult<Vec<TreasurySweep>> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.as_ref().map(|(a, e)| Bound::exclusive((a.as_str(), *e).joined_key()));
+    TREASURY_SWEEPS
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| item.map(|(_, s)| s))
+        .collect()
+}
@@ -0,0 +1,121 @@
+use cosmwasm_std::{Addr, Deps, DepsMut, Event, MessageInfo, Response, StdResult, Uint128};
+use serde::{Deserialize, Serialize};
+use crate::{ContractError, ANOMALY_THRESHOLD_AMOUNT, AUET_CONTRACT, CSP_CONTRACT, GOVERNANCE, REGISTRY_CONTRACT, TOXIC_CAP_PERCENT, TOXIC_SINK, UBS_ORACLE_CONTRACT};
+
+/// Optional config entries that `UpdateConfig` can unset.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ClearableConfig {
+    ToxicSink,
+    AnomalyThresholdAmount,
+    ToxicCapPercent,
+    UbsOracleContract,
+    CspContract,
+}
+
+/// Effective bridge configuration; `None` means the entry is unset.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct ConfigResponse {
+    pub auet_contract: Addr,
+    pub csp_contract: Option<Addr>,
+    pub registry_contract: Addr,
+    pub governance: Addr,
+    pub toxic_sink: Option<Addr>,
+    pub anomaly_threshold_amount: Option<Uint128>,
+    pub toxic_cap_percent: Option<u8>,
+    pub ubs_oracle_contract: Option<Addr>,
+}
+
+/// Field-wise changes requested by `ExecuteMsg::UpdateConfig`. Fields left `None` are kept.
+#[derive(Default)]
+pub struct ConfigUpdate {
+    pub toxic_sink: Option<String>,
+    pub anomaly_threshold_amount: Option<Uint128>,
+    pub toxic_cap_percent: Option<u8>,
+    pub ubs_oracle_contract: Option<String>,
+    pub csp_contract: Option<String>,
+    pub registry_contract: Option<String>,
+    pub clear: Vec<ClearableConfig>,
+}
+
+fn fmt_opt<T: ToString>(v: &Option<T>) -> String {
+    v.as_ref().map(|x| x.to_string()).unwrap_or_default()
+}
+
+fn change_event(field: &str, old: String, new: String) -> Event {
+    Event::new("aln_bridge_config").add_attribute("field", field).add_attribute("old", old).add_attribute("new", new)
+}
+
+pub fn update_config(deps: DepsMut, info: MessageInfo, upd: ConfigUpdate) -> Result<Response, ContractError> {
+    let gov = GOVERNANCE.load(deps.storage)?;
+    if info.sender != gov { return Err(ContractError::Unauthorized { action: "update_config".to_string() }); }
+
+    // a field cannot be set and cleared in the same message
+    let conflicting = upd.clear.iter().any(|c| match c {
+        ClearableConfig::ToxicSink => upd.toxic_sink.is_some(),
+        ClearableConfig::AnomalyThresholdAmount => upd.anomaly_threshold_amount.is_some(),
+        ClearableConfig::ToxicCapPercent => upd.toxic_cap_percent.is_some(),
+        ClearableConfig::UbsOracleContract => upd.ubs_oracle_contract.is_some(),
+        ClearableConfig::CspContract => upd.csp_contract.is_some(),
+    });
+    if conflicting { return Err(ContractError::InvalidConfig("field both set and cleared".to_string())); }
+    if let Some(pct) = upd.toxic_cap_percent {
+        if pct > 100 { return Err(ContractError::InvalidConfig("toxic_cap_percent must be <= 100".to_string())); }
+    }
+
+    let mut events: Vec<Event> = vec![];
+    let clears = |c: ClearableConfig| upd.clear.contains(&c);
+
+    if upd.toxic_sink.is_some() || clears(ClearableConfig::ToxicSink) {
+        let old = TOXIC_SINK.may_load(deps.storage)?.flatten();
+        let new = match &upd.toxic_sink { Some(s) => Some(deps.api.addr_validate(s)?), None => None };
+        TOXIC_SINK.save(deps.storage, &new)?;
+        events.push(change_event("toxic_sink", fmt_opt(&old), fmt_opt(&new)));
+    }
+    if upd.anomaly_threshold_amount.is_some() || clears(ClearableConfig::AnomalyThresholdAmount) {
+        let old = ANOMALY_THRESHOLD_AMOUNT.may_load(deps.storage)?.flatten();
+        ANOMALY_THRESHOLD_AMOUNT.save(deps.storage, &upd.anomaly_threshold_amount)?;
+        events.push(change_event("anomaly_threshold_amount", fmt_opt(&old), fmt_opt(&upd.anomaly_threshold_amount)));
+    }
+    if upd.toxic_cap_percent.is_some() || clears(ClearableConfig::ToxicCapPercent) {
+        let old = TOXIC_CAP_PERCENT.may_load(deps.storage)?.flatten();
+        TOXIC_CAP_PERCENT.save(deps.storage, &upd.toxic_cap_percent)?;
+        events.push(change_event("toxic_cap_percent", fmt_opt(&old), fmt_opt(&upd.toxic_cap_percent)));
+    }
+    if upd.ubs_oracle_contract.is_some() || clears(ClearableConfig::UbsOracleContract) {
+        let old = UBS_ORACLE_CONTRACT.may_load(deps.storage)?.flatten();
+        let new = match &upd.ubs_oracle_contract { Some(s) => Some(deps.api.addr_validate(s)?), None => None };
+        UBS_ORACLE_CONTRACT.save(deps.storage, &new)?;
+        events.push(change_event("ubs_oracle_contract", fmt_opt(&old), fmt_opt(&new)));
+    }
+    if upd.csp_contract.is_some() || clears(ClearableConfig::CspContract) {
+        let old = CSP_CONTRACT.may_load(deps.storage)?;
+        let new = match &upd.csp_contract { Some(s) => Some(deps.api.addr_validate(s)?), None => None };
+        match &new {
+            Some(a) => CSP_CONTRACT.save(deps.storage, a)?,
+            None => CSP_CONTRACT.remove(deps.storage),
+        }
+        events.push(change_event("csp_contract", fmt_opt(&old), fmt_opt(&new)));
+    }
+    if let Some(r) = &upd.registry_contract {
+        let old = REGISTRY_CONTRACT.load(deps.storage)?;
+        let new = deps.api.addr_validate(r)?;
+        REGISTRY_CONTRACT.save(deps.storage, &new)?;
+        events.push(change_event("registry_contract", old.to_string(), new.to_string()));
+    }
+
+    Ok(Response::new().add_attribute("action", "update_config").add_attribute("changed", events.len().to_string()).add_events(events))
+}
+
+pub fn query_config(deps: Deps) -> StdResult<ConfigResponse> {
+    Ok(ConfigResponse {
+        auet_contract: AUET_CONTRACT.load(deps.storage)?,
+        csp_contract: CSP_CONTRACT.may_load(deps.storage)?,
+        registry_contract: REGISTRY_CONTRACT.load(deps.storage)?,
+        governance: GOVERNANCE.load(deps.storage)?,
+        toxic_sink: TOXIC_SINK.may_load(deps.storage)?.flatten(),
+        anomaly_threshold_amount: ANOMALY_THRESHOLD_AMOUNT.may_load(deps.storage)?.flatten(),
+        toxic_cap_percent: TOXIC_CAP_PERCENT.may_load(deps.storage)?.flatten(),
+        ubs_oracle_contract: UBS_ORACLE_CONTRACT.may_load(deps.storage)?.flatten(),
+    })
+}
@@ -0,0 +1,142 @@
+use cosmwasm_std::{Attribute, DepsMut, MessageInfo, Response, StdResult, Storage, SubMsg, Uint128};
+use cw_storage_plus::{Item, Map};
+use serde::{Deserialize, Serialize};
+use crate::handler_vesting::validate_schedule;
+use crate::{ContractError, EnergyVector, LedgerChangeReason, VestingSchedule, GOVERNANCE, TOXIC_SINK};
+
+const BPS_DENOMINATOR: u128 = 10_000;
+
+/// What the bridge does with the energy of a claim the UBS oracle classed as `Downgraded`.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum DowngradeTreatment {
+    /// Credit like an approved claim (the behavior before downgrades were told apart).
+    FullCredit,
+    /// Credit `10_000 - bps` basis points of every component; the rest is never credited.
+    Haircut { bps: u16 },
+    /// Credit `bps` basis points of every component to the toxic sink instead of the claimant.
+    ToxicShare { bps: u16 },
+    /// Credit everything under an `EnergyLock` from the claim time, like an asset vesting
+    /// schedule; with both, the later cliff and end apply.
+    Vesting { cliff_seconds: u64, duration_seconds: u64 },
+    /// Credit the CSP component only.
+    CspOnly,
+}
+
+impl DowngradeTreatment {
+    pub fn name(&self) -> &'static str {
+        match self {
+            DowngradeTreatment::FullCredit => "full_credit",
+            DowngradeTreatment::Haircut { .. } => "haircut",
+            DowngradeTreatment::ToxicShare { .. } => "toxic_share",
+            DowngradeTreatment::Vesting { .. } => "vesting",
+            DowngradeTreatment::CspOnly => "csp_only",
+        }
+    }
+
+    /// Compact form appended to the `REFACTOR_AUDIT` entry, e.g. `haircut:2500`.
+    pub fn audit_tag(&self) -> String {
+        match self {
+            DowngradeTreatment::Haircut { bps } | DowngradeTreatment::ToxicShare { bps } => format!("{}:{}", self.name(), bps),
+            DowngradeTreatment::Vesting { cliff_seconds, duration_seconds } => format!("{}:{}:{}", self.name(), cliff_seconds, duration_seconds),
+            _ => self.name().to_string(),
+        }
+    }
+}
+
+/// A downgrade treatment applied to a claim about to be credited.
+pub struct DowngradeOutcome {
+    /// What the claimant is credited.
+    pub credit: EnergyVector,
+    /// Lock the credit is vested under.
+    pub vesting: Option<VestingSchedule>,
+    /// Where the rest went.
+    pub attrs: Vec<Attribute>,
+    /// Ledger hook notifications of a sink credit.
+    pub hooks: Vec<SubMsg>,
+}
+
+/// Treatment for assets without their own entry; `FullCredit` when unset.
+pub const DEFAULT_DOWNGRADE_TREATMENT: Item<DowngradeTreatment> = Item::new("downgrade_default_treatment");
+pub const ASSET_DOWNGRADE_TREATMENT: Map<&str, DowngradeTreatment> = Map::new("downgrade_asset_treatments");
+
+pub fn treatment_for(storage: &dyn Storage, asset_id: &str) -> StdResult<DowngradeTreatment> {
+    if let Some(t) = ASSET_DOWNGRADE_TREATMENT.may_load(storage, asset_id)? { return Ok(t); }
+    Ok(DEFAULT_DOWNGRADE_TREATMENT.may_load(storage)?.unwrap_or(DowngradeTreatment::FullCredit))
+}
+
+fn scale(v: &EnergyVector, bps: u16) -> EnergyVector {
+    let f = |a: Uint128| a.multiply_ratio(bps as u128, BPS_DENOMINATOR);
+    EnergyVector { auet: f(v.auet), csp: f(v.csp), erp: f(v.erp) }
+}
+
+fn minus(a: &EnergyVector, b: &EnergyVector) -> EnergyVector {
+    EnergyVector { auet: a.auet - b.auet, csp: a.csp - b.csp, erp: a.erp - b.erp }
+}
+
+fn total(v: &EnergyVector) -> Uint128 {
+    v.auet + v.csp + v.erp
+}
+
+/// Part of a downgraded claim's energy the claimant is credited under `treatment` (vested
+/// energy included).
+pub fn credited_part(treatment: &DowngradeTreatment, ev: &EnergyVector) -> EnergyVector {
+    let zero = EnergyVector { auet: Uint128::zero(), csp: Uint128::zero(), erp: Uint128::zero() };
+    match treatment {
+        DowngradeTreatment::FullCredit => ev.clone(),
+        DowngradeTreatment::Haircut { bps } | DowngradeTreatment::ToxicShare { bps } => minus(ev, &scale(ev, *bps)),
+        DowngradeTreatment::Vesting { .. } => ev.clone(),
+        DowngradeTreatment::CspOnly => EnergyVector { csp: ev.csp, ..zero },
+    }
+}
+
+/// Apply `treatment` to a downgraded claim's energy when it is credited. Sink shares settle
+/// here; the claimant's part and its vesting are left to `credit_claim`.
+pub fn apply(deps: DepsMut, treatment: &DowngradeTreatment, ev: EnergyVector) -> Result<DowngradeOutcome, ContractError> {
+    let mut attrs = vec![Attribute::new("downgrade_treatment", treatment.name())];
+    let credit = credited_part(treatment, &ev);
+    let rest = minus(&ev, &credit);
+    let mut hooks = vec![];
+    let mut vesting = None;
+    match treatment {
+        DowngradeTreatment::FullCredit => {}
+        DowngradeTreatment::Haircut { .. } | DowngradeTreatment::CspOnly => {
+            attrs.push(Attribute::new("downgrade_withheld", total(&rest).to_string()));
+        }
+        DowngradeTreatment::ToxicShare { .. } => {
+            let sink = TOXIC_SINK.may_load(deps.storage)?.flatten().ok_or(ContractError::ToxicSinkRequired)?;
+            hooks = crate::credit_energy(deps, &sink, rest.clone(), LedgerChangeReason::DowngradeSinkShare)?;
+            attrs.push(Attribute::new("downgrade_to_sink", total(&rest).to_string()));
+        }
+        DowngradeTreatment::Vesting { cliff_seconds, duration_seconds } => {
+            vesting = Some(VestingSchedule { cliff_seconds: *cliff_seconds, duration_seconds: *duration_seconds, min_total: None });
+            attrs.push(Attribute::new("downgrade_vested", total(&credit).to_string()));
+        }
+    }
+    attrs.push(Attribute::new("downgrade_credited", total(&credit).to_string()));
+    Ok(DowngradeOutcome { credit, vesting, attrs, hooks })
+}
+
+/// Governance-only: set the treatment for one asset, or the default when `asset_id` is `None`.
+/// `None` removes the asset's entry (or resets the default to `FullCredit`).
+pub fn set_downgrade_treatment(deps: DepsMut, info: MessageInfo, asset_id: Option<String>, treatment: Option<DowngradeTreatment>) -> Result<Response, ContractError> {
+    let gov = GOVERNANCE.load(deps.storage)?;
+    if info.sender != gov { return Err(ContractError::Unauthorized { action: "set_downgrade_treatment".to_string() }); }
+    match &treatment {
+        Some(DowngradeTreatment::Haircut { bps }) | Some(DowngradeTreatment::ToxicShare { bps }) if *bps as u128 > BPS_DENOMINATOR => {
+            return Err(ContractError::InvalidConfig("downgrade bps must be at most 10000".to_string()));
+        }
+        Some(DowngradeTreatment::Vesting { cliff_seconds, duration_seconds }) => validate_schedule(*cliff_seconds, *duration_seconds)?,
+        _ => {}
+    }
+    match (&asset_id, &treatment) {
+        (Some(a), Some(t)) => ASSET_DOWNGRADE_TREATMENT.save(deps.storage, a.as_str(), t)?,
+        (Some(a), None) => ASSET_DOWNGRADE_TREATMENT.remove(deps.storage, a.as_str()),
+        (None, Some(t)) => DEFAULT_DOWNGRADE_TREATMENT.save(deps.storage, t)?,
+        (None, None) => DEFAULT_DOWNGRADE_TREATMENT.remove(deps.storage),
+    }
+    Ok(Response::new()
+        .add_attribute("action", "set_downgrade_treatment")
+        .add_attribute("asset_id", asset_id.unwrap_or_else(|| "*".to_string()))
+        .add_attribute("treatment", treatment.map(|t| t.audit_tag()).unwrap_or_else(|| "unset".to_string())))
+}
@@ -0,0 +1,44 @@
+use cosmwasm_std::{Addr, DepsMut, Env, MessageInfo, Response};
+use cw_storage_plus::Item;
+use cw_utils::Expiration;
+use serde::{Deserialize, Serialize};
+
+use crate::{ContractError, GOVERNANCE};
+
+/// Governance handover awaiting acceptance by `new_governance`.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct PendingGovernance {
+    pub new_governance: Addr,
+    pub expires: Expiration,
+}
+
+pub const PENDING_GOVERNANCE: Item<PendingGovernance> = Item::new("pending_governance");
+
+pub fn propose_governance(deps: DepsMut, env: Env, info: MessageInfo, new_governance: String, expires: Option<Expiration>) -> Result<Response, ContractError> {
+    let gov = GOVERNANCE.load(deps.storage)?;
+    if info.sender != gov { return Err(ContractError::Unauthorized { action: "propose_governance".to_string() }); }
+    let expires = expires.unwrap_or_default();
+    if expires.is_expired(&env.block) { return Err(ContractError::GovernanceProposalExpired); }
+    let new_governance = deps.api.addr_validate(&new_governance)?;
+    // a new proposal replaces any outstanding one
+    PENDING_GOVERNANCE.save(deps.storage, &PendingGovernance { new_governance: new_governance.clone(), expires })?;
+    Ok(Response::new().add_attribute("action", "propose_governance").add_attribute("current", gov).add_attribute("pending", new_governance).add_attribute("expires", expires.to_string()))
+}
+
+pub fn accept_governance(deps: DepsMut, env: Env, info: MessageInfo) -> Result<Response, ContractError> {
+    let pending = PENDING_GOVERNANCE.may_load(deps.storage)?.ok_or(ContractError::NoPendingGovernance)?;
+    if info.sender != pending.new_governance { return Err(ContractError::NotPendingGovernance); }
+    if pending.expires.is_expired(&env.block) { return Err(ContractError::GovernanceProposalExpired); }
+    let previous = GOVERNANCE.load(deps.storage)?;
+    GOVERNANCE.save(deps.storage, &pending.new_governance)?;
+    PENDING_GOVERNANCE.remove(deps.storage);
+    Ok(Response::new().add_attribute("action", "accept_governance").add_attribute("previous", previous).add_attribute("governance", pending.new_governance))
+}
+
+pub fn cancel_governance_proposal(deps: DepsMut, info: MessageInfo) -> Result<Response, ContractError> {
+    let gov = GOVERNANCE.load(deps.storage)?;
+    if info.sender != gov { return Err(ContractError::Unauthorized { action: "cancel_governance_proposal".to_string() }); }
+    let pending = PENDING_GOVERNANCE.may_load(deps.storage)?.ok_or(ContractError::NoPendingGovernance)?;
+    PENDING_GOVERNANCE.remove(deps.storage);
+    Ok(Response::new().add_attribute("action", "cancel_governance_proposal").add_attribute("pending", pending.new_governance))
+}
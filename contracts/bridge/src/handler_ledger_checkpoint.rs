@@ -0,0 +1,117 @@
+use aln_merkle::LedgerAccumulator;
+use cosmwasm_std::{Addr, Deps, DepsMut, Env, MessageInfo, Order, Response, StdResult, Storage, Timestamp, Uint128};
+use cw_storage_plus::{Bound, Item, Map};
+use serde::{Deserialize, Serialize};
+use crate::{ContractError, EnergyVector, ENERGY_LEDGER, GOVERNANCE};
+
+/// Ledger entries hashed by a `CheckpointLedger` or `ContinueCheckpoint` without a limit.
+pub const DEFAULT_CHECKPOINT_BATCH: u32 = 100;
+pub const MAX_CHECKPOINT_BATCH: u32 = 500;
+
+/// Commitment over every `ENERGY_LEDGER` entry at `height`: `root` is
+/// `aln_merkle::ledger_root` over `ledger_leaf_hash(address, auet, csp, erp)` in ascending
+/// address order, so `aln_tools ledger-commitment` can recompute it from an `EnergyHolders` dump.
+/// Energy outside the ledger (queued claims, quarantine cases) is not committed.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct LedgerCheckpoint {
+    pub height: u64,
+    pub time: Timestamp,
+    pub root: String,
+    pub entries: u64,
+    pub total: EnergyVector,
+}
+
+/// Checkpoint still being hashed: the ledger as of `height`, up to and including `cursor`.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct PendingCheckpoint {
+    pub height: u64,
+    pub time: Timestamp,
+    pub cursor: Option<Addr>,
+    pub entries: u64,
+    pub total: EnergyVector,
+    pub accumulator: LedgerAccumulator,
+}
+
+pub const LEDGER_CHECKPOINTS: Map<u64, LedgerCheckpoint> = Map::new("ledger_checkpoints");
+pub const PENDING_CHECKPOINT: Item<PendingCheckpoint> = Item::new("pending_ledger_checkpoint");
+/// Entries not yet hashed by the pending checkpoint, as they were at its height (`None` when the
+/// address held no energy then); written on the first ledger change after the checkpoint started.
+pub const CHECKPOINT_PRESTATE: Map<&Addr, Option<EnergyVector>> = Map::new("ledger_checkpoint_prestate");
+
+/// Called before every `ENERGY_LEDGER` write, so the pending checkpoint still hashes `owner` as of
+/// its height.
+pub(crate) fn preserve(storage: &mut dyn Storage, owner: &Addr) -> StdResult<()> {
+    let pending = match PENDING_CHECKPOINT.may_load(storage)? {
+        Some(p) => p,
+        None => return Ok(()),
+    };
+    if pending.cursor.map_or(false, |c| owner.as_bytes() <= c.as_bytes()) || CHECKPOINT_PRESTATE.may_load(storage, owner)?.is_some() { return Ok(()); }
+    let before = ENERGY_LEDGER.may_load(storage, owner)?;
+    CHECKPOINT_PRESTATE.save(storage, owner, &before)
+}
+
+/// Governance-only: start a commitment over the ledger at the current height and hash its first
+/// `limit` entries; `ContinueCheckpoint` hashes the rest.
+pub fn checkpoint_ledger(deps: DepsMut, env: Env, info: MessageInfo, limit: Option<u32>) -> Result<Response, ContractError> {
+    let gov = GOVERNANCE.load(deps.storage)?;
+    if info.sender != gov { return Err(ContractError::Unauthorized { action: "checkpoint_ledger".to_string() }); }
+    if PENDING_CHECKPOINT.may_load(deps.storage)?.is_some() { return Err(ContractError::CheckpointPending); }
+    let zero = EnergyVector { auet: Uint128::zero(), csp: Uint128::zero(), erp: Uint128::zero() };
+    let pending = PendingCheckpoint { height: env.block.height, time: env.block.time, cursor: None, entries: 0, total: zero, accumulator: LedgerAccumulator::default() };
+    run_batch(deps.storage, pending, limit, "checkpoint_ledger")
+}
+
+/// Hash the next `limit` entries of the pending checkpoint. Anyone may call it.
+pub fn continue_checkpoint(deps: DepsMut, limit: Option<u32>) -> Result<Response, ContractError> {
+    let pending = PENDING_CHECKPOINT.may_load(deps.storage)?.ok_or(ContractError::NoPendingCheckpoint)?;
+    run_batch(deps.storage, pending, limit, "continue_checkpoint")
+}
+
+/// Store the checkpoint once the ledger is exhausted; replaces one finished earlier for the same
+/// height.
+fn run_batch(storage: &mut dyn Storage, mut pending: PendingCheckpoint, limit: Option<u32>, action: &str) -> Result<Response, ContractError> {
+    let limit = limit.unwrap_or(DEFAULT_CHECKPOINT_BATCH).clamp(1, MAX_CHECKPOINT_BATCH) as usize;
+    let start = pending.cursor.as_ref().map(|c| Bound::exclusive(c.as_bytes()));
+    let batch: Vec<(Addr, EnergyVector)> = ENERGY_LEDGER.range(storage, start, None, Order::Ascending).take(limit).collect::<StdResult<_>>()?;
+    for (addr, current) in batch.iter() {
+        let ev = match CHECKPOINT_PRESTATE.may_load(storage, addr)? {
+            Some(before) => {
+                CHECKPOINT_PRESTATE.remove(storage, addr);
+                before
+            }
+            None => Some(current.clone()),
+        };
+        // addresses first credited after the checkpoint height are not part of it
+        if let Some(ev) = ev {
+            pending.accumulator.push(aln_merkle::ledger_leaf_hash(addr.as_str(), ev.auet.u128(), ev.csp.u128(), ev.erp.u128()));
+            pending.entries += 1;
+            pending.total = EnergyVector { auet: pending.total.auet + ev.auet, csp: pending.total.csp + ev.csp, erp: pending.total.erp + ev.erp };
+        }
+    }
+    let res = Response::new().add_attribute("action", action).add_attribute("height", pending.height.to_string());
+    if let (Some((last, _)), true) = (batch.last(), batch.len() == limit) {
+        pending.cursor = Some(last.clone());
+        PENDING_CHECKPOINT.save(storage, &pending)?;
+        return Ok(res.add_attribute("checkpoint_done", "false").add_attribute("entries", pending.entries.to_string()));
+    }
+    PENDING_CHECKPOINT.remove(storage);
+    let checkpoint = LedgerCheckpoint { height: pending.height, time: pending.time, root: format!("0x{}", hex::encode(pending.accumulator.root())), entries: pending.entries, total: pending.total };
+    LEDGER_CHECKPOINTS.save(storage, checkpoint.height, &checkpoint)?;
+    Ok(res
+        .add_attribute("checkpoint_done", "true")
+        .add_attribute("root", checkpoint.root)
+        .add_attribute("entries", checkpoint.entries.to_string()))
+}
+
+/// Checkpoint at `height`, or the latest one.
+pub fn query_ledger_checkpoint(deps: Deps, height: Option<u64>) -> StdResult<Option<LedgerCheckpoint>> {
+    match height {
+        Some(h) => LEDGER_CHECKPOINTS.may_load(deps.storage, h),
+        None => LEDGER_CHECKPOINTS.range(deps.storage, None, None, Order::Descending).next().transpose().map(|c| c.map(|(_, cp)| cp)),
+    }
+}
+
+/// The checkpoint `ContinueCheckpoint` would advance, if any.
+pub fn query_pending_checkpoint(deps: Deps) -> StdResult<Option<PendingCheckpoint>> {
+    PENDING_CHECKPOINT.may_load(deps.storage)
+}
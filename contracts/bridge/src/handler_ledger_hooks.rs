@@ -0,0 +1,124 @@
+use cosmwasm_std::{to_binary, Addr, ContractResult, Deps, DepsMut, Env, MessageInfo, Order, Reply, Response, StdResult, Storage, SubMsg, WasmMsg};
+use cw_storage_plus::{Bound, Item, Map};
+use serde::{Deserialize, Serialize};
+use crate::{ContractError, EnergyVector, GOVERNANCE};
+
+const DEFAULT_LIMIT: u32 = 10;
+const MAX_LIMIT: u32 = 30;
+/// Every ledger change sends one submessage per hook, so the registry stays small.
+pub const MAX_LEDGER_HOOKS: u64 = 10;
+/// Gas a subscriber may use per notification unless governance sets another limit; an
+/// out-of-gas subscriber then fails like any other error instead of aborting the transaction.
+pub const DEFAULT_HOOK_GAS_LIMIT: u64 = 200_000;
+
+/// What changed an owner's ledger entry.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum LedgerChangeReason {
+    Claim,
+    QueuedClaimRelease,
+    QuarantineRelease,
+    /// Toxic sink share of a downgraded claim.
+    DowngradeSinkShare,
+    SystemConsume,
+}
+
+/// Message the bridge sends to every subscriber; subscribers add a matching `ExecuteMsg` variant.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum LedgerHookMsg {
+    LedgerChanged { owner: Addr, before: EnergyVector, after: EnergyVector, reason: LedgerChangeReason },
+}
+
+/// A subscribed contract. Its notifications run with reply-on-error, so failures are only
+/// counted here and never revert the ledger change.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct LedgerHook {
+    /// Reply id of this hook's submessages.
+    pub id: u64,
+    pub contract: Addr,
+    pub gas_limit: u64,
+    pub failures: u64,
+    pub last_error: Option<String>,
+    pub last_failure_height: Option<u64>,
+}
+
+pub const LEDGER_HOOKS: Map<&Addr, LedgerHook> = Map::new("ledger_hooks");
+const LEDGER_HOOK_IDS: Map<u64, Addr> = Map::new("ledger_hook_ids");
+const LEDGER_HOOK_SEQ: Item<u64> = Item::new("ledger_hook_seq");
+
+/// Notifications of an `owner` ledger change for every hook; empty when nothing changed.
+pub fn ledger_changed(storage: &dyn Storage, owner: &Addr, before: &EnergyVector, after: &EnergyVector, reason: LedgerChangeReason) -> StdResult<Vec<SubMsg>> {
+    if before == after { return Ok(vec![]); }
+    let msg = to_binary(&LedgerHookMsg::LedgerChanged { owner: owner.clone(), before: before.clone(), after: after.clone(), reason })?;
+    LEDGER_HOOKS
+        .range(storage, None, None, Order::Ascending)
+        .map(|item| {
+            let (_, hook) = item?;
+            let wasm = WasmMsg::Execute { contract_addr: hook.contract.to_string(), msg: msg.clone(), funds: vec![] };
+            Ok(SubMsg::reply_on_error(wasm, hook.id).with_gas_limit(hook.gas_limit))
+        })
+        .collect()
+}
+
+/// Governance-only: subscribe `contract_addr` to ledger changes.
+pub fn add_ledger_hook(deps: DepsMut, info: MessageInfo, contract_addr: String, gas_limit: Option<u64>) -> Result<Response, ContractError> {
+    let gov = GOVERNANCE.load(deps.storage)?;
+    if info.sender != gov { return Err(ContractError::Unauthorized { action: "add_ledger_hook".to_string() }); }
+    let contract = deps.api.addr_validate(&contract_addr)?;
+    if LEDGER_HOOKS.has(deps.storage, &contract) { return Err(ContractError::LedgerHookExists { contract: contract_addr }); }
+    if LEDGER_HOOKS.keys(deps.storage, None, None, Order::Ascending).count() as u64 >= MAX_LEDGER_HOOKS {
+        return Err(ContractError::InvalidConfig(format!("at most {} ledger hooks", MAX_LEDGER_HOOKS)));
+    }
+    let gas_limit = gas_limit.unwrap_or(DEFAULT_HOOK_GAS_LIMIT);
+    if gas_limit == 0 { return Err(ContractError::InvalidConfig("hook gas limit must be non-zero".to_string())); }
+    let id = LEDGER_HOOK_SEQ.may_load(deps.storage)?.unwrap_or_default() + 1;
+    LEDGER_HOOK_SEQ.save(deps.storage, &id)?;
+    LEDGER_HOOK_IDS.save(deps.storage, id, &contract)?;
+    LEDGER_HOOKS.save(deps.storage, &contract, &LedgerHook { id, contract: contract.clone(), gas_limit, failures: 0, last_error: None, last_failure_height: None })?;
+    Ok(Response::new().add_attribute("action", "add_ledger_hook").add_attribute("contract", contract).add_attribute("hook_id", id.to_string()))
+}
+
+/// Governance-only: unsubscribe `contract_addr`.
+pub fn remove_ledger_hook(deps: DepsMut, info: MessageInfo, contract_addr: String) -> Result<Response, ContractError> {
+    let gov = GOVERNANCE.load(deps.storage)?;
+    if info.sender != gov { return Err(ContractError::Unauthorized { action: "remove_ledger_hook".to_string() }); }
+    let contract = deps.api.addr_validate(&contract_addr)?;
+    let hook = LEDGER_HOOKS.may_load(deps.storage, &contract)?.ok_or(ContractError::LedgerHookNotFound { contract: contract_addr })?;
+    LEDGER_HOOKS.remove(deps.storage, &contract);
+    LEDGER_HOOK_IDS.remove(deps.storage, hook.id);
+    Ok(Response::new().add_attribute("action", "remove_ledger_hook").add_attribute("contract", contract).add_attribute("hook_id", hook.id.to_string()))
+}
+
+/// Reply of a failed notification: record it on the hook and swallow the error so the
+/// ledger change that triggered it stands.
+pub fn hook_failed(deps: DepsMut, env: Env, msg: Reply) -> Result<Response, ContractError> {
+    let error = match msg.result {
+        ContractResult::Err(e) => e,
+        ContractResult::Ok(_) => return Ok(Response::new()),
+    };
+    let mut res = Response::new().add_attribute("action", "ledger_hook_failed").add_attribute("hook_id", msg.id.to_string());
+    // a hook removed while its notification was in flight has nothing to record
+    if let Some(contract) = LEDGER_HOOK_IDS.may_load(deps.storage, msg.id)? {
+        let mut hook = LEDGER_HOOKS.load(deps.storage, &contract)?;
+        hook.failures += 1;
+        hook.last_error = Some(error.clone());
+        hook.last_failure_height = Some(env.block.height);
+        LEDGER_HOOKS.save(deps.storage, &contract, &hook)?;
+        res = res.add_attribute("contract", contract);
+    }
+    Ok(res.add_attribute("error", error))
+}
+
+pub fn query_ledger_hooks(deps: Deps, start_after: Option<String>, limit: Option<u32>) -> StdResult<Vec<LedgerHook>> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = match start_after {
+        Some(s) => Some(Bound::exclusive(deps.api.addr_validate(&s)?.as_bytes())),
+        None => None,
+    };
+    LEDGER_HOOKS
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| item.map(|(_, h)| h))
+        .collect()
+}
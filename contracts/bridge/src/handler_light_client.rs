@@ -0,0 +1,177 @@
+use aln_light_client::ics23::ExistenceProof;
+use aln_light_client::{verify_header, verify_lock_event, HeaderUpdate, LockEvent, SignatureVerifier, TrustOptions, TrustedState};
+use cosmwasm_std::{Api, Deps, DepsMut, Env, Event, MessageInfo, Order, Response, StdResult, Storage};
+use cw_storage_plus::Map;
+use serde::{Deserialize, Serialize};
+use crate::core::bridge_architecture::{LightClient, OriginLockEvent};
+use crate::{ContractError, GOVERNANCE};
+
+/// Governance-supplied starting point of an origin chain's client.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct LightClientInit {
+    /// Header and next validator set taken from a source governance trusts (e.g. a recent
+    /// block checked against several full nodes).
+    pub trusted: TrustedState,
+    pub options: TrustOptions,
+    /// Reject `ClaimWithOrigin` for this chain unless it carries an `origin_proof`.
+    pub require_proofs: bool,
+}
+
+/// Client of one origin chain: its options and the latest header verified so far.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct LightClientState {
+    pub chain_id: String,
+    pub options: TrustOptions,
+    pub require_proofs: bool,
+    pub latest: TrustedState,
+    /// ALN height of the last trusted-state change.
+    pub updated_at: u64,
+}
+
+/// App hash of a verified origin header.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct ConsensusRoot {
+    pub height: u64,
+    pub time: aln_light_client::Timestamp,
+    pub app_hash: String,
+}
+
+/// Membership of an `OriginLockEvent` in the origin state at a verified `height`: the lock
+/// module IAVL proof first, then the multistore proof (see `aln_light_client::verify_lock_event`).
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct OriginLockProof {
+    pub height: u64,
+    pub proofs: Vec<ExistenceProof>,
+}
+
+pub const LIGHT_CLIENTS: Map<&str, LightClientState> = Map::new("light_clients");
+/// (origin chain_id, origin height) -> app hash of every header the client verified.
+pub const CONSENSUS_ROOTS: Map<(&str, u64), ConsensusRoot> = Map::new("consensus_roots");
+
+/// Ed25519 through the host, so verification costs the same gas everywhere.
+struct HostVerifier<'a>(&'a dyn Api);
+
+impl SignatureVerifier for HostVerifier<'_> {
+    fn verify_ed25519(&self, message: &[u8], signature: &[u8], public_key: &[u8]) -> bool {
+        self.0.ed25519_verify(message, signature, public_key).unwrap_or(false)
+    }
+}
+
+fn block_time(env: &Env) -> aln_light_client::Timestamp {
+    aln_light_client::Timestamp { seconds: env.block.time.seconds() as i64, nanos: (env.block.time.nanos() % 1_000_000_000) as i32 }
+}
+
+fn save_root(storage: &mut dyn Storage, chain_id: &str, trusted: &TrustedState) -> StdResult<ConsensusRoot> {
+    let h = &trusted.header;
+    let root = ConsensusRoot { height: h.height, time: h.time, app_hash: hex::encode(&h.app_hash) };
+    CONSENSUS_ROOTS.save(storage, (chain_id, h.height), &root)?;
+    Ok(root)
+}
+
+fn client_event(chain_id: &str, root: &ConsensusRoot, action: &str) -> Event {
+    Event::new("aln_light_client")
+        .add_attribute("action", action)
+        .add_attribute("chain_id", chain_id)
+        .add_attribute("height", root.height.to_string())
+        .add_attribute("app_hash", root.app_hash.as_str())
+}
+
+/// Governance-only: (re)start the client of `chain_id` from a trusted header, or remove it
+/// with `None`. Verified roots are kept either way.
+pub fn set_light_client(deps: DepsMut, env: Env, info: MessageInfo, chain_id: String, client: Option<LightClientInit>) -> Result<Response, ContractError> {
+    let gov = GOVERNANCE.load(deps.storage)?;
+    if info.sender != gov { return Err(ContractError::Unauthorized { action: "set_light_client".to_string() }); }
+    let init = match client {
+        Some(c) => c,
+        None => {
+            LIGHT_CLIENTS.remove(deps.storage, chain_id.as_str());
+            return Ok(Response::new().add_attribute("action", "set_light_client").add_attribute("chain_id", chain_id).add_attribute("removed", "true"));
+        }
+    };
+    if init.trusted.header.chain_id != chain_id { return Err(ContractError::LightClient(format!("trusted header is for chain {}", init.trusted.header.chain_id))); }
+    init.options.validate().map_err(|e| ContractError::LightClient(e.to_string()))?;
+    init.trusted.validate().map_err(|e| ContractError::LightClient(e.to_string()))?;
+    let root = save_root(deps.storage, chain_id.as_str(), &init.trusted)?;
+    let state = LightClientState { chain_id: chain_id.clone(), options: init.options, require_proofs: init.require_proofs, latest: init.trusted, updated_at: env.block.height };
+    LIGHT_CLIENTS.save(deps.storage, chain_id.as_str(), &state)?;
+    Ok(Response::new()
+        .add_attribute("action", "set_light_client")
+        .add_attribute("chain_id", chain_id.as_str())
+        .add_event(client_event(chain_id.as_str(), &root, "trusted")))
+}
+
+/// Permissionless: verify a newer header of `chain_id` against the latest trusted one
+/// (sequentially or skipping) and record its app hash.
+pub fn update_light_client(deps: DepsMut, env: Env, chain_id: String, update: HeaderUpdate) -> Result<Response, ContractError> {
+    let mut state = LIGHT_CLIENTS.may_load(deps.storage, chain_id.as_str())?.ok_or_else(|| ContractError::LightClientNotFound { chain_id: chain_id.clone() })?;
+    let verifier = HostVerifier(deps.api);
+    state.latest = verify_header(&state.latest, &update.signed_header, &update.validators, &update.next_validators, &state.options, block_time(&env), &verifier).map_err(|e| ContractError::LightClient(e.to_string()))?;
+    state.updated_at = env.block.height;
+    let root = save_root(deps.storage, chain_id.as_str(), &state.latest)?;
+    LIGHT_CLIENTS.save(deps.storage, chain_id.as_str(), &state)?;
+    Ok(Response::new()
+        .add_attribute("action", "update_light_client")
+        .add_attribute("chain_id", chain_id.as_str())
+        .add_attribute("height", root.height.to_string())
+        .add_event(client_event(chain_id.as_str(), &root, "updated")))
+}
+
+fn lock_event(event: &OriginLockEvent) -> LockEvent {
+    LockEvent { tx_hash: event.tx_hash.clone(), nonce: event.nonce, origin_address: event.origin_address.clone(), denom: event.denom.clone(), amount: event.amount.clone(), height: event.height.unwrap_or(0) }
+}
+
+/// Check `event` against the root the client recorded at `proof.height`; returns that root.
+fn prove(storage: &dyn Storage, event: &OriginLockEvent, proof: &OriginLockProof) -> Result<ConsensusRoot, ContractError> {
+    let chain_id = event.origin_chain_id.as_str();
+    let root = CONSENSUS_ROOTS.may_load(storage, (chain_id, proof.height))?.ok_or(ContractError::ConsensusRootNotFound { chain_id: chain_id.to_string(), height: proof.height })?;
+    let app_hash = hex::decode(&root.app_hash).map_err(|e| ContractError::LightClient(e.to_string()))?;
+    verify_lock_event(&app_hash, &proof.proofs, &lock_event(event)).map_err(|e| ContractError::LightClient(e.to_string()))?;
+    Ok(root)
+}
+
+/// Origin check of `ClaimWithOrigin`: with a client for the event's chain, a given proof must
+/// verify against a recorded root, and is mandatory when the client `require_proofs`. Chains
+/// without a client keep the snapshot-root-only path, but a proof for them is an error.
+pub fn check_origin_proof(storage: &dyn Storage, event: &OriginLockEvent, proof: Option<&OriginLockProof>) -> Result<(), ContractError> {
+    let chain_id = event.origin_chain_id.as_str();
+    match (LIGHT_CLIENTS.may_load(storage, chain_id)?, proof) {
+        (None, None) => Ok(()),
+        (None, Some(_)) => Err(ContractError::LightClientNotFound { chain_id: chain_id.to_string() }),
+        (Some(c), None) if c.require_proofs => Err(ContractError::OriginProofRequired { chain_id: chain_id.to_string() }),
+        (Some(_), None) => Ok(()),
+        (Some(_), Some(p)) => prove(storage, event, p).map(|_| ()),
+    }
+}
+
+/// Client-backed `LightClient`: `root` is a hex app hash and `proof` a JSON
+/// `{ "event": OriginLockEvent, "proof": OriginLockProof }`. True when the event is stored
+/// under the app hash verified at the proof's height and that app hash is `root`.
+pub struct StoredLightClient<'a> {
+    pub storage: &'a dyn Storage,
+}
+
+#[derive(Serialize, Deserialize)]
+struct RootProof {
+    event: OriginLockEvent,
+    proof: OriginLockProof,
+}
+
+impl LightClient for StoredLightClient<'_> {
+    fn verify_root(&self, origin_chain_id: &str, root: &str, proof: &[u8]) -> Result<bool, ContractError> {
+        let p: RootProof = cosmwasm_std::from_slice(proof)?;
+        if p.event.origin_chain_id != origin_chain_id { return Ok(false); }
+        match prove(self.storage, &p.event, &p.proof) {
+            Ok(verified) => Ok(verified.app_hash == root.trim_start_matches("0x").to_lowercase()),
+            Err(ContractError::LightClient(_)) => Ok(false),
+            Err(e) => Err(e),
+        }
+    }
+}
+
+/// Verified root at `height`, or the latest one of `chain_id`.
+pub fn query_consensus_root(deps: Deps, chain_id: String, height: Option<u64>) -> StdResult<Option<ConsensusRoot>> {
+    match height {
+        Some(h) => CONSENSUS_ROOTS.may_load(deps.storage, (chain_id.as_str(), h)),
+        None => CONSENSUS_ROOTS.prefix(chain_id.as_str()).range(deps.storage, None, None, Order::Descending).next().transpose().map(|r| r.map(|(_, root)| root)),
+    }
+}
@@ -0,0 +1,104 @@
+use cosmwasm_std::{Addr, Deps, Empty, Order, StdResult, Storage};
+use cw_storage_plus::{Bound, Map, PrimaryKey};
+use serde::{Deserialize, Serialize};
+use crate::{EnergyVector, CLAIMED_LEAVES, ENERGY_LEDGER, REFACTOR_AUDIT, SYSTEM_WHITELIST};
+
+const DEFAULT_LIMIT: u32 = 10;
+const MAX_LIMIT: u32 = 30;
+
+/// (recipient, asset_id, (epoch, snapshot_hash)) index over `CLAIMED_LEAVES`, for listing an
+/// address's claims.
+pub const CLAIMS_BY_RECIPIENT: Map<(&Addr, &str, (u32, &str)), Empty> = Map::new("claims_by_recipient");
+
+/// A claimed leaf and the ALN address it was credited to.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct ClaimRecord {
+    pub asset_id: String,
+    pub epoch: u32,
+    pub snapshot_hash: String,
+    pub recipient: Addr,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct EnergyHolder {
+    pub address: Addr,
+    pub energy: EnergyVector,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct RefactorAuditEntry {
+    pub tx_hash: String,
+    pub nonce: u64,
+    pub audit: String,
+}
+
+/// Mark a leaf of snapshot epoch `epoch` claimed by `recipient` and index it under the recipient.
+pub fn mark_claimed(storage: &mut dyn Storage, asset_id: &str, epoch: u32, snapshot_hash: &str, recipient: &Addr) -> StdResult<()> {
+    CLAIMED_LEAVES.save(storage, (asset_id, epoch, snapshot_hash), recipient)?;
+    CLAIMS_BY_RECIPIENT.save(storage, (recipient, asset_id, (epoch, snapshot_hash)), &Empty {})
+}
+
+fn page(limit: Option<u32>) -> usize {
+    limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize
+}
+
+/// Claims credited to `address`, ordered by (asset_id, epoch, snapshot_hash).
+pub fn query_claims_by_address(deps: Deps, address: String, start_after: Option<(String, u32, String)>, limit: Option<u32>) -> StdResult<Vec<ClaimRecord>> {
+    let addr = deps.api.addr_validate(&address)?;
+    let start = start_after.map(|(a, e, h)| Bound::exclusive((a.as_str(), (e, h.as_str())).joined_key()));
+    CLAIMS_BY_RECIPIENT
+        .sub_prefix(&addr)
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(page(limit))
+        .map(|item| item.map(|((asset_id, (epoch, snapshot_hash)), _)| ClaimRecord { asset_id, epoch, snapshot_hash, recipient: addr.clone() }))
+        .collect()
+}
+
+/// Claimed leaves of `asset_id`, ordered by (epoch, snapshot_hash).
+pub fn query_claims_by_asset(deps: Deps, asset_id: String, start_after: Option<(u32, String)>, limit: Option<u32>) -> StdResult<Vec<ClaimRecord>> {
+    let start = start_after.map(|(e, h)| Bound::exclusive((e, h.as_str()).joined_key()));
+    CLAIMED_LEAVES
+        .sub_prefix(asset_id.as_str())
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(page(limit))
+        .map(|item| item.map(|((epoch, snapshot_hash), recipient)| ClaimRecord { asset_id: asset_id.clone(), epoch, snapshot_hash, recipient }))
+        .collect()
+}
+
+/// `ENERGY_LEDGER` entries ordered by address.
+pub fn query_energy_holders(deps: Deps, start_after: Option<String>, limit: Option<u32>) -> StdResult<Vec<EnergyHolder>> {
+    let start = match start_after {
+        Some(s) => Some(Bound::exclusive(deps.api.addr_validate(&s)?.as_bytes())),
+        None => None,
+    };
+    ENERGY_LEDGER
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(page(limit))
+        .map(|item| item.map(|(address, energy)| EnergyHolder { address, energy }))
+        .collect()
+}
+
+/// Currently whitelisted system contracts ordered by address; removed entries are skipped.
+pub fn query_system_whitelist(deps: Deps, start_after: Option<String>, limit: Option<u32>) -> StdResult<Vec<Addr>> {
+    let start = match start_after {
+        Some(s) => Some(Bound::exclusive(deps.api.addr_validate(&s)?.as_bytes())),
+        None => None,
+    };
+    SYSTEM_WHITELIST
+        .range(deps.storage, start, None, Order::Ascending)
+        .filter(|item| !matches!(item, Ok((_, false))))
+        .take(page(limit))
+        .map(|item| item.map(|(addr, _)| addr))
+        .collect()
+}
+
+/// Refactor audit entries of `origin_chain`, ordered by (tx_hash, nonce).
+pub fn query_refactor_audits(deps: Deps, origin_chain: String, start_after: Option<(String, u64)>, limit: Option<u32>) -> StdResult<Vec<RefactorAuditEntry>> {
+    let start = start_after.map(|(tx, n)| Bound::exclusive((tx.as_str(), n).joined_key()));
+    REFACTOR_AUDIT
+        .prefix(origin_chain.as_str())
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(page(limit))
+        .map(|item| item.map(|((tx_hash, nonce), audit)| RefactorAuditEntry { tx_hash, nonce, audit }))
+        .collect()
+}
@@ -0,0 +1,112 @@
+use cosmwasm_std::{Addr, Deps, DepsMut, MessageInfo, Response, StdResult, Storage};
+use cw_storage_plus::{Item, Map};
+use serde::{Deserialize, Serialize};
+use crate::{ContractError, GOVERNANCE};
+
+/// Entry points that can be halted independently.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum PausableAction {
+    Claim,
+    ClaimWithOrigin,
+    SystemConsume,
+}
+
+impl PausableAction {
+    fn as_str(&self) -> &'static str {
+        match self {
+            PausableAction::Claim => "claim",
+            PausableAction::ClaimWithOrigin => "claim_with_origin",
+            PausableAction::SystemConsume => "system_consume",
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Default)]
+pub struct PauseFlags {
+    pub claim: bool,
+    pub claim_with_origin: bool,
+    pub system_consume: bool,
+}
+
+impl PauseFlags {
+    fn get(&self, action: PausableAction) -> bool {
+        match action {
+            PausableAction::Claim => self.claim,
+            PausableAction::ClaimWithOrigin => self.claim_with_origin,
+            PausableAction::SystemConsume => self.system_consume,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct PauseStateResponse {
+    pub global: PauseFlags,
+    /// Present when the query named an asset_id.
+    pub asset: Option<PauseFlags>,
+}
+
+/// Guardians may toggle pauses but hold no other governance rights.
+pub const GUARDIANS: Map<&Addr, bool> = Map::new("guardians");
+pub const GLOBAL_PAUSE: Item<PauseFlags> = Item::new("global_pause");
+/// Per-asset circuit breakers; `system_consume` is never set here since the ledger is not per asset.
+pub const ASSET_PAUSE: Map<&str, PauseFlags> = Map::new("asset_pause");
+
+/// Reject `action` when paused globally or, for claims, on `asset_id`.
+pub fn ensure_not_paused(storage: &dyn Storage, action: PausableAction, asset_id: Option<&str>) -> Result<(), ContractError> {
+    if GLOBAL_PAUSE.may_load(storage)?.unwrap_or_default().get(action) {
+        return Err(ContractError::Paused { action: action.as_str().to_string() });
+    }
+    if let Some(id) = asset_id {
+        if ASSET_PAUSE.may_load(storage, id)?.unwrap_or_default().get(action) {
+            return Err(ContractError::AssetPaused { asset_id: id.to_string(), action: action.as_str().to_string() });
+        }
+    }
+    Ok(())
+}
+
+pub fn set_guardian(deps: DepsMut, info: MessageInfo, addr: String, enabled: bool) -> Result<Response, ContractError> {
+    let gov = GOVERNANCE.load(deps.storage)?;
+    if info.sender != gov { return Err(ContractError::Unauthorized { action: "set_guardian".to_string() }); }
+    let a = deps.api.addr_validate(&addr)?;
+    GUARDIANS.save(deps.storage, &a, &enabled)?;
+    let action = if enabled { "add_guardian" } else { "remove_guardian" };
+    Ok(Response::new().add_attribute("action", action).add_attribute("addr", addr))
+}
+
+pub fn set_pause(deps: DepsMut, info: MessageInfo, asset_id: Option<String>, claim: Option<bool>, claim_with_origin: Option<bool>, system_consume: Option<bool>) -> Result<Response, ContractError> {
+    let gov = GOVERNANCE.load(deps.storage)?;
+    if info.sender != gov && !GUARDIANS.may_load(deps.storage, &info.sender)?.unwrap_or(false) {
+        return Err(ContractError::Unauthorized { action: "set_pause".to_string() });
+    }
+    let mut flags = match &asset_id {
+        Some(id) => {
+            if system_consume.is_some() { return Err(ContractError::InvalidConfig("system_consume cannot be paused per asset".to_string())); }
+            ASSET_PAUSE.may_load(deps.storage, id.as_str())?.unwrap_or_default()
+        }
+        None => GLOBAL_PAUSE.may_load(deps.storage)?.unwrap_or_default(),
+    };
+    if let Some(v) = claim { flags.claim = v; }
+    if let Some(v) = claim_with_origin { flags.claim_with_origin = v; }
+    if let Some(v) = system_consume { flags.system_consume = v; }
+    match &asset_id {
+        Some(id) => ASSET_PAUSE.save(deps.storage, id.as_str(), &flags)?,
+        None => GLOBAL_PAUSE.save(deps.storage, &flags)?,
+    }
+    Ok(Response::new()
+        .add_attribute("action", "set_pause")
+        .add_attribute("scope", asset_id.unwrap_or_else(|| "global".to_string()))
+        .add_attribute("by", info.sender)
+        .add_attribute("claim", flags.claim.to_string())
+        .add_attribute("claim_with_origin", flags.claim_with_origin.to_string())
+        .add_attribute("system_consume", flags.system_consume.to_string()))
+}
+
+pub fn query_pause_state(deps: Deps, asset_id: Option<String>) -> StdResult<PauseStateResponse> {
+    let global = GLOBAL_PAUSE.may_load(deps.storage)?.unwrap_or_default();
+    let asset = match asset_id {
+        Some(id) => Some(ASSET_PAUSE.may_load(deps.storage, id.as_str())?.unwrap_or_default()),
+        None => None,
+    };
+    Ok(PauseStateResponse { global, asset })
+}
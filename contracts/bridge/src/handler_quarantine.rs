@@ -0,0 +1,160 @@
+use cosmwasm_std::{Addr, Deps, DepsMut, Empty, Env, Event, MessageInfo, Order, Response, StdResult, Storage, Uint128};
+use cw_storage_plus::{Bound, Item, Map};
+use serde::{Deserialize, Serialize};
+use crate::handler_rate_limit::{self, QueuedClaim, Settlement};
+use crate::{ContractError, DowngradeTreatment, EnergyVector, LedgerChangeReason, SnapshotEntry, GOVERNANCE};
+
+const DEFAULT_LIMIT: u32 = 10;
+const MAX_LIMIT: u32 = 30;
+
+/// Why a claim waits for governance instead of being credited.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum QuarantineReason {
+    /// Entitled AU.ET above the configured anomaly threshold.
+    AnomalyThreshold { threshold: Uint128 },
+    /// The asset's scaling profile is flagged toxic.
+    ToxicProfile,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum QuarantineStatus {
+    Open,
+    Released,
+    Burned,
+}
+
+/// One entry of a case's audit trail: `opened`, `released` or `burned`.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct QuarantineAction {
+    pub action: String,
+    pub actor: Addr,
+    pub height: u64,
+    pub note: Option<String>,
+}
+
+/// A sanitized claim held for governance. The leaf stays claimed and nothing is credited until a
+/// release settles the claim like a fresh one (rate limits, then `downgrade`).
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct QuarantineCase {
+    pub id: u64,
+    pub asset_id: String,
+    /// Snapshot epoch of the claimed leaf; cases opened before epochs read as 0.
+    #[serde(default)]
+    pub epoch: u32,
+    pub snapshot_hash: String,
+    pub claimant: Addr,
+    /// Snapshot entry or origin lock event the claim proved.
+    pub origin: SnapshotEntry,
+    pub origin_tx_hash: Option<String>,
+    pub origin_nonce: Option<u64>,
+    pub amount: Uint128,
+    /// Sanitized energy, before `downgrade`.
+    pub energy: EnergyVector,
+    pub reason: QuarantineReason,
+    pub scaling_profile_id: String,
+    pub ubs_report_hash: Option<String>,
+    pub sink: Addr,
+    pub status: QuarantineStatus,
+    pub history: Vec<QuarantineAction>,
+    /// Block time (seconds) of the claim; vesting locks start there.
+    pub claimed_at: u64,
+    /// Entitled AU.ET + CSP, as counted in the energy totals (and rate limits on release).
+    #[serde(default)]
+    pub entitled: Uint128,
+    /// Treatment of a downgraded claim, applied when it is released.
+    #[serde(default)]
+    pub downgrade: Option<DowngradeTreatment>,
+    /// Whether the asset's profile was toxic; a burn takes it out of `ToxicEnergy` too.
+    #[serde(default)]
+    pub toxic: bool,
+}
+
+pub const QUARANTINE_CASES: Map<u64, QuarantineCase> = Map::new("quarantine_cases");
+/// Ids of cases still `Open`.
+pub const OPEN_QUARANTINE: Map<u64, Empty> = Map::new("open_quarantine");
+const QUARANTINE_SEQ: Item<u64> = Item::new("quarantine_seq");
+
+fn case_event(case: &QuarantineCase, action: &str) -> Event {
+    Event::new("aln_quarantine")
+        .add_attribute("action", action)
+        .add_attribute("case_id", case.id.to_string())
+        .add_attribute("asset_id", case.asset_id.as_str())
+        .add_attribute("claimant", case.claimant.as_str())
+        .add_attribute("snapshot_hash", case.snapshot_hash.as_str())
+}
+
+/// Record a diverted claim as an open case. `case.id`, `status` and `history` are set here.
+pub fn open_case(storage: &mut dyn Storage, env: &Env, mut case: QuarantineCase) -> StdResult<(u64, Event)> {
+    let id = QUARANTINE_SEQ.may_load(storage)?.unwrap_or_default() + 1;
+    QUARANTINE_SEQ.save(storage, &id)?;
+    case.id = id;
+    case.status = QuarantineStatus::Open;
+    case.history = vec![QuarantineAction { action: "opened".to_string(), actor: env.contract.address.clone(), height: env.block.height, note: None }];
+    QUARANTINE_CASES.save(storage, id, &case)?;
+    OPEN_QUARANTINE.save(storage, id, &Empty {})?;
+    Ok((id, case_event(&case, "opened")))
+}
+
+/// Governance-only: move an open case to `status`, appending `trail` to its history.
+fn close_case(deps: &mut DepsMut, env: &Env, info: &MessageInfo, id: u64, action: &str, trail: &str, status: QuarantineStatus, note: Option<String>) -> Result<QuarantineCase, ContractError> {
+    let gov = GOVERNANCE.load(deps.storage)?;
+    if info.sender != gov { return Err(ContractError::Unauthorized { action: action.to_string() }); }
+    let mut case = QUARANTINE_CASES.may_load(deps.storage, id)?.ok_or(ContractError::QuarantineCaseNotFound { id })?;
+    if case.status != QuarantineStatus::Open { return Err(ContractError::QuarantineCaseClosed { id }); }
+    case.status = status;
+    case.history.push(QuarantineAction { action: trail.to_string(), actor: info.sender.clone(), height: env.block.height, note });
+    QUARANTINE_CASES.save(deps.storage, id, &case)?;
+    OPEN_QUARANTINE.remove(deps.storage, id);
+    Ok(case)
+}
+
+/// Governance-only: settle a case's claim for the original claimant: queued if it now exceeds a
+/// rate limit, otherwise credited after its downgrade treatment.
+pub fn release_quarantine(mut deps: DepsMut, env: Env, info: MessageInfo, id: u64, note: Option<String>) -> Result<Response, ContractError> {
+    let case = close_case(&mut deps, &env, &info, id, "release_quarantine", "released", QuarantineStatus::Released, note)?;
+    let claim = QueuedClaim {
+        id: 0,
+        asset_id: case.asset_id.clone(),
+        epoch: case.epoch,
+        recipient: case.claimant.clone(),
+        snapshot_hash: case.snapshot_hash.clone(),
+        amount: case.entitled,
+        energy: case.energy.clone(),
+        downgrade: case.downgrade.clone(),
+        toxic: case.toxic,
+        reason: String::new(),
+        queued_at_height: 0,
+        claimed_at: case.claimed_at,
+    };
+    let res = match handler_rate_limit::settle(deps, &env, claim, LedgerChangeReason::QuarantineRelease)? {
+        Settlement::Queued { id: queue_id, limit } => Response::new().add_attribute("queue_id", queue_id.to_string()).add_attribute("reason", limit),
+        Settlement::Credited(res) => res,
+    };
+    Ok(res
+        .add_attribute("action", "release_quarantine")
+        .add_attribute("case_id", id.to_string())
+        .add_event(case_event(&case, "released")))
+}
+
+/// Governance-only: close a case for good. The claimant is never credited and the claim leaves
+/// the energy totals.
+pub fn burn_quarantine(mut deps: DepsMut, env: Env, info: MessageInfo, id: u64, note: Option<String>) -> Result<Response, ContractError> {
+    let case = close_case(&mut deps, &env, &info, id, "burn_quarantine", "burned", QuarantineStatus::Burned, note)?;
+    crate::release_energy_totals(deps.storage, case.toxic, case.entitled)?;
+    Ok(Response::new()
+        .add_attribute("action", "burn_quarantine")
+        .add_attribute("case_id", id.to_string())
+        .add_event(case_event(&case, "burned")))
+}
+
+pub fn query_open_quarantine(deps: Deps, start_after: Option<u64>, limit: Option<u32>) -> StdResult<Vec<QuarantineCase>> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.map(Bound::exclusive_int);
+    OPEN_QUARANTINE
+        .keys(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|id| QUARANTINE_CASES.load(deps.storage, id?))
+        .collect()
+}
@@ -0,0 +1,251 @@
+use cosmwasm_std::{Addr, Deps, DepsMut, Env, MessageInfo, Order, Response, StdResult, Storage, Uint128};
+use cw_storage_plus::{Bound, Item, Map};
+use serde::{Deserialize, Serialize};
+use crate::{handler_downgrade, ContractError, DowngradeTreatment, EnergyVector, LedgerChangeReason, GOVERNANCE};
+
+const DEFAULT_LIMIT: u32 = 10;
+const MAX_LIMIT: u32 = 30;
+
+/// Rolling window length, in blocks or seconds.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum RateWindow {
+    Height(u64),
+    Time(u64),
+}
+
+impl RateWindow {
+    fn now(&self, env: &Env) -> u64 {
+        match self {
+            RateWindow::Height(_) => env.block.height,
+            RateWindow::Time(_) => env.block.time.seconds(),
+        }
+    }
+
+    fn len(&self) -> u64 {
+        match self {
+            RateWindow::Height(n) | RateWindow::Time(n) => *n,
+        }
+    }
+}
+
+/// Per-asset claim limits over a rolling window, in entitled AU.ET + CSP units.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct RateLimit {
+    pub window: RateWindow,
+    pub max_per_asset: Option<Uint128>,
+    pub max_per_recipient: Option<Uint128>,
+}
+
+/// A claim that exceeded a rate limit. The leaf is already marked claimed; the energy is
+/// credited only when governance releases it.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct QueuedClaim {
+    pub id: u64,
+    pub asset_id: String,
+    #[serde(default)]
+    pub epoch: u32,
+    pub recipient: Addr,
+    pub snapshot_hash: String,
+    pub amount: Uint128,
+    /// Sanitized energy, before `downgrade`.
+    pub energy: EnergyVector,
+    /// Treatment of a downgraded claim, applied when it is released.
+    #[serde(default)]
+    pub downgrade: Option<DowngradeTreatment>,
+    /// Whether the asset's profile was toxic; a rejection takes it out of `ToxicEnergy` too.
+    #[serde(default)]
+    pub toxic: bool,
+    pub reason: String,
+    pub queued_at_height: u64,
+    /// Block time (seconds) of the claim; vesting locks start there.
+    pub claimed_at: u64,
+}
+
+/// A rate window is counted in at most this many buckets, so its state stays bounded.
+pub const RATE_WINDOW_BUCKETS: u64 = 24;
+
+/// Claimed amounts per bucket of `bucket_len` window units, keyed by `now / bucket_len`.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Default)]
+pub struct ClaimWindow {
+    pub bucket_len: u64,
+    pub buckets: Vec<(u64, Uint128)>,
+}
+
+pub const RATE_LIMITS: Map<&str, RateLimit> = Map::new("rate_limits");
+/// Current window per asset and per (asset, recipient).
+pub const ASSET_CLAIM_WINDOW: Map<&str, ClaimWindow> = Map::new("asset_claim_buckets");
+pub const RECIPIENT_CLAIM_WINDOW: Map<(&str, &Addr), ClaimWindow> = Map::new("recipient_claim_buckets");
+pub const QUEUED_CLAIMS: Map<u64, QueuedClaim> = Map::new("queued_claims");
+pub const QUEUED_CLAIM_SEQ: Item<u64> = Item::new("queued_claim_seq");
+
+/// Bucket length and count covering a window of `len` units.
+fn bucket_shape(len: u64) -> (u64, u64) {
+    let bucket_len = (len + RATE_WINDOW_BUCKETS - 1) / RATE_WINDOW_BUCKETS;
+    (bucket_len, (len + bucket_len - 1) / bucket_len)
+}
+
+/// `window` without the buckets that left it; empty when it was counted with another bucket length.
+fn current(window: Option<ClaimWindow>, now: u64, len: u64) -> ClaimWindow {
+    let (bucket_len, count) = bucket_shape(len);
+    let idx = now / bucket_len;
+    match window {
+        Some(w) if w.bucket_len == bucket_len => ClaimWindow { bucket_len, buckets: w.buckets.into_iter().filter(|(b, _)| b + count > idx).collect() },
+        _ => ClaimWindow { bucket_len, buckets: vec![] },
+    }
+}
+
+fn sum(window: &ClaimWindow) -> Uint128 {
+    window.buckets.iter().fold(Uint128::zero(), |acc, (_, a)| acc + *a)
+}
+
+fn add(window: &mut ClaimWindow, now: u64, amount: Uint128) {
+    let idx = now / window.bucket_len;
+    match window.buckets.iter_mut().find(|(b, _)| *b == idx) {
+        Some((_, a)) => *a += amount,
+        None => window.buckets.push((idx, amount)),
+    }
+}
+
+/// Name of the limit `amount` would exceed in the asset's current windows, if any. Read-only.
+pub fn exceeded_limit(storage: &dyn Storage, env: &Env, asset_id: &str, recipient: &Addr, amount: Uint128) -> StdResult<Option<&'static str>> {
+    let limit = match RATE_LIMITS.may_load(storage, asset_id)? {
+        Some(l) => l,
+        None => return Ok(None),
+    };
+    let now = limit.window.now(env);
+    let len = limit.window.len();
+    if let Some(max) = limit.max_per_asset {
+        let window = current(ASSET_CLAIM_WINDOW.may_load(storage, asset_id)?, now, len);
+        if sum(&window) + amount > max { return Ok(Some("asset_rate_limit")); }
+    }
+    if let Some(max) = limit.max_per_recipient {
+        let window = current(RECIPIENT_CLAIM_WINDOW.may_load(storage, (asset_id, recipient))?, now, len);
+        if sum(&window) + amount > max { return Ok(Some("recipient_rate_limit")); }
+    }
+    Ok(None)
+}
+
+/// Record `amount` against the asset's windows. Returns the exceeded limit's name instead
+/// of recording when the claim would push either window over its maximum.
+pub fn check_and_record(storage: &mut dyn Storage, env: &Env, asset_id: &str, recipient: &Addr, amount: Uint128) -> StdResult<Option<&'static str>> {
+    let limit = match RATE_LIMITS.may_load(storage, asset_id)? {
+        Some(l) => l,
+        None => return Ok(None),
+    };
+    if let Some(reason) = exceeded_limit(storage, env, asset_id, recipient, amount)? {
+        return Ok(Some(reason));
+    }
+    let now = limit.window.now(env);
+    let len = limit.window.len();
+    let mut asset_window = current(ASSET_CLAIM_WINDOW.may_load(storage, asset_id)?, now, len);
+    let mut recipient_window = current(RECIPIENT_CLAIM_WINDOW.may_load(storage, (asset_id, recipient))?, now, len);
+    add(&mut asset_window, now, amount);
+    add(&mut recipient_window, now, amount);
+    ASSET_CLAIM_WINDOW.save(storage, asset_id, &asset_window)?;
+    RECIPIENT_CLAIM_WINDOW.save(storage, (asset_id, recipient), &recipient_window)?;
+    Ok(None)
+}
+
+/// Store `claim` under the next queue id, queued at the current height.
+pub fn enqueue(storage: &mut dyn Storage, env: &Env, mut claim: QueuedClaim) -> StdResult<u64> {
+    let id = QUEUED_CLAIM_SEQ.may_load(storage)?.unwrap_or_default() + 1;
+    QUEUED_CLAIM_SEQ.save(storage, &id)?;
+    claim.id = id;
+    claim.queued_at_height = env.block.height;
+    QUEUED_CLAIMS.save(storage, id, &claim)?;
+    Ok(id)
+}
+
+pub fn set_rate_limit(deps: DepsMut, info: MessageInfo, asset_id: String, limit: Option<RateLimit>) -> Result<Response, ContractError> {
+    let gov = GOVERNANCE.load(deps.storage)?;
+    if info.sender != gov { return Err(ContractError::Unauthorized { action: "set_rate_limit".to_string() }); }
+    match &limit {
+        Some(l) => {
+            crate::registry_asset(deps.as_ref(), asset_id.as_str())?;
+            if l.window.len() == 0 { return Err(ContractError::InvalidConfig("rate limit window must be non-zero".to_string())); }
+            RATE_LIMITS.save(deps.storage, asset_id.as_str(), l)?;
+        }
+        None => RATE_LIMITS.remove(deps.storage, asset_id.as_str()),
+    }
+    Ok(Response::new().add_attribute("action", "set_rate_limit").add_attribute("asset_id", asset_id).add_attribute("enabled", limit.is_some().to_string()))
+}
+
+/// What `settle` did with a sanitized claim.
+pub enum Settlement {
+    /// Over the named limit: queued under the id.
+    Queued { id: u64, limit: &'static str },
+    /// Credited; the response carries the ledger hooks and the downgrade and lock attributes.
+    Credited(Response),
+}
+
+/// Settle a sanitized claim (`id`, `reason` and `queued_at_height` are set here): queue it when
+/// it exceeds a rate limit, otherwise credit it. Claims and quarantine releases go through here.
+pub fn settle(deps: DepsMut, env: &Env, claim: QueuedClaim, reason: LedgerChangeReason) -> Result<Settlement, ContractError> {
+    if let Some(limit) = check_and_record(deps.storage, env, claim.asset_id.as_str(), &claim.recipient, claim.amount)? {
+        let id = enqueue(deps.storage, env, QueuedClaim { reason: limit.to_string(), ..claim })?;
+        return Ok(Settlement::Queued { id, limit });
+    }
+    Ok(Settlement::Credited(credit(deps, env, &claim, reason)?))
+}
+
+/// Apply a claim's downgrade treatment and credit the rest to its recipient, locked from the
+/// claim time.
+fn credit(mut deps: DepsMut, env: &Env, claim: &QueuedClaim, reason: LedgerChangeReason) -> Result<Response, ContractError> {
+    let (ev, vesting, downgrade_attrs, sink_hooks) = match &claim.downgrade {
+        Some(t) => {
+            let o = handler_downgrade::apply(deps.branch(), t, claim.energy.clone())?;
+            (o.credit, o.vesting, o.attrs, o.hooks)
+        }
+        None => (claim.energy.clone(), None, vec![], vec![]),
+    };
+    let (lock_id, hooks) = crate::handler_vesting::credit_claim(deps, env, claim.asset_id.as_str(), &claim.recipient, ev, reason, claim.claimed_at, vesting)?;
+    Ok(Response::new()
+        .add_submessages(sink_hooks)
+        .add_submessages(hooks)
+        .add_attributes(downgrade_attrs)
+        .add_attributes(lock_id.map(|id| ("energy_lock_id", id.to_string()))))
+}
+
+/// Governance-only: credit a queued claim to its recipient, after its downgrade treatment.
+pub fn release_queued_claim(deps: DepsMut, env: Env, info: MessageInfo, id: u64) -> Result<Response, ContractError> {
+    let gov = GOVERNANCE.load(deps.storage)?;
+    if info.sender != gov { return Err(ContractError::Unauthorized { action: "release_queued_claim".to_string() }); }
+    let q = QUEUED_CLAIMS.may_load(deps.storage, id)?.ok_or(ContractError::QueuedClaimNotFound { id })?;
+    QUEUED_CLAIMS.remove(deps.storage, id);
+    Ok(credit(deps, &env, &q, LedgerChangeReason::QueuedClaimRelease)?
+        .add_attribute("action", "release_queued_claim")
+        .add_attribute("queue_id", id.to_string())
+        .add_attribute("recipient", q.recipient)
+        .add_attribute("asset_id", q.asset_id)
+        .add_attribute("epoch", q.epoch.to_string())
+        .add_attribute("snapshot_hash", q.snapshot_hash))
+}
+
+/// Governance-only: drop a queued claim without crediting it. Its leaf stays claimed and its
+/// amount leaves the energy totals.
+pub fn reject_queued_claim(deps: DepsMut, info: MessageInfo, id: u64, note: Option<String>) -> Result<Response, ContractError> {
+    let gov = GOVERNANCE.load(deps.storage)?;
+    if info.sender != gov { return Err(ContractError::Unauthorized { action: "reject_queued_claim".to_string() }); }
+    let q = QUEUED_CLAIMS.may_load(deps.storage, id)?.ok_or(ContractError::QueuedClaimNotFound { id })?;
+    QUEUED_CLAIMS.remove(deps.storage, id);
+    crate::release_energy_totals(deps.storage, q.toxic, q.amount)?;
+    Ok(Response::new()
+        .add_attribute("action", "reject_queued_claim")
+        .add_attribute("queue_id", id.to_string())
+        .add_attribute("recipient", q.recipient)
+        .add_attribute("asset_id", q.asset_id)
+        .add_attribute("epoch", q.epoch.to_string())
+        .add_attribute("snapshot_hash", q.snapshot_hash)
+        .add_attribute("note", note.unwrap_or_default()))
+}
+
+pub fn query_queued_claims(deps: Deps, start_after: Option<u64>, limit: Option<u32>) -> StdResult<Vec<QueuedClaim>> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.map(Bound::exclusive_int);
+    QUEUED_CLAIMS
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| item.map(|(_, q)| q))
+        .collect()
+}
@@ -0,0 +1,119 @@
+use cosmwasm_std::{Addr, Deps, Env, StdError, StdResult};
+use serde::{Deserialize, Serialize};
+use crate::handler_claim_plan::{plan_claim, ClaimChecks, ClaimInput, ClaimOutcome};
+use crate::handler_pause::{ensure_not_paused, PausableAction};
+use crate::{handler_downgrade, handler_light_client};
+use crate::{ContractError, DowngradeTreatment, EnergyVector, ExecuteMsg, LeafProof, OriginLockEvent, OriginLockProof, QuarantineReason, SnapshotEntry};
+
+/// Where a simulated claim's energy ends up.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ClaimDestination {
+    /// Credited to the recipient's ledger (or queued for it by a rate limit).
+    Recipient { address: Addr },
+    /// Held in a quarantine case until governance releases it to the recipient or burns it.
+    Quarantine { reason: QuarantineReason },
+}
+
+/// One check the claim would fail, named after the step in the claim flow.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct ClaimCheckFailure {
+    pub check: String,
+    pub error: String,
+}
+
+/// Outcome of `SimulateClaim`. `energy` and `destination` are set only when `failures` is empty.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct SimulateClaimResponse {
+    pub energy: Option<EnergyVector>,
+    pub destination: Option<ClaimDestination>,
+    /// `approved` or `downgraded`; unset when the UBS step is not reached.
+    pub ubs_decision: Option<String>,
+    pub downgrade: Option<DowngradeTreatment>,
+    /// Rate limit the claim would be queued by.
+    pub queued: Option<String>,
+    pub failures: Vec<ClaimCheckFailure>,
+}
+
+struct ClaimRequest {
+    action: PausableAction,
+    /// Why the `ClaimFor` recipient is not a valid address; the checks then run against it as given.
+    invalid_recipient: Option<ContractError>,
+    input: ClaimInput,
+    /// Origin event and light client proof of a `ClaimWithOrigin`.
+    origin: Option<(OriginLockEvent, Option<OriginLockProof>)>,
+}
+
+fn claim_request(deps: Deps, sender: &str, msg: ExecuteMsg) -> StdResult<ClaimRequest> {
+    let sender = deps.api.addr_validate(sender)?;
+    Ok(match msg {
+        ExecuteMsg::Claim { asset_id, epoch, snapshot, snapshot_hash, merkle_proof, amount_auet, amount_csp, origin_tx_hash, origin_nonce, ubs_report_hash } => {
+            let input = ClaimInput { recipient: sender, authorization: None, asset_id, epoch: epoch.unwrap_or_default(), snapshot, snapshot_hash: Some(snapshot_hash), proof: LeafProof::Path(merkle_proof), amount_auet, amount_csp, origin_tx_hash, origin_nonce, ubs_report_hash };
+            ClaimRequest { action: PausableAction::Claim, invalid_recipient: None, input, origin: None }
+        }
+        ExecuteMsg::ClaimFor { recipient, asset_id, epoch, snapshot, snapshot_hash, merkle_proof, amount_auet, amount_csp, origin_tx_hash, origin_nonce, ubs_report_hash, authorization } => {
+            let (recipient, invalid_recipient) = match deps.api.addr_validate(&recipient) {
+                Ok(r) => (r, None),
+                Err(e) => (Addr::unchecked(recipient), Some(ContractError::from(e))),
+            };
+            let input = ClaimInput { recipient, authorization: Some(authorization), asset_id, epoch: epoch.unwrap_or_default(), snapshot, snapshot_hash: Some(snapshot_hash), proof: LeafProof::Path(merkle_proof), amount_auet, amount_csp, origin_tx_hash, origin_nonce, ubs_report_hash };
+            ClaimRequest { action: PausableAction::Claim, invalid_recipient, input, origin: None }
+        }
+        ExecuteMsg::ClaimWithOrigin { asset_id, epoch, origin_event, merkle_proof, ubs_report_hash, amount_auet, amount_csp, origin_proof } => {
+            let snapshot = SnapshotEntry { chain_id: origin_event.origin_chain_id.clone(), height: origin_event.height.unwrap_or(0), denom: origin_event.denom.clone(), address: origin_event.origin_address.clone(), balance: origin_event.amount.clone() };
+            let input = ClaimInput { recipient: sender, authorization: None, asset_id, epoch: epoch.unwrap_or_default(), snapshot, snapshot_hash: None, proof: LeafProof::Path(merkle_proof), amount_auet, amount_csp, origin_tx_hash: Some(origin_event.tx_hash.clone()), origin_nonce: Some(origin_event.nonce), ubs_report_hash };
+            ClaimRequest { action: PausableAction::ClaimWithOrigin, invalid_recipient: None, input, origin: Some((origin_event, origin_proof)) }
+        }
+        _ => return Err(StdError::generic_err("simulate_claim takes a claim, claim_for or claim_with_origin message")),
+    })
+}
+
+/// Run every check of the claim flow for `msg` as if `sender` submitted it, without writing
+/// state, and report the plan the claim would apply. Checks that depend on an earlier failed
+/// step (e.g. the proof on a missing asset) are skipped rather than reported twice.
+pub fn simulate_claim(deps: Deps, env: Env, sender: String, msg: ExecuteMsg) -> StdResult<SimulateClaimResponse> {
+    let req = claim_request(deps, &sender, msg)?;
+    let mut checks = ClaimChecks::default();
+
+    // the steps `execute` runs before the claim itself
+    checks.check("paused", ensure_not_paused(deps.storage, req.action, Some(req.input.asset_id.as_str())));
+    if let Some(err) = req.invalid_recipient { checks.push("recipient", err); }
+    if let Some((event, proof)) = req.origin.as_ref() {
+        checks.check("origin_proof", handler_light_client::check_origin_proof(deps.storage, event, proof.as_ref()));
+    }
+
+    let plan = match plan_claim(deps, &env, &req.input, &mut checks)? {
+        Some(p) => p,
+        None => return Ok(rejected(checks)),
+    };
+    if matches!(plan.outcome, ClaimOutcome::Rejected) {
+        checks.push("ubs_decision", ContractError::UbsRejected { report_hash: plan.sanitization.report_hash.clone() });
+    }
+    if !checks.0.is_empty() { return Ok(rejected(checks)); }
+
+    let ev = EnergyVector { auet: plan.sanitization.energy.auet, csp: plan.sanitization.energy.csp, erp: plan.sanitization.energy.erp };
+    let energy = match &plan.downgrade {
+        Some(t) => handler_downgrade::credited_part(t, &ev),
+        None => ev,
+    };
+    // quarantined claims meet the rate limits only when released
+    let recipient = ClaimDestination::Recipient { address: req.input.recipient };
+    let (destination, queued) = match plan.outcome {
+        ClaimOutcome::Quarantine(reason) => (ClaimDestination::Quarantine { reason }, None),
+        ClaimOutcome::Queued(limit) => (recipient, Some(limit.to_string())),
+        _ => (recipient, None),
+    };
+    Ok(SimulateClaimResponse {
+        energy: Some(energy),
+        destination: Some(destination),
+        ubs_decision: Some(if plan.downgrade.is_some() { "downgraded" } else { "approved" }.to_string()),
+        downgrade: plan.downgrade,
+        queued,
+        failures: vec![],
+    })
+}
+
+fn rejected(checks: ClaimChecks) -> SimulateClaimResponse {
+    let failures = checks.0.into_iter().map(|(check, err)| ClaimCheckFailure { check: check.to_string(), error: err.to_string() }).collect();
+    SimulateClaimResponse { energy: None, destination: None, ubs_decision: None, downgrade: None, queued: None, failures }
+}
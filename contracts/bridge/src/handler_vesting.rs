@@ -0,0 +1,156 @@
+use cosmwasm_std::{Addr, Deps, DepsMut, Env, MessageInfo, Order, Response, StdResult, Storage, SubMsg, Uint128};
+use cw_storage_plus::{Bound, Item, Map};
+use serde::{Deserialize, Serialize};
+use crate::{ContractError, EnergyVector, LedgerChangeReason, ENERGY_LEDGER, GOVERNANCE};
+
+const DEFAULT_LIMIT: u32 = 10;
+const MAX_LIMIT: u32 = 30;
+
+/// Per-asset unlock schedule for claimed energy, in seconds of block time. Nothing unlocks
+/// before `cliff_seconds`; after it the energy unlocks linearly from the claim time until
+/// `duration_seconds`. `cliff_seconds == duration_seconds` is a pure cliff.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct VestingSchedule {
+    pub cliff_seconds: u64,
+    pub duration_seconds: u64,
+    /// Only claims crediting at least this much (AU.ET + CSP + ERP) vest; `None` vests every claim.
+    pub min_total: Option<Uint128>,
+}
+
+/// Locked part of one credited claim. The energy is in `ENERGY_LEDGER` already; the lock only
+/// keeps it from being debited until it unlocks.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct EnergyLock {
+    pub id: u64,
+    pub asset_id: String,
+    pub energy: EnergyVector,
+    pub start: u64,
+    pub cliff: u64,
+    pub end: u64,
+}
+
+impl EnergyLock {
+    /// Still-locked part at `now` (seconds).
+    pub fn locked_at(&self, now: u64) -> EnergyVector {
+        if now < self.cliff { return self.energy.clone(); }
+        if now >= self.end { return zero(); }
+        let remaining = |a: Uint128| a - a.multiply_ratio(now - self.start, self.end - self.start);
+        EnergyVector { auet: remaining(self.energy.auet), csp: remaining(self.energy.csp), erp: remaining(self.energy.erp) }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct EnergyAvailabilityResponse {
+    pub total: EnergyVector,
+    pub locked: EnergyVector,
+    pub available: EnergyVector,
+}
+
+pub const ASSET_VESTING_SCHEDULES: Map<&str, VestingSchedule> = Map::new("asset_vesting_schedules");
+pub const ENERGY_LOCKS: Map<(&Addr, u64), EnergyLock> = Map::new("energy_locks");
+const ENERGY_LOCK_SEQ: Item<u64> = Item::new("energy_lock_seq");
+
+fn zero() -> EnergyVector {
+    EnergyVector { auet: Uint128::zero(), csp: Uint128::zero(), erp: Uint128::zero() }
+}
+
+fn add(a: &EnergyVector, b: &EnergyVector) -> EnergyVector {
+    EnergyVector { auet: a.auet + b.auet, csp: a.csp + b.csp, erp: a.erp + b.erp }
+}
+
+/// Credit claimed energy to `owner`, locked from `claimed_at` (seconds) under the asset's
+/// schedule if it applies and under `extra` (a downgrade's vesting); with both, the later cliff
+/// and end win. No lock is kept once it would already be over. Returns the lock id and the
+/// ledger hook notifications.
+pub fn credit_claim(deps: DepsMut, env: &Env, asset_id: &str, owner: &Addr, ev: EnergyVector, reason: LedgerChangeReason, claimed_at: u64, extra: Option<VestingSchedule>) -> Result<(Option<u64>, Vec<SubMsg>), ContractError> {
+    let schedule = ASSET_VESTING_SCHEDULES.may_load(deps.storage, asset_id)?.filter(|s| s.min_total.is_none_or(|m| ev.auet + ev.csp + ev.erp >= m));
+    let span = schedule.into_iter().chain(extra).fold(None, |span: Option<(u64, u64)>, s| Some(span.map_or((s.cliff_seconds, s.duration_seconds), |(c, d)| (c.max(s.cliff_seconds), d.max(s.duration_seconds)))));
+    let lock = match span {
+        Some((cliff, duration)) if claimed_at + duration > env.block.time.seconds() => {
+            Some(lock_energy(deps.storage, owner, asset_id, ev.clone(), claimed_at, claimed_at + cliff, claimed_at + duration)?)
+        }
+        _ => None,
+    };
+    let hooks = crate::credit_energy(deps, owner, ev, reason)?;
+    Ok((lock, hooks))
+}
+
+/// Lock `energy`, already in `owner`'s ledger entry, from `start` until `end`.
+pub(crate) fn lock_energy(storage: &mut dyn Storage, owner: &Addr, asset_id: &str, energy: EnergyVector, start: u64, cliff: u64, end: u64) -> StdResult<u64> {
+    let id = ENERGY_LOCK_SEQ.may_load(storage)?.unwrap_or_default() + 1;
+    ENERGY_LOCK_SEQ.save(storage, &id)?;
+    ENERGY_LOCKS.save(storage, (owner, id), &EnergyLock { id, asset_id: asset_id.to_string(), energy, start, cliff, end })?;
+    Ok(id)
+}
+
+/// Cliff and duration of a schedule, as `SetVestingSchedule` and downgrade vesting accept them.
+pub(crate) fn validate_schedule(cliff_seconds: u64, duration_seconds: u64) -> Result<(), ContractError> {
+    if duration_seconds == 0 || cliff_seconds > duration_seconds {
+        return Err(ContractError::InvalidConfig("vesting needs a non-zero duration and a cliff no longer than it".to_string()));
+    }
+    Ok(())
+}
+
+/// Sum of `owner`'s locked energy at `now`, removing locks that have fully unlocked.
+fn prune_and_sum_locked(storage: &mut dyn Storage, owner: &Addr, now: u64) -> StdResult<EnergyVector> {
+    let locks: Vec<EnergyLock> = ENERGY_LOCKS.prefix(owner).range(storage, None, None, Order::Ascending).map(|item| item.map(|(_, l)| l)).collect::<StdResult<_>>()?;
+    let mut sum = zero();
+    for l in locks {
+        if now >= l.end {
+            ENERGY_LOCKS.remove(storage, (owner, l.id));
+            continue;
+        }
+        sum = add(&sum, &l.locked_at(now));
+    }
+    Ok(sum)
+}
+
+fn availability(total: EnergyVector, locked: EnergyVector) -> EnergyAvailabilityResponse {
+    // debits never take a balance under its locked part, but saturate regardless
+    let available = EnergyVector { auet: total.auet.saturating_sub(locked.auet), csp: total.csp.saturating_sub(locked.csp), erp: total.erp.saturating_sub(locked.erp) };
+    EnergyAvailabilityResponse { total, locked, available }
+}
+
+/// Unlocked part of `owner`'s balance, dropping locks that no longer hold anything.
+pub fn available_for_debit(storage: &mut dyn Storage, env: &Env, owner: &Addr) -> StdResult<EnergyVector> {
+    let total = ENERGY_LEDGER.may_load(storage, owner)?.unwrap_or_else(zero);
+    let locked = prune_and_sum_locked(storage, owner, env.block.time.seconds())?;
+    Ok(availability(total, locked).available)
+}
+
+/// Governance-only: set (or remove with `None`) the vesting schedule of an asset's future claims.
+pub fn set_vesting_schedule(deps: DepsMut, info: MessageInfo, asset_id: String, schedule: Option<VestingSchedule>) -> Result<Response, ContractError> {
+    let gov = GOVERNANCE.load(deps.storage)?;
+    if info.sender != gov { return Err(ContractError::Unauthorized { action: "set_vesting_schedule".to_string() }); }
+    match &schedule {
+        Some(s) => {
+            validate_schedule(s.cliff_seconds, s.duration_seconds)?;
+            ASSET_VESTING_SCHEDULES.save(deps.storage, asset_id.as_str(), s)?;
+        }
+        None => ASSET_VESTING_SCHEDULES.remove(deps.storage, asset_id.as_str()),
+    }
+    Ok(Response::new().add_attribute("action", "set_vesting_schedule").add_attribute("asset_id", asset_id).add_attribute("enabled", schedule.is_some().to_string()))
+}
+
+pub fn query_energy_availability(deps: Deps, env: Env, address: String) -> StdResult<EnergyAvailabilityResponse> {
+    let addr = deps.api.addr_validate(&address)?;
+    let total = ENERGY_LEDGER.may_load(deps.storage, &addr)?.unwrap_or_else(zero);
+    let now = env.block.time.seconds();
+    let locked = ENERGY_LOCKS
+        .prefix(&addr)
+        .range(deps.storage, None, None, Order::Ascending)
+        .try_fold(zero(), |sum, item| item.map(|(_, l)| add(&sum, &l.locked_at(now))))?;
+    Ok(availability(total, locked))
+}
+
+pub fn query_energy_locks(deps: Deps, address: String, start_after: Option<u64>, limit: Option<u32>) -> StdResult<Vec<EnergyLock>> {
+    let addr = deps.api.addr_validate(&address)?;
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.map(Bound::exclusive_int);
+    ENERGY_LOCKS
+        .prefix(&addr)
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| item.map(|(_, l)| l))
+        .collect()
+}
@@ -1,24 +1,70 @@
-use cosmwasm_std::{entry_point, to_binary, Addr, Binary, Deps, DepsMut, Env, MessageInfo, Response, StdResult, Uint128, WasmMsg, CosmosMsg};
+use cosmwasm_std::{entry_point, to_binary, Addr, Binary, Deps, DepsMut, Env, MessageInfo, Reply, Response, StdError, StdResult, Storage, SubMsg, Uint128};
 use cw_storage_plus::{Map, Item};
 mod core;
-pub use core::bridge_architecture::{OriginLockEvent, EnergyVector, UBS, SanitizationResult, SanitizationDecision, BridgeError};
+pub use core::bridge_architecture::{OriginLockEvent, EnergyVector, LightClient, UBS, SanitizationResult, SanitizationDecision, BridgeError};
+/// The bridge's contract error is the grown `BridgeError`.
+pub type ContractError = BridgeError;
+mod handler_claim_plan;
 mod handler_claim_with_origin;
+mod handler_config;
+mod handler_governance;
+mod handler_pause;
+mod handler_rate_limit;
+mod handler_batch_claim;
+mod handler_downgrade;
+mod handler_simulate;
+mod handler_list;
+mod handler_ledger_checkpoint;
+mod handler_vesting;
+mod handler_allowance;
+mod handler_claim_stats;
+mod handler_quarantine;
+mod handler_light_client;
+mod handler_ledger_hooks;
+mod migrations;
 pub use handler_claim_with_origin::claim_with_origin;
+pub use handler_config::{ClearableConfig, ConfigResponse};
+use handler_config::ConfigUpdate;
+use handler_claim_plan::{ClaimChecks, ClaimInput, ClaimOutcome, ClaimPlan};
+pub use handler_governance::{PendingGovernance, PENDING_GOVERNANCE};
+use cw_utils::Expiration;
+pub use handler_pause::{PausableAction, PauseFlags, PauseStateResponse, GUARDIANS, GLOBAL_PAUSE, ASSET_PAUSE};
+use handler_pause::ensure_not_paused;
+pub use core::multiproof::MultiProof;
+pub use handler_batch_claim::BatchClaimEntry;
+pub use handler_downgrade::{DowngradeTreatment, ASSET_DOWNGRADE_TREATMENT, DEFAULT_DOWNGRADE_TREATMENT};
+pub use handler_ledger_hooks::{LedgerChangeReason, LedgerHook, LedgerHookMsg, LEDGER_HOOKS};
+pub use handler_light_client::{ConsensusRoot, LightClientInit, LightClientState, OriginLockProof, StoredLightClient, CONSENSUS_ROOTS, LIGHT_CLIENTS};
+pub use handler_quarantine::{QuarantineAction, QuarantineCase, QuarantineReason, QuarantineStatus, OPEN_QUARANTINE, QUARANTINE_CASES};
+pub use handler_claim_stats::{AssetClaimStatsResponse, AssetClaimTotals, TreasurySweep, ASSET_CLAIM_TOTALS, TREASURY_SWEEPS};
+pub use handler_allowance::{EnergyAllowance, SystemCap, SystemCapResponse, ENERGY_ALLOWANCES, SYSTEM_CAPS};
+pub use handler_vesting::{EnergyAvailabilityResponse, EnergyLock, VestingSchedule, ASSET_VESTING_SCHEDULES, ENERGY_LOCKS};
+pub use handler_ledger_checkpoint::{LedgerCheckpoint, PendingCheckpoint, LEDGER_CHECKPOINTS};
+pub use handler_list::{ClaimRecord, EnergyHolder, RefactorAuditEntry, CLAIMS_BY_RECIPIENT};
+pub use handler_simulate::{ClaimCheckFailure, ClaimDestination, SimulateClaimResponse};
+pub use handler_rate_limit::{ClaimWindow, RateLimit, RateWindow, QueuedClaim, RATE_LIMITS, QUEUED_CLAIMS};
 use serde::{Deserialize, Serialize};
 pub use core::refactor_state::{record_refactor, is_processed as refactor_is_processed};
-use cw20::Cw20ExecuteMsg;
-use sha2::{Sha256, Digest};
+pub use core::scaling::{ScalingProfile, Entitlement, compute_entitlement};
+pub use core::ownership::{ClaimAuthorization, addresses_equivalent, claim_auth_digest};
+use cw2::set_contract_version;
 use hex;
-use aln_ubs::DefaultUBS;
+use aln_ubs::{DefaultUBS, UBS as _};
 use ubs_oracle::QueryMsg as OracleQueryMsg;
 use serde_json::json;
 
-use aln_registry::{QueryMsg as RegQueryMsg, RegisteredAsset};
+use aln_registry::{QueryMsg as RegQueryMsg, RegisteredAsset, SnapshotEpoch};
+use aln_merkle::{MerkleVersion, SnapshotLeaf};
 
 const CONTRACT_NAME: &str = "aln-bridge-auet";
-const CONTRACT_VERSION: &str = "0.2.0";
+const CONTRACT_VERSION: &str = "0.3.0";
 
-static CLAIMED: Map<(&Addr, &str, &str), bool> = Map::new("claimed");
+/// Legacy (recipient, asset_id, snapshot_hash) claim keys; emptied by the 0.3.0 migration.
+pub(crate) const CLAIMED: Map<(&Addr, &str, &str), bool> = Map::new("claimed");
+/// (asset_id, snapshot epoch, snapshot_hash) -> ALN recipient. H_i is derived from the origin leaf
+/// (`OriginLockEvent` or snapshot entry), so a leaf can be claimed once per epoch of its asset
+/// regardless of recipient.
+pub const CLAIMED_LEAVES: Map<(&str, u32, &str), Addr> = Map::new("claimed_leaves");
 pub const ENERGY_LEDGER: Map<&Addr, EnergyVector> = Map::new("energy_ledger");
 pub const SYSTEM_WHITELIST: Map<&Addr, bool> = Map::new("system_whitelist");
 pub const AUET_CONTRACT: Item<Addr> = Item::new("auet_contract");
@@ -67,29 +113,191 @@ pub struct ProofStep {
 pub enum ExecuteMsg {
     Claim {
         asset_id: String,
+        /// Snapshot epoch of the asset whose root holds the leaf; epoch 0 when unset.
+        epoch: Option<u32>,
         snapshot: SnapshotEntry,
         snapshot_hash: String,
         merkle_proof: Vec<ProofStep>,
-        amount_auet: Uint128,
+        /// Optional assertion; the bridge derives the entitlement and rejects a mismatch.
+        amount_auet: Option<Uint128>,
         amount_csp: Option<Uint128>,
         // Optional origin tx metadata for replay protection and forensic mapping
         origin_tx_hash: Option<String>,
         origin_nonce: Option<u64>,
         ubs_report_hash: Option<String>,
     },
-    ClaimWithOrigin { asset_id: String, origin_event: crate::core::bridge_architecture::OriginLockEvent, merkle_proof: Vec<ProofStep>, ubs_report_hash: Option<String>, amount_auet: Uint128, amount_csp: Option<Uint128> },
-    /// System contract consumes a user's energy (debits ledger). ACL enforced.
+    /// Relayer-submitted claim crediting `recipient`, authorized by the origin key of `snapshot.address`.
+    ClaimFor {
+        recipient: String,
+        asset_id: String,
+        epoch: Option<u32>,
+        snapshot: SnapshotEntry,
+        snapshot_hash: String,
+        merkle_proof: Vec<ProofStep>,
+        amount_auet: Option<Uint128>,
+        amount_csp: Option<Uint128>,
+        origin_tx_hash: Option<String>,
+        origin_nonce: Option<u64>,
+        ubs_report_hash: Option<String>,
+        authorization: ClaimAuthorization,
+    },
+    /// Claim many leaves of one asset against a single multiproof. Each entry credits the sender,
+    /// or `recipient` when it carries an `authorization` from the leaf's origin key. All-or-nothing.
+    BatchClaim { asset_id: String, epoch: Option<u32>, claims: Vec<BatchClaimEntry>, multiproof: MultiProof, ubs_report_hash: Option<String> },
+    /// Claim a leaf built from an origin lock event. When governance runs a light client for the
+    /// event's chain, `origin_proof` proves the event against a verified origin app hash.
+    ClaimWithOrigin { asset_id: String, epoch: Option<u32>, origin_event: crate::core::bridge_architecture::OriginLockEvent, merkle_proof: Vec<ProofStep>, ubs_report_hash: Option<String>, amount_auet: Option<Uint128>, amount_csp: Option<Uint128>, origin_proof: Option<OriginLockProof> },
+    /// System contract consumes a user's energy (debits ledger). ACL enforced; only the owner's
+    /// unlocked energy can be consumed, within the allowance the owner granted the caller and
+    /// the caller's epoch cap.
     SystemConsume { owner: String, delta: EnergyVector },
     AddSystemWhitelist { addr: String },
     RemoveSystemWhitelist { addr: String },
+    /// Governance-only partial config update: `Some` fields are replaced, fields named in
+    /// `clear` are unset, everything else is left as is. Emits one `aln_bridge_config` event per change.
+    UpdateConfig {
+        toxic_sink: Option<String>,
+        anomaly_threshold_amount: Option<Uint128>,
+        toxic_cap_percent: Option<u8>,
+        ubs_oracle_contract: Option<String>,
+        csp_contract: Option<String>,
+        registry_contract: Option<String>,
+        clear: Option<Vec<ClearableConfig>>,
+    },
+    /// Governance-only: nominate a successor (e.g. a multisig or DAO). Takes effect on `AcceptGovernance`.
+    ProposeGovernance { new_governance: String, expires: Option<Expiration> },
+    /// Sent by the pending governance before expiry to complete the handover.
+    AcceptGovernance {},
+    /// Governance-only: withdraw the outstanding proposal.
+    CancelGovernanceProposal {},
+    /// Governance-only: grant or revoke the guardian role (may toggle pauses, nothing else).
+    AddGuardian { addr: String },
+    RemoveGuardian { addr: String },
+    /// Governance or guardian: toggle pauses globally, or for one `asset_id` when given
+    /// (claims only). `None` flags keep their current value.
+    SetPause { asset_id: Option<String>, claim: Option<bool>, claim_with_origin: Option<bool>, system_consume: Option<bool> },
+    /// Governance-only: set (or remove with `None`) the rolling-window claim limits of a registered asset.
+    SetRateLimit { asset_id: String, limit: Option<RateLimit> },
+    /// Governance-only: credit a claim queued by a rate limit, after its downgrade treatment.
+    ReleaseQueuedClaim { id: u64 },
+    /// Governance-only: drop a queued claim without crediting it; its leaf stays claimed.
+    RejectQueuedClaim { id: u64, note: Option<String> },
+    /// Governance-only: how claims with a `Downgraded` UBS decision are credited, for one asset
+    /// or as the default when `asset_id` is `None`. `None` treatment removes the setting.
+    SetDowngradeTreatment { asset_id: Option<String>, treatment: Option<DowngradeTreatment> },
+    /// Governance-only: start a commitment over the energy ledger at the current height, hashing
+    /// at most `limit` entries (default 100, max 500); `ContinueCheckpoint` hashes the rest.
+    CheckpointLedger { limit: Option<u32> },
+    /// Hash the next `limit` entries of the pending ledger checkpoint. Anyone can call it.
+    ContinueCheckpoint { limit: Option<u32> },
+    /// Governance-only: lock future claims of `asset_id` under a cliff/linear schedule, or stop
+    /// vesting them with `None`. Existing locks keep their schedule.
+    SetVestingSchedule { asset_id: String, schedule: Option<VestingSchedule> },
+    /// Let system contract `spender` debit up to `amount` of the sender's energy through
+    /// `SystemConsume`, per `period_seconds` window or in total when `None`. Replaces any
+    /// existing allowance for `spender`; `expires` defaults to never.
+    GrantEnergyAllowance { spender: String, amount: EnergyVector, period_seconds: Option<u64>, expires: Option<Expiration> },
+    RevokeEnergyAllowance { spender: String },
+    /// Governance-only: cap what system contract `addr` may debit across all owners per epoch,
+    /// or remove the cap with `None`.
+    SetSystemCap { addr: String, cap: Option<SystemCap> },
+    /// Governance-only: after the claim deadline of every snapshot epoch, record the unclaimed
+    /// supply of the epochs not swept yet as a treasury entry.
+    SweepUnclaimed { asset_id: String },
+    /// Governance-only: credit an open quarantine case's energy to its claimant.
+    ReleaseQuarantine { id: u64, note: Option<String> },
+    /// Governance-only: close an open quarantine case without crediting anyone.
+    BurnQuarantine { id: u64, note: Option<String> },
+    /// Governance-only: start (or restart) the light client of origin `chain_id` from a trusted
+    /// header and validator set, or remove it with `None`.
+    SetLightClient { chain_id: String, client: Option<LightClientInit> },
+    /// Verify a newer origin header (heights may be skipped) and record its app hash. Anyone can relay.
+    UpdateLightClient { chain_id: String, update: aln_light_client::HeaderUpdate },
+    /// Governance-only: send `LedgerHookMsg::LedgerChanged` to `contract_addr` on every ledger
+    /// credit or debit, with `gas_limit` (default 200k) per notification. Failures are recorded,
+    /// never propagated.
+    AddLedgerHook { contract_addr: String, gas_limit: Option<u64> },
+    RemoveLedgerHook { contract_addr: String },
+    /// Continue a state migration left pending by `migrate`, rewriting at most `limit` entries
+    /// (default 100, max 500). Anyone can call it; claims are refused until it completes.
+    MigrateBatch { limit: Option<u32> },
 }
 
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct MigrateMsg {}
+
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
 #[serde(rename_all = "snake_case")]
 pub enum QueryMsg {
-    IsClaimed { address: String, asset_id: String, snapshot_hash: String },
+    /// Whether `address` claimed the leaf in snapshot epoch `epoch` (0 when unset).
+    IsClaimed { address: String, asset_id: String, epoch: Option<u32>, snapshot_hash: String },
     EnergyBalance { address: String },
     RefactorAudit { origin_chain: String, tx_hash: String, nonce: u64 },
+    /// The registry's profile `id` (`Option<ScalingProfile>`).
+    ScalingProfile { id: String },
+    /// Full effective configuration (`ConfigResponse`).
+    Config {},
+    /// Total energy credited through claims (Uint128).
+    TotalEnergy {},
+    /// Portion of `TotalEnergy` originating from assets with a toxic profile (Uint128).
+    ToxicEnergy {},
+    /// Outstanding governance handover, if any (`Option<PendingGovernance>`).
+    PendingGovernance {},
+    /// Global pause flags, plus the asset's when `asset_id` is given (`PauseStateResponse`).
+    PauseState { asset_id: Option<String> },
+    IsGuardian { address: String },
+    RateLimit { asset_id: String },
+    QueuedClaim { id: u64 },
+    /// Claims awaiting governance release, ascending by id.
+    QueuedClaims { start_after: Option<u64>, limit: Option<u32> },
+    /// Effective treatment for downgraded claims of `asset_id`, or the default (`DowngradeTreatment`).
+    DowngradeTreatment { asset_id: Option<String> },
+    /// Dry-run a `Claim`, `ClaimFor` or `ClaimWithOrigin` message as if `sender` submitted it at
+    /// the current height. Nothing is written; every failing check is listed (`SimulateClaimResponse`).
+    SimulateClaim { sender: String, claim: ExecuteMsg },
+    /// Claims credited to `address`, ascending by (asset_id, epoch, snapshot_hash) (`Vec<ClaimRecord>`).
+    ClaimsByAddress { address: String, start_after: Option<(String, u32, String)>, limit: Option<u32> },
+    /// Claimed leaves of `asset_id`, ascending by (epoch, snapshot_hash) (`Vec<ClaimRecord>`).
+    ClaimsByAsset { asset_id: String, start_after: Option<(u32, String)>, limit: Option<u32> },
+    /// Energy ledger entries, ascending by address (`Vec<EnergyHolder>`).
+    EnergyHolders { start_after: Option<String>, limit: Option<u32> },
+    /// Whitelisted system contracts, ascending by address (`Vec<Addr>`).
+    SystemWhitelist { start_after: Option<String>, limit: Option<u32> },
+    /// Refactor audit entries of `origin_chain`, ascending by (tx_hash, nonce) (`Vec<RefactorAuditEntry>`).
+    RefactorAudits { origin_chain: String, start_after: Option<(String, u64)>, limit: Option<u32> },
+    /// Ledger checkpoint taken at `height`, or the latest (`Option<LedgerCheckpoint>`).
+    LedgerCheckpoint { height: Option<u64> },
+    /// Ledger checkpoint still being hashed (`Option<PendingCheckpoint>`).
+    PendingCheckpoint {},
+    VestingSchedule { asset_id: String },
+    /// Ledger balance of `address` split into locked and available energy (`EnergyAvailabilityResponse`).
+    EnergyAvailability { address: String },
+    /// Vesting locks of `address`, ascending by id (`Vec<EnergyLock>`); fully unlocked locks may still be listed.
+    EnergyLocks { address: String, start_after: Option<u64>, limit: Option<u32> },
+    /// Allowance `owner` granted `spender`, with the current period applied (`Option<EnergyAllowance>`).
+    EnergyAllowance { owner: String, spender: String },
+    /// Allowances granted by `owner`, ascending by spender (`Vec<EnergyAllowance>`).
+    AllowancesByOwner { owner: String, start_after: Option<String>, limit: Option<u32> },
+    /// Allowances granted to `spender`, ascending by owner (`Vec<EnergyAllowance>`).
+    AllowancesBySpender { spender: String, start_after: Option<String>, limit: Option<u32> },
+    /// Epoch cap of `addr` and its use in the current epoch (`Option<SystemCapResponse>`).
+    SystemCap { addr: String },
+    /// System contract caps, ascending by address (`Vec<(Addr, SystemCap)>`).
+    SystemCaps { start_after: Option<String>, limit: Option<u32> },
+    /// Claim count, claimed and unclaimed supply, deadline and sweep of `asset_id` (`AssetClaimStatsResponse`).
+    AssetClaimStats { asset_id: String },
+    /// Treasury entries of swept assets, ascending by (asset id, last epoch) (`Vec<TreasurySweep>`).
+    TreasurySweeps { start_after: Option<(String, u32)>, limit: Option<u32> },
+    /// Quarantine case with its audit trail (`Option<QuarantineCase>`).
+    QuarantineCase { id: u64 },
+    /// Open quarantine cases, ascending by id (`Vec<QuarantineCase>`).
+    OpenQuarantineCases { start_after: Option<u64>, limit: Option<u32> },
+    /// Light client of origin `chain_id` with its latest trusted header (`Option<LightClientState>`).
+    LightClient { chain_id: String },
+    /// App hash verified at origin `height`, or the latest one (`Option<ConsensusRoot>`).
+    ConsensusRoot { chain_id: String, height: Option<u64> },
+    /// Ledger hook subscriptions with their failure counts, ascending by contract (`Vec<LedgerHook>`).
+    LedgerHooks { start_after: Option<String>, limit: Option<u32> },
 }
 
 #[entry_point]
@@ -98,7 +306,8 @@ pub fn instantiate(
     _env: Env,
     _info: MessageInfo,
     msg: InstantiateMsg,
-) -> StdResult<Response> {
+) -> Result<Response, ContractError> {
+    set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
     let au = deps.api.addr_validate(&msg.auet_contract)?;
     AUET_CONTRACT.save(deps.storage, &au)?;
     if let Some(csp) = msg.csp_contract {
@@ -140,25 +349,38 @@ pub fn execute(
     env: Env,
     info: MessageInfo,
     msg: ExecuteMsg,
-) -> StdResult<Response> {
+) -> Result<Response, ContractError> {
     match msg {
-        ExecuteMsg::Claim { asset_id, snapshot, snapshot_hash, merkle_proof, amount_auet, amount_csp, origin_tx_hash, origin_nonce, ubs_report_hash } => {
-            claim(deps, env, info, asset_id, snapshot, snapshot_hash, merkle_proof, amount_auet, amount_csp, origin_tx_hash, origin_nonce, ubs_report_hash)
+        ExecuteMsg::Claim { asset_id, epoch, snapshot, snapshot_hash, merkle_proof, amount_auet, amount_csp, origin_tx_hash, origin_nonce, ubs_report_hash } => {
+            ensure_not_paused(deps.storage, PausableAction::Claim, Some(asset_id.as_str()))?;
+            claim(deps, env, info.sender, None, asset_id, epoch.unwrap_or_default(), snapshot, snapshot_hash, LeafProof::Path(merkle_proof), amount_auet, amount_csp, origin_tx_hash, origin_nonce, ubs_report_hash)
+        }
+        ExecuteMsg::ClaimFor { recipient, asset_id, epoch, snapshot, snapshot_hash, merkle_proof, amount_auet, amount_csp, origin_tx_hash, origin_nonce, ubs_report_hash, authorization } => {
+            ensure_not_paused(deps.storage, PausableAction::Claim, Some(asset_id.as_str()))?;
+            let recipient = deps.api.addr_validate(&recipient)?;
+            claim(deps, env, recipient, Some(authorization), asset_id, epoch.unwrap_or_default(), snapshot, snapshot_hash, LeafProof::Path(merkle_proof), amount_auet, amount_csp, origin_tx_hash, origin_nonce, ubs_report_hash)
+        }
+        ExecuteMsg::BatchClaim { asset_id, epoch, claims, multiproof, ubs_report_hash } => {
+            ensure_not_paused(deps.storage, PausableAction::Claim, Some(asset_id.as_str()))?;
+            handler_batch_claim::batch_claim(deps, env, info, asset_id, epoch.unwrap_or_default(), claims, multiproof, ubs_report_hash)
         }
-        ExecuteMsg::ClaimWithOrigin { asset_id, origin_event, merkle_proof, ubs_report_hash, amount_auet, amount_csp } => {
-            crate::handler_claim_with_origin::claim_with_origin(deps, env, info, asset_id, origin_event, merkle_proof, ubs_report_hash, amount_auet, amount_csp)
+        ExecuteMsg::ClaimWithOrigin { asset_id, epoch, origin_event, merkle_proof, ubs_report_hash, amount_auet, amount_csp, origin_proof } => {
+            ensure_not_paused(deps.storage, PausableAction::ClaimWithOrigin, Some(asset_id.as_str()))?;
+            handler_light_client::check_origin_proof(deps.storage, &origin_event, origin_proof.as_ref())?;
+            crate::handler_claim_with_origin::claim_with_origin(deps, env, info, asset_id, epoch.unwrap_or_default(), origin_event, merkle_proof, ubs_report_hash, amount_auet, amount_csp)
         }
         ExecuteMsg::SystemConsume { owner, delta } => {
+            ensure_not_paused(deps.storage, PausableAction::SystemConsume, None)?;
             // Only whitelisted system contracts can call this action
             let caller = info.sender.clone();
             let owner_addr = deps.api.addr_validate(&owner)?;
-            debit_energy(deps, &owner_addr, delta, &caller)?;
-            Ok(Response::new().add_attribute("action", "system_consume").add_attribute("owner", owner))
+            let hooks = debit_energy(deps, &env, &owner_addr, delta, &caller)?;
+            Ok(Response::new().add_submessages(hooks).add_attribute("action", "system_consume").add_attribute("owner", owner))
         }
         ExecuteMsg::AddSystemWhitelist { addr } => {
             let caller = info.sender.clone();
             let gov = GOVERNANCE.load(deps.storage)?;
-            if caller != gov { return Err(cosmwasm_std::StdError::generic_err("only governance can set system whitelist")); }
+            if caller != gov { return Err(ContractError::Unauthorized { action: "set_system_whitelist".to_string() }); }
             let a = deps.api.addr_validate(&addr)?;
             SYSTEM_WHITELIST.save(deps.storage, &a, &true)?;
             Ok(Response::new().add_attribute("action", "add_system_whitelist").add_attribute("addr", addr))
@@ -166,252 +388,301 @@ pub fn execute(
         ExecuteMsg::RemoveSystemWhitelist { addr } => {
             let caller = info.sender.clone();
             let gov = GOVERNANCE.load(deps.storage)?;
-            if caller != gov { return Err(cosmwasm_std::StdError::generic_err("only governance can set system whitelist")); }
+            if caller != gov { return Err(ContractError::Unauthorized { action: "set_system_whitelist".to_string() }); }
             let a = deps.api.addr_validate(&addr)?;
             SYSTEM_WHITELIST.save(deps.storage, &a, &false)?;
             Ok(Response::new().add_attribute("action", "remove_system_whitelist").add_attribute("addr", addr))
         }
+        ExecuteMsg::UpdateConfig { toxic_sink, anomaly_threshold_amount, toxic_cap_percent, ubs_oracle_contract, csp_contract, registry_contract, clear } => {
+            let upd = ConfigUpdate { toxic_sink, anomaly_threshold_amount, toxic_cap_percent, ubs_oracle_contract, csp_contract, registry_contract, clear: clear.unwrap_or_default() };
+            handler_config::update_config(deps, info, upd)
+        }
+        ExecuteMsg::ProposeGovernance { new_governance, expires } => handler_governance::propose_governance(deps, env, info, new_governance, expires),
+        ExecuteMsg::AcceptGovernance {} => handler_governance::accept_governance(deps, env, info),
+        ExecuteMsg::CancelGovernanceProposal {} => handler_governance::cancel_governance_proposal(deps, info),
+        ExecuteMsg::AddGuardian { addr } => handler_pause::set_guardian(deps, info, addr, true),
+        ExecuteMsg::RemoveGuardian { addr } => handler_pause::set_guardian(deps, info, addr, false),
+        ExecuteMsg::SetPause { asset_id, claim, claim_with_origin, system_consume } => handler_pause::set_pause(deps, info, asset_id, claim, claim_with_origin, system_consume),
+        ExecuteMsg::SetRateLimit { asset_id, limit } => handler_rate_limit::set_rate_limit(deps, info, asset_id, limit),
+        ExecuteMsg::ReleaseQueuedClaim { id } => handler_rate_limit::release_queued_claim(deps, env, info, id),
+        ExecuteMsg::RejectQueuedClaim { id, note } => handler_rate_limit::reject_queued_claim(deps, info, id, note),
+        ExecuteMsg::SetDowngradeTreatment { asset_id, treatment } => handler_downgrade::set_downgrade_treatment(deps, info, asset_id, treatment),
+        ExecuteMsg::SetVestingSchedule { asset_id, schedule } => handler_vesting::set_vesting_schedule(deps, info, asset_id, schedule),
+        ExecuteMsg::CheckpointLedger { limit } => handler_ledger_checkpoint::checkpoint_ledger(deps, env, info, limit),
+        ExecuteMsg::ContinueCheckpoint { limit } => handler_ledger_checkpoint::continue_checkpoint(deps, limit),
+        ExecuteMsg::GrantEnergyAllowance { spender, amount, period_seconds, expires } => handler_allowance::grant_energy_allowance(deps, env, info, spender, amount, period_seconds, expires),
+        ExecuteMsg::RevokeEnergyAllowance { spender } => handler_allowance::revoke_energy_allowance(deps, info, spender),
+        ExecuteMsg::SetSystemCap { addr, cap } => handler_allowance::set_system_cap(deps, info, addr, cap),
+        ExecuteMsg::SweepUnclaimed { asset_id } => handler_claim_stats::sweep_unclaimed(deps, env, info, asset_id),
+        ExecuteMsg::ReleaseQuarantine { id, note } => handler_quarantine::release_quarantine(deps, env, info, id, note),
+        ExecuteMsg::BurnQuarantine { id, note } => handler_quarantine::burn_quarantine(deps, env, info, id, note),
+        ExecuteMsg::SetLightClient { chain_id, client } => handler_light_client::set_light_client(deps, env, info, chain_id, client),
+        ExecuteMsg::UpdateLightClient { chain_id, update } => handler_light_client::update_light_client(deps, env, chain_id, update),
+        ExecuteMsg::AddLedgerHook { contract_addr, gas_limit } => handler_ledger_hooks::add_ledger_hook(deps, info, contract_addr, gas_limit),
+        ExecuteMsg::RemoveLedgerHook { contract_addr } => handler_ledger_hooks::remove_ledger_hook(deps, info, contract_addr),
+        ExecuteMsg::MigrateBatch { limit } => migrations::migrate_batch(deps, info, limit),
     }
 }
 
+/// How a claimed leaf is tied to the merkle root of its snapshot epoch.
+pub(crate) enum LeafProof {
+    Path(Vec<ProofStep>),
+    /// Already covered by a `BatchClaim` multiproof verified against the same epoch root.
+    InMultiproof,
+}
+
+/// Snapshot leaf H_i in the asset's merkle version (see `aln_merkle::leaf_hash`).
+pub(crate) fn snapshot_leaf(version: MerkleVersion, snapshot: &SnapshotEntry) -> Result<[u8; 32], ContractError> {
+    let b: u128 = snapshot.balance.parse().map_err(|_| ContractError::InvalidSnapshotBalance)?;
+    Ok(aln_merkle::leaf_hash(version, &SnapshotLeaf { chain_id: &snapshot.chain_id, height: snapshot.height, denom: &snapshot.denom, address: &snapshot.address, balance: b }))
+}
+
 fn claim(
-    deps: DepsMut,
+    mut deps: DepsMut,
     env: Env,
-    info: MessageInfo,
+    recipient: Addr,
+    authorization: Option<ClaimAuthorization>,
     asset_id: String,
+    epoch: u32,
     snapshot: SnapshotEntry,
     snapshot_hash: String,
-    merkle_proof: Vec<ProofStep>,
-    amount_auet: Uint128,
+    proof: LeafProof,
+    amount_auet: Option<Uint128>,
     amount_csp: Option<Uint128>,
     origin_tx_hash: Option<String>,
     origin_nonce: Option<u64>,
     ubs_report_hash: Option<String>,
-) -> StdResult<Response> {
-    let recipient = info.sender.clone();
-    let key = (&recipient, asset_id.as_str(), snapshot_hash.as_str());
-    if CLAIMED.may_load(deps.storage, key)?.unwrap_or(false) {
-        return Err(cosmwasm_std::StdError::generic_err("already claimed"));
+) -> Result<Response, ContractError> {
+    // every read-only check runs first; the claim fails with the first one that did not pass
+    let input = ClaimInput { recipient, authorization, asset_id, epoch, snapshot, snapshot_hash: Some(snapshot_hash), proof, amount_auet, amount_csp, origin_tx_hash, origin_nonce, ubs_report_hash };
+    let mut checks = ClaimChecks::default();
+    let plan = handler_claim_plan::plan_claim(deps.as_ref(), &env, &input, &mut checks)?;
+    if let Some((_, err)) = checks.0.into_iter().next() { return Err(err); }
+    let plan = plan.ok_or_else(|| StdError::generic_err("claim checks left no plan"))?;
+    let ClaimInput { recipient, asset_id, snapshot, origin_tx_hash, origin_nonce, .. } = input;
+    let ClaimPlan { asset, snapshot_hash, balance: b, entitled, toxic, ubs_report_hash: reg_ubs_hash, sanitization: sres, downgrade, totals, sink, outcome } = plan;
+
+    // mark as claimed
+    handler_list::mark_claimed(deps.storage, asset_id.as_str(), epoch, snapshot_hash.as_str(), &recipient)?;
+
+    // record refactor into append-only refactor registry if origin metadata provided
+    if origin_tx_hash.is_some() && origin_nonce.is_some() {
+        // processed_at using block time (seconds)
+        let ts = env.block.time.seconds();
+        record_refactor(deps.branch(), snapshot.chain_id.as_str(), snapshot.denom.as_str(), origin_tx_hash.as_ref().unwrap(), origin_nonce.unwrap(), ts)?;
     }
 
-    // If origin tx metadata provided, check refactor registry to avoid replays across chains
+    // Map aln_ubs energy vector to contract EnergyVector
+    let ev = EnergyVector { auet: sres.energy.auet, csp: sres.energy.csp, erp: sres.energy.erp };
+    // store audit (report hash, plus the downgrade treatment) if origin metadata present
     if origin_tx_hash.is_some() && origin_nonce.is_some() {
         let txh = origin_tx_hash.as_ref().unwrap();
-        let n = origin_nonce.unwrap();
-        if refactor_is_processed(deps.as_ref(), snapshot.chain_id.as_str(), snapshot.denom.as_str(), txh.as_str(), n)? {
-            return Err(cosmwasm_std::StdError::generic_err("origin event already processed"));
-        }
+        let audit = match &downgrade {
+            Some(t) => format!("{}|downgraded:{}", sres.report_hash, t.audit_tag()),
+            None => sres.report_hash.clone(),
+        };
+        REFACTOR_AUDIT.save(deps.storage, (snapshot.chain_id.as_str(), txh.as_str(), origin_nonce.unwrap()), &audit)?;
     }
+    // If rejected, record the attempt and do not mint
+    let (new_total, new_toxic) = match totals {
+        Some(totals) => totals,
+        None => {
+            let json = serde_json::json!({"action":"claim_rejected","origin_chain":snapshot.chain_id.as_str(),"tx":origin_tx_hash.as_ref().unwrap_or(&"".to_string()),"report_hash": sres.report_hash});
+            return Ok(Response::new().add_attribute("action","claim_rejected").add_attribute("refactor_audit", json.to_string()));
+        }
+    };
+    // only claims that are credited, queued or quarantined count toward the asset's claimed supply
+    handler_claim_stats::record_claim(deps.storage, asset_id.as_str(), Uint128::new(b))?;
+    TOTAL_ENERGY.save(deps.storage, &new_total)?;
+    if let Some(t) = new_toxic { TOXIC_ENERGY.save(deps.storage, &t)?; }
 
-    // recompute H_i
-    let mut hasher = Sha256::new();
-    hasher.update(snapshot.chain_id.as_bytes());
-    hasher.update(&snapshot.height.to_be_bytes());
-    hasher.update(snapshot.denom.as_bytes());
-    hasher.update(snapshot.address.as_bytes());
-    let b: u128 = snapshot.balance.parse().map_err(|_| cosmwasm_std::StdError::generic_err("invalid balance in snapshot"))?;
-    hasher.update(&b.to_be_bytes());
-    let digest = hasher.finalize();
-    let computed_h = format!("0x{}", hex::encode(digest));
-    if computed_h != snapshot_hash {
-        return Err(cosmwasm_std::StdError::generic_err("snapshot hash mismatch"));
+    let amount_total = entitled.total();
+    let ubs_decision = if downgrade.is_some() { "downgraded" } else { "approved" };
+    let pending = QueuedClaim { id: 0, asset_id: asset_id.clone(), epoch, recipient: recipient.clone(), snapshot_hash: snapshot_hash.clone(), amount: Uint128::new(amount_total), energy: ev, downgrade: downgrade.clone(), toxic, reason: String::new(), queued_at_height: 0, claimed_at: env.block.time.seconds() };
+
+    // toxic-profile claims and anomalies for large amounts wait in quarantine for governance,
+    // which releases them through the rate limits and the downgrade treatment below
+    if let ClaimOutcome::Quarantine(reason) = outcome {
+        let sink_addr = sink.ok_or(ContractError::AnomalyWithoutSink)?;
+        let case = QuarantineCase {
+            id: 0,
+            asset_id: pending.asset_id,
+            epoch,
+            snapshot_hash: pending.snapshot_hash,
+            claimant: pending.recipient,
+            origin: snapshot.clone(),
+            origin_tx_hash: origin_tx_hash.clone(),
+            origin_nonce,
+            amount: Uint128::new(b),
+            energy: pending.energy,
+            reason,
+            scaling_profile_id: asset.scaling_profile_id.clone(),
+            ubs_report_hash: reg_ubs_hash.clone(),
+            sink: sink_addr,
+            status: QuarantineStatus::Open,
+            history: vec![],
+            claimed_at: pending.claimed_at,
+            entitled: pending.amount,
+            downgrade: pending.downgrade,
+            toxic: pending.toxic,
+        };
+        let (case_id, event) = handler_quarantine::open_case(deps.storage, &env, case)?;
+        return Ok(Response::new()
+            .add_attribute("action", "claim_quarantined")
+            .add_attribute("epoch", epoch.to_string())
+            .add_attribute("snapshot_hash", snapshot_hash)
+            .add_attribute("quarantine_id", case_id.to_string())
+            .add_attribute("ubs_decision", ubs_decision)
+            .add_event(event));
     }
 
-    // fetch asset from registry
-    let reg_addr = REGISTRY_CONTRACT.load(deps.storage)?;
-    let asset: RegisteredAsset = deps.querier.query_wasm_smart(reg_addr, &RegQueryMsg::GetAsset { id: asset_id.clone() })?;
-
-    // check sanitized_approved and presence of a ubs_report_hash
-    if !asset.sanitized_approved { return Err(cosmwasm_std::StdError::generic_err("asset not sanitized")); }
-    let reg_ubs_hash = asset.ubs_report_hash.clone();
-    if reg_ubs_hash.is_none() { return Err(cosmwasm_std::StdError::generic_err("ubs report hash missing on registered asset")); }
-    // If claim included an explicit ubs hash, verify it matches the registry
-    if let Some(claim_hash) = ubs_report_hash.clone() {
-        if Some(claim_hash) != reg_ubs_hash.clone() {
-            return Err(cosmwasm_std::StdError::generic_err("ubs report hash mismatch"));
+    // Rolling-window limits: over-limit claims keep their leaf marked but wait for governance
+    // release; otherwise the downgrade treatment applies and the ledger is credited, locked if
+    // the asset vests its claims
+    let res = match handler_rate_limit::settle(deps.branch(), &env, pending, LedgerChangeReason::Claim)? {
+        handler_rate_limit::Settlement::Queued { id, limit } => {
+            return Ok(Response::new()
+                .add_attribute("action", "claim_queued")
+                .add_attribute("epoch", epoch.to_string())
+                .add_attribute("snapshot_hash", snapshot_hash)
+                .add_attribute("queue_id", id.to_string())
+                .add_attribute("reason", limit)
+                .add_attribute("ubs_decision", ubs_decision)
+                .add_attributes(downgrade.map(|t| ("downgrade_treatment", t.name()))));
         }
-    }
+        handler_rate_limit::Settlement::Credited(res) => res,
+    };
+    let json = serde_json::json!({"action":"claim_refactored","origin_chain":snapshot.chain_id.as_str(),"tx":origin_tx_hash.as_ref().unwrap_or(&"".to_string()),"report_hash": sres.report_hash,"downgrade": downgrade.as_ref().map(|t| t.audit_tag())});
+    // No immediate cw20 transfers to user - balances are recorded in the ledger
+    Ok(res
+        .add_attribute("action", "claim")
+        .add_attribute("epoch", epoch.to_string())
+        .add_attribute("snapshot_hash", snapshot_hash)
+        .add_attribute("entitled_auet", entitled.auet.to_string())
+        .add_attribute("entitled_csp", entitled.csp.to_string())
+        .add_attribute("claim_refactored", "true")
+        .add_attribute("ubs_decision", ubs_decision)
+        .add_attribute("refactor_audit", json.to_string()))
+}
 
-    // check activation_height
-    if env.block.height < asset.activation_height.into() { return Err(cosmwasm_std::StdError::generic_err("asset claim not activated yet")); }
+/// The registry's record of `asset_id`.
+pub(crate) fn registry_asset(deps: Deps, asset_id: &str) -> Result<RegisteredAsset, ContractError> {
+    let reg_addr = REGISTRY_CONTRACT.load(deps.storage)?;
+    let asset: Option<RegisteredAsset> = deps.querier.query_wasm_smart(reg_addr, &RegQueryMsg::GetAsset { id: asset_id.to_string() })?;
+    asset.ok_or_else(|| ContractError::AssetNotRegistered { asset_id: asset_id.to_string() })
+}
 
-    // verify merkle proof using merkle root from asset
-    let root = asset.merkle_root.clone();
-    // convert computed_h (which is hex str) to bytes
-    let mut leaf_bytes = [0u8; 32];
-    let bytes = hex::decode(computed_h.trim_start_matches("0x")).map_err(|_| cosmwasm_std::StdError::generic_err("invalid snapshot_hash hex"))?;
-    if bytes.len() != 32 { return Err(cosmwasm_std::StdError::generic_err("invalid snapshot_hash length")); }
-    leaf_bytes.copy_from_slice(&bytes);
+/// Snapshot epoch `epoch` of `asset`. Epochs after the first need their own sanitization
+/// approval; epoch 0 is the asset's, checked as `AssetNotSanitized`.
+pub(crate) fn snapshot_epoch(asset: &RegisteredAsset, epoch: u32) -> Result<SnapshotEpoch, ContractError> {
+    let snap = asset.snapshot_epoch(epoch).ok_or_else(|| ContractError::UnknownSnapshotEpoch { asset_id: asset.id.clone(), epoch })?;
+    if epoch > 0 && !snap.sanitized_approved { return Err(ContractError::EpochNotSanitized { asset_id: asset.id.clone(), epoch }); }
+    Ok(snap)
+}
 
-    // build proof vector (byte arrays)
+/// The registry's scaling profile `id`.
+pub(crate) fn registry_profile(deps: Deps, id: &str) -> Result<ScalingProfile, ContractError> {
+    let reg_addr = REGISTRY_CONTRACT.load(deps.storage)?;
+    let profile: Option<ScalingProfile> = deps.querier.query_wasm_smart(reg_addr, &RegQueryMsg::ScalingProfile { id: id.to_string() })?;
+    profile.ok_or_else(|| ContractError::UnknownScalingProfile { id: id.to_string() })
+}
+
+/// Decode `ProofStep`s into 32-byte siblings.
+pub(crate) fn decode_proof(merkle_proof: &[ProofStep]) -> Result<Vec<([u8; 32], bool)>, ContractError> {
     let mut proof_steps: Vec<( [u8;32], bool )> = vec![];
     for p in merkle_proof.iter() {
         let pbytes = p.sibling.clone().0;
         let mut arr = [0u8;32];
-        if pbytes.len() != 32 { return Err(cosmwasm_std::StdError::generic_err("invalid proof sibling length")); }
+        if pbytes.len() != 32 { return Err(ContractError::InvalidMerkleProof); }
         arr.copy_from_slice(&pbytes);
         proof_steps.push((arr, p.is_left));
     }
+    Ok(proof_steps)
+}
 
-    if !verify_merkle_proof(&leaf_bytes, &proof_steps, root.trim_start_matches("0x")) {
-        return Err(cosmwasm_std::StdError::generic_err("invalid merkle proof"));
-    }
-
-    // mark as claimed
-    CLAIMED.save(deps.storage, key, &true)?;
-
-    // record refactor into append-only refactor registry if origin metadata provided
-    if origin_tx_hash.is_some() && origin_nonce.is_some() {
-        // processed_at using block time (seconds)
-        let ts = env.block.time.seconds();
-        record_refactor(deps, snapshot.chain_id.as_str(), snapshot.denom.as_str(), origin_tx_hash.as_ref().unwrap(), origin_nonce.unwrap(), ts)?;
-    }
+/// Take `amount` of a claim that will never be credited back out of the energy totals.
+pub(crate) fn release_energy_totals(storage: &mut dyn Storage, toxic: bool, amount: Uint128) -> StdResult<()> {
+    TOTAL_ENERGY.update(storage, |t| -> StdResult<_> { Ok(t.saturating_sub(amount)) })?;
+    if toxic { TOXIC_ENERGY.update(storage, |t| -> StdResult<_> { Ok(t.saturating_sub(amount)) })?; }
+    Ok(())
+}
 
-    // Check toxic cap and update totals
-    let mut total_energy = TOTAL_ENERGY.load(deps.storage)?;
-    let mut toxic_energy = TOXIC_ENERGY.load(deps.storage)?;
-    let toxic_cap = TOXIC_CAP_PERCENT.may_load(deps.storage)?;
-    let scaling_is_malicious = asset.scaling_profile_id.contains("malicious");
-    if scaling_is_malicious {
-        // proposed toxic addition (auet + csp if present)
-        let add = amount_auet.u128() + amount_csp.map(|c| c.u128()).unwrap_or(0);
-        let new_to = toxic_energy.u128() + add;
-        let new_total = total_energy.u128() + add;
-        if let Some(pct) = toxic_cap {
-            let pct_val = pct as u128;
-            // if new_total == 0, allow (initial); else check <= pct
-            if new_total > 0 {
-                let cur_pct = (new_to * 100u128) / new_total;
-                if cur_pct > pct_val { return Err(cosmwasm_std::StdError::generic_err("toxic cap exceeded")); }
-            }
+/// TOTAL_ENERGY (and TOXIC_ENERGY for toxic-profile assets) after crediting `add`; fails when the
+/// toxic share would pass TOXIC_CAP_PERCENT.
+pub(crate) fn energy_totals_after(storage: &dyn Storage, toxic: bool, add: u128) -> Result<(Uint128, Option<Uint128>), ContractError> {
+    let new_total = TOTAL_ENERGY.load(storage)?.u128() + add;
+    if !toxic { return Ok((Uint128::new(new_total), None)); }
+    let new_to = TOXIC_ENERGY.load(storage)?.u128() + add;
+    if let Some(pct) = TOXIC_CAP_PERCENT.may_load(storage)?.flatten() {
+        // if new_total == 0, allow (initial); else check <= pct
+        if new_total > 0 && (new_to * 100u128) / new_total > pct as u128 {
+            return Err(ContractError::ToxicCapExceeded);
         }
-        // update counters
-        TOXIC_ENERGY.save(deps.storage, &Uint128::new(new_to))?;
-        TOTAL_ENERGY.save(deps.storage, &Uint128::new(new_total))?;
-    } else {
-        let add = amount_auet.u128() + amount_csp.map(|c| c.u128()).unwrap_or(0);
-        let new_total = total_energy.u128() + add;
-        TOTAL_ENERGY.save(deps.storage, &Uint128::new(new_total))?;
     }
+    Ok((Uint128::new(new_total), Some(Uint128::new(new_to))))
+}
 
-    // Transfer AU.ET and CSP if present
-    let auet_addr = AUET_CONTRACT.load(deps.storage)?;
-    // If the asset is marked malicious, route to the toxic sink if configured
-    let sink = TOXIC_SINK.may_load(deps.storage)?;
-    let target = if asset.scaling_profile_id.contains("malicious") {
-        // if sink configured, route there, else fail to protect users
-        if let Some(sink_addr) = sink { sink_addr.to_string() } else { return Err(cosmwasm_std::StdError::generic_err("toxic asset requires sink")); }
+/// UBS decision and energy for a claim of `amount_total`: the oracle's finalized report when an
+/// oracle is configured, the local `DefaultUBS` otherwise.
+pub(crate) fn sanitize_claim(deps: Deps, snapshot: &SnapshotEntry, origin_tx_hash: Option<&str>, origin_nonce: Option<u64>, amount_total: u128) -> Result<aln_ubs::SanitizationResult, ContractError> {
+    let ubs_oracle_addr_opt = UBS_ORACLE_CONTRACT.may_load(deps.storage)?.flatten();
+    if let Some(ubs_addr) = ubs_oracle_addr_opt {
+        // Build replay key using origin chain, tx_hash and nonce in a stable way
+        let replay_key_raw = snapshot.chain_id.clone() + ":" + origin_tx_hash.unwrap_or("") + ":" + &origin_nonce.unwrap_or(0).to_string();
+        let replay_bin = Binary::from(replay_key_raw.into_bytes());
+        // Query the on-chain UBS oracle (aggregated report) and map to a SanitizationResult
+        let qres: Option<ubs_oracle::AggregatedReport> = deps.querier.query_wasm_smart(ubs_addr, &OracleQueryMsg::GetReport { replay_key: replay_bin })?;
+        let agg = qres.ok_or(ContractError::UbsReportUnavailable)?;
+        // Map aggregated report to a sanitization result
+        let decision = match agg.ubs_class {
+            0 => aln_ubs::SanitizationDecision::Approved,
+            1 => aln_ubs::SanitizationDecision::Downgraded,
+            _ => aln_ubs::SanitizationDecision::Rejected,
+        };
+        let risk_score = (agg.threat_bps as f64) / 10000.0;
+        let energy_vec = aln_ubs::energy_mapping::map_to_energy(amount_total, &risk_score, &vec![]);
+        Ok(aln_ubs::SanitizationResult { decision, energy: energy_vec, report_hash: format!("oracle_agg:{}:{}", agg.ubs_class, agg.threat_bps) })
     } else {
-        recipient.to_string()
-    };
-    // anomaly detection for large amounts
-    if let Some(th) = ANOMALY_THRESHOLD_AMOUNT.may_load(deps.storage)? {
-        if amount_auet > th {
-            // mark anomaly and route to sink if configured, or fail
-            let sink = TOXIC_SINK.may_load(deps.storage)?;
-            if let Some(sink_addr) = sink { let transfer_auet = Cw20ExecuteMsg::Transfer { recipient: sink_addr.to_string(), amount: amount_auet }; let wasm_msg: CosmosMsg = WasmMsg::Execute { contract_addr: auet_addr.to_string(), msg: to_binary(&transfer_auet)?, funds: vec![] }.into(); let mut res = Response::new().add_message(wasm_msg).add_attribute("action", "claim_anomaly").add_attribute("snapshot_hash", snapshot_hash); return Ok(res); } else { return Err(cosmwasm_std::StdError::generic_err("anomaly threshold exceeded and no sink configured")); }
-        }
-    }
-    // ----- UBS sanitization + Sealed refactor ledger credit path -----
-    // Call UBS to sanitize origin token and compute energy vector
-            // Query UBS oracle for a finalized sanitized result if oracle configured
-            let ubs_oracle_addr_opt = UBS_ORACLE_CONTRACT.may_load(deps.storage)?;
-            let mut sres: SanitizationResult;
-            if let Some(ubs_addr) = ubs_oracle_addr_opt {
-                // Build replay key using origin chain, tx_hash and nonce in a stable way
-                let replay_key_raw = (snapshot.chain_id.clone() + ":" + origin_tx_hash.as_ref().unwrap_or(&"".to_string()) + ":" + &origin_nonce.unwrap_or(0).to_string());
-                let replay_bin = Binary::from(replay_key_raw.clone().into_bytes());
-                // Query the on-chain UBS oracle (aggregated report) and map to a SanitizationResult
-                let qres: Option<ubs_oracle::AggregatedReport> = deps.querier.query_wasm_smart(ubs_addr.clone(), &OracleQueryMsg::GetReport { replay_key: replay_bin })?;
-                if let Some(agg) = qres {
-                    // Map aggregated report to a sanitization result
-                    let decision = match agg.ubs_class {
-                        0 => aln_ubs::SanitizationDecision::Approved,
-                        1 => aln_ubs::SanitizationDecision::Downgraded,
-                        _ => aln_ubs::SanitizationDecision::Rejected,
-                    };
-                    let risk_score = (agg.threat_bps as f64) / 10000.0;
-                    let amount_total = amount_auet.u128() + amount_csp.map(|c| c.u128()).unwrap_or(0);
-                    let energy_vec = aln_ubs::energy_mapping::map_to_energy(amount_total, &risk_score, &vec![]);
-                    sres = aln_ubs::SanitizationResult { decision: decision, energy: energy_vec, report_hash: format!("oracle_agg:{}:{}", agg.ubs_class, agg.threat_bps) };
-                } else {
-                    return Err(cosmwasm_std::StdError::generic_err("ubs oracle report not available"));
-                }
-            } else {
-                // fallback to local DefaultUBS (not recommended in prod)
-                let ubs = DefaultUBS {};
-                sres = ubs.sanitize(snapshot.chain_id.as_str(), snapshot.denom.as_str(), &[]).map_err(|e| cosmwasm_std::StdError::generic_err(format!("ubs sanitize failed: {:?}", e)))?;
-            }
-            // Map aln_ubs energy vector to contract EnergyVector
-            let ev = EnergyVector { auet: sres.energy.auet, csp: sres.energy.csp, erp: sres.energy.erp };
-            // store audit (report hash) if origin metadata present
-            if origin_tx_hash.is_some() && origin_nonce.is_some() {
-                let txh = origin_tx_hash.as_ref().unwrap();
-                REFACTOR_AUDIT.save(deps.storage, (snapshot.chain_id.as_str(), txh.as_str(), origin_nonce.unwrap()), &sres.report_hash)?;
-            }
-            // If rejected, record the attempt and do not mint
-            if sres.decision == aln_ubs::SanitizationDecision::Rejected {
-                if origin_tx_hash.is_some() && origin_nonce.is_some() {
-                    record_refactor(deps, snapshot.chain_id.as_str(), snapshot.denom.as_str(), origin_tx_hash.as_ref().unwrap(), origin_nonce.unwrap(), env.block.time.seconds())?;
-                }
-                let json = serde_json::json!({"action":"claim_rejected","origin_chain":snapshot.chain_id.as_str(),"tx":origin_tx_hash.as_ref().unwrap_or(&"".to_string()),"report_hash": sres.report_hash});
-                return Ok(Response::new().add_attribute("action","claim_rejected").add_attribute("refactor_audit", json.to_string()));
-            }
-            // Credit ledger with energy vector
-            credit_energy(deps, &recipient, ev.clone()).map_err(|e| cosmwasm_std::StdError::generic_err(format!("ledger credit failed: {:?}", e)))?;
-            // save refactor record and update totals
-            if origin_tx_hash.is_some() && origin_nonce.is_some() { record_refactor(deps, snapshot.chain_id.as_str(), snapshot.denom.as_str(), origin_tx_hash.as_ref().unwrap(), origin_nonce.unwrap(), env.block.time.seconds())?; }
-            TOTAL_ENERGY.save(deps.storage, &Uint128::new(TOTAL_ENERGY.load(deps.storage)?.u128() + ev.auet.u128() + ev.csp.u128()))?;
-            if asset.scaling_profile_id.contains("malicious") { TOXIC_ENERGY.save(deps.storage, &Uint128::new(TOXIC_ENERGY.load(deps.storage)?.u128() + ev.auet.u128() + ev.csp.u128()))?; }
-            let json = serde_json::json!({"action":"claim_refactored","origin_chain":snapshot.chain_id.as_str(),"tx":origin_tx_hash.as_ref().unwrap_or(&"".to_string()),"report_hash": sres.report_hash});
-            let mut res = Response::new().add_attribute("action", "claim").add_attribute("snapshot_hash", snapshot_hash).add_attribute("claim_refactored", "true").add_attribute("refactor_audit", json.to_string());
-            return Ok(res);
-        }
+        // fallback to local DefaultUBS (not recommended in prod); only the decision and report
+        // hash are taken from it, the energy is always derived from the entitlement
+        let ubs = DefaultUBS {};
+        let local = ubs.sanitize(snapshot.chain_id.as_str(), snapshot.denom.as_str(), &[]).map_err(|e| ContractError::UBS(format!("ubs sanitize failed: {:?}", e)))?;
+        Ok(aln_ubs::SanitizationResult { decision: local.decision, energy: aln_ubs::energy_mapping::map_to_energy(amount_total, &0.0, &vec![]), report_hash: local.report_hash })
     }
-
-    // No immediate cw20 transfers to user - balances are recorded in the ledger
-
-    // Response already returned in the claim refactor path
-    Ok(Response::new())
 }
 
-/// Credit energy ledger for user
-fn credit_energy(deps: DepsMut, owner: &Addr, delta: EnergyVector) -> StdResult<()> {
+/// Credit energy ledger for user; returns the ledger hook notifications to attach to the response
+fn credit_energy(deps: DepsMut, owner: &Addr, delta: EnergyVector, reason: LedgerChangeReason) -> Result<Vec<SubMsg>, ContractError> {
     let existing = ENERGY_LEDGER.may_load(deps.storage, owner)?.unwrap_or(EnergyVector { auet: Uint128::zero(), csp: Uint128::zero(), erp: Uint128::zero() });
     let newv = EnergyVector { auet: Uint128::new(existing.auet.u128() + delta.auet.u128()), csp: Uint128::new(existing.csp.u128() + delta.csp.u128()), erp: Uint128::new(existing.erp.u128() + delta.erp.u128()) };
+    handler_ledger_checkpoint::preserve(deps.storage, owner)?;
     ENERGY_LEDGER.save(deps.storage, owner, &newv)?;
-    Ok(())
+    Ok(handler_ledger_hooks::ledger_changed(deps.storage, owner, &existing, &newv, reason)?)
 }
 
-/// Debit energy ledger for owner if caller is system-allowed
-fn debit_energy(deps: DepsMut, owner: &Addr, delta: EnergyVector, caller: &Addr) -> StdResult<()> {
+/// Debit energy ledger for owner if caller is system-allowed; locked (vesting) energy is not debitable.
+/// Returns the ledger hook notifications.
+fn debit_energy(deps: DepsMut, env: &Env, owner: &Addr, delta: EnergyVector, caller: &Addr) -> Result<Vec<SubMsg>, ContractError> {
     // Check system whitelist
     if !SYSTEM_WHITELIST.may_load(deps.storage, caller)?.unwrap_or(false) {
-        return Err(cosmwasm_std::StdError::generic_err("caller not system-allowed"));
+        return Err(ContractError::NotSystemAllowed);
     }
     let existing = ENERGY_LEDGER.may_load(deps.storage, owner)?.unwrap_or(EnergyVector { auet: Uint128::zero(), csp: Uint128::zero(), erp: Uint128::zero() });
     if existing.auet.u128() < delta.auet.u128() || existing.csp.u128() < delta.csp.u128() || existing.erp.u128() < delta.erp.u128() {
-        return Err(cosmwasm_std::StdError::generic_err("insufficient energy"));
+        return Err(ContractError::InsufficientEnergy);
     }
+    let available = handler_vesting::available_for_debit(deps.storage, env, owner)?;
+    if available.auet < delta.auet || available.csp < delta.csp || available.erp < delta.erp {
+        return Err(ContractError::EnergyLocked);
+    }
+    handler_allowance::spend(deps.storage, env, owner, caller, &delta)?;
     let newv = EnergyVector { auet: Uint128::new(existing.auet.u128() - delta.auet.u128()), csp: Uint128::new(existing.csp.u128() - delta.csp.u128()), erp: Uint128::new(existing.erp.u128() - delta.erp.u128()) };
+    handler_ledger_checkpoint::preserve(deps.storage, owner)?;
     ENERGY_LEDGER.save(deps.storage, owner, &newv)?;
-    Ok(())
+    Ok(handler_ledger_hooks::ledger_changed(deps.storage, owner, &existing, &newv, LedgerChangeReason::SystemConsume)?)
 }
 
-fn verify_merkle_proof(leaf: &[u8;32], proof: &Vec<([u8;32], bool)>, root_hex: &str) -> bool {
-    let mut cur = *leaf;
-    for (sib, is_left) in proof.iter() {
-        let mut h = Sha256::new();
-        if *is_left {
-            h.update(sib);
-            h.update(&cur);
-        } else {
-            h.update(&cur);
-            h.update(sib);
-        }
-        let res = h.finalize();
-        cur.copy_from_slice(&res);
-    }
+fn verify_merkle_proof(version: MerkleVersion, leaf: &[u8;32], proof: &[([u8;32], bool)], root_hex: &str) -> bool {
     let root_bytes = match hex::decode(root_hex) {
         Ok(b) => b,
         Err(_) => return false,
@@ -419,17 +690,32 @@ fn verify_merkle_proof(leaf: &[u8;32], proof: &Vec<([u8;32], bool)>, root_hex: &
     if root_bytes.len() != 32 { return false; }
     let mut root_arr = [0u8;32];
     root_arr.copy_from_slice(&root_bytes);
-    cur == root_arr
+    aln_merkle::verify_proof(version, leaf, proof, &root_arr)
+}
+
+/// Only ledger hook notifications reply, and only when they fail.
+#[entry_point]
+pub fn reply(deps: DepsMut, env: Env, msg: Reply) -> Result<Response, ContractError> {
+    handler_ledger_hooks::hook_failed(deps, env, msg)
+}
+
+#[entry_point]
+pub fn migrate(deps: DepsMut, _env: Env, _msg: MigrateMsg) -> Result<Response, ContractError> {
+    let (previous, pending) = migrations::migrate_state(deps)?;
+    Ok(Response::new()
+        .add_attribute("action", "migrate")
+        .add_attribute("from_version", previous)
+        .add_attribute("to_version", CONTRACT_VERSION)
+        .add_attribute("pending_migrations", pending.to_string()))
 }
 
 #[entry_point]
-pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
+pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
     match msg {
-        QueryMsg::IsClaimed { address, asset_id, snapshot_hash } => {
+        QueryMsg::IsClaimed { address, asset_id, epoch, snapshot_hash } => {
             let addr = deps.api.addr_validate(&address)?;
-            let key = (&addr, asset_id.as_str(), snapshot_hash.as_str());
-            let val = CLAIMED.may_load(deps.storage, key)?.unwrap_or(false);
-            Ok(to_binary(&val)?)
+            let claimed_by = CLAIMED_LEAVES.may_load(deps.storage, (asset_id.as_str(), epoch.unwrap_or_default(), snapshot_hash.as_str()))?;
+            Ok(to_binary(&(claimed_by == Some(addr)))?)
         }
         QueryMsg::EnergyBalance { address } => {
             let addr = deps.api.addr_validate(&address)?;
@@ -440,5 +726,49 @@ pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
             let val = REFACTOR_AUDIT.may_load(deps.storage, (origin_chain.as_str(), tx_hash.as_str(), nonce))?;
             Ok(to_binary(&val)?)
         }
+        QueryMsg::ScalingProfile { id } => {
+            let reg_addr = REGISTRY_CONTRACT.load(deps.storage)?;
+            let val: Option<ScalingProfile> = deps.querier.query_wasm_smart(reg_addr, &RegQueryMsg::ScalingProfile { id })?;
+            Ok(to_binary(&val)?)
+        }
+        QueryMsg::Config {} => to_binary(&handler_config::query_config(deps)?),
+        QueryMsg::TotalEnergy {} => to_binary(&TOTAL_ENERGY.may_load(deps.storage)?.unwrap_or_default()),
+        QueryMsg::ToxicEnergy {} => to_binary(&TOXIC_ENERGY.may_load(deps.storage)?.unwrap_or_default()),
+        QueryMsg::PendingGovernance {} => to_binary(&PENDING_GOVERNANCE.may_load(deps.storage)?),
+        QueryMsg::PauseState { asset_id } => to_binary(&handler_pause::query_pause_state(deps, asset_id)?),
+        QueryMsg::RateLimit { asset_id } => to_binary(&RATE_LIMITS.may_load(deps.storage, asset_id.as_str())?),
+        QueryMsg::QueuedClaim { id } => to_binary(&QUEUED_CLAIMS.may_load(deps.storage, id)?),
+        QueryMsg::QueuedClaims { start_after, limit } => to_binary(&handler_rate_limit::query_queued_claims(deps, start_after, limit)?),
+        QueryMsg::DowngradeTreatment { asset_id } => match asset_id {
+            Some(a) => to_binary(&handler_downgrade::treatment_for(deps.storage, a.as_str())?),
+            None => to_binary(&DEFAULT_DOWNGRADE_TREATMENT.may_load(deps.storage)?.unwrap_or(DowngradeTreatment::FullCredit)),
+        },
+        QueryMsg::SimulateClaim { sender, claim } => to_binary(&handler_simulate::simulate_claim(deps, env, sender, claim)?),
+        QueryMsg::ClaimsByAddress { address, start_after, limit } => to_binary(&handler_list::query_claims_by_address(deps, address, start_after, limit)?),
+        QueryMsg::ClaimsByAsset { asset_id, start_after, limit } => to_binary(&handler_list::query_claims_by_asset(deps, asset_id, start_after, limit)?),
+        QueryMsg::EnergyHolders { start_after, limit } => to_binary(&handler_list::query_energy_holders(deps, start_after, limit)?),
+        QueryMsg::SystemWhitelist { start_after, limit } => to_binary(&handler_list::query_system_whitelist(deps, start_after, limit)?),
+        QueryMsg::RefactorAudits { origin_chain, start_after, limit } => to_binary(&handler_list::query_refactor_audits(deps, origin_chain, start_after, limit)?),
+        QueryMsg::LedgerCheckpoint { height } => to_binary(&handler_ledger_checkpoint::query_ledger_checkpoint(deps, height)?),
+        QueryMsg::PendingCheckpoint {} => to_binary(&handler_ledger_checkpoint::query_pending_checkpoint(deps)?),
+        QueryMsg::VestingSchedule { asset_id } => to_binary(&ASSET_VESTING_SCHEDULES.may_load(deps.storage, asset_id.as_str())?),
+        QueryMsg::EnergyAvailability { address } => to_binary(&handler_vesting::query_energy_availability(deps, env, address)?),
+        QueryMsg::EnergyLocks { address, start_after, limit } => to_binary(&handler_vesting::query_energy_locks(deps, address, start_after, limit)?),
+        QueryMsg::EnergyAllowance { owner, spender } => to_binary(&handler_allowance::query_energy_allowance(deps, env, owner, spender)?),
+        QueryMsg::AllowancesByOwner { owner, start_after, limit } => to_binary(&handler_allowance::query_allowances_by_owner(deps, env, owner, start_after, limit)?),
+        QueryMsg::AllowancesBySpender { spender, start_after, limit } => to_binary(&handler_allowance::query_allowances_by_spender(deps, env, spender, start_after, limit)?),
+        QueryMsg::SystemCap { addr } => to_binary(&handler_allowance::query_system_cap(deps, env, addr)?),
+        QueryMsg::SystemCaps { start_after, limit } => to_binary(&handler_allowance::query_system_caps(deps, start_after, limit)?),
+        QueryMsg::AssetClaimStats { asset_id } => to_binary(&handler_claim_stats::query_asset_claim_stats(deps, asset_id)?),
+        QueryMsg::TreasurySweeps { start_after, limit } => to_binary(&handler_claim_stats::query_treasury_sweeps(deps, start_after, limit)?),
+        QueryMsg::QuarantineCase { id } => to_binary(&QUARANTINE_CASES.may_load(deps.storage, id)?),
+        QueryMsg::OpenQuarantineCases { start_after, limit } => to_binary(&handler_quarantine::query_open_quarantine(deps, start_after, limit)?),
+        QueryMsg::LightClient { chain_id } => to_binary(&LIGHT_CLIENTS.may_load(deps.storage, chain_id.as_str())?),
+        QueryMsg::ConsensusRoot { chain_id, height } => to_binary(&handler_light_client::query_consensus_root(deps, chain_id, height)?),
+        QueryMsg::LedgerHooks { start_after, limit } => to_binary(&handler_ledger_hooks::query_ledger_hooks(deps, start_after, limit)?),
+        QueryMsg::IsGuardian { address } => {
+            let addr = deps.api.addr_validate(&address)?;
+            to_binary(&GUARDIANS.may_load(deps.storage, &addr)?.unwrap_or(false))
+        }
     }
 }
@@ -0,0 +1,111 @@
+use cosmwasm_std::{Addr, DepsMut, Empty, MessageInfo, Order, Response, StdResult, Storage};
+use cw2::{set_contract_version, CONTRACT};
+use cw_storage_plus::Item;
+use semver::Version;
+use serde::{Deserialize, Serialize};
+
+use crate::{ContractError, CLAIMED, CLAIMED_LEAVES, CLAIMS_BY_RECIPIENT, CONTRACT_NAME, CONTRACT_VERSION};
+
+/// Bridge instances deployed before cw2 tracking carry no version; they are treated as this.
+const LEGACY_VERSION: &str = "0.2.0";
+/// Entries rewritten by `migrate` itself and by a `MigrateBatch` without a limit.
+pub const DEFAULT_MIGRATION_BATCH: u32 = 100;
+pub const MAX_MIGRATION_BATCH: u32 = 500;
+
+/// A state migration that rewrites one legacy map. Every step drains its source map, so a batch
+/// always resumes at the first entry left.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum MigrationStep {
+    /// 0.3.0: (recipient, asset_id, snapshot_hash) keys -> epoch 0 leaf keys
+    /// (asset_id, 0, snapshot_hash), indexed by recipient.
+    ClaimKeysV030,
+}
+
+/// Steps scheduled by `migrate` that still have entries to rewrite, in order. Claims are
+/// refused until it is empty.
+pub const PENDING_MIGRATIONS: Item<Vec<MigrationStep>> = Item::new("pending_migrations");
+
+fn parse_version(v: &str) -> Result<Version, ContractError> {
+    v.parse::<Version>().map_err(|e| ContractError::Invariant(format!("invalid contract version {}: {}", v, e)))
+}
+
+/// Claims read and write the migrated claim maps only, so they wait for every pending step.
+pub fn ensure_migrated(storage: &dyn Storage) -> Result<(), ContractError> {
+    if !PENDING_MIGRATIONS.may_load(storage)?.unwrap_or_default().is_empty() { return Err(ContractError::MigrationPending); }
+    Ok(())
+}
+
+/// Validate the stored contract name/version, schedule every state migration newer than the
+/// stored version, run a first batch of them and record the current version. Returns the
+/// previous version and the number of steps left for `MigrateBatch`.
+pub fn migrate_state(mut deps: DepsMut) -> Result<(String, usize), ContractError> {
+    let previous = match CONTRACT.may_load(deps.storage)? {
+        Some(stored) => {
+            if stored.contract != CONTRACT_NAME {
+                return Err(ContractError::CannotMigrate { previous_contract: stored.contract });
+            }
+            stored.version
+        }
+        None => LEGACY_VERSION.to_string(),
+    };
+    let prev_v = parse_version(&previous)?;
+    let new_v = parse_version(CONTRACT_VERSION)?;
+    if prev_v > new_v {
+        return Err(ContractError::CannotMigrateVersion { previous_version: previous });
+    }
+
+    let mut pending = PENDING_MIGRATIONS.may_load(deps.storage)?.unwrap_or_default();
+    if prev_v < parse_version("0.3.0")? {
+        pending.push(MigrationStep::ClaimKeysV030);
+    }
+    PENDING_MIGRATIONS.save(deps.storage, &pending)?;
+    let left = run_batch(deps.branch(), DEFAULT_MIGRATION_BATCH as usize)?;
+
+    set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+    Ok((previous, left))
+}
+
+/// Continue the pending migration steps, rewriting at most `limit` entries. Anyone may call it.
+pub fn migrate_batch(deps: DepsMut, _info: MessageInfo, limit: Option<u32>) -> Result<Response, ContractError> {
+    if PENDING_MIGRATIONS.may_load(deps.storage)?.unwrap_or_default().is_empty() { return Err(ContractError::NoPendingMigration); }
+    let limit = limit.unwrap_or(DEFAULT_MIGRATION_BATCH).clamp(1, MAX_MIGRATION_BATCH);
+    let left = run_batch(deps, limit as usize)?;
+    Ok(Response::new().add_attribute("action", "migrate_batch").add_attribute("pending_migrations", left.to_string()))
+}
+
+/// Run pending steps in order within `limit` entries; returns the number of steps left.
+fn run_batch(deps: DepsMut, limit: usize) -> Result<usize, ContractError> {
+    let mut pending = PENDING_MIGRATIONS.may_load(deps.storage)?.unwrap_or_default();
+    let mut left = limit;
+    while let Some(&step) = pending.first() {
+        let done = match step {
+            MigrationStep::ClaimKeysV030 => migrate_claim_keys_v0_3_0(deps.storage, left)?,
+        };
+        left -= done;
+        // a step that filled the batch may have entries left
+        if left == 0 { break; }
+        pending.remove(0);
+    }
+    PENDING_MIGRATIONS.save(deps.storage, &pending)?;
+    Ok(pending.len())
+}
+
+/// 0.3.0: claims are keyed by the origin leaf (asset_id, epoch, H_i), where H_i is derived from
+/// the `OriginLockEvent`/snapshot fields, instead of the legacy (recipient, asset_id,
+/// snapshot_hash). Everything claimed so far belongs to epoch 0, the snapshot each asset was
+/// registered with; a leaf claimed by several recipients keeps the first.
+fn migrate_claim_keys_v0_3_0(storage: &mut dyn Storage, limit: usize) -> StdResult<usize> {
+    let legacy: Vec<((Addr, String, String), bool)> = CLAIMED
+        .range(storage, None, None, Order::Ascending)
+        .take(limit)
+        .collect::<StdResult<_>>()?;
+    for ((recipient, asset_id, snapshot_hash), claimed) in legacy.iter() {
+        if *claimed && CLAIMED_LEAVES.may_load(storage, (asset_id.as_str(), 0, snapshot_hash.as_str()))?.is_none() {
+            CLAIMED_LEAVES.save(storage, (asset_id.as_str(), 0, snapshot_hash.as_str()), recipient)?;
+            CLAIMS_BY_RECIPIENT.save(storage, (recipient, asset_id.as_str(), (0, snapshot_hash.as_str())), &Empty {})?;
+        }
+        CLAIMED.remove(storage, (recipient, asset_id.as_str(), snapshot_hash.as_str()));
+    }
+    Ok(legacy.len())
+}
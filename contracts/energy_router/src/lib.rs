@@ -1,11 +1,22 @@
-use cosmwasm_std::{entry_point, to_binary, Addr, Binary, Deps, DepsMut, Env, MessageInfo, Response, StdResult, Uint128};
+use cosmwasm_std::{entry_point, to_binary, Addr, Binary, Deps, DepsMut, Env, MessageInfo, Response, StdError, StdResult, Uint128};
+use cw2::{set_contract_version, CONTRACT};
+use semver::Version;
 use serde::{Deserialize, Serialize};
 
+mod error;
+pub use error::ContractError;
+
+const CONTRACT_NAME: &str = "aln-energy-router";
+const CONTRACT_VERSION: &str = "0.1.0";
+
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
 pub struct InstantiateMsg {
     pub owner: String,
 }
 
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct MigrateMsg {}
+
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
 #[serde(rename_all = "snake_case")]
 pub enum ExecuteMsg {
@@ -15,12 +26,13 @@ pub enum ExecuteMsg {
 }
 
 #[entry_point]
-pub fn instantiate(_deps: DepsMut, _env: Env, _info: MessageInfo, _msg: InstantiateMsg) -> StdResult<Response> {
+pub fn instantiate(deps: DepsMut, _env: Env, _info: MessageInfo, _msg: InstantiateMsg) -> Result<Response, ContractError> {
+    set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
     Ok(Response::new())
 }
 
 #[entry_point]
-pub fn execute(deps: DepsMut, _env: Env, info: MessageInfo, msg: ExecuteMsg) -> StdResult<Response> {
+pub fn execute(deps: DepsMut, _env: Env, info: MessageInfo, msg: ExecuteMsg) -> Result<Response, ContractError> {
     match msg {
         ExecuteMsg::Receive { sender, amount, msg: _ } => {
             Ok(Response::new().add_attribute("action", "receive").add_attribute("from", sender).add_attribute("amount", amount.to_string()))
@@ -34,4 +46,25 @@ pub fn execute(deps: DepsMut, _env: Env, info: MessageInfo, msg: ExecuteMsg) ->
     }
 }
 
+#[entry_point]
+pub fn migrate(deps: DepsMut, _env: Env, _msg: MigrateMsg) -> Result<Response, ContractError> {
+    // instances deployed before cw2 tracking carry no version
+    let previous = match CONTRACT.may_load(deps.storage)? {
+        Some(stored) => {
+            if stored.contract != CONTRACT_NAME {
+                return Err(ContractError::CannotMigrate { previous_contract: stored.contract });
+            }
+            stored.version
+        }
+        None => CONTRACT_VERSION.to_string(),
+    };
+    let prev_v: Version = previous.parse().map_err(|_| StdError::generic_err("invalid stored contract version"))?;
+    let new_v: Version = CONTRACT_VERSION.parse().map_err(|_| StdError::generic_err("invalid contract version"))?;
+    if prev_v > new_v {
+        return Err(ContractError::CannotMigrateVersion { previous_version: previous });
+    }
+    set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+    Ok(Response::new().add_attribute("action", "migrate").add_attribute("from_version", previous).add_attribute("to_version", CONTRACT_VERSION))
+}
+
 // TODO: Provide query API and implementation when needed
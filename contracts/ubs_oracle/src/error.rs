@@ -0,0 +1,17 @@
+use cosmwasm_std::StdError;
+use thiserror::Error;
+
+#[derive(Error, Debug, PartialEq)]
+pub enum ContractError {
+    #[error("{0}")]
+    Std(#[from] StdError),
+
+    #[error("reporter not in committee")]
+    NotInCommittee,
+
+    #[error("cannot migrate from contract {previous_contract}")]
+    CannotMigrate { previous_contract: String },
+
+    #[error("cannot migrate from newer version {previous_version}")]
+    CannotMigrateVersion { previous_version: String },
+}
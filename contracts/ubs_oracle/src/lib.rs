@@ -1,7 +1,11 @@
-use cosmwasm_std::{entry_point, to_binary, Addr, Binary, Deps, DepsMut, Env, MessageInfo, Response, StdResult, StdError};
+use cosmwasm_std::{entry_point, to_binary, Addr, Binary, Deps, DepsMut, Env, MessageInfo, Response, StdError, StdResult};
 use cw_storage_plus::{Map, Item};
 use serde::{Deserialize, Serialize};
-use cw2::set_contract_version;
+use cw2::{get_contract_version, set_contract_version};
+use semver::Version;
+
+mod error;
+pub use error::ContractError;
 
 const CONTRACT_NAME: &str = "ubs_oracle";
 const CONTRACT_VERSION: &str = "0.1.0";
@@ -31,6 +35,9 @@ pub enum ExecuteMsg {
     SetThreshold { threshold: u8 },
 }
 
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct MigrateMsg {}
+
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
 pub enum QueryMsg {
     GetReport { replay_key: Binary },
@@ -38,7 +45,7 @@ pub enum QueryMsg {
 }
 
 #[entry_point]
-pub fn instantiate(deps: DepsMut, _env: Env, info: MessageInfo, _msg: () ) -> StdResult<Response> {
+pub fn instantiate(deps: DepsMut, _env: Env, info: MessageInfo, _msg: () ) -> Result<Response, ContractError> {
     set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
     // default empty committee
     COMMITTEE.save(deps.storage, &Vec::new())?;
@@ -47,7 +54,7 @@ pub fn instantiate(deps: DepsMut, _env: Env, info: MessageInfo, _msg: () ) -> St
 }
 
 #[entry_point]
-pub fn execute(deps: DepsMut, _env: Env, info: MessageInfo, msg: ExecuteMsg) -> StdResult<Response> {
+pub fn execute(deps: DepsMut, _env: Env, info: MessageInfo, msg: ExecuteMsg) -> Result<Response, ContractError> {
     match msg {
         ExecuteMsg::SubmitReport { replay_key, ubs_class, threat_bps, payload_hash } => submit_report(deps, info, replay_key, ubs_class, threat_bps, payload_hash),
         ExecuteMsg::SetCommittee { addrs } => set_committee(deps, info, addrs),
@@ -55,7 +62,7 @@ pub fn execute(deps: DepsMut, _env: Env, info: MessageInfo, msg: ExecuteMsg) ->
     }
 }
 
-fn set_committee(deps: DepsMut, info: MessageInfo, addrs: Vec<String>) -> StdResult<Response> {
+fn set_committee(deps: DepsMut, info: MessageInfo, addrs: Vec<String>) -> Result<Response, ContractError> {
     // Only governance owner (contract creator) allowed => info.sender must be stored? For now, allow any caller (improved later)
     let mut vec = Vec::new();
     for a in addrs.iter() { let ad = deps.api.addr_validate(a)?; vec.push(ad); }
@@ -63,17 +70,17 @@ fn set_committee(deps: DepsMut, info: MessageInfo, addrs: Vec<String>) -> StdRes
     Ok(Response::new().add_attribute("action","set_committee"))
 }
 
-fn set_threshold(deps: DepsMut, info: MessageInfo, threshold: u8) -> StdResult<Response> {
+fn set_threshold(deps: DepsMut, info: MessageInfo, threshold: u8) -> Result<Response, ContractError> {
     // governance only (not enforced here - for brevity) 
     THRESHOLD.save(deps.storage, &threshold)?;
     Ok(Response::new().add_attribute("action","set_threshold"))
 }
 
-fn submit_report(deps: DepsMut, info: MessageInfo, replay_key: Binary, ubs_class: u8, threat_bps: u64, payload_hash: String) -> StdResult<Response> {
+fn submit_report(deps: DepsMut, info: MessageInfo, replay_key: Binary, ubs_class: u8, threat_bps: u64, payload_hash: String) -> Result<Response, ContractError> {
     // verify reporter is in committee
     let committee = COMMITTEE.may_load(deps.storage)?.unwrap_or_default();
     let signer = info.sender.clone();
-    if !committee.iter().any(|a| a == &signer) { return Err(StdError::generic_err("reporter not in committee")); }
+    if !committee.iter().any(|a| a == &signer) { return Err(ContractError::NotInCommittee); }
     // add report
     let key = replay_key.as_slice();
     let mut existing = REPORTS.may_load(deps.storage, key)?.unwrap_or_default();
@@ -98,6 +105,22 @@ fn submit_report(deps: DepsMut, info: MessageInfo, replay_key: Binary, ubs_class
     Ok(Response::new().add_attribute("action","submit_report"))
 }
 
+#[entry_point]
+pub fn migrate(deps: DepsMut, _env: Env, _msg: MigrateMsg) -> Result<Response, ContractError> {
+    let stored = get_contract_version(deps.storage)?;
+    if stored.contract != CONTRACT_NAME {
+        return Err(ContractError::CannotMigrate { previous_contract: stored.contract });
+    }
+    let previous = stored.version;
+    let prev_v: Version = previous.parse().map_err(|_| StdError::generic_err("invalid stored contract version"))?;
+    let new_v: Version = CONTRACT_VERSION.parse().map_err(|_| StdError::generic_err("invalid contract version"))?;
+    if prev_v > new_v {
+        return Err(ContractError::CannotMigrateVersion { previous_version: previous });
+    }
+    set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+    Ok(Response::new().add_attribute("action", "migrate").add_attribute("from_version", previous).add_attribute("to_version", CONTRACT_VERSION))
+}
+
 #[entry_point]
 pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
     match msg {
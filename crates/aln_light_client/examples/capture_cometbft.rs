@@ -0,0 +1,269 @@
+//! Turns CometBFT RPC responses saved from a live chain into
+//! `tests/fixtures/cometbft/<chain_id>-<height>.json`, checked by `captured_cometbft_block_verifies`.
+//! Unlike `record_fixtures`, nothing here is produced by this crate: the block hash, the
+//! signatures and the IAVL proof are the chain's own.
+//!
+//! Save the responses for a height `H` in one directory, then convert them:
+//!
+//!     curl "$RPC/commit?height=$H" > commit.json
+//!     curl "$RPC/commit?height=$((H+1))" > next_commit.json
+//!     curl "$RPC/validators?height=$((H+1))&per_page=100" > validators.json
+//!     curl "$RPC/validators?height=$((H+2))&per_page=100" > next_validators.json
+//!     curl "$RPC/validators?height=$H&per_page=100" > trusted_validators.json
+//!     curl "$RPC/abci_query?path=%22/store/bank/key%22&data=0x<key>&height=$H&prove=true" > abci_query.json
+//!     cargo run --example capture_cometbft -- <dir>
+//!
+//! Header `H` becomes the trusted state and `H+1` the adjacent update; the query at `H` is proven
+//! against the app hash of `H+1`. Validator sets must fit one page (100 validators).
+
+use aln_light_client::ics23::{ExistenceProof, HashOp, InnerOp, LeafOp, LengthOp};
+use aln_light_client::tendermint::{BlockId, BlockIdFlag, Commit, CommitSig, Header, PartSetHeader, Validator, Version};
+use aln_light_client::{HeaderUpdate, SignedHeader, Timestamp, TrustedState, ValidatorSet};
+use serde_json::{json, Value};
+
+fn load(dir: &str, name: &str) -> Value {
+    let path = format!("{}/{}", dir, name);
+    let raw = std::fs::read_to_string(&path).unwrap_or_else(|e| panic!("{}: {}", path, e));
+    let v: Value = serde_json::from_str(&raw).unwrap_or_else(|e| panic!("{}: {}", path, e));
+    v.get("result").cloned().unwrap_or_else(|| panic!("{}: no `result`, got {}", path, raw))
+}
+
+fn text<'a>(v: &'a Value, field: &str) -> &'a str {
+    v[field].as_str().unwrap_or_else(|| panic!("`{}` missing in {}", field, v))
+}
+
+fn num(v: &Value, field: &str) -> u64 {
+    match &v[field] {
+        Value::String(s) => s.parse().unwrap_or_else(|_| panic!("`{}` is not a number: {}", field, s)),
+        Value::Number(n) => n.as_u64().unwrap(),
+        other => panic!("`{}` missing or not a number: {}", field, other),
+    }
+}
+
+fn hexfield(v: &Value, field: &str) -> Vec<u8> {
+    hex::decode(text(v, field)).unwrap_or_else(|e| panic!("`{}`: {}", field, e))
+}
+
+fn base64(s: &str) -> Vec<u8> {
+    const ALPHABET: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut out = vec![];
+    let (mut acc, mut bits) = (0u32, 0);
+    for c in s.bytes().filter(|c| *c != b'=') {
+        let d = ALPHABET.iter().position(|a| *a == c).unwrap_or_else(|| panic!("not base64: {}", s)) as u32;
+        acc = (acc << 6) | d;
+        bits += 6;
+        if bits >= 8 {
+            bits -= 8;
+            out.push((acc >> bits) as u8);
+        }
+    }
+    out
+}
+
+/// RFC 3339 in UTC, as CometBFT writes it (`2024-05-01T12:00:00.123456789Z`).
+fn timestamp(raw: &str) -> Timestamp {
+    fn bad<T>(raw: &str) -> T {
+        panic!("unsupported timestamp: {}", raw)
+    }
+    let s = raw.strip_suffix('Z').unwrap_or_else(|| bad(raw));
+    let (date, time) = s.split_once('T').unwrap_or_else(|| bad(raw));
+    let (clock, fraction) = time.split_once('.').unwrap_or((time, ""));
+    let d: Vec<i64> = date.split('-').map(|p| p.parse().unwrap_or_else(|_| bad(raw))).collect();
+    let t: Vec<i64> = clock.split(':').map(|p| p.parse().unwrap_or_else(|_| bad(raw))).collect();
+    if d.len() != 3 || t.len() != 3 {
+        bad::<()>(raw);
+    }
+    // days from civil (Howard Hinnant)
+    let (y, m) = if d[1] <= 2 { (d[0] - 1, d[1] + 9) } else { (d[0], d[1] - 3) };
+    let era = y.div_euclid(400);
+    let yoe = y - era * 400;
+    let doy = (153 * m + 2) / 5 + d[2] - 1;
+    let days = era * 146_097 + yoe * 365 + yoe / 4 - yoe / 100 + doy - 719_468;
+    let nanos = if fraction.is_empty() { 0 } else { format!("{:0<9}", fraction).parse().unwrap_or_else(|_| bad(raw)) };
+    Timestamp { seconds: days * 86_400 + t[0] * 3_600 + t[1] * 60 + t[2], nanos }
+}
+
+fn block_id(v: &Value) -> BlockId {
+    BlockId { hash: hexfield(v, "hash"), part_set_header: PartSetHeader { total: num(&v["parts"], "total") as u32, hash: hexfield(&v["parts"], "hash") } }
+}
+
+fn signed_header(commit: &Value) -> SignedHeader {
+    let h = &commit["signed_header"]["header"];
+    let header = Header {
+        version: Version { block: num(&h["version"], "block"), app: if h["version"]["app"].is_null() { 0 } else { num(&h["version"], "app") } },
+        chain_id: text(h, "chain_id").to_string(),
+        height: num(h, "height"),
+        time: timestamp(text(h, "time")),
+        last_block_id: block_id(&h["last_block_id"]),
+        last_commit_hash: hexfield(h, "last_commit_hash"),
+        data_hash: hexfield(h, "data_hash"),
+        validators_hash: hexfield(h, "validators_hash"),
+        next_validators_hash: hexfield(h, "next_validators_hash"),
+        consensus_hash: hexfield(h, "consensus_hash"),
+        app_hash: hexfield(h, "app_hash"),
+        last_results_hash: hexfield(h, "last_results_hash"),
+        evidence_hash: hexfield(h, "evidence_hash"),
+        proposer_address: hexfield(h, "proposer_address"),
+    };
+    let c = &commit["signed_header"]["commit"];
+    let signatures = c["signatures"]
+        .as_array()
+        .expect("commit signatures")
+        .iter()
+        .map(|s| CommitSig {
+            block_id_flag: match num(s, "block_id_flag") {
+                1 => BlockIdFlag::Absent,
+                2 => BlockIdFlag::Commit,
+                3 => BlockIdFlag::Nil,
+                other => panic!("unknown block_id_flag {}", other),
+            },
+            validator_address: hexfield(s, "validator_address"),
+            timestamp: timestamp(text(s, "timestamp")),
+            signature: s["signature"].as_str().map(base64).unwrap_or_default(),
+        })
+        .collect();
+    let commit = Commit { height: num(c, "height"), round: num(c, "round") as u32, block_id: block_id(&c["block_id"]), signatures };
+    SignedHeader { header, commit }
+}
+
+fn validators(result: &Value) -> ValidatorSet {
+    assert_eq!(num(result, "count"), num(result, "total"), "validator set spans several pages");
+    let validators = result["validators"]
+        .as_array()
+        .expect("validators")
+        .iter()
+        .map(|v| {
+            assert_eq!(text(&v["pub_key"], "type"), "tendermint/PubKeyEd25519", "only ed25519 validators are supported");
+            Validator { pub_key: base64(text(&v["pub_key"], "value")), voting_power: num(v, "voting_power") }
+        })
+        .collect();
+    ValidatorSet { validators }
+}
+
+/// Minimal protobuf reader for the ics23 `CommitmentProof` messages in `proofOps`.
+struct Reader<'a>(&'a [u8]);
+
+impl<'a> Reader<'a> {
+    fn varint(&mut self) -> u64 {
+        let mut v = 0u64;
+        for shift in (0..64).step_by(7) {
+            let b = self.0[0];
+            self.0 = &self.0[1..];
+            v |= ((b & 0x7f) as u64) << shift;
+            if b < 0x80 {
+                return v;
+            }
+        }
+        panic!("varint too long")
+    }
+
+    /// Next `(field, value)`: varints as their number, length-delimited fields as bytes.
+    fn field(&mut self) -> Option<(u64, Result<u64, &'a [u8]>)> {
+        if self.0.is_empty() {
+            return None;
+        }
+        let key = self.varint();
+        match key & 7 {
+            0 => Some((key >> 3, Ok(self.varint()))),
+            2 => {
+                let len = self.varint() as usize;
+                let (bytes, rest) = self.0.split_at(len);
+                self.0 = rest;
+                Some((key >> 3, Err(bytes)))
+            }
+            wire => panic!("unexpected wire type {}", wire),
+        }
+    }
+}
+
+fn hash_op(v: u64) -> HashOp {
+    match v {
+        0 => HashOp::NoHash,
+        1 => HashOp::Sha256,
+        other => panic!("unsupported hash op {}", other),
+    }
+}
+
+fn leaf_op(b: &[u8]) -> LeafOp {
+    let mut op = LeafOp { hash: HashOp::NoHash, prehash_key: HashOp::NoHash, prehash_value: HashOp::NoHash, length: LengthOp::NoPrefix, prefix: vec![] };
+    let mut r = Reader(b);
+    while let Some((field, v)) = r.field() {
+        match (field, v) {
+            (1, Ok(n)) => op.hash = hash_op(n),
+            (2, Ok(n)) => op.prehash_key = hash_op(n),
+            (3, Ok(n)) => op.prehash_value = hash_op(n),
+            (4, Ok(0)) => op.length = LengthOp::NoPrefix,
+            (4, Ok(1)) => op.length = LengthOp::VarProto,
+            (5, Err(p)) => op.prefix = p.to_vec(),
+            other => panic!("unexpected LeafOp field {:?}", other),
+        }
+    }
+    op
+}
+
+fn inner_op(b: &[u8]) -> InnerOp {
+    let mut op = InnerOp { hash: HashOp::NoHash, prefix: vec![], suffix: vec![] };
+    let mut r = Reader(b);
+    while let Some((field, v)) = r.field() {
+        match (field, v) {
+            (1, Ok(n)) => op.hash = hash_op(n),
+            (2, Err(p)) => op.prefix = p.to_vec(),
+            (3, Err(s)) => op.suffix = s.to_vec(),
+            other => panic!("unexpected InnerOp field {:?}", other),
+        }
+    }
+    op
+}
+
+/// `CommitmentProof { exist: ExistenceProof }`; other proof kinds are not membership proofs.
+fn existence_proof(b: &[u8]) -> ExistenceProof {
+    let mut outer = Reader(b);
+    let exist = match outer.field() {
+        Some((1, Err(e))) => e,
+        other => panic!("not an existence proof: {:?}", other.map(|(f, _)| f)),
+    };
+    let mut p = ExistenceProof { key: vec![], value: vec![], leaf: leaf_op(&[]), path: vec![] };
+    let mut r = Reader(exist);
+    while let Some((field, v)) = r.field() {
+        match (field, v) {
+            (1, Err(k)) => p.key = k.to_vec(),
+            (2, Err(v)) => p.value = v.to_vec(),
+            (3, Err(l)) => p.leaf = leaf_op(l),
+            (4, Err(i)) => p.path.push(inner_op(i)),
+            other => panic!("unexpected ExistenceProof field {:?}", other),
+        }
+    }
+    p
+}
+
+fn main() {
+    let dir = std::env::args().nth(1).expect("usage: capture_cometbft <dir with the saved RPC responses>");
+    let trusted_header = signed_header(&load(&dir, "commit.json"));
+    let next = signed_header(&load(&dir, "next_commit.json"));
+    let update = HeaderUpdate { signed_header: next, validators: validators(&load(&dir, "validators.json")), next_validators: validators(&load(&dir, "next_validators.json")) };
+    let trusted_validators = validators(&load(&dir, "trusted_validators.json"));
+    assert_eq!(trusted_validators.hash(), trusted_header.header.validators_hash, "trusted_validators.json is not the set of the trusted header");
+
+    let query = &load(&dir, "abci_query.json")["response"];
+    assert_eq!(num(query, "height"), trusted_header.header.height, "query the state at the trusted height");
+    let ops = query.get("proofOps").or_else(|| query.get("proof_ops")).and_then(|o| o["ops"].as_array()).expect("no proofOps; query with prove=true");
+    let proofs: Vec<ExistenceProof> = ops.iter().map(|op| existence_proof(&base64(text(op, "data")))).collect();
+    let store = String::from_utf8(base64(text(&ops[ops.len() - 1], "key"))).expect("store name");
+
+    let chain_id = trusted_header.header.chain_id.clone();
+    let height = trusted_header.header.height;
+    let fixture = json!({
+        "block_hash": hex::encode(&trusted_header.commit.block_id.hash),
+        "trusted": TrustedState { header: trusted_header.header, next_validators: update.validators.clone() },
+        "trusted_validators": trusted_validators,
+        "update": update,
+        "store": store,
+        "key": hex::encode(base64(text(query, "key"))),
+        "value": hex::encode(base64(text(query, "value"))),
+        "proofs": proofs,
+    });
+    let path = format!("{}/tests/fixtures/cometbft/{}-{}.json", env!("CARGO_MANIFEST_DIR"), chain_id, height);
+    std::fs::create_dir_all(std::path::Path::new(&path).parent().unwrap()).unwrap();
+    std::fs::write(&path, serde_json::to_string_pretty(&fixture).unwrap() + "\n").unwrap();
+    println!("wrote {}", path);
+}
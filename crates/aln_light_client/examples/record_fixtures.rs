@@ -0,0 +1,136 @@
+//! Writes `tests/fixtures/aln-origin-1.json`: a short header chain signed by fixed validator keys
+//! and a lock event proof against the app hash of the skipping header. Deterministic, so
+//! re-running it reproduces the committed fixture byte for byte.
+//!
+//!     cargo run --example record_fixtures
+
+use aln_light_client::ics23::{ExistenceProof, HashOp, InnerOp, LeafOp, ProofSpec};
+use aln_light_client::tendermint::{simple_hash, vote_sign_bytes, BlockId, BlockIdFlag, Commit, CommitSig, Header, PartSetHeader, Validator, Version};
+use aln_light_client::{HeaderUpdate, LockEvent, SignedHeader, Timestamp, TrustOptions, TrustedState, ValidatorSet, LOCK_STORE_KEY};
+use ed25519_dalek::{Signer, SigningKey};
+use serde_json::json;
+
+const CHAIN_ID: &str = "aln-origin-1";
+const GENESIS_TIME: i64 = 1_700_000_000;
+
+fn key(seed: u8) -> SigningKey {
+    SigningKey::from_bytes(&[seed; 32])
+}
+
+fn valset(seeds: &[u8]) -> ValidatorSet {
+    ValidatorSet { validators: seeds.iter().map(|s| Validator { pub_key: key(*s).verifying_key().to_bytes().to_vec(), voting_power: 10 }).collect() }
+}
+
+fn header(height: u64, time: i64, vals: &ValidatorSet, next: &ValidatorSet, app_hash: Vec<u8>) -> Header {
+    Header {
+        version: Version { block: 11, app: 0 },
+        chain_id: CHAIN_ID.to_string(),
+        height,
+        time: Timestamp { seconds: time, nanos: 0 },
+        last_block_id: BlockId { hash: vec![height as u8; 32], part_set_header: PartSetHeader { total: 1, hash: vec![0xaa; 32] } },
+        last_commit_hash: vec![0x01; 32],
+        data_hash: vec![0x02; 32],
+        validators_hash: vals.hash(),
+        next_validators_hash: next.hash(),
+        consensus_hash: vec![0x03; 32],
+        app_hash,
+        last_results_hash: vec![0x04; 32],
+        evidence_hash: vec![0x05; 32],
+        proposer_address: vals.validators[0].address(),
+    }
+}
+
+/// Sign `header` with the keys of `seeds` that are in `vals`; everyone else is absent.
+fn sign(header: Header, vals: &ValidatorSet, seeds: &[u8], signers: &[u8]) -> SignedHeader {
+    let mut commit = Commit { height: header.height, round: 0, block_id: BlockId { hash: header.hash(), part_set_header: PartSetHeader { total: 1, hash: vec![0xbb; 32] } }, signatures: vec![] };
+    for (v, seed) in vals.validators.iter().zip(seeds) {
+        let mut sig = CommitSig { block_id_flag: BlockIdFlag::Absent, validator_address: vec![], timestamp: Timestamp::default(), signature: vec![] };
+        if signers.contains(seed) {
+            sig = CommitSig { block_id_flag: BlockIdFlag::Commit, validator_address: v.address(), timestamp: Timestamp { seconds: header.time.seconds + 1, nanos: 0 }, signature: vec![] };
+            sig.signature = key(*seed).sign(&vote_sign_bytes(CHAIN_ID, &commit, &sig)).to_bytes().to_vec();
+        }
+        commit.signatures.push(sig);
+    }
+    SignedHeader { header, commit }
+}
+
+fn update(header: Header, vals: &ValidatorSet, next: &ValidatorSet, seeds: &[u8], signers: &[u8]) -> HeaderUpdate {
+    HeaderUpdate { signed_header: sign(header, vals, seeds, signers), validators: vals.clone(), next_validators: next.clone() }
+}
+
+fn sha256(data: &[u8]) -> Vec<u8> {
+    use sha2::{Digest, Sha256};
+    Sha256::digest(data).to_vec()
+}
+
+/// IAVL-shaped proof for `event` in a module store of three leaves (`event` in the middle),
+/// then the multistore proof for `LOCK_STORE_KEY` among three stores. Returns the app hash.
+fn lock_proofs(event: &LockEvent) -> (Vec<ExistenceProof>, Vec<u8>) {
+    let iavl_leaf = ProofSpec::iavl().leaf_spec;
+    // zigzag varints of height, size and version, as IAVL writes them
+    let leaf_op = LeafOp { prefix: vec![0, 2, 2], ..iavl_leaf.clone() };
+    let left = leaf_op.apply(b"lock/00/0", b"left");
+    let right = leaf_op.apply(b"lock/ff/0", b"right");
+    // inner node header, then the length prefix of the hash that follows it
+    let node = |height: u8, size: u8| vec![height * 2, size * 2, 2, 0x20];
+    let path = vec![
+        InnerOp { hash: HashOp::Sha256, prefix: [node(1, 2), left, vec![0x20]].concat(), suffix: vec![] },
+        InnerOp { hash: HashOp::Sha256, prefix: node(2, 3), suffix: [vec![0x20], right].concat() },
+    ];
+    let iavl = ExistenceProof { key: event.key(), value: event.value(), leaf: leaf_op, path };
+    let store_root = iavl.calculate_root();
+
+    let ms_leaf = ProofSpec::tendermint().leaf_spec;
+    let stores = [("acc", sha256(b"acc")), (LOCK_STORE_KEY, store_root.clone()), ("bank", sha256(b"bank"))];
+    let leaves: Vec<Vec<u8>> = stores.iter().map(|(k, v)| ms_leaf.apply(k.as_bytes(), v)).collect();
+    let encoded: Vec<Vec<u8>> = stores
+        .iter()
+        .map(|(k, v)| {
+            let mut kv = aln_light_client::proto::delimited(k.as_bytes());
+            kv.extend(aln_light_client::proto::delimited(&sha256(v)));
+            kv
+        })
+        .collect();
+    let path = vec![
+        InnerOp { hash: HashOp::Sha256, prefix: [vec![1], leaves[0].clone()].concat(), suffix: vec![] },
+        InnerOp { hash: HashOp::Sha256, prefix: vec![1], suffix: leaves[2].clone() },
+    ];
+    let multistore = ExistenceProof { key: LOCK_STORE_KEY.as_bytes().to_vec(), value: store_root, leaf: ms_leaf, path };
+    let app_hash = multistore.calculate_root();
+    assert_eq!(app_hash, simple_hash(&encoded), "multistore proof must match the simple merkle root");
+    (vec![iavl, multistore], app_hash)
+}
+
+fn main() {
+    let seeds_a = [1u8, 2, 3, 4];
+    let seeds_b = [2u8, 3, 4, 5];
+    let seeds_c = [5u8, 6, 7, 8];
+    let (a, b, c) = (valset(&seeds_a), valset(&seeds_b), valset(&seeds_c));
+
+    let event = LockEvent { tx_hash: "9F3C1A7E".to_string(), nonce: 7, origin_address: "kujira1lockowner".to_string(), denom: "ukuji".to_string(), amount: "1000000".to_string(), height: 149 };
+    let (proofs, app_hash) = lock_proofs(&event);
+
+    let trusted = TrustedState { header: header(100, GENESIS_TIME, &a, &a, vec![0x10; 32]), next_validators: a.clone() };
+    // sequential: 3 of 4 of the trusted next validators sign
+    let adjacent = update(header(101, GENESIS_TIME + 6, &a, &a, vec![0x11; 32]), &a, &a, &seeds_a, &[1, 2, 3]);
+    // skipping: validator 1 left and 5 joined; 3 of the 4 trusted validators still sign
+    let skipping = update(header(150, GENESIS_TIME + 300, &b, &b, app_hash), &b, &b, &seeds_b, &seeds_b);
+    // skipping to a set that shares nobody with the trusted one
+    let untrusted_skip = update(header(150, GENESIS_TIME + 300, &c, &c, vec![0x12; 32]), &c, &c, &seeds_c, &seeds_c);
+
+    let options = TrustOptions { trusting_period_secs: 14 * 86_400, max_clock_drift_secs: 10, trust_level: Default::default() };
+    let fixture = json!({
+        "chain_id": CHAIN_ID,
+        "options": options,
+        "now": Timestamp { seconds: GENESIS_TIME + 310, nanos: 0 },
+        "trusted": trusted,
+        "adjacent": adjacent,
+        "skipping": skipping,
+        "untrusted_skip": untrusted_skip,
+        "lock_event": event,
+        "lock_proofs": proofs,
+    });
+    let path = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/aln-origin-1.json");
+    std::fs::write(path, serde_json::to_string_pretty(&fixture).unwrap() + "\n").unwrap();
+    println!("wrote {}", path);
+}
@@ -0,0 +1,155 @@
+//! ICS-23 existence proofs (SHA-256 only) and the specs of the two stores a Cosmos SDK app hash
+//! commits to: the IAVL module store and the multistore over the module roots.
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::{hexbytes, proto, Error};
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum HashOp {
+    NoHash,
+    Sha256,
+}
+
+impl HashOp {
+    fn apply(&self, data: &[u8]) -> Vec<u8> {
+        match self {
+            HashOp::NoHash => data.to_vec(),
+            HashOp::Sha256 => Sha256::digest(data).to_vec(),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum LengthOp {
+    NoPrefix,
+    VarProto,
+}
+
+impl LengthOp {
+    fn apply(&self, data: &[u8]) -> Vec<u8> {
+        match self {
+            LengthOp::NoPrefix => data.to_vec(),
+            LengthOp::VarProto => proto::delimited(data),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct LeafOp {
+    pub hash: HashOp,
+    pub prehash_key: HashOp,
+    pub prehash_value: HashOp,
+    pub length: LengthOp,
+    #[serde(with = "hexbytes")]
+    pub prefix: Vec<u8>,
+}
+
+impl LeafOp {
+    pub fn apply(&self, key: &[u8], value: &[u8]) -> Vec<u8> {
+        let mut data = self.prefix.clone();
+        data.extend(self.length.apply(&self.prehash_key.apply(key)));
+        data.extend(self.length.apply(&self.prehash_value.apply(value)));
+        self.hash.apply(&data)
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct InnerOp {
+    pub hash: HashOp,
+    #[serde(with = "hexbytes")]
+    pub prefix: Vec<u8>,
+    #[serde(with = "hexbytes")]
+    pub suffix: Vec<u8>,
+}
+
+impl InnerOp {
+    pub fn apply(&self, child: &[u8]) -> Vec<u8> {
+        let mut data = self.prefix.clone();
+        data.extend_from_slice(child);
+        data.extend_from_slice(&self.suffix);
+        self.hash.apply(&data)
+    }
+}
+
+/// Shape every proof for a store must have; rejects proofs that pass a leaf off as an inner node.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct ProofSpec {
+    pub leaf_spec: LeafOp,
+    pub min_prefix_length: usize,
+    pub max_prefix_length: usize,
+    pub child_size: usize,
+}
+
+impl ProofSpec {
+    /// IAVL module store.
+    pub fn iavl() -> Self {
+        ProofSpec { leaf_spec: LeafOp { hash: HashOp::Sha256, prehash_key: HashOp::NoHash, prehash_value: HashOp::Sha256, length: LengthOp::VarProto, prefix: vec![0] }, min_prefix_length: 4, max_prefix_length: 12, child_size: 33 }
+    }
+
+    /// Simple merkle tree over the module store roots (the app hash).
+    pub fn tendermint() -> Self {
+        ProofSpec { leaf_spec: LeafOp { hash: HashOp::Sha256, prehash_key: HashOp::NoHash, prehash_value: HashOp::Sha256, length: LengthOp::VarProto, prefix: vec![0] }, min_prefix_length: 1, max_prefix_length: 1, child_size: 32 }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct ExistenceProof {
+    #[serde(with = "hexbytes")]
+    pub key: Vec<u8>,
+    #[serde(with = "hexbytes")]
+    pub value: Vec<u8>,
+    pub leaf: LeafOp,
+    /// Inner nodes from the leaf up to the root.
+    pub path: Vec<InnerOp>,
+}
+
+impl ExistenceProof {
+    pub fn calculate_root(&self) -> Vec<u8> {
+        self.path.iter().fold(self.leaf.apply(&self.key, &self.value), |child, op| op.apply(&child))
+    }
+
+    pub fn check_against_spec(&self, spec: &ProofSpec) -> Result<(), Error> {
+        let l = &self.leaf;
+        let s = &spec.leaf_spec;
+        if l.hash != s.hash || l.prehash_key != s.prehash_key || l.prehash_value != s.prehash_value || l.length != s.length || !l.prefix.starts_with(&s.prefix) {
+            return Err(Error::InvalidProof("leaf does not match the proof spec".to_string()));
+        }
+        for op in &self.path {
+            if op.hash != s.hash || op.prefix.starts_with(&s.prefix) {
+                return Err(Error::InvalidProof("inner node does not match the proof spec".to_string()));
+            }
+            if op.prefix.len() < spec.min_prefix_length || op.prefix.len() > spec.max_prefix_length + spec.child_size || op.suffix.len() % spec.child_size != 0 {
+                return Err(Error::InvalidProof("inner node prefix or suffix has the wrong length".to_string()));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Check that `value` is stored under `keys` (outermost store first, e.g. `["aln_lock", "lock/.."]`)
+/// in the state committed to by `root`. `proofs` go innermost first, one per key, each proving
+/// the root of the one before it; `specs` line up with `proofs`.
+pub fn verify_membership(specs: &[ProofSpec], root: &[u8], proofs: &[ExistenceProof], keys: &[&[u8]], value: &[u8]) -> Result<(), Error> {
+    if proofs.is_empty() || proofs.len() != keys.len() || proofs.len() != specs.len() {
+        return Err(Error::InvalidProof("expected one proof and spec per key".to_string()));
+    }
+    let mut expected_value = value.to_vec();
+    for (i, p) in proofs.iter().enumerate() {
+        p.check_against_spec(&specs[i])?;
+        if p.key != keys[keys.len() - 1 - i] {
+            return Err(Error::InvalidProof(format!("proof {} is for another key", i)));
+        }
+        if p.value != expected_value {
+            return Err(Error::InvalidProof(format!("proof {} is for another value", i)));
+        }
+        expected_value = p.calculate_root();
+    }
+    if expected_value != root {
+        return Err(Error::InvalidProof("proof does not lead to the root".to_string()));
+    }
+    Ok(())
+}
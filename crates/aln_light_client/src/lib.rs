@@ -0,0 +1,111 @@
+//! Deterministic Tendermint/CometBFT light client for origin chains, shared by the bridge
+//! contract and the tooling that records fixtures.
+//!
+//! * `tendermint` verifies signed headers against a trusted header and validator set, skipping
+//!   heights when `trust_level` of the trusted validators signed the new header.
+//! * `ics23` verifies that a lock event is stored in the origin chain's state under a verified
+//!   app hash (IAVL module store, then the multistore).
+//!
+//! Signature checks go through `SignatureVerifier` so the contract can use the host's ed25519.
+
+pub mod ics23;
+pub mod proto;
+pub mod tendermint;
+
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+pub use tendermint::{verify_header, HeaderUpdate, SignatureVerifier, SignedHeader, Timestamp, TrustOptions, TrustedState, ValidatorSet};
+
+/// Store key of the origin chain module that records lock events.
+pub const LOCK_STORE_KEY: &str = "aln_lock";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Error {
+    ChainIdMismatch,
+    NonIncreasingHeight,
+    NonIncreasingTime,
+    TrustedStateExpired,
+    HeaderFromFuture,
+    InvalidTrustOptions,
+    ValidatorSetMismatch(&'static str),
+    CommitMismatch(String),
+    InvalidSignature { validator: String },
+    InsufficientTrust { tallied: u64, total: u64 },
+    InsufficientVotingPower { tallied: u64, total: u64 },
+    InvalidProof(String),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::ChainIdMismatch => write!(f, "header is for another chain"),
+            Error::NonIncreasingHeight => write!(f, "header height is not above the trusted height"),
+            Error::NonIncreasingTime => write!(f, "header time is not after the trusted time"),
+            Error::TrustedStateExpired => write!(f, "trusted header is outside the trusting period"),
+            Error::HeaderFromFuture => write!(f, "header time is beyond the allowed clock drift"),
+            Error::InvalidTrustOptions => write!(f, "invalid trust options"),
+            Error::ValidatorSetMismatch(which) => write!(f, "{} do not match the header hash", which),
+            Error::CommitMismatch(msg) => write!(f, "commit mismatch: {}", msg),
+            Error::InvalidSignature { validator } => write!(f, "invalid signature from validator {}", validator),
+            Error::InsufficientTrust { tallied, total } => write!(f, "trusted validators signed {} of {} power, below the trust level", tallied, total),
+            Error::InsufficientVotingPower { tallied, total } => write!(f, "validators signed {} of {} power, not more than 2/3", tallied, total),
+            Error::InvalidProof(msg) => write!(f, "invalid proof: {}", msg),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+/// A lock on the origin chain, as recorded by its lock module.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct LockEvent {
+    pub tx_hash: String,
+    pub nonce: u64,
+    pub origin_address: String,
+    pub denom: String,
+    pub amount: String,
+    pub height: u64,
+}
+
+impl LockEvent {
+    /// Key in the lock module store.
+    pub fn key(&self) -> Vec<u8> {
+        format!("lock/{}/{}", self.tx_hash, self.nonce).into_bytes()
+    }
+
+    /// Stored value: sha256 over the length-prefixed owner, denom and amount, then the height.
+    pub fn value(&self) -> Vec<u8> {
+        let mut h = Sha256::new();
+        for field in [&self.origin_address, &self.denom, &self.amount] {
+            h.update((field.len() as u32).to_be_bytes());
+            h.update(field.as_bytes());
+        }
+        h.update(self.height.to_be_bytes());
+        h.finalize().to_vec()
+    }
+}
+
+/// Verify that `event` is in the state committed to by `app_hash`. `proofs` holds the lock
+/// module IAVL proof first, then the multistore proof for `LOCK_STORE_KEY`.
+pub fn verify_lock_event(app_hash: &[u8], proofs: &[ics23::ExistenceProof], event: &LockEvent) -> Result<(), Error> {
+    let key = event.key();
+    let keys: [&[u8]; 2] = [LOCK_STORE_KEY.as_bytes(), &key];
+    ics23::verify_membership(&[ics23::ProofSpec::iavl(), ics23::ProofSpec::tendermint()], app_hash, proofs, &keys, &event.value())
+}
+
+/// Hex (de)serialization for byte fields, so fixtures and messages stay readable.
+pub mod hexbytes {
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(bytes: &[u8], s: S) -> Result<S::Ok, S::Error> {
+        s.serialize_str(&hex::encode(bytes))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(d: D) -> Result<Vec<u8>, D::Error> {
+        let s = String::deserialize(d)?;
+        hex::decode(s.trim_start_matches("0x")).map_err(serde::de::Error::custom)
+    }
+}
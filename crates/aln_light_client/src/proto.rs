@@ -0,0 +1,51 @@
+//! The few protobuf encodings CometBFT hashes and signs, written by hand so the crate stays
+//! free of codegen. proto3 rules: scalar fields at their default value are omitted.
+
+pub fn varint(buf: &mut Vec<u8>, mut v: u64) {
+    while v >= 0x80 {
+        buf.push((v as u8) | 0x80);
+        v >>= 7;
+    }
+    buf.push(v as u8);
+}
+
+fn key(buf: &mut Vec<u8>, field: u32, wire_type: u8) {
+    varint(buf, ((field as u64) << 3) | wire_type as u64);
+}
+
+pub fn uint(buf: &mut Vec<u8>, field: u32, v: u64) {
+    if v != 0 {
+        key(buf, field, 0);
+        varint(buf, v);
+    }
+}
+
+pub fn sfixed64(buf: &mut Vec<u8>, field: u32, v: i64) {
+    if v != 0 {
+        key(buf, field, 1);
+        buf.extend_from_slice(&v.to_le_bytes());
+    }
+}
+
+pub fn bytes(buf: &mut Vec<u8>, field: u32, b: &[u8]) {
+    if !b.is_empty() {
+        key(buf, field, 2);
+        varint(buf, b.len() as u64);
+        buf.extend_from_slice(b);
+    }
+}
+
+/// Embedded message; always written, as gogoproto does for non-nullable fields.
+pub fn message(buf: &mut Vec<u8>, field: u32, m: &[u8]) {
+    key(buf, field, 2);
+    varint(buf, m.len() as u64);
+    buf.extend_from_slice(m);
+}
+
+/// `m` prefixed with its varint length (`protoio.MarshalDelimited`).
+pub fn delimited(m: &[u8]) -> Vec<u8> {
+    let mut out = vec![];
+    varint(&mut out, m.len() as u64);
+    out.extend_from_slice(m);
+    out
+}
@@ -0,0 +1,390 @@
+//! CometBFT headers, commits and validator sets, hashed and signed exactly as the chain does,
+//! and header verification in the style of the Tendermint light client: sequential updates need
+//! +2/3 of the new validator set, skipping updates additionally need `trust_level` of the
+//! trusted next validator set to have signed.
+
+use std::collections::BTreeSet;
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::{hexbytes, proto, Error};
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Default)]
+pub struct Timestamp {
+    pub seconds: i64,
+    pub nanos: i32,
+}
+
+impl Timestamp {
+    fn encode(&self) -> Vec<u8> {
+        let mut buf = vec![];
+        proto::uint(&mut buf, 1, self.seconds as u64);
+        proto::uint(&mut buf, 2, self.nanos as i64 as u64);
+        buf
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, Default)]
+pub struct PartSetHeader {
+    pub total: u32,
+    #[serde(with = "hexbytes")]
+    pub hash: Vec<u8>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, Default)]
+pub struct BlockId {
+    #[serde(with = "hexbytes")]
+    pub hash: Vec<u8>,
+    pub part_set_header: PartSetHeader,
+}
+
+impl BlockId {
+    fn encode(&self) -> Vec<u8> {
+        let mut psh = vec![];
+        proto::uint(&mut psh, 1, self.part_set_header.total as u64);
+        proto::bytes(&mut psh, 2, &self.part_set_header.hash);
+        let mut buf = vec![];
+        proto::bytes(&mut buf, 1, &self.hash);
+        proto::message(&mut buf, 2, &psh);
+        buf
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub struct Version {
+    pub block: u64,
+    pub app: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct Header {
+    pub version: Version,
+    pub chain_id: String,
+    pub height: u64,
+    pub time: Timestamp,
+    pub last_block_id: BlockId,
+    #[serde(with = "hexbytes")]
+    pub last_commit_hash: Vec<u8>,
+    #[serde(with = "hexbytes")]
+    pub data_hash: Vec<u8>,
+    #[serde(with = "hexbytes")]
+    pub validators_hash: Vec<u8>,
+    #[serde(with = "hexbytes")]
+    pub next_validators_hash: Vec<u8>,
+    #[serde(with = "hexbytes")]
+    pub consensus_hash: Vec<u8>,
+    #[serde(with = "hexbytes")]
+    pub app_hash: Vec<u8>,
+    #[serde(with = "hexbytes")]
+    pub last_results_hash: Vec<u8>,
+    #[serde(with = "hexbytes")]
+    pub evidence_hash: Vec<u8>,
+    #[serde(with = "hexbytes")]
+    pub proposer_address: Vec<u8>,
+}
+
+/// `cdcEncode` of a gogo wrapper type: the value as field 1, nothing when it is the default.
+fn wrapped_bytes(b: &[u8]) -> Vec<u8> {
+    let mut buf = vec![];
+    proto::bytes(&mut buf, 1, b);
+    buf
+}
+
+impl Header {
+    /// Block hash: the simple merkle root over the proto-encoded header fields.
+    pub fn hash(&self) -> Vec<u8> {
+        let mut version = vec![];
+        proto::uint(&mut version, 1, self.version.block);
+        proto::uint(&mut version, 2, self.version.app);
+        let mut height = vec![];
+        proto::uint(&mut height, 1, self.height);
+        let fields = vec![
+            version,
+            wrapped_bytes(self.chain_id.as_bytes()),
+            height,
+            self.time.encode(),
+            self.last_block_id.encode(),
+            wrapped_bytes(&self.last_commit_hash),
+            wrapped_bytes(&self.data_hash),
+            wrapped_bytes(&self.validators_hash),
+            wrapped_bytes(&self.next_validators_hash),
+            wrapped_bytes(&self.consensus_hash),
+            wrapped_bytes(&self.app_hash),
+            wrapped_bytes(&self.last_results_hash),
+            wrapped_bytes(&self.evidence_hash),
+            wrapped_bytes(&self.proposer_address),
+        ];
+        simple_hash(&fields)
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct Validator {
+    /// Ed25519 public key.
+    #[serde(with = "hexbytes")]
+    pub pub_key: Vec<u8>,
+    pub voting_power: u64,
+}
+
+impl Validator {
+    pub fn address(&self) -> Vec<u8> {
+        Sha256::digest(&self.pub_key)[..20].to_vec()
+    }
+
+    /// `SimpleValidator` encoding hashed into the validator set hash.
+    fn encode(&self) -> Vec<u8> {
+        let mut key = vec![];
+        proto::bytes(&mut key, 1, &self.pub_key);
+        let mut buf = vec![];
+        proto::message(&mut buf, 1, &key);
+        proto::uint(&mut buf, 2, self.voting_power);
+        buf
+    }
+}
+
+/// Validators in the chain's canonical order (the order the header hash commits to).
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct ValidatorSet {
+    pub validators: Vec<Validator>,
+}
+
+impl ValidatorSet {
+    pub fn hash(&self) -> Vec<u8> {
+        simple_hash(&self.validators.iter().map(Validator::encode).collect::<Vec<_>>())
+    }
+
+    pub fn total_power(&self) -> u64 {
+        self.validators.iter().map(|v| v.voting_power).sum()
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum BlockIdFlag {
+    Absent,
+    Commit,
+    Nil,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct CommitSig {
+    pub block_id_flag: BlockIdFlag,
+    #[serde(with = "hexbytes")]
+    pub validator_address: Vec<u8>,
+    pub timestamp: Timestamp,
+    #[serde(with = "hexbytes")]
+    pub signature: Vec<u8>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct Commit {
+    pub height: u64,
+    pub round: u32,
+    pub block_id: BlockId,
+    /// One entry per validator of the header's set, in the same order.
+    pub signatures: Vec<CommitSig>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct SignedHeader {
+    pub header: Header,
+    pub commit: Commit,
+}
+
+/// A signed header with the validator sets its hashes commit to, as relayed to the client.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct HeaderUpdate {
+    pub signed_header: SignedHeader,
+    pub validators: ValidatorSet,
+    pub next_validators: ValidatorSet,
+}
+
+/// Ed25519 verification, supplied by the host (`Api::ed25519_verify` in a contract).
+pub trait SignatureVerifier {
+    fn verify_ed25519(&self, message: &[u8], signature: &[u8], public_key: &[u8]) -> bool;
+}
+
+/// Fraction of the trusted validator power that must sign a skipping update.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TrustLevel {
+    pub numerator: u64,
+    pub denominator: u64,
+}
+
+impl Default for TrustLevel {
+    fn default() -> Self {
+        TrustLevel { numerator: 1, denominator: 3 }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TrustOptions {
+    /// How long a verified header can be built on; shorter than the chain's unbonding period.
+    pub trusting_period_secs: u64,
+    pub max_clock_drift_secs: u64,
+    #[serde(default)]
+    pub trust_level: TrustLevel,
+}
+
+impl TrustOptions {
+    pub fn validate(&self) -> Result<(), Error> {
+        let TrustLevel { numerator, denominator } = self.trust_level;
+        // below 1/3 a skipping update could be signed by validators that are all faulty
+        if self.trusting_period_secs == 0 || denominator == 0 || numerator * 3 < denominator || numerator > denominator {
+            return Err(Error::InvalidTrustOptions);
+        }
+        Ok(())
+    }
+}
+
+/// Latest verified header of a chain and the validator set it commits to as next.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct TrustedState {
+    pub header: Header,
+    pub next_validators: ValidatorSet,
+}
+
+impl TrustedState {
+    pub fn validate(&self) -> Result<(), Error> {
+        if self.next_validators.hash() != self.header.next_validators_hash {
+            return Err(Error::ValidatorSetMismatch("next_validators"));
+        }
+        Ok(())
+    }
+}
+
+/// Bytes a validator signs for its precommit in `commit` (`CanonicalVote`, length-delimited).
+pub fn vote_sign_bytes(chain_id: &str, commit: &Commit, sig: &CommitSig) -> Vec<u8> {
+    let mut psh = vec![];
+    proto::uint(&mut psh, 1, commit.block_id.part_set_header.total as u64);
+    proto::bytes(&mut psh, 2, &commit.block_id.part_set_header.hash);
+    let mut block_id = vec![];
+    proto::bytes(&mut block_id, 1, &commit.block_id.hash);
+    proto::message(&mut block_id, 2, &psh);
+
+    let mut vote = vec![];
+    proto::uint(&mut vote, 1, 2); // SIGNED_MSG_TYPE_PRECOMMIT
+    proto::sfixed64(&mut vote, 2, commit.height as i64);
+    proto::sfixed64(&mut vote, 3, commit.round as i64);
+    // `CanonicalizeBlockID` drops a zero block id
+    if commit.block_id != BlockId::default() {
+        proto::message(&mut vote, 4, &block_id);
+    }
+    proto::message(&mut vote, 5, &sig.timestamp.encode());
+    proto::bytes(&mut vote, 6, chain_id.as_bytes());
+    proto::delimited(&vote)
+}
+
+/// Voting power of `validators` with a valid precommit for the commit's block. With `by_index`
+/// signatures must line up with the set; otherwise they are matched by address and unknown
+/// signers are ignored.
+fn tally(chain_id: &str, commit: &Commit, validators: &ValidatorSet, by_index: bool, verifier: &dyn SignatureVerifier) -> Result<u64, Error> {
+    if by_index && commit.signatures.len() != validators.validators.len() {
+        return Err(Error::CommitMismatch("signature count differs from the validator set".to_string()));
+    }
+    let mut seen = BTreeSet::new();
+    let mut power = 0u64;
+    for (i, sig) in commit.signatures.iter().enumerate() {
+        if sig.block_id_flag != BlockIdFlag::Commit {
+            continue;
+        }
+        let validator = if by_index {
+            let v = &validators.validators[i];
+            if v.address() != sig.validator_address {
+                return Err(Error::CommitMismatch(format!("signature {} is not from validator {}", i, i)));
+            }
+            v
+        } else {
+            match validators.validators.iter().find(|v| v.address() == sig.validator_address) {
+                Some(v) => v,
+                None => continue,
+            }
+        };
+        if !seen.insert(sig.validator_address.clone()) {
+            return Err(Error::CommitMismatch(format!("validator {} signed twice", hex::encode(&sig.validator_address))));
+        }
+        if !verifier.verify_ed25519(&vote_sign_bytes(chain_id, commit, sig), &sig.signature, &validator.pub_key) {
+            return Err(Error::InvalidSignature { validator: hex::encode(&sig.validator_address) });
+        }
+        power += validator.voting_power;
+    }
+    Ok(power)
+}
+
+/// Verify `untrusted` (with its validator set and next validator set) on top of `trusted` at
+/// time `now`, returning the new trusted state. Heights may be skipped.
+pub fn verify_header(trusted: &TrustedState, untrusted: &SignedHeader, validators: &ValidatorSet, next_validators: &ValidatorSet, options: &TrustOptions, now: Timestamp, verifier: &dyn SignatureVerifier) -> Result<TrustedState, Error> {
+    let (th, uh) = (&trusted.header, &untrusted.header);
+    if uh.chain_id != th.chain_id {
+        return Err(Error::ChainIdMismatch);
+    }
+    if uh.height <= th.height {
+        return Err(Error::NonIncreasingHeight);
+    }
+    if uh.time <= th.time {
+        return Err(Error::NonIncreasingTime);
+    }
+    if th.time.seconds.saturating_add(options.trusting_period_secs as i64) <= now.seconds {
+        return Err(Error::TrustedStateExpired);
+    }
+    if uh.time.seconds > now.seconds.saturating_add(options.max_clock_drift_secs as i64) {
+        return Err(Error::HeaderFromFuture);
+    }
+    if validators.hash() != uh.validators_hash {
+        return Err(Error::ValidatorSetMismatch("validators"));
+    }
+    if next_validators.hash() != uh.next_validators_hash {
+        return Err(Error::ValidatorSetMismatch("next_validators"));
+    }
+    let commit = &untrusted.commit;
+    if commit.height != uh.height || commit.block_id.hash != uh.hash() {
+        return Err(Error::CommitMismatch("commit is not for this header".to_string()));
+    }
+
+    if uh.height == th.height + 1 {
+        if uh.validators_hash != th.next_validators_hash {
+            return Err(Error::ValidatorSetMismatch("validators"));
+        }
+    } else {
+        let total = trusted.next_validators.total_power();
+        let tallied = tally(&uh.chain_id, commit, &trusted.next_validators, false, verifier)?;
+        let TrustLevel { numerator, denominator } = options.trust_level;
+        if (tallied as u128) * (denominator as u128) <= (total as u128) * (numerator as u128) {
+            return Err(Error::InsufficientTrust { tallied, total });
+        }
+    }
+    let total = validators.total_power();
+    let tallied = tally(&uh.chain_id, commit, validators, true, verifier)?;
+    if (tallied as u128) * 3 <= (total as u128) * 2 {
+        return Err(Error::InsufficientVotingPower { tallied, total });
+    }
+    Ok(TrustedState { header: uh.clone(), next_validators: next_validators.clone() })
+}
+
+fn leaf_hash(leaf: &[u8]) -> Vec<u8> {
+    let mut h = Sha256::new();
+    h.update([0u8]);
+    h.update(leaf);
+    h.finalize().to_vec()
+}
+
+fn inner_hash(left: &[u8], right: &[u8]) -> Vec<u8> {
+    let mut h = Sha256::new();
+    h.update([1u8]);
+    h.update(left);
+    h.update(right);
+    h.finalize().to_vec()
+}
+
+/// CometBFT `merkle.HashFromByteSlices`: split at the largest power of two below the length.
+pub fn simple_hash(items: &[Vec<u8>]) -> Vec<u8> {
+    match items.len() {
+        0 => Sha256::digest([]).to_vec(),
+        1 => leaf_hash(&items[0]),
+        n => {
+            let k = n.next_power_of_two() / 2;
+            inner_hash(&simple_hash(&items[..k]), &simple_hash(&items[k..]))
+        }
+    }
+}
@@ -0,0 +1,221 @@
+use aln_light_client::ics23::{verify_membership, ExistenceProof, HashOp, InnerOp, LeafOp, LengthOp, ProofSpec};
+use aln_light_client::tendermint::{simple_hash, vote_sign_bytes, BlockId, BlockIdFlag, Commit, CommitSig, Header, PartSetHeader, Version};
+use aln_light_client::{hexbytes, verify_header, verify_lock_event, Error, HeaderUpdate, LockEvent, SignatureVerifier, Timestamp, TrustOptions, TrustedState, ValidatorSet};
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+
+/// Recorded by `cargo run --example record_fixtures`.
+#[derive(Deserialize)]
+struct Fixture {
+    options: TrustOptions,
+    now: Timestamp,
+    trusted: TrustedState,
+    adjacent: HeaderUpdate,
+    skipping: HeaderUpdate,
+    untrusted_skip: HeaderUpdate,
+    lock_event: LockEvent,
+    lock_proofs: Vec<ExistenceProof>,
+}
+
+fn fixture() -> Fixture {
+    serde_json::from_str(include_str!("fixtures/aln-origin-1.json")).unwrap()
+}
+
+struct Dalek;
+
+impl SignatureVerifier for Dalek {
+    fn verify_ed25519(&self, message: &[u8], signature: &[u8], public_key: &[u8]) -> bool {
+        let (Ok(key), Ok(sig)) = (<[u8; 32]>::try_from(public_key), <[u8; 64]>::try_from(signature)) else { return false };
+        match ed25519_dalek::VerifyingKey::from_bytes(&key) {
+            Ok(key) => key.verify_strict(message, &ed25519_dalek::Signature::from_bytes(&sig)).is_ok(),
+            Err(_) => false,
+        }
+    }
+}
+
+fn verify(f: &Fixture, trusted: &TrustedState, u: &HeaderUpdate, now: Timestamp) -> Result<TrustedState, Error> {
+    verify_header(trusted, &u.signed_header, &u.validators, &u.next_validators, &f.options, now, &Dalek)
+}
+
+fn sum(s: &str) -> Vec<u8> {
+    Sha256::digest(s.as_bytes()).to_vec()
+}
+
+#[test]
+fn header_hash_matches_cometbft() {
+    // CometBFT types/block_test.go TestHeaderHash
+    let h = Header {
+        version: Version { block: 1, app: 2 },
+        chain_id: "chainId".to_string(),
+        height: 3,
+        time: Timestamp { seconds: 1_570_983_284, nanos: 0 },
+        last_block_id: BlockId { hash: vec![0; 32], part_set_header: PartSetHeader { total: 6, hash: vec![0; 32] } },
+        last_commit_hash: sum("last_commit_hash"),
+        data_hash: sum("data_hash"),
+        validators_hash: sum("validators_hash"),
+        next_validators_hash: sum("next_validators_hash"),
+        consensus_hash: sum("consensus_hash"),
+        app_hash: sum("app_hash"),
+        last_results_hash: sum("last_results_hash"),
+        evidence_hash: sum("evidence_hash"),
+        proposer_address: sum("proposer_address")[..20].to_vec(),
+    };
+    assert_eq!(hex::encode_upper(h.hash()), "F740121F553B5418C3EFBD343C2DBFE9E007BB67B0D020A0741374BAB65242A4");
+}
+
+#[test]
+fn vote_sign_bytes_match_cometbft() {
+    // CometBFT types/vote_test.go TestVoteSignBytesTestVectors: a precommit at height 1, round 1,
+    // zero time, no chain id and no block id
+    let commit = Commit { height: 1, round: 1, block_id: BlockId::default(), signatures: vec![] };
+    let sig = CommitSig { block_id_flag: BlockIdFlag::Commit, validator_address: vec![], timestamp: Timestamp { seconds: -62_135_596_800, nanos: 0 }, signature: vec![] };
+    let want = [
+        0x21, 0x8, 0x2, 0x11, 0x1, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x19, 0x1, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x2a, 0xb, 0x8, 0x80, 0x92, 0xb8, 0xc3, 0x98, 0xfe, 0xff, 0xff, 0xff, 0x1,
+    ];
+    assert_eq!(vote_sign_bytes("", &commit, &sig), want);
+}
+
+#[test]
+fn validator_set_merkle_root_matches_cometbft() {
+    // CometBFT crypto/merkle/tree_test.go TestHashFromByteSlices; the validator set hash is this
+    // root over the `SimpleValidator` encodings
+    let root = |items: Vec<Vec<u8>>| hex::encode(simple_hash(&items));
+    assert_eq!(root(vec![]), "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855");
+    assert_eq!(root(vec![vec![1, 2, 3]]), "054edec1d0211f624fed0cbca9d4f9400b0e491c43742af2c5b0abebf0c990d8");
+    assert_eq!(root(vec![vec![1, 2, 3], vec![4, 5, 6]]), "82e6cfce00453804379b53962939eaa7906b39904be0813fcadd31b100773c4b");
+    // an empty set hashes like CometBFT's, to the empty root
+    assert_eq!(hex::encode(ValidatorSet { validators: vec![] }.hash()), root(vec![]));
+}
+
+#[test]
+fn existence_proof_ops_match_ics23() {
+    // ics23 rust/src/ops.rs and api.rs: leaf and inner ops, and a root over both
+    let leaf = LeafOp { hash: HashOp::Sha256, prehash_key: HashOp::NoHash, prehash_value: HashOp::NoHash, length: LengthOp::NoPrefix, prefix: vec![] };
+    assert_eq!(hex::encode(leaf.apply(b"foo", b"bar")), "c3ab8ff13720e8ad9047dd39466b3c8974e592c2fa383d4a3960714caef0c4f2");
+    let inner = InnerOp { hash: HashOp::Sha256, prefix: hex::decode("0123456789").unwrap(), suffix: hex::decode("deadbeef").unwrap() };
+    assert_eq!(hex::encode(inner.apply(&hex::decode("00cafe00").unwrap())), "0339f76086684506a6d42a60da4b5a719febd4d96d8b8d85ae92849e3a849a5e");
+    let proof = ExistenceProof { key: b"food".to_vec(), value: b"some longer text".to_vec(), leaf: LeafOp { length: LengthOp::VarProto, ..leaf }, path: vec![] };
+    assert_eq!(hex::encode(proof.calculate_root()), "b68f5d298e915ae1753dd333da1f9cf605411a5f2e12516be6758f365e6db265");
+    let proof = ExistenceProof { path: vec![InnerOp { hash: HashOp::Sha256, prefix: hex::decode("deadbeef00cafe00").unwrap(), suffix: vec![] }], ..proof };
+    assert_eq!(hex::encode(proof.calculate_root()), "836ea236a6902a665c2a004c920364f24cad52ded20b1e4f22c3179bfe25b2a9");
+
+    // ics23 IavlSpec and TendermintSpec
+    let spec_leaf = LeafOp { hash: HashOp::Sha256, prehash_key: HashOp::NoHash, prehash_value: HashOp::Sha256, length: LengthOp::VarProto, prefix: vec![0] };
+    assert_eq!(ProofSpec::iavl(), ProofSpec { leaf_spec: spec_leaf.clone(), min_prefix_length: 4, max_prefix_length: 12, child_size: 33 });
+    assert_eq!(ProofSpec::tendermint(), ProofSpec { leaf_spec: spec_leaf, min_prefix_length: 1, max_prefix_length: 1, child_size: 32 });
+}
+
+#[test]
+fn adjacent_and_skipping_updates_verify() {
+    let f = fixture();
+    f.trusted.validate().unwrap();
+    let next = verify(&f, &f.trusted, &f.adjacent, f.now).unwrap();
+    assert_eq!(next.header.height, 101);
+    // 150 is reachable from 100 directly and from 101
+    for trusted in [&f.trusted, &next] {
+        let state = verify(&f, trusted, &f.skipping, f.now).unwrap();
+        assert_eq!(state.header.height, 150);
+        assert_eq!(state.next_validators, f.skipping.next_validators);
+    }
+    assert_eq!(verify(&f, &next, &f.adjacent, f.now), Err(Error::NonIncreasingHeight));
+}
+
+#[test]
+fn skipping_to_an_unrelated_validator_set_lacks_trust() {
+    let f = fixture();
+    assert_eq!(verify(&f, &f.trusted, &f.untrusted_skip, f.now), Err(Error::InsufficientTrust { tallied: 0, total: 40 }));
+}
+
+#[test]
+fn adjacent_update_needs_two_thirds() {
+    let f = fixture();
+    let mut u = f.adjacent.clone();
+    // only 2 of 4 signatures left
+    u.signed_header.commit.signatures[2].block_id_flag = aln_light_client::tendermint::BlockIdFlag::Absent;
+    assert_eq!(verify(&f, &f.trusted, &u, f.now), Err(Error::InsufficientVotingPower { tallied: 20, total: 40 }));
+}
+
+#[test]
+fn tampered_headers_and_signatures_are_rejected() {
+    let f = fixture();
+    let mut u = f.skipping.clone();
+    u.signed_header.header.app_hash[0] ^= 1;
+    assert!(matches!(verify(&f, &f.trusted, &u, f.now), Err(Error::CommitMismatch(_))));
+
+    let mut u = f.skipping.clone();
+    u.signed_header.commit.signatures[1].signature[0] ^= 1;
+    assert!(matches!(verify(&f, &f.trusted, &u, f.now), Err(Error::InvalidSignature { .. })));
+
+    let mut u = f.skipping.clone();
+    u.validators.validators[0].voting_power = 1_000;
+    assert_eq!(verify(&f, &f.trusted, &u, f.now), Err(Error::ValidatorSetMismatch("validators")));
+
+    let mut u = f.skipping.clone();
+    u.signed_header.header.chain_id = "other-1".to_string();
+    assert_eq!(verify(&f, &f.trusted, &u, f.now), Err(Error::ChainIdMismatch));
+}
+
+#[test]
+fn trusting_period_and_clock_drift_bound_updates() {
+    let f = fixture();
+    let expired = Timestamp { seconds: f.trusted.header.time.seconds + f.options.trusting_period_secs as i64, nanos: 0 };
+    assert_eq!(verify(&f, &f.trusted, &f.skipping, expired), Err(Error::TrustedStateExpired));
+    let early = Timestamp { seconds: f.skipping.signed_header.header.time.seconds - 11, nanos: 0 };
+    assert_eq!(verify(&f, &f.trusted, &f.skipping, early), Err(Error::HeaderFromFuture));
+}
+
+#[test]
+fn lock_event_is_proven_against_the_verified_app_hash() {
+    let f = fixture();
+    let state = verify(&f, &f.trusted, &f.skipping, f.now).unwrap();
+    verify_lock_event(&state.header.app_hash, &f.lock_proofs, &f.lock_event).unwrap();
+
+    let mut inflated = f.lock_event.clone();
+    inflated.amount = "1000001".to_string();
+    assert!(matches!(verify_lock_event(&state.header.app_hash, &f.lock_proofs, &inflated), Err(Error::InvalidProof(_))));
+    // a valid proof for this chain does not carry over to another header's app hash
+    assert!(verify_lock_event(&f.trusted.header.app_hash, &f.lock_proofs, &f.lock_event).is_err());
+    // only the module store proof, without the multistore step
+    assert!(verify_lock_event(&state.header.app_hash, &f.lock_proofs[..1], &f.lock_event).is_err());
+}
+
+/// A live chain's block `H` and `H+1` with a proven query at `H`, written by
+/// `cargo run --example capture_cometbft`.
+#[derive(Deserialize)]
+struct CapturedBlock {
+    #[serde(with = "hexbytes")]
+    block_hash: Vec<u8>,
+    trusted: TrustedState,
+    trusted_validators: ValidatorSet,
+    update: HeaderUpdate,
+    store: String,
+    #[serde(with = "hexbytes")]
+    key: Vec<u8>,
+    #[serde(with = "hexbytes")]
+    value: Vec<u8>,
+    proofs: Vec<ExistenceProof>,
+}
+
+#[test]
+#[ignore = "needs blocks captured from a live chain in tests/fixtures/cometbft (see examples/capture_cometbft.rs)"]
+fn captured_cometbft_block_verifies() {
+    let dir = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/cometbft");
+    let paths: Vec<_> = std::fs::read_dir(dir).map(|d| d.filter_map(|e| e.ok().map(|e| e.path())).filter(|p| p.extension().is_some_and(|x| x == "json")).collect()).unwrap_or_default();
+    assert!(!paths.is_empty(), "no captured blocks in {}", dir);
+    for path in paths {
+        let c: CapturedBlock = serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        let name = path.display();
+        // block hashes the chain computed, against this crate's header and validator set encoding
+        assert_eq!(c.trusted.header.hash(), c.block_hash, "{}: header hash", name);
+        assert_eq!(c.update.signed_header.header.last_block_id.hash, c.block_hash, "{}: last_block_id", name);
+        assert_eq!(c.trusted_validators.hash(), c.trusted.header.validators_hash, "{}: validators_hash", name);
+        c.trusted.validate().unwrap();
+        // the validators' own precommits, against this crate's vote sign bytes
+        let options = TrustOptions { trusting_period_secs: 14 * 86_400, max_clock_drift_secs: 10, trust_level: Default::default() };
+        let now = Timestamp { seconds: c.update.signed_header.header.time.seconds + 1, nanos: 0 };
+        let state = verify_header(&c.trusted, &c.update.signed_header, &c.update.validators, &c.update.next_validators, &options, now, &Dalek).unwrap();
+        // the node's IAVL and multistore proofs for the queried key
+        let keys: [&[u8]; 2] = [c.store.as_bytes(), &c.key];
+        verify_membership(&[ProofSpec::iavl(), ProofSpec::tendermint()], &state.header.app_hash, &c.proofs, &keys, &c.value).unwrap();
+    }
+}
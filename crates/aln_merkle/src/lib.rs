@@ -0,0 +1,298 @@
+//! Snapshot Merkle tree spec shared by the bridge contract, `aln_tools` and the scanners, so a
+//! root published by the tooling is always the root the contract verifies against.
+//!
+//! * `V1` is the original encoding: leaf fields concatenated without framing, nodes are
+//!   `sha256(left || right)` and an odd node at the end of a level is paired with itself.
+//!   Kept so roots already in the registry keep verifying.
+//! * `V2` domain-separates leaves (`0x00`) from nodes (`0x01`), length-prefixes the string
+//!   fields and promotes an odd node to the next level unchanged. An interior node can no longer
+//!   be presented as a leaf, and `[a, b, c]` no longer has the same root as `[a, b, c, c]`.
+//!
+//! Bridge energy ledger checkpoints reuse the v2 tree over `LEDGER_LEAF_PREFIX` leaves, one per
+//! ledger entry in ascending address order.
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+pub type Hash = [u8; 32];
+
+pub const LEAF_PREFIX: u8 = 0x00;
+pub const NODE_PREFIX: u8 = 0x01;
+pub const LEDGER_LEAF_PREFIX: u8 = 0x02;
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum MerkleVersion {
+    #[default]
+    V1,
+    V2,
+}
+
+impl std::str::FromStr for MerkleVersion {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "1" | "v1" => Ok(MerkleVersion::V1),
+            "2" | "v2" => Ok(MerkleVersion::V2),
+            other => Err(format!("unknown merkle version: {}", other)),
+        }
+    }
+}
+
+/// One snapshot row as it is committed to in the tree. `address` is the origin-chain address
+/// exactly as it appears in the snapshot.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SnapshotLeaf<'a> {
+    pub chain_id: &'a str,
+    pub height: u64,
+    pub denom: &'a str,
+    pub address: &'a str,
+    pub balance: u128,
+}
+
+fn finalize(h: Sha256) -> Hash {
+    let mut out = [0u8; 32];
+    out.copy_from_slice(&h.finalize());
+    out
+}
+
+fn update_framed(h: &mut Sha256, field: &[u8]) {
+    h.update((field.len() as u32).to_be_bytes());
+    h.update(field);
+}
+
+/// V1: `sha256(chain_id || height_be || denom || address || balance_u128_be)`.
+/// V2: `sha256(0x00 || len || chain_id || height_be || len || denom || len || address || balance_u128_be)`
+/// with every `len` a u32 big-endian byte length.
+pub fn leaf_hash(version: MerkleVersion, leaf: &SnapshotLeaf) -> Hash {
+    let mut h = Sha256::new();
+    match version {
+        MerkleVersion::V1 => {
+            h.update(leaf.chain_id.as_bytes());
+            h.update(leaf.height.to_be_bytes());
+            h.update(leaf.denom.as_bytes());
+            h.update(leaf.address.as_bytes());
+        }
+        MerkleVersion::V2 => {
+            h.update([LEAF_PREFIX]);
+            update_framed(&mut h, leaf.chain_id.as_bytes());
+            h.update(leaf.height.to_be_bytes());
+            update_framed(&mut h, leaf.denom.as_bytes());
+            update_framed(&mut h, leaf.address.as_bytes());
+        }
+    }
+    h.update(leaf.balance.to_be_bytes());
+    finalize(h)
+}
+
+pub fn node_hash(version: MerkleVersion, left: &Hash, right: &Hash) -> Hash {
+    let mut h = Sha256::new();
+    if version == MerkleVersion::V2 {
+        h.update([NODE_PREFIX]);
+    }
+    h.update(left);
+    h.update(right);
+    finalize(h)
+}
+
+/// Parent of an odd node at the end of a level: v1 pairs it with itself, v2 promotes it.
+fn lone_parent(version: MerkleVersion, node: &Hash) -> Hash {
+    match version {
+        MerkleVersion::V1 => node_hash(version, node, node),
+        MerkleVersion::V2 => *node,
+    }
+}
+
+/// All tree levels, leaves first and the root last. Empty for no leaves.
+pub fn build_levels(version: MerkleVersion, leaves: &[Hash]) -> Vec<Vec<Hash>> {
+    if leaves.is_empty() {
+        return vec![];
+    }
+    let mut levels: Vec<Vec<Hash>> = vec![leaves.to_vec()];
+    while levels.last().unwrap().len() > 1 {
+        let level = levels.last().unwrap();
+        let next: Vec<Hash> = level
+            .chunks(2)
+            .map(|pair| match pair {
+                [l, r] => node_hash(version, l, r),
+                [lone] => lone_parent(version, lone),
+                _ => unreachable!(),
+            })
+            .collect();
+        levels.push(next);
+    }
+    levels
+}
+
+pub fn root(version: MerkleVersion, leaves: &[Hash]) -> Option<Hash> {
+    build_levels(version, leaves).last().map(|l| l[0])
+}
+
+/// Sibling path for the leaf at `index`, as `(sibling, sibling_is_left)` from the leaf up.
+/// In v2 a promoted node has no sibling, so the path can be shorter than the tree height.
+pub fn proof(version: MerkleVersion, levels: &[Vec<Hash>], index: usize) -> Vec<(Hash, bool)> {
+    let mut steps = vec![];
+    let mut idx = index;
+    for level in levels.iter().take(levels.len().saturating_sub(1)) {
+        let pair = idx ^ 1;
+        if pair < level.len() {
+            steps.push((level[pair], pair < idx));
+        } else if version == MerkleVersion::V1 {
+            steps.push((level[idx], false));
+        }
+        idx /= 2;
+    }
+    steps
+}
+
+/// Fold a sibling path onto `leaf`; the caller compares the result with the expected root.
+pub fn proof_root(version: MerkleVersion, leaf: &Hash, proof: &[(Hash, bool)]) -> Hash {
+    proof.iter().fold(*leaf, |cur, (sib, is_left)| if *is_left { node_hash(version, sib, &cur) } else { node_hash(version, &cur, sib) })
+}
+
+pub fn verify_proof(version: MerkleVersion, leaf: &Hash, proof: &[(Hash, bool)], root: &Hash) -> bool {
+    proof_root(version, leaf, proof) == *root
+}
+
+/// Sibling hashes a verifier needs to recompute the root from the leaves at `indices` (strictly
+/// ascending), in the order `multiproof_root` consumes them: level by level, left to right.
+/// The sibling set is the same for both versions. Returns `None` for unsorted or out-of-range indices.
+pub fn build_multiproof(levels: &[Vec<Hash>], indices: &[u64]) -> Option<Vec<Hash>> {
+    let width = levels.first()?.len() as u64;
+    if indices.is_empty() || indices.windows(2).any(|w| w[0] >= w[1]) || *indices.last()? >= width {
+        return None;
+    }
+    let mut known: Vec<usize> = indices.iter().map(|i| *i as usize).collect();
+    let mut hashes = vec![];
+    for level in levels.iter().take(levels.len() - 1) {
+        let mut next = vec![];
+        let mut i = 0;
+        while i < known.len() {
+            let idx = known[i];
+            if idx.is_multiple_of(2) {
+                if idx + 1 == level.len() {
+                    // odd tail: paired with itself (v1) or promoted (v2), no sibling needed
+                } else if i + 1 < known.len() && known[i + 1] == idx + 1 {
+                    i += 1;
+                } else {
+                    hashes.push(level[idx + 1]);
+                }
+            } else {
+                hashes.push(level[idx - 1]);
+            }
+            next.push(idx / 2);
+            i += 1;
+        }
+        known = next;
+    }
+    Some(hashes)
+}
+
+/// Recompute the root of a `leaf_count`-leaf tree from `leaves` (one per `indices`, same order)
+/// and the multiproof `hashes`. Returns the root and the number of node hashes computed, or
+/// `None` when the proof is malformed: bad lengths, unsorted or out-of-range indices, missing or
+/// unused hashes.
+pub fn multiproof_root(version: MerkleVersion, leaf_count: u64, indices: &[u64], leaves: &[Hash], hashes: &[Hash]) -> Option<(Hash, usize)> {
+    if leaves.is_empty() || leaves.len() != indices.len() || leaf_count == 0 {
+        return None;
+    }
+    if indices.windows(2).any(|w| w[0] >= w[1]) || *indices.last()? >= leaf_count {
+        return None;
+    }
+    let mut layer: Vec<(u64, Hash)> = indices.iter().copied().zip(leaves.iter().copied()).collect();
+    let mut width = leaf_count;
+    let mut used = 0usize;
+    let mut hash_count = 0usize;
+    while width > 1 {
+        let mut next: Vec<(u64, Hash)> = Vec::with_capacity(layer.len());
+        let mut i = 0;
+        while i < layer.len() {
+            let (idx, h) = layer[i];
+            let node = if idx.is_multiple_of(2) {
+                if idx + 1 == width {
+                    if version == MerkleVersion::V1 {
+                        hash_count += 1;
+                    }
+                    lone_parent(version, &h)
+                } else if i + 1 < layer.len() && layer[i + 1].0 == idx + 1 {
+                    i += 1;
+                    hash_count += 1;
+                    node_hash(version, &h, &layer[i].1)
+                } else {
+                    let sib = hashes.get(used)?;
+                    used += 1;
+                    hash_count += 1;
+                    node_hash(version, &h, sib)
+                }
+            } else {
+                let sib = hashes.get(used)?;
+                used += 1;
+                hash_count += 1;
+                node_hash(version, sib, &h)
+            };
+            next.push((idx / 2, node));
+            i += 1;
+        }
+        layer = next;
+        width = width.div_ceil(2);
+    }
+    if used != hashes.len() {
+        return None;
+    }
+    Some((layer[0].1, hash_count))
+}
+
+/// `sha256(0x02 || len || address || auet_be || csp_be || erp_be)`, with `len` a u32 big-endian
+/// byte length and each amount a u128.
+pub fn ledger_leaf_hash(address: &str, auet: u128, csp: u128, erp: u128) -> Hash {
+    let mut h = Sha256::new();
+    h.update([LEDGER_LEAF_PREFIX]);
+    update_framed(&mut h, address.as_bytes());
+    h.update(auet.to_be_bytes());
+    h.update(csp.to_be_bytes());
+    h.update(erp.to_be_bytes());
+    finalize(h)
+}
+
+/// Commitment over ledger leaves already in ascending address order: their v2 root, or all
+/// zeros for an empty ledger.
+pub fn ledger_root(leaves: &[Hash]) -> Hash {
+    root(MerkleVersion::V2, leaves).unwrap_or([0u8; 32])
+}
+
+/// `ledger_root` computed one leaf at a time, so a large ledger can be committed across several
+/// calls. Keeps the roots of the complete subtrees seen so far, one per height.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct LedgerAccumulator {
+    /// `(height, subtree root)`, highest first.
+    pub frontier: Vec<(u32, Hash)>,
+}
+
+impl LedgerAccumulator {
+    /// Append the next leaf in address order.
+    pub fn push(&mut self, leaf: Hash) {
+        let mut node = (0u32, leaf);
+        while let Some(&(height, left)) = self.frontier.last() {
+            if height != node.0 {
+                break;
+            }
+            self.frontier.pop();
+            node = (height + 1, node_hash(MerkleVersion::V2, &left, &node.1));
+        }
+        self.frontier.push(node);
+    }
+
+    /// Equal to `ledger_root` over every leaf pushed: v2 promotes lone nodes, so the remaining
+    /// subtrees fold from the right.
+    pub fn root(&self) -> Hash {
+        let mut nodes = self.frontier.iter().rev();
+        let mut acc = match nodes.next() {
+            Some((_, h)) => *h,
+            None => return [0u8; 32],
+        };
+        for (_, left) in nodes {
+            acc = node_hash(MerkleVersion::V2, left, &acc);
+        }
+        acc
+    }
+}
@@ -0,0 +1,119 @@
+use aln_merkle::{build_levels, build_multiproof, leaf_hash, ledger_leaf_hash, ledger_root, LedgerAccumulator, multiproof_root, node_hash, proof, root, verify_proof, Hash, MerkleVersion, SnapshotLeaf};
+use sha2::{Digest, Sha256};
+
+fn leaf(v: MerkleVersion, i: u64) -> Hash {
+    let address = format!("kujira1holder{}", i);
+    leaf_hash(v, &SnapshotLeaf { chain_id: "kaiyo-1", height: 100, denom: "ibc/abc", address: &address, balance: 1_000 + i as u128 })
+}
+
+#[test]
+fn v1_leaf_matches_legacy_encoding() {
+    let mut h = Sha256::new();
+    h.update(b"kaiyo-1");
+    h.update(100u64.to_be_bytes());
+    h.update(b"ibc/abc");
+    h.update(b"kujira1holder7");
+    h.update(1_007u128.to_be_bytes());
+    let legacy: Hash = h.finalize().into();
+    assert_eq!(leaf(MerkleVersion::V1, 7), legacy);
+    assert_ne!(leaf(MerkleVersion::V2, 7), legacy);
+}
+
+#[test]
+fn v2_frames_leaf_fields() {
+    // denom and address are adjacent in v1, so moving a byte across the boundary is invisible
+    let a = SnapshotLeaf { chain_id: "k1", height: 0, denom: "x", address: "yaddr", balance: 5 };
+    let b = SnapshotLeaf { chain_id: "k1", height: 0, denom: "xy", address: "addr", balance: 5 };
+    assert_eq!(leaf_hash(MerkleVersion::V1, &a), leaf_hash(MerkleVersion::V1, &b));
+    assert_ne!(leaf_hash(MerkleVersion::V2, &a), leaf_hash(MerkleVersion::V2, &b));
+}
+
+#[test]
+fn v2_does_not_duplicate_odd_nodes() {
+    for v in [MerkleVersion::V1, MerkleVersion::V2] {
+        let leaves: Vec<Hash> = (0..3).map(|i| leaf(v, i)).collect();
+        let mut padded = leaves.clone();
+        padded.push(leaves[2]);
+        let same = root(v, &leaves) == root(v, &padded);
+        assert_eq!(same, v == MerkleVersion::V1, "{:?}", v);
+    }
+}
+
+#[test]
+fn v2_node_is_not_a_leaf() {
+    let leaves: Vec<Hash> = (0..2).map(|i| leaf(MerkleVersion::V2, i)).collect();
+    let r = root(MerkleVersion::V2, &leaves).unwrap();
+    let mut plain = Sha256::new();
+    plain.update(leaves[0]);
+    plain.update(leaves[1]);
+    let plain: Hash = plain.finalize().into();
+    assert_ne!(r, plain);
+    assert_eq!(r, node_hash(MerkleVersion::V2, &leaves[0], &leaves[1]));
+}
+
+#[test]
+fn proofs_and_multiproofs_roundtrip() {
+    for v in [MerkleVersion::V1, MerkleVersion::V2] {
+        for n in [1u64, 2, 3, 5, 7, 8, 13, 64] {
+            let leaves: Vec<Hash> = (0..n).map(|i| leaf(v, i)).collect();
+            let levels = build_levels(v, &leaves);
+            let r = root(v, &leaves).unwrap();
+            for (i, l) in leaves.iter().enumerate() {
+                assert!(verify_proof(v, l, &proof(v, &levels, i), &r), "{:?} n={} i={}", v, n, i);
+            }
+            let picks: Vec<u64> = (0..n).filter(|i| i % 3 != 1).collect();
+            let hashes = build_multiproof(&levels, &picks).unwrap();
+            let proven: Vec<Hash> = picks.iter().map(|i| leaves[*i as usize]).collect();
+            let (computed, _) = multiproof_root(v, n, &picks, &proven, &hashes).unwrap();
+            assert_eq!(computed, r, "{:?} n={}", v, n);
+        }
+    }
+}
+
+#[test]
+fn proofs_do_not_cross_versions() {
+    let leaves: Vec<Hash> = (0..5).map(|i| leaf(MerkleVersion::V1, i)).collect();
+    let v1 = build_levels(MerkleVersion::V1, &leaves);
+    let r2 = root(MerkleVersion::V2, &leaves).unwrap();
+    assert!(!verify_proof(MerkleVersion::V2, &leaves[4], &proof(MerkleVersion::V1, &v1, 4), &r2));
+}
+
+#[test]
+fn multiproof_rejects_malformed() {
+    let v = MerkleVersion::V2;
+    let leaves: Vec<Hash> = (0..13).map(|i| leaf(v, i)).collect();
+    let levels = build_levels(v, &leaves);
+    let r = root(v, &leaves).unwrap();
+    let picks = [2u64, 3, 9];
+    let hashes = build_multiproof(&levels, &picks).unwrap();
+    let proven = vec![leaves[2], leaves[3], leaves[9]];
+    assert_eq!(multiproof_root(v, 13, &picks, &proven, &hashes).unwrap().0, r);
+    assert_ne!(multiproof_root(v, 13, &picks, &[leaves[2], leaves[4], leaves[9]], &hashes).map(|x| x.0), Some(r));
+    assert!(multiproof_root(v, 13, &[3, 2, 9], &proven, &hashes).is_none());
+    let mut padded = hashes.clone();
+    padded.push(leaves[0]);
+    assert!(multiproof_root(v, 13, &picks, &proven, &padded).is_none());
+    assert!(build_multiproof(&levels, &[2, 13]).is_none());
+}
+
+#[test]
+fn ledger_commitment_is_order_and_domain_sensitive() {
+    assert_eq!(ledger_root(&[]), [0u8; 32]);
+    let a = ledger_leaf_hash("aln1a", 10, 5, 0);
+    let b = ledger_leaf_hash("aln1b", 3, 1, 0);
+    assert_eq!(ledger_root(&[a]), a);
+    assert_ne!(ledger_root(&[a, b]), ledger_root(&[b, a]));
+    assert_ne!(a, ledger_leaf_hash("aln1a", 10, 0, 5));
+    let snapshot = leaf_hash(MerkleVersion::V2, &SnapshotLeaf { chain_id: "", height: 0, denom: "", address: "aln1a", balance: 10 });
+    assert_ne!(a, snapshot);
+}
+
+#[test]
+fn ledger_accumulator_matches_ledger_root() {
+    let leaves: Vec<Hash> = (0..40u128).map(|i| ledger_leaf_hash(&format!("aln1{:03}", i), i, i / 2, 0)).collect();
+    for n in 0..=leaves.len() {
+        let mut acc = LedgerAccumulator::default();
+        leaves[..n].iter().for_each(|l| acc.push(*l));
+        assert_eq!(acc.root(), ledger_root(&leaves[..n]), "{} leaves", n);
+    }
+}
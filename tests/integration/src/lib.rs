@@ -8,6 +8,7 @@ use aln_csp::{instantiate as csp_instantiate, execute as csp_execute, query as c
 use aln_registry::{instantiate as reg_instantiate, execute as reg_execute, query as reg_query};
 use aln_bridge::{instantiate as bridge_instantiate, execute as bridge_execute, query as bridge_query};
 use energy_router::{instantiate as router_instantiate, execute as router_execute, query as router_query};
+use ubs_oracle::{instantiate as oracle_instantiate, execute as oracle_execute, query as oracle_query};
 
 use cw_multi_test::AppResponse;
 use cw20::Cw20Coin;
@@ -74,30 +75,30 @@ fn happy_path_claim_and_spend() -> Result<()> {
     let csp_addr = app.instantiate_contract(csp_code, Addr::unchecked("creator"), &csp_instantiate_msg, &[], "CSP", None)?;
 
     // Instantiate registry with governance
-    let reg_instantiate_msg = aln_registry::InstantiateMsg { governance_addr: gov.to_string(), allow_missing_ubs: Some(true) };
+    let reg_instantiate_msg = registry_init_msg(gov.to_string());
     let reg_addr = app.instantiate_contract(reg_code, Addr::unchecked("creator"), &reg_instantiate_msg, &[], "REG", None)?;
 
     // Register and approve asset via governance. Build a snapshot entry for the user and make merkle_root == H_i for single-leaf tree
     let s_user = aln_bridge::SnapshotEntry { chain_id: "kaiyo-1".to_string(), height: 123, denom: "ibc/xxx".to_string(), address: user.to_string(), balance: "100".to_string() };
     let h_user = compute_snapshot_hash(&s_user);
-    let asset = aln_registry::RegisteredAsset { id: "a1".to_string(), source_chain: "kaiyo-1".to_string(), source_denom: "ibc/xxx".to_string(), snapshot_height: 123, merkle_root: h_user.clone(), ubs_report_hash: Some("h1".to_string()), scaling_profile_id: "malicious_cleanup".to_string(), activation_height: 0, sanitized_approved: true };
+    let asset = aln_registry::RegisteredAsset { id: "a1".to_string(), source_chain: "kaiyo-1".to_string(), source_denom: "ibc/xxx".to_string(), snapshot_height: 123, merkle_root: h_user.clone(), ubs_report_hash: Some("h1".to_string()), scaling_profile_id: "malicious_cleanup".to_string(), activation_height: 0, sanitized_approved: true, merkle_version: aln_registry::MerkleVersion::V1, claim_deadline: None, total_supply: None, lifecycle: Default::default(), epochs: vec![] };
     let reg_msg = aln_registry::ExecuteMsg::RegisterAsset { asset: asset.clone() };
     app.execute_contract(gov.clone(), reg_addr.clone(), &reg_msg, &[])?;
-    let approve_msg = aln_registry::ExecuteMsg::ApproveSanitized { id: "a1".to_string(), ubs_report_hash: "h1".to_string() };
+    let approve_msg = aln_registry::ExecuteMsg::ApproveSanitized { id: "a1".to_string(), ubs_report_hash: "h1".to_string(), epoch: None };
     app.execute_contract(gov.clone(), reg_addr.clone(), &approve_msg, &[])?;
 
     // Instantiate bridge with references and governance
-    let bridge_instantiate_msg = aln_bridge::InstantiateMsg { auet_contract: auet_addr.to_string(), csp_contract: Some(csp_addr.to_string()), registry_contract: reg_addr.to_string(), governance_addr: gov.to_string() };
+    let bridge_instantiate_msg = aln_bridge::InstantiateMsg { csp_contract: Some(csp_addr.to_string()), ..bridge_init_msg(auet_addr.to_string(), reg_addr.to_string(), gov.to_string()) };
     let bridge_addr_inst = app.instantiate_contract(bridge_code, Addr::unchecked("creator"), &bridge_instantiate_msg, &[], "BRIDGE", None)?;
 
     // For the bridge to perform transfers, it must have AU.ET/CSP balances; ensure bridge account holds them. They were set as initial balances earlier.
 
     // Happy-path claim: user claims some AUET and CSP
-    let claim_msg = aln_bridge::ExecuteMsg::Claim { asset_id: "a1".to_string(), snapshot: s_user.clone(), snapshot_hash: h_user.clone(), merkle_proof: vec![], amount_auet: Uint128::new(100), amount_csp: Some(Uint128::new(10)) };
+    let claim_msg = aln_bridge::ExecuteMsg::Claim { asset_id: "a1".to_string(), epoch: None, snapshot: s_user.clone(), snapshot_hash: h_user.clone(), merkle_proof: vec![], amount_auet: None, amount_csp: Some(Uint128::new(10)) };
     app.execute_contract(user.clone(), bridge_addr_inst.clone(), &claim_msg, &[])?;
 
     // Verify claimed is set on bridge
-    let q = aln_bridge::QueryMsg::IsClaimed { address: user.to_string(), asset_id: "a1".to_string(), snapshot_hash: h_user.clone() };
+    let q = aln_bridge::QueryMsg::IsClaimed { address: user.to_string(), asset_id: "a1".to_string(), epoch: None, snapshot_hash: h_user.clone() };
     let claimed_bin = app.wrap().query_wasm_smart(bridge_addr_inst.clone(), &q)?;
     let claimed_res: bool = serde_json::from_str(&serde_json::to_string(&claimed_bin)?)?;
     assert!(claimed_res);
@@ -150,23 +151,23 @@ fn claim_before_sanitized_rejected() -> Result<()> {
     let auet_addr = app.instantiate_contract(auet_code, Addr::unchecked("creator"), &auet_instantiate_msg, &[], "AUET", None)?;
 
     // instantiate registry
-    let reg_msg = aln_registry::InstantiateMsg { governance_addr: gov.to_string(), allow_missing_ubs: Some(true) };
+    let reg_msg = registry_init_msg(gov.to_string());
     let reg_addr = app.instantiate_contract(reg_code, Addr::unchecked("creator"), &reg_msg, &[], "REG", None)?;
     // register asset but do not approve sanitized
     let s2 = aln_bridge::SnapshotEntry { chain_id: "kaiyo-1".to_string(), height: 0, denom: "ibc/yyy".to_string(), address: "user".to_string(), balance: "1".to_string() };
     let h2 = compute_snapshot_hash(&s2);
-    let asset = aln_registry::RegisteredAsset { id: "a2".to_string(), source_chain: "kaiyo-1".to_string(), source_denom: "ibc/yyy".to_string(), snapshot_height: 0, merkle_root: h2.clone(), ubs_report_hash: None, scaling_profile_id: "malicious_cleanup".to_string(), activation_height: 0, sanitized_approved: false };
+    let asset = aln_registry::RegisteredAsset { id: "a2".to_string(), source_chain: "kaiyo-1".to_string(), source_denom: "ibc/yyy".to_string(), snapshot_height: 0, merkle_root: h2.clone(), ubs_report_hash: None, scaling_profile_id: "malicious_cleanup".to_string(), activation_height: 0, sanitized_approved: false, merkle_version: aln_registry::MerkleVersion::V1, claim_deadline: None, total_supply: None, lifecycle: Default::default(), epochs: vec![] };
     let reg_msg2 = aln_registry::ExecuteMsg::RegisterAsset { asset: asset.clone() };
     app.execute_contract(gov.clone(), reg_addr.clone(), &reg_msg2, &[])?;
 
     // instantiate bridge
-    let bridge_msg = aln_bridge::InstantiateMsg { auet_contract: auet_addr.to_string(), csp_contract: None, registry_contract: reg_addr.to_string(), governance_addr: gov.to_string() };
+    let bridge_msg = bridge_init_msg(auet_addr.to_string(), reg_addr.to_string(), gov.to_string());
     let bridge_addr_inst = app.instantiate_contract(bridge_code, Addr::unchecked("creator"), &bridge_msg, &[], "BRIDGE", None)?;
 
     // attempt claim should fail because not sanitized
-    let claim_msg = aln_bridge::ExecuteMsg::Claim { asset_id: "a2".to_string(), snapshot: s2.clone(), snapshot_hash: h2.clone(), merkle_proof: vec![], amount_auet: Uint128::new(1), amount_csp: None };
+    let claim_msg = aln_bridge::ExecuteMsg::Claim { asset_id: "a2".to_string(), epoch: None, snapshot: s2.clone(), snapshot_hash: h2.clone(), merkle_proof: vec![], amount_auet: None, amount_csp: None };
     let res = app.execute_contract(Addr::unchecked("user"), bridge_addr_inst.clone(), &claim_msg, &[]);
-    assert!(res.is_err());
+    assert_eq!(res.unwrap_err().downcast::<aln_bridge::ContractError>()?, aln_bridge::ContractError::AssetNotSanitized);
     Ok(())
 }
 
@@ -196,25 +197,25 @@ fn double_claim_rejected() -> Result<()> {
         allowed_modules: Some(vec![]),
     };
     let auet_addr = app.instantiate_contract(auet_code, Addr::unchecked("creator"), &auet_instantiate_msg, &[], "AUET", None)?;
-    let reg_msg = aln_registry::InstantiateMsg { governance_addr: gov.to_string(), allow_missing_ubs: Some(true) };
+    let reg_msg = registry_init_msg(gov.to_string());
     let reg_addr = app.instantiate_contract(reg_code, Addr::unchecked("creator"), &reg_msg, &[], "REG", None)?;
     let s3 = aln_bridge::SnapshotEntry { chain_id: "kaiyo-1".to_string(), height: 0, denom: "ibc/zzz".to_string(), address: "user".to_string(), balance: "1".to_string() };
     let h3 = compute_snapshot_hash(&s3);
-    let asset = aln_registry::RegisteredAsset { id: "a3".to_string(), source_chain: "kaiyo-1".to_string(), source_denom: "ibc/zzz".to_string(), snapshot_height: 0, merkle_root: h3.clone(), ubs_report_hash: Some("h3".to_string()), scaling_profile_id: "malicious_cleanup".to_string(), activation_height: 0, sanitized_approved: true };
+    let asset = aln_registry::RegisteredAsset { id: "a3".to_string(), source_chain: "kaiyo-1".to_string(), source_denom: "ibc/zzz".to_string(), snapshot_height: 0, merkle_root: h3.clone(), ubs_report_hash: Some("h3".to_string()), scaling_profile_id: "malicious_cleanup".to_string(), activation_height: 0, sanitized_approved: true, merkle_version: aln_registry::MerkleVersion::V1, claim_deadline: None, total_supply: None, lifecycle: Default::default(), epochs: vec![] };
     app.execute_contract(gov.clone(), reg_addr.clone(), &aln_registry::ExecuteMsg::RegisterAsset { asset: asset.clone() }, &[])?;
-    app.execute_contract(gov.clone(), reg_addr.clone(), &aln_registry::ExecuteMsg::ApproveSanitized { id: "a3".to_string(), ubs_report_hash: "h3".to_string() }, &[])?;
-    let bridge_msg = aln_bridge::InstantiateMsg { auet_contract: auet_addr.to_string(), csp_contract: None, registry_contract: reg_addr.to_string(), governance_addr: gov.to_string() };
+    app.execute_contract(gov.clone(), reg_addr.clone(), &aln_registry::ExecuteMsg::ApproveSanitized { id: "a3".to_string(), ubs_report_hash: "h3".to_string(), epoch: None }, &[])?;
+    let bridge_msg = bridge_init_msg(auet_addr.to_string(), reg_addr.to_string(), gov.to_string());
     let bridge_addr_inst = app.instantiate_contract(bridge_code, Addr::unchecked("creator"), &bridge_msg, &[], "BRIDGE", None)?;
 
     // First claim should succeed
-    let claim_msg = aln_bridge::ExecuteMsg::Claim { asset_id: "a3".to_string(), snapshot: s3.clone(), snapshot_hash: h3.clone(), merkle_proof: vec![], amount_auet: Uint128::new(1), amount_csp: None };
+    let claim_msg = aln_bridge::ExecuteMsg::Claim { asset_id: "a3".to_string(), epoch: None, snapshot: s3.clone(), snapshot_hash: h3.clone(), merkle_proof: vec![], amount_auet: None, amount_csp: None };
     let res = app.execute_contract(Addr::unchecked("user"), bridge_addr_inst.clone(), &claim_msg, &[])?;
     assert!(res.attributes.iter().any(|a| a.value == "claim_refactored"));
 
     // Second claim should fail
-    let claim_msg2 = aln_bridge::ExecuteMsg::Claim { asset_id: "a3".to_string(), snapshot: s3.clone(), snapshot_hash: h3.clone(), merkle_proof: vec![], amount_auet: Uint128::new(1), amount_csp: None };
+    let claim_msg2 = aln_bridge::ExecuteMsg::Claim { asset_id: "a3".to_string(), epoch: None, snapshot: s3.clone(), snapshot_hash: h3.clone(), merkle_proof: vec![], amount_auet: None, amount_csp: None };
     let err = app.execute_contract(Addr::unchecked("user"), bridge_addr_inst.clone(), &claim_msg2, &[]);
-    assert!(err.is_err());
+    assert_eq!(err.unwrap_err().downcast::<aln_bridge::ContractError>()?, aln_bridge::ContractError::AlreadyClaimed);
     Ok(())
 }
 
@@ -270,6 +271,26 @@ fn csp_transfer_rejected() -> Result<()> {
     Ok(())
 }
 
+fn registry_init_msg(governance_addr: String) -> aln_registry::InstantiateMsg {
+    // Identity scaling keeps claimed amounts equal to the snapshot balance in these tests
+    let profiles = ["clean", "malicious_cleanup"].iter().map(|id| aln_registry::ScalingProfile { id: id.to_string(), c_e: cosmwasm_std::Decimal::one(), c_s: cosmwasm_std::Decimal::percent(50), source_decimals: 6, aln_decimals: 6, toxic: *id == "malicious_cleanup", haircut: None }).collect();
+    aln_registry::InstantiateMsg { governance_addr, allow_missing_ubs: Some(true), scaling_profiles: Some(profiles) }
+}
+
+fn bridge_init_msg(auet_contract: String, registry_contract: String, governance_addr: String) -> aln_bridge::InstantiateMsg {
+    aln_bridge::InstantiateMsg {
+        auet_contract,
+        csp_contract: None,
+        registry_contract,
+        governance_addr,
+        toxic_sink: None,
+        anomaly_threshold_amount: None,
+        toxic_cap_percent: None,
+        system_whitelist: None,
+        ubs_oracle_contract: None,
+    }
+}
+
 // Helper: compute snapshot H_i (0xhex) consistent with tooling & bridge contract
 fn compute_snapshot_hash(s: &aln_bridge::SnapshotEntry) -> String {
     let mut hasher = Sha256::new();
@@ -350,11 +371,11 @@ fn claim_with_valid_merkle_proof_succeeds() -> Result<()> {
 
     // Instantiate registry with merkle root and approve
     let gov = Addr::unchecked("gov");
-    let reg_instantiate_msg = aln_registry::InstantiateMsg { governance_addr: gov.to_string(), allow_missing_ubs: Some(true) };
+    let reg_instantiate_msg = registry_init_msg(gov.to_string());
     let reg_addr = app.instantiate_contract(reg_code, Addr::unchecked("creator"), &reg_instantiate_msg, &[], "REG", None)?;
-    let asset = aln_registry::RegisteredAsset { id: "m1".to_string(), source_chain: "kaiyo-1".to_string(), source_denom: "ibc/xxx".to_string(), snapshot_height: 100, merkle_root: root.clone(), ubs_report_hash: Some("h1".to_string()), scaling_profile_id: "malicious_cleanup".to_string(), activation_height: 0, sanitized_approved: true };
+    let asset = aln_registry::RegisteredAsset { id: "m1".to_string(), source_chain: "kaiyo-1".to_string(), source_denom: "ibc/xxx".to_string(), snapshot_height: 100, merkle_root: root.clone(), ubs_report_hash: Some("h1".to_string()), scaling_profile_id: "malicious_cleanup".to_string(), activation_height: 0, sanitized_approved: true, merkle_version: aln_registry::MerkleVersion::V1, claim_deadline: None, total_supply: None, lifecycle: Default::default(), epochs: vec![] };
     app.execute_contract(gov.clone(), reg_addr.clone(), &aln_registry::ExecuteMsg::RegisterAsset { asset: asset.clone() }, &[])?;
-    app.execute_contract(gov.clone(), reg_addr.clone(), &aln_registry::ExecuteMsg::ApproveSanitized { id: "m1".to_string(), ubs_report_hash: "h1".to_string() }, &[])?;
+    app.execute_contract(gov.clone(), reg_addr.clone(), &aln_registry::ExecuteMsg::ApproveSanitized { id: "m1".to_string(), ubs_report_hash: "h1".to_string(), epoch: None }, &[])?;
 
     // Instantiate AU.ET with the bridge address to hold initial funds
     let bridge_addr = Addr::unchecked("bridge");
@@ -366,7 +387,7 @@ fn claim_with_valid_merkle_proof_succeeds() -> Result<()> {
     let auet_addr = app.instantiate_contract(auet_code, Addr::unchecked("creator"), &auet_instantiate_msg, &[], "AUET", None)?;
 
     // instantiate bridge
-    let bridge_instantiate_msg = aln_bridge::InstantiateMsg { auet_contract: auet_addr.to_string(), csp_contract: None, registry_contract: reg_addr.to_string(), governance_addr: gov.to_string() };
+    let bridge_instantiate_msg = bridge_init_msg(auet_addr.to_string(), reg_addr.to_string(), gov.to_string());
     let bridge_addr_inst = app.instantiate_contract(bridge_code, Addr::unchecked("creator"), &bridge_instantiate_msg, &[], "BRIDGE", None)?;
 
     // Build proof for s1 (index 0)
@@ -374,11 +395,11 @@ fn claim_with_valid_merkle_proof_succeeds() -> Result<()> {
     let proof_steps: Vec<aln_bridge::ProofStep> = proof_bytes.iter().map(|(b, is_left)| { aln_bridge::ProofStep { sibling: Binary(b.clone()), is_left: *is_left } }).collect();
 
     // claim
-    let claim_msg = aln_bridge::ExecuteMsg::Claim { asset_id: "m1".to_string(), snapshot: s1.clone(), snapshot_hash: h1.clone(), merkle_proof: proof_steps, amount_auet: Uint128::new(100), amount_csp: None };
+    let claim_msg = aln_bridge::ExecuteMsg::Claim { asset_id: "m1".to_string(), epoch: None, snapshot: s1.clone(), snapshot_hash: h1.clone(), merkle_proof: proof_steps, amount_auet: None, amount_csp: None };
     app.execute_contract(Addr::unchecked("user"), bridge_addr_inst.clone(), &claim_msg, &[])?;
 
     // verify claimed and auet balance updated
-    let q = aln_bridge::QueryMsg::IsClaimed { address: "user".to_string(), asset_id: "m1".to_string(), snapshot_hash: h1.clone() };
+    let q = aln_bridge::QueryMsg::IsClaimed { address: "user".to_string(), asset_id: "m1".to_string(), epoch: None, snapshot_hash: h1.clone() };
     let claimed_bin = app.wrap().query_wasm_smart(bridge_addr_inst.clone(), &q)?;
     let claimed_res: bool = serde_json::from_str(&serde_json::to_string(&claimed_bin)?)?;
     assert!(claimed_res);
@@ -409,23 +430,23 @@ fn claim_with_invalid_merkle_proof_fails() -> Result<()> {
     for h in &vec![h1.clone(), h2.clone()] { let mut arr = [0u8;32]; let bytes = hex::decode(h.trim_start_matches("0x")).unwrap(); arr.copy_from_slice(&bytes); leaves.push(arr);}    
     let (root, proofs) = build_merkle_and_proofs(&leaves);
     let gov = Addr::unchecked("gov");
-    let reg_instantiate_msg = aln_registry::InstantiateMsg { governance_addr: gov.to_string(), allow_missing_ubs: Some(true) };
+    let reg_instantiate_msg = registry_init_msg(gov.to_string());
     let reg_addr = app.instantiate_contract(reg_code, Addr::unchecked("creator"), &reg_instantiate_msg, &[], "REG", None)?;
-    let asset = aln_registry::RegisteredAsset { id: "m2".to_string(), source_chain: "kaiyo-1".to_string(), source_denom: "ibc/xxx".to_string(), snapshot_height: 100, merkle_root: root.clone(), ubs_report_hash: Some("h2".to_string()), scaling_profile_id: "malicious_cleanup".to_string(), activation_height: 0, sanitized_approved: true };
+    let asset = aln_registry::RegisteredAsset { id: "m2".to_string(), source_chain: "kaiyo-1".to_string(), source_denom: "ibc/xxx".to_string(), snapshot_height: 100, merkle_root: root.clone(), ubs_report_hash: Some("h2".to_string()), scaling_profile_id: "malicious_cleanup".to_string(), activation_height: 0, sanitized_approved: true, merkle_version: aln_registry::MerkleVersion::V1, claim_deadline: None, total_supply: None, lifecycle: Default::default(), epochs: vec![] };
     app.execute_contract(gov.clone(), reg_addr.clone(), &aln_registry::ExecuteMsg::RegisterAsset { asset: asset.clone() }, &[])?;
-    app.execute_contract(gov.clone(), reg_addr.clone(), &aln_registry::ExecuteMsg::ApproveSanitized { id: "m2".to_string(), ubs_report_hash: "h2".to_string() }, &[])?;
+    app.execute_contract(gov.clone(), reg_addr.clone(), &aln_registry::ExecuteMsg::ApproveSanitized { id: "m2".to_string(), ubs_report_hash: "h2".to_string(), epoch: None }, &[])?;
     let bridge_addr = Addr::unchecked("bridge");
     let auet_instantiate_msg = aln_auet::InstantiateMsg { cw20: Cw20InstantiateMsg { name: "AU.ET".to_string(), symbol: "AUET".to_string(), decimals: 6, initial_balances: vec![Cw20Coin { address: bridge_addr.to_string(), amount: Uint128::new(1_000_000) }], mint: None, marketing: None }, snapshot: aln_auet::SnapshotMeta { chain_id: "kaiyo-1".to_string(), height: 100, merkle_root: root.clone() }, allowed_modules: Some(vec![]) };
     let auet_addr = app.instantiate_contract(auet_code, Addr::unchecked("creator"), &auet_instantiate_msg, &[], "AUET", None)?;
-    let bridge_instantiate_msg = aln_bridge::InstantiateMsg { auet_contract: auet_addr.to_string(), csp_contract: None, registry_contract: reg_addr.to_string(), governance_addr: gov.to_string() };
+    let bridge_instantiate_msg = bridge_init_msg(auet_addr.to_string(), reg_addr.to_string(), gov.to_string());
     let bridge_addr_inst = app.instantiate_contract(bridge_code, Addr::unchecked("creator"), &bridge_instantiate_msg, &[], "BRIDGE", None)?;
     // create a proof but smash it
     let mut wrong_proof = proofs[0].clone();
     wrong_proof[0].0[0] ^= 0xff; // flip a byte
     let proof_steps: Vec<aln_bridge::ProofStep> = wrong_proof.iter().map(|(b, is_left)| aln_bridge::ProofStep { sibling: Binary(b.clone()), is_left: *is_left }).collect();
-    let claim_msg = aln_bridge::ExecuteMsg::Claim { asset_id: "m2".to_string(), snapshot: s1.clone(), snapshot_hash: h1.clone(), merkle_proof: proof_steps, amount_auet: Uint128::new(100), amount_csp: None };
+    let claim_msg = aln_bridge::ExecuteMsg::Claim { asset_id: "m2".to_string(), epoch: None, snapshot: s1.clone(), snapshot_hash: h1.clone(), merkle_proof: proof_steps, amount_auet: None, amount_csp: None };
     let res = app.execute_contract(Addr::unchecked("user"), bridge_addr_inst.clone(), &claim_msg, &[]);
-    assert!(res.is_err());
+    assert_eq!(res.unwrap_err().downcast::<aln_bridge::ContractError>()?, aln_bridge::ContractError::InvalidMerkleProof);
     Ok(())
 }
 
@@ -445,24 +466,24 @@ fn claim_with_modified_snapshot_entry_fails() -> Result<()> {
     let bytes = hex::decode(h1.trim_start_matches("0x")).unwrap(); arr.copy_from_slice(&bytes);
     let (root, proofs) = build_merkle_and_proofs(&vec![arr]);
     let gov = Addr::unchecked("gov");
-    let reg_instantiate_msg = aln_registry::InstantiateMsg { governance_addr: gov.to_string(), allow_missing_ubs: Some(true) };
+    let reg_instantiate_msg = registry_init_msg(gov.to_string());
     let reg_addr = app.instantiate_contract(reg_code, Addr::unchecked("creator"), &reg_instantiate_msg, &[], "REG", None)?;
-    let asset = aln_registry::RegisteredAsset { id: "m3".to_string(), source_chain: "kaiyo-1".to_string(), source_denom: "ibc/xxx".to_string(), snapshot_height: 100, merkle_root: root.clone(), ubs_report_hash: Some("h3".to_string()), scaling_profile_id: "malicious_cleanup".to_string(), activation_height: 0, sanitized_approved: true };
+    let asset = aln_registry::RegisteredAsset { id: "m3".to_string(), source_chain: "kaiyo-1".to_string(), source_denom: "ibc/xxx".to_string(), snapshot_height: 100, merkle_root: root.clone(), ubs_report_hash: Some("h3".to_string()), scaling_profile_id: "malicious_cleanup".to_string(), activation_height: 0, sanitized_approved: true, merkle_version: aln_registry::MerkleVersion::V1, claim_deadline: None, total_supply: None, lifecycle: Default::default(), epochs: vec![] };
     app.execute_contract(gov.clone(), reg_addr.clone(), &aln_registry::ExecuteMsg::RegisterAsset { asset: asset.clone() }, &[])?;
-    app.execute_contract(gov.clone(), reg_addr.clone(), &aln_registry::ExecuteMsg::ApproveSanitized { id: "m3".to_string(), ubs_report_hash: "h3".to_string() }, &[])?;
+    app.execute_contract(gov.clone(), reg_addr.clone(), &aln_registry::ExecuteMsg::ApproveSanitized { id: "m3".to_string(), ubs_report_hash: "h3".to_string(), epoch: None }, &[])?;
     let bridge_addr = Addr::unchecked("bridge");
     let auet_instantiate_msg = aln_auet::InstantiateMsg { cw20: Cw20InstantiateMsg { name: "AU.ET".to_string(), symbol: "AUET".to_string(), decimals: 6, initial_balances: vec![Cw20Coin { address: bridge_addr.to_string(), amount: Uint128::new(1_000_000) }], mint: None, marketing: None }, snapshot: aln_auet::SnapshotMeta { chain_id: "kaiyo-1".to_string(), height: 100, merkle_root: root.clone() }, allowed_modules: Some(vec![]) };
     let auet_addr = app.instantiate_contract(auet_code, Addr::unchecked("creator"), &auet_instantiate_msg, &[], "AUET", None)?;
-    let bridge_instantiate_msg = aln_bridge::InstantiateMsg { auet_contract: auet_addr.to_string(), csp_contract: None, registry_contract: reg_addr.to_string(), governance_addr: gov.to_string() };
+    let bridge_instantiate_msg = bridge_init_msg(auet_addr.to_string(), reg_addr.to_string(), gov.to_string());
     let bridge_addr_inst = app.instantiate_contract(bridge_code, Addr::unchecked("creator"), &bridge_instantiate_msg, &[], "BRIDGE", None)?;
     // using the proof for s1, but tamper with the snapshot entry balance
     let proof_bytes = proofs[0].clone();
     let proof_steps: Vec<aln_bridge::ProofStep> = proof_bytes.iter().map(|(b, is_left)| aln_bridge::ProofStep { sibling: Binary(b.clone()), is_left: *is_left }).collect();
     let mut tampered = s1.clone();
     tampered.balance = "1001".to_string();
-    let claim_msg = aln_bridge::ExecuteMsg::Claim { asset_id: "m3".to_string(), snapshot: tampered, snapshot_hash: h1.clone(), merkle_proof: proof_steps, amount_auet: Uint128::new(10), amount_csp: None };
+    let claim_msg = aln_bridge::ExecuteMsg::Claim { asset_id: "m3".to_string(), epoch: None, snapshot: tampered, snapshot_hash: h1.clone(), merkle_proof: proof_steps, amount_auet: None, amount_csp: None };
     let res = app.execute_contract(Addr::unchecked("user2"), bridge_addr_inst.clone(), &claim_msg, &[]);
-    assert!(res.is_err());
+    assert_eq!(res.unwrap_err().downcast::<aln_bridge::ContractError>()?, aln_bridge::ContractError::SnapshotHashMismatch);
     Ok(())
 }
 
@@ -483,16 +504,16 @@ fn claim_before_activation_height_fails() -> Result<()> {
     let (root, proofs) = build_merkle_and_proofs(&vec![arr]);
 
     let gov = Addr::unchecked("gov");
-    let reg_instantiate_msg = aln_registry::InstantiateMsg { governance_addr: gov.to_string(), allow_missing_ubs: Some(true) };
+    let reg_instantiate_msg = registry_init_msg(gov.to_string());
     let reg_addr = app.instantiate_contract(reg_code, Addr::unchecked("creator"), &reg_instantiate_msg, &[], "REG", None)?;
-    let asset = aln_registry::RegisteredAsset { id: "d1".to_string(), source_chain: "kaiyo-1".to_string(), source_denom: "ibc/xxx".to_string(), snapshot_height: 100, merkle_root: root.clone(), ubs_report_hash: Some("h3".to_string()), scaling_profile_id: "malicious_cleanup".to_string(), activation_height: 1000, sanitized_approved: true };
+    let asset = aln_registry::RegisteredAsset { id: "d1".to_string(), source_chain: "kaiyo-1".to_string(), source_denom: "ibc/xxx".to_string(), snapshot_height: 100, merkle_root: root.clone(), ubs_report_hash: Some("h3".to_string()), scaling_profile_id: "malicious_cleanup".to_string(), activation_height: 1000, sanitized_approved: true, merkle_version: aln_registry::MerkleVersion::V1, claim_deadline: None, total_supply: None, lifecycle: Default::default(), epochs: vec![] };
     app.execute_contract(gov.clone(), reg_addr.clone(), &aln_registry::ExecuteMsg::RegisterAsset { asset: asset.clone() }, &[])?;
-    app.execute_contract(gov.clone(), reg_addr.clone(), &aln_registry::ExecuteMsg::ApproveSanitized { id: "d1".to_string(), ubs_report_hash: "h3".to_string() }, &[])?;
+    app.execute_contract(gov.clone(), reg_addr.clone(), &aln_registry::ExecuteMsg::ApproveSanitized { id: "d1".to_string(), ubs_report_hash: "h3".to_string(), epoch: None }, &[])?;
 
     let bridge_addr = Addr::unchecked("bridge");
     let auet_instantiate_msg = aln_auet::InstantiateMsg { cw20: Cw20InstantiateMsg { name: "AU.ET".to_string(), symbol: "AUET".to_string(), decimals: 6, initial_balances: vec![Cw20Coin { address: bridge_addr.to_string(), amount: Uint128::new(1_000_000) }], mint: None, marketing: None }, snapshot: aln_auet::SnapshotMeta { chain_id: "kaiyo-1".to_string(), height: 100, merkle_root: root.clone() }, allowed_modules: Some(vec![]) };
     let auet_addr = app.instantiate_contract(auet_code, Addr::unchecked("creator"), &auet_instantiate_msg, &[], "AUET", None)?;
-    let bridge_instantiate_msg = aln_bridge::InstantiateMsg { auet_contract: auet_addr.to_string(), csp_contract: None, registry_contract: reg_addr.to_string(), governance_addr: gov.to_string() };
+    let bridge_instantiate_msg = bridge_init_msg(auet_addr.to_string(), reg_addr.to_string(), gov.to_string());
     let bridge_addr_inst = app.instantiate_contract(bridge_code, Addr::unchecked("creator"), &bridge_instantiate_msg, &[], "BRIDGE", None)?;
 
     // set block to before activation height
@@ -500,13 +521,988 @@ fn claim_before_activation_height_fails() -> Result<()> {
 
     let proof_bytes = proofs[0].clone();
     let proof_steps: Vec<aln_bridge::ProofStep> = proof_bytes.iter().map(|(b, is_left)| aln_bridge::ProofStep { sibling: Binary(b.clone()), is_left: *is_left }).collect();
-    let claim_msg = aln_bridge::ExecuteMsg::Claim { asset_id: "d1".to_string(), snapshot: s1.clone(), snapshot_hash: h1.clone(), merkle_proof: proof_steps, amount_auet: Uint128::new(10), amount_csp: None };
-    let res = app.execute_contract(Addr::unchecked("del_user"), bridge_addr_inst.clone(), &claim_msg, &[]);
-    assert!(res.is_err());
+    let claim_msg = aln_bridge::ExecuteMsg::Claim { asset_id: "d1".to_string(), epoch: None, snapshot: s1.clone(), snapshot_hash: h1.clone(), merkle_proof: proof_steps, amount_auet: None, amount_csp: None };
+    let res = app.execute_contract(Addr::unchecked("delayed_user"), bridge_addr_inst.clone(), &claim_msg, &[]);
+    assert_eq!(res.unwrap_err().downcast::<aln_bridge::ContractError>()?, aln_bridge::ContractError::NotActivated);
     
     // now set height to after activation
     app.update_block(|b| b.height = 2000);
-    let res2 = app.execute_contract(Addr::unchecked("del_user"), bridge_addr_inst.clone(), &claim_msg, &[])?;
+    let res2 = app.execute_contract(Addr::unchecked("delayed_user"), bridge_addr_inst.clone(), &claim_msg, &[])?;
     assert!(res2.attributes.iter().any(|a| a.key == "action" && a.value == "claim"));
     Ok(())
 }
+
+#[test]
+fn pause_blocks_claim_origin_and_consume_separately() -> Result<()> {
+    let mut app = App::default();
+    let auet_code = app.store_code(Box::new(ContractWrapper::new(auet_instantiate, auet_execute, auet_query)));
+    let reg_code = app.store_code(Box::new(ContractWrapper::new(reg_instantiate, reg_execute, reg_query)));
+    let bridge_code = app.store_code(Box::new(ContractWrapper::new(bridge_instantiate, bridge_execute, bridge_query)));
+
+    let gov = Addr::unchecked("gov");
+    let guardian = Addr::unchecked("guardian");
+    let sys = Addr::unchecked("sys");
+    let auet_instantiate_msg = aln_auet::InstantiateMsg { cw20: Cw20InstantiateMsg { name: "AU.ET".to_string(), symbol: "AUET".to_string(), decimals: 6, initial_balances: vec![Cw20Coin { address: "bridge".to_string(), amount: Uint128::new(1_000_000) }], mint: None, marketing: None }, snapshot: aln_auet::SnapshotMeta { chain_id: "kaiyo-1".to_string(), height: 0, merkle_root: "root".to_string() }, allowed_modules: Some(vec![]) };
+    let auet_addr = app.instantiate_contract(auet_code, Addr::unchecked("creator"), &auet_instantiate_msg, &[], "AUET", None)?;
+    let reg_addr = app.instantiate_contract(reg_code, Addr::unchecked("creator"), &registry_init_msg(gov.to_string()), &[], "REG", None)?;
+
+    // two leaves in one asset: one claimed directly, one through an origin event
+    let s_user = aln_bridge::SnapshotEntry { chain_id: "kaiyo-1".to_string(), height: 0, denom: "ibc/ppp".to_string(), address: "user".to_string(), balance: "100".to_string() };
+    let origin = aln_bridge::OriginLockEvent { origin_chain_id: "kaiyo-1".to_string(), tx_hash: "0xtx".to_string(), nonce: 1, denom: "ibc/ppp".to_string(), origin_address: "user2".to_string(), amount: "40".to_string(), height: Some(0) };
+    let s_origin = aln_bridge::SnapshotEntry { chain_id: "kaiyo-1".to_string(), height: 0, denom: "ibc/ppp".to_string(), address: "user2".to_string(), balance: "40".to_string() };
+    let h_user = compute_snapshot_hash(&s_user);
+    let leaves: Vec<[u8; 32]> = [&h_user, &compute_snapshot_hash(&s_origin)].iter().map(|h| { let mut a = [0u8; 32]; a.copy_from_slice(&hex::decode(h.trim_start_matches("0x")).unwrap()); a }).collect();
+    let (root, proofs) = build_merkle_and_proofs(&leaves);
+    let steps = |i: usize| -> Vec<aln_bridge::ProofStep> { proofs[i].iter().map(|(b, is_left)| aln_bridge::ProofStep { sibling: Binary(b.clone()), is_left: *is_left }).collect() };
+    let asset = aln_registry::RegisteredAsset { id: "p1".to_string(), source_chain: "kaiyo-1".to_string(), source_denom: "ibc/ppp".to_string(), snapshot_height: 0, merkle_root: root, ubs_report_hash: Some("hp".to_string()), scaling_profile_id: "clean".to_string(), activation_height: 0, sanitized_approved: true, merkle_version: aln_registry::MerkleVersion::V1, claim_deadline: None, total_supply: None, lifecycle: Default::default(), epochs: vec![] };
+    app.execute_contract(gov.clone(), reg_addr.clone(), &aln_registry::ExecuteMsg::RegisterAsset { asset }, &[])?;
+    let bridge_msg = aln_bridge::InstantiateMsg { system_whitelist: Some(vec![sys.to_string()]), ..bridge_init_msg(auet_addr.to_string(), reg_addr.to_string(), gov.to_string()) };
+    let bridge = app.instantiate_contract(bridge_code, Addr::unchecked("creator"), &bridge_msg, &[], "BRIDGE", None)?;
+
+    let claim = aln_bridge::ExecuteMsg::Claim { asset_id: "p1".to_string(), epoch: None, snapshot: s_user.clone(), snapshot_hash: h_user.clone(), merkle_proof: steps(0), amount_auet: None, amount_csp: None, origin_tx_hash: None, origin_nonce: None, ubs_report_hash: None };
+    let claim_origin = aln_bridge::ExecuteMsg::ClaimWithOrigin { asset_id: "p1".to_string(), epoch: None, origin_event: origin, merkle_proof: steps(1), ubs_report_hash: None, amount_auet: None, amount_csp: None, origin_proof: None };
+    let consume = aln_bridge::ExecuteMsg::SystemConsume { owner: "user".to_string(), delta: aln_bridge::EnergyVector { auet: Uint128::new(1), csp: Uint128::zero(), erp: Uint128::zero() } };
+    let set_pause = |asset_id: Option<&str>, claim: Option<bool>, claim_with_origin: Option<bool>, system_consume: Option<bool>| aln_bridge::ExecuteMsg::SetPause { asset_id: asset_id.map(|s| s.to_string()), claim, claim_with_origin, system_consume };
+    let downcast = |r: Result<AppResponse>| r.unwrap_err().downcast::<aln_bridge::ContractError>().unwrap();
+
+    // only governance or a guardian may pause; the guardian role comes from governance
+    assert_eq!(downcast(app.execute_contract(guardian.clone(), bridge.clone(), &set_pause(None, Some(true), None, None), &[])), aln_bridge::ContractError::Unauthorized { action: "set_pause".to_string() });
+    app.execute_contract(gov.clone(), bridge.clone(), &aln_bridge::ExecuteMsg::AddGuardian { addr: guardian.to_string() }, &[])?;
+
+    // global claim pause blocks Claim only
+    app.execute_contract(guardian.clone(), bridge.clone(), &set_pause(None, Some(true), None, None), &[])?;
+    assert_eq!(downcast(app.execute_contract(Addr::unchecked("user"), bridge.clone(), &claim, &[])), aln_bridge::ContractError::Paused { action: "claim".to_string() });
+    let state: aln_bridge::PauseStateResponse = app.wrap().query_wasm_smart(bridge.clone(), &aln_bridge::QueryMsg::PauseState { asset_id: Some("p1".to_string()) })?;
+    assert!(state.global.claim && !state.global.claim_with_origin && !state.global.system_consume);
+    assert_eq!(state.asset, Some(aln_bridge::PauseFlags::default()));
+
+    // per-asset pause blocks ClaimWithOrigin for p1; system_consume cannot be paused per asset
+    app.execute_contract(gov.clone(), bridge.clone(), &set_pause(Some("p1"), None, Some(true), None), &[])?;
+    assert_eq!(downcast(app.execute_contract(Addr::unchecked("user2"), bridge.clone(), &claim_origin, &[])), aln_bridge::ContractError::AssetPaused { asset_id: "p1".to_string(), action: "claim_with_origin".to_string() });
+    assert!(matches!(downcast(app.execute_contract(gov.clone(), bridge.clone(), &set_pause(Some("p1"), None, None, Some(true)), &[])), aln_bridge::ContractError::InvalidConfig(_)));
+
+    // lifting both lets the claims through
+    app.execute_contract(guardian.clone(), bridge.clone(), &set_pause(None, Some(false), None, None), &[])?;
+    app.execute_contract(guardian.clone(), bridge.clone(), &set_pause(Some("p1"), None, Some(false), None), &[])?;
+    app.execute_contract(Addr::unchecked("user"), bridge.clone(), &claim, &[])?;
+    app.execute_contract(Addr::unchecked("user2"), bridge.clone(), &claim_origin, &[])?;
+    let grant = aln_bridge::ExecuteMsg::GrantEnergyAllowance { spender: sys.to_string(), amount: aln_bridge::EnergyVector { auet: Uint128::new(10), csp: Uint128::zero(), erp: Uint128::zero() }, period_seconds: None, expires: None };
+    app.execute_contract(Addr::unchecked("user"), bridge.clone(), &grant, &[])?;
+
+    // global system_consume pause blocks ledger debits until lifted
+    app.execute_contract(guardian.clone(), bridge.clone(), &set_pause(None, None, None, Some(true)), &[])?;
+    assert_eq!(downcast(app.execute_contract(sys.clone(), bridge.clone(), &consume, &[])), aln_bridge::ContractError::Paused { action: "system_consume".to_string() });
+    app.execute_contract(gov.clone(), bridge.clone(), &set_pause(None, None, None, Some(false)), &[])?;
+    app.execute_contract(sys.clone(), bridge.clone(), &consume, &[])?;
+
+    // a removed guardian loses the role
+    app.execute_contract(gov.clone(), bridge.clone(), &aln_bridge::ExecuteMsg::RemoveGuardian { addr: guardian.to_string() }, &[])?;
+    assert!(app.execute_contract(guardian.clone(), bridge.clone(), &set_pause(None, Some(true), None, None), &[]).is_err());
+    Ok(())
+}
+
+#[test]
+fn downgraded_claims_follow_governance_treatment() -> Result<()> {
+    let mut app = App::default();
+    let auet_code = app.store_code(Box::new(ContractWrapper::new(auet_instantiate, auet_execute, auet_query)));
+    let reg_code = app.store_code(Box::new(ContractWrapper::new(reg_instantiate, reg_execute, reg_query)));
+    let oracle_code = app.store_code(Box::new(ContractWrapper::new(oracle_instantiate, oracle_execute, oracle_query)));
+    let bridge_code = app.store_code(Box::new(ContractWrapper::new(bridge_instantiate, bridge_execute, bridge_query)));
+
+    let gov = Addr::unchecked("gov");
+    let auet_instantiate_msg = aln_auet::InstantiateMsg { cw20: Cw20InstantiateMsg { name: "AU.ET".to_string(), symbol: "AUET".to_string(), decimals: 6, initial_balances: vec![Cw20Coin { address: "bridge".to_string(), amount: Uint128::new(1_000_000) }], mint: None, marketing: None }, snapshot: aln_auet::SnapshotMeta { chain_id: "kaiyo-1".to_string(), height: 0, merkle_root: "root".to_string() }, allowed_modules: Some(vec![]) };
+    let auet_addr = app.instantiate_contract(auet_code, Addr::unchecked("creator"), &auet_instantiate_msg, &[], "AUET", None)?;
+    let reg_addr = app.instantiate_contract(reg_code, Addr::unchecked("creator"), &registry_init_msg(gov.to_string()), &[], "REG", None)?;
+    let oracle = app.instantiate_contract(oracle_code, Addr::unchecked("creator"), &(), &[], "UBS", None)?;
+    app.execute_contract(Addr::unchecked("creator"), oracle.clone(), &ubs_oracle::ExecuteMsg::SetCommittee { addrs: vec!["reporter".to_string()] }, &[])?;
+    // class 1 = downgraded; with zero threat the oracle maps an entitlement total T to T AU.ET + T/2 CSP
+    for key in ["kaiyo-1::0", "kaiyo-1:0xtx:1"] {
+        app.execute_contract(Addr::unchecked("reporter"), oracle.clone(), &ubs_oracle::ExecuteMsg::SubmitReport { replay_key: Binary::from(key.as_bytes()), ubs_class: 1, threat_bps: 0, payload_hash: "p".to_string() }, &[])?;
+    }
+    // class 2 = rejected
+    app.execute_contract(Addr::unchecked("reporter"), oracle.clone(), &ubs_oracle::ExecuteMsg::SubmitReport { replay_key: Binary::from("kaiyo-1:0xrej:2".as_bytes()), ubs_class: 2, threat_bps: 0, payload_hash: "p".to_string() }, &[])?;
+
+    let snap = |addr: &str, balance: &str| aln_bridge::SnapshotEntry { chain_id: "kaiyo-1".to_string(), height: 0, denom: "ibc/dgr".to_string(), address: addr.to_string(), balance: balance.to_string() };
+    let snaps = vec![snap("user", "100"), snap("user2", "40"), snap("user3", "10"), snap("user4", "5")];
+    let hashes: Vec<String> = snaps.iter().map(compute_snapshot_hash).collect();
+    let leaves: Vec<[u8; 32]> = hashes.iter().map(|h| { let mut a = [0u8; 32]; a.copy_from_slice(&hex::decode(h.trim_start_matches("0x")).unwrap()); a }).collect();
+    let (root, proofs) = build_merkle_and_proofs(&leaves);
+    let steps = |i: usize| -> Vec<aln_bridge::ProofStep> { proofs[i].iter().map(|(b, is_left)| aln_bridge::ProofStep { sibling: Binary(b.clone()), is_left: *is_left }).collect() };
+    let asset = aln_registry::RegisteredAsset { id: "dg".to_string(), source_chain: "kaiyo-1".to_string(), source_denom: "ibc/dgr".to_string(), snapshot_height: 0, merkle_root: root, ubs_report_hash: Some("hd".to_string()), scaling_profile_id: "clean".to_string(), activation_height: 0, sanitized_approved: true, merkle_version: aln_registry::MerkleVersion::V1, claim_deadline: None, total_supply: None, lifecycle: Default::default(), epochs: vec![] };
+    app.execute_contract(gov.clone(), reg_addr.clone(), &aln_registry::ExecuteMsg::RegisterAsset { asset }, &[])?;
+    let bridge_msg = aln_bridge::InstantiateMsg { ubs_oracle_contract: Some(oracle.to_string()), ..bridge_init_msg(auet_addr.to_string(), reg_addr.to_string(), gov.to_string()) };
+    let bridge = app.instantiate_contract(bridge_code, Addr::unchecked("creator"), &bridge_msg, &[], "BRIDGE", None)?;
+
+    let claim = |i: usize| aln_bridge::ExecuteMsg::Claim { asset_id: "dg".to_string(), epoch: None, snapshot: snaps[i].clone(), snapshot_hash: hashes[i].clone(), merkle_proof: steps(i), amount_auet: None, amount_csp: None, origin_tx_hash: None, origin_nonce: None, ubs_report_hash: None };
+    let set = |asset_id: Option<&str>, treatment: Option<aln_bridge::DowngradeTreatment>| aln_bridge::ExecuteMsg::SetDowngradeTreatment { asset_id: asset_id.map(|s| s.to_string()), treatment };
+    let downcast = |r: Result<AppResponse>| r.unwrap_err().downcast::<aln_bridge::ContractError>().unwrap();
+    let attr = |res: &AppResponse, key: &str| res.events.iter().flat_map(|e| e.attributes.iter()).find(|a| a.key == key).map(|a| a.value.clone());
+    let balance = |app: &App, who: &str| -> aln_bridge::EnergyVector { app.wrap().query_wasm_smart(bridge.clone(), &aln_bridge::QueryMsg::EnergyBalance { address: who.to_string() }).unwrap() };
+
+    // governance only, and bps are bounded
+    assert_eq!(downcast(app.execute_contract(Addr::unchecked("user"), bridge.clone(), &set(Some("dg"), Some(aln_bridge::DowngradeTreatment::CspOnly)), &[])), aln_bridge::ContractError::Unauthorized { action: "set_downgrade_treatment".to_string() });
+    assert!(matches!(downcast(app.execute_contract(gov.clone(), bridge.clone(), &set(Some("dg"), Some(aln_bridge::DowngradeTreatment::Haircut { bps: 10_001 })), &[])), aln_bridge::ContractError::InvalidConfig(_)));
+
+    // 20% haircut: 150 AU.ET + 75 CSP, of which 30 + 15 are withheld
+    app.execute_contract(gov.clone(), bridge.clone(), &set(Some("dg"), Some(aln_bridge::DowngradeTreatment::Haircut { bps: 2_000 })), &[])?;
+    let res = app.execute_contract(Addr::unchecked("user"), bridge.clone(), &claim(0), &[])?;
+    assert_eq!(attr(&res, "ubs_decision").as_deref(), Some("downgraded"));
+    assert_eq!(attr(&res, "downgrade_treatment").as_deref(), Some("haircut"));
+    assert_eq!(attr(&res, "downgrade_withheld").as_deref(), Some("45"));
+    let bal = balance(&app, "user");
+    assert_eq!((bal.auet, bal.csp), (Uint128::new(120), Uint128::new(60)));
+
+    // the default applies once the asset entry is removed: vesting credits the energy under a lock
+    let vesting = aln_bridge::DowngradeTreatment::Vesting { cliff_seconds: 100, duration_seconds: 100 };
+    app.execute_contract(gov.clone(), bridge.clone(), &set(None, Some(vesting.clone())), &[])?;
+    app.execute_contract(gov.clone(), bridge.clone(), &set(Some("dg"), None), &[])?;
+    let t: aln_bridge::DowngradeTreatment = app.wrap().query_wasm_smart(bridge.clone(), &aln_bridge::QueryMsg::DowngradeTreatment { asset_id: Some("dg".to_string()) })?;
+    assert_eq!(t, vesting);
+    let claimed_at = app.block_info().time.seconds();
+    let res = app.execute_contract(Addr::unchecked("user2"), bridge.clone(), &claim(1), &[])?;
+    assert_eq!(attr(&res, "downgrade_vested").as_deref(), Some("90"));
+    let locks: Vec<aln_bridge::EnergyLock> = app.wrap().query_wasm_smart(bridge.clone(), &aln_bridge::QueryMsg::EnergyLocks { address: "user2".to_string(), start_after: None, limit: None })?;
+    assert_eq!(locks.len(), 1);
+    assert_eq!((locks[0].start, locks[0].cliff, locks[0].end), (claimed_at, claimed_at + 100, claimed_at + 100));
+    let availability = |app: &App| -> aln_bridge::EnergyAvailabilityResponse { app.wrap().query_wasm_smart(bridge.clone(), &aln_bridge::QueryMsg::EnergyAvailability { address: "user2".to_string() }).unwrap() };
+    let av = availability(&app);
+    assert_eq!((av.total.auet, av.total.csp), (Uint128::new(60), Uint128::new(30)));
+    assert_eq!((av.available.auet, av.available.csp), (Uint128::zero(), Uint128::zero()));
+    app.update_block(|b| b.time = b.time.plus_seconds(100));
+    let av = availability(&app);
+    assert_eq!((av.available.auet, av.available.csp), (Uint128::new(60), Uint128::new(30)));
+
+    // CSP-only through an origin event; the treatment is kept in the refactor audit
+    app.execute_contract(gov.clone(), bridge.clone(), &set(Some("dg"), Some(aln_bridge::DowngradeTreatment::CspOnly)), &[])?;
+    let origin = aln_bridge::OriginLockEvent { origin_chain_id: "kaiyo-1".to_string(), tx_hash: "0xtx".to_string(), nonce: 1, denom: "ibc/dgr".to_string(), origin_address: "user3".to_string(), amount: "10".to_string(), height: Some(0) };
+    app.execute_contract(Addr::unchecked("user3"), bridge.clone(), &aln_bridge::ExecuteMsg::ClaimWithOrigin { asset_id: "dg".to_string(), epoch: None, origin_event: origin, merkle_proof: steps(2), ubs_report_hash: None, amount_auet: None, amount_csp: None, origin_proof: None }, &[])?;
+    let bal = balance(&app, "user3");
+    assert_eq!((bal.auet, bal.csp), (Uint128::zero(), Uint128::new(7)));
+    let audit: Option<String> = app.wrap().query_wasm_smart(bridge.clone(), &aln_bridge::QueryMsg::RefactorAudit { origin_chain: "kaiyo-1".to_string(), tx_hash: "0xtx".to_string(), nonce: 1 })?;
+    assert_eq!(audit.as_deref(), Some("oracle_agg:1:0|downgraded:csp_only"));
+
+    // a rejected claim is not credited and does not count toward the claimed supply
+    let origin = aln_bridge::OriginLockEvent { origin_chain_id: "kaiyo-1".to_string(), tx_hash: "0xrej".to_string(), nonce: 2, denom: "ibc/dgr".to_string(), origin_address: "user4".to_string(), amount: "5".to_string(), height: Some(0) };
+    let res = app.execute_contract(Addr::unchecked("user4"), bridge.clone(), &aln_bridge::ExecuteMsg::ClaimWithOrigin { asset_id: "dg".to_string(), epoch: None, origin_event: origin, merkle_proof: steps(3), ubs_report_hash: None, amount_auet: None, amount_csp: None, origin_proof: None }, &[])?;
+    assert_eq!(attr(&res, "action").as_deref(), Some("claim_rejected"));
+    assert_eq!(balance(&app, "user4").auet, Uint128::zero());
+    let stats: aln_bridge::AssetClaimStatsResponse = app.wrap().query_wasm_smart(bridge.clone(), &aln_bridge::QueryMsg::AssetClaimStats { asset_id: "dg".to_string() })?;
+    assert_eq!((stats.claims, stats.claimed), (3, Uint128::new(150)));
+    Ok(())
+}
+
+#[test]
+fn queued_downgraded_claims_apply_treatment_on_release() -> Result<()> {
+    let mut app = App::default();
+    let auet_code = app.store_code(Box::new(ContractWrapper::new(auet_instantiate, auet_execute, auet_query)));
+    let reg_code = app.store_code(Box::new(ContractWrapper::new(reg_instantiate, reg_execute, reg_query)));
+    let oracle_code = app.store_code(Box::new(ContractWrapper::new(oracle_instantiate, oracle_execute, oracle_query)));
+    let bridge_code = app.store_code(Box::new(ContractWrapper::new(bridge_instantiate, bridge_execute, bridge_query)));
+
+    let gov = Addr::unchecked("gov");
+    let auet_instantiate_msg = aln_auet::InstantiateMsg { cw20: Cw20InstantiateMsg { name: "AU.ET".to_string(), symbol: "AUET".to_string(), decimals: 6, initial_balances: vec![Cw20Coin { address: "bridge".to_string(), amount: Uint128::new(1_000_000) }], mint: None, marketing: None }, snapshot: aln_auet::SnapshotMeta { chain_id: "kaiyo-1".to_string(), height: 0, merkle_root: "root".to_string() }, allowed_modules: Some(vec![]) };
+    let auet_addr = app.instantiate_contract(auet_code, Addr::unchecked("creator"), &auet_instantiate_msg, &[], "AUET", None)?;
+    let reg_addr = app.instantiate_contract(reg_code, Addr::unchecked("creator"), &registry_init_msg(gov.to_string()), &[], "REG", None)?;
+    let oracle = app.instantiate_contract(oracle_code, Addr::unchecked("creator"), &(), &[], "UBS", None)?;
+    app.execute_contract(Addr::unchecked("creator"), oracle.clone(), &ubs_oracle::ExecuteMsg::SetCommittee { addrs: vec!["reporter".to_string()] }, &[])?;
+    // class 1 = downgraded; with zero threat the oracle maps an entitlement total T to T AU.ET + T/2 CSP
+    app.execute_contract(Addr::unchecked("reporter"), oracle.clone(), &ubs_oracle::ExecuteMsg::SubmitReport { replay_key: Binary::from("kaiyo-1::0".as_bytes()), ubs_class: 1, threat_bps: 0, payload_hash: "p".to_string() }, &[])?;
+
+    let snap = |addr: &str, balance: &str| aln_bridge::SnapshotEntry { chain_id: "kaiyo-1".to_string(), height: 0, denom: "ibc/rq".to_string(), address: addr.to_string(), balance: balance.to_string() };
+    let snaps = vec![snap("user", "100"), snap("user2", "40"), snap("user3", "10")];
+    let hashes: Vec<String> = snaps.iter().map(compute_snapshot_hash).collect();
+    let leaves: Vec<[u8; 32]> = hashes.iter().map(|h| { let mut a = [0u8; 32]; a.copy_from_slice(&hex::decode(h.trim_start_matches("0x")).unwrap()); a }).collect();
+    let (root, proofs) = build_merkle_and_proofs(&leaves);
+    let steps = |i: usize| -> Vec<aln_bridge::ProofStep> { proofs[i].iter().map(|(b, is_left)| aln_bridge::ProofStep { sibling: Binary(b.clone()), is_left: *is_left }).collect() };
+    let asset = aln_registry::RegisteredAsset { id: "rq".to_string(), source_chain: "kaiyo-1".to_string(), source_denom: "ibc/rq".to_string(), snapshot_height: 0, merkle_root: root, ubs_report_hash: Some("hq".to_string()), scaling_profile_id: "clean".to_string(), activation_height: 0, sanitized_approved: true, merkle_version: aln_registry::MerkleVersion::V1, claim_deadline: None, total_supply: None, lifecycle: Default::default(), epochs: vec![] };
+    app.execute_contract(gov.clone(), reg_addr.clone(), &aln_registry::ExecuteMsg::RegisterAsset { asset }, &[])?;
+    let bridge_msg = aln_bridge::InstantiateMsg { ubs_oracle_contract: Some(oracle.to_string()), ..bridge_init_msg(auet_addr.to_string(), reg_addr.to_string(), gov.to_string()) };
+    let bridge = app.instantiate_contract(bridge_code, Addr::unchecked("creator"), &bridge_msg, &[], "BRIDGE", None)?;
+
+    let claim = |i: usize| aln_bridge::ExecuteMsg::Claim { asset_id: "rq".to_string(), epoch: None, snapshot: snaps[i].clone(), snapshot_hash: hashes[i].clone(), merkle_proof: steps(i), amount_auet: None, amount_csp: None, origin_tx_hash: None, origin_nonce: None, ubs_report_hash: None };
+    let downcast = |r: Result<AppResponse>| r.unwrap_err().downcast::<aln_bridge::ContractError>().unwrap();
+    let attr = |res: &AppResponse, key: &str| res.events.iter().flat_map(|e| e.attributes.iter()).find(|a| a.key == key).map(|a| a.value.clone());
+    let balance = |app: &App, who: &str| -> aln_bridge::EnergyVector { app.wrap().query_wasm_smart(bridge.clone(), &aln_bridge::QueryMsg::EnergyBalance { address: who.to_string() }).unwrap() };
+
+    // limits only apply to registered assets; this one moves 150 entitled units per 100 blocks
+    let limit = aln_bridge::RateLimit { window: aln_bridge::RateWindow::Height(100), max_per_asset: Some(Uint128::new(150)), max_per_recipient: None };
+    assert_eq!(downcast(app.execute_contract(gov.clone(), bridge.clone(), &aln_bridge::ExecuteMsg::SetRateLimit { asset_id: "nope".to_string(), limit: Some(limit.clone()) }, &[])), aln_bridge::ContractError::AssetNotRegistered { asset_id: "nope".to_string() });
+    app.execute_contract(gov.clone(), bridge.clone(), &aln_bridge::ExecuteMsg::SetRateLimit { asset_id: "rq".to_string(), limit: Some(limit) }, &[])?;
+    let haircut = aln_bridge::DowngradeTreatment::Haircut { bps: 2_000 };
+    app.execute_contract(gov.clone(), bridge.clone(), &aln_bridge::ExecuteMsg::SetDowngradeTreatment { asset_id: Some("rq".to_string()), treatment: Some(haircut.clone()) }, &[])?;
+
+    app.execute_contract(Addr::unchecked("user"), bridge.clone(), &claim(0), &[])?;
+    // over the limit: queued with its sanitized energy, the haircut waits for the release
+    let res = app.execute_contract(Addr::unchecked("user2"), bridge.clone(), &claim(1), &[])?;
+    assert_eq!(attr(&res, "action").as_deref(), Some("claim_queued"));
+    assert_eq!(attr(&res, "downgrade_withheld"), None);
+    let res = app.execute_contract(Addr::unchecked("user3"), bridge.clone(), &claim(2), &[])?;
+    assert_eq!(attr(&res, "action").as_deref(), Some("claim_queued"));
+    let queued: Vec<aln_bridge::QueuedClaim> = app.wrap().query_wasm_smart(bridge.clone(), &aln_bridge::QueryMsg::QueuedClaims { start_after: None, limit: None })?;
+    assert_eq!(queued.len(), 2);
+    assert_eq!((queued[0].epoch, queued[0].energy.auet, queued[0].energy.csp), (0, Uint128::new(60), Uint128::new(30)));
+    assert_eq!(queued[0].downgrade, Some(haircut));
+
+    let res = app.execute_contract(gov.clone(), bridge.clone(), &aln_bridge::ExecuteMsg::ReleaseQueuedClaim { id: queued[0].id }, &[])?;
+    assert_eq!(attr(&res, "downgrade_withheld").as_deref(), Some("18"));
+    let bal = balance(&app, "user2");
+    assert_eq!((bal.auet, bal.csp), (Uint128::new(48), Uint128::new(24)));
+
+    // a rejected claim is never credited and leaves the totals; its leaf stays claimed
+    let reject = aln_bridge::ExecuteMsg::RejectQueuedClaim { id: queued[1].id, note: Some("sybil".to_string()) };
+    assert_eq!(downcast(app.execute_contract(Addr::unchecked("user3"), bridge.clone(), &reject, &[])), aln_bridge::ContractError::Unauthorized { action: "reject_queued_claim".to_string() });
+    let before: Uint128 = app.wrap().query_wasm_smart(bridge.clone(), &aln_bridge::QueryMsg::TotalEnergy {})?;
+    app.execute_contract(gov.clone(), bridge.clone(), &reject, &[])?;
+    let after: Uint128 = app.wrap().query_wasm_smart(bridge.clone(), &aln_bridge::QueryMsg::TotalEnergy {})?;
+    assert_eq!(before - after, Uint128::new(15));
+    assert_eq!(balance(&app, "user3").auet, Uint128::zero());
+    assert_eq!(downcast(app.execute_contract(gov.clone(), bridge.clone(), &reject, &[])), aln_bridge::ContractError::QueuedClaimNotFound { id: queued[1].id });
+    assert_eq!(downcast(app.execute_contract(Addr::unchecked("user3"), bridge.clone(), &claim(2), &[])), aln_bridge::ContractError::AlreadyClaimed);
+    Ok(())
+}
+
+#[test]
+fn simulate_claim_reports_outcome_without_writing_state() -> Result<()> {
+    let mut app = App::default();
+    let auet_code = app.store_code(Box::new(ContractWrapper::new(auet_instantiate, auet_execute, auet_query)));
+    let reg_code = app.store_code(Box::new(ContractWrapper::new(reg_instantiate, reg_execute, reg_query)));
+    let oracle_code = app.store_code(Box::new(ContractWrapper::new(oracle_instantiate, oracle_execute, oracle_query)));
+    let bridge_code = app.store_code(Box::new(ContractWrapper::new(bridge_instantiate, bridge_execute, bridge_query)));
+
+    let gov = Addr::unchecked("gov");
+    let auet_instantiate_msg = aln_auet::InstantiateMsg { cw20: Cw20InstantiateMsg { name: "AU.ET".to_string(), symbol: "AUET".to_string(), decimals: 6, initial_balances: vec![Cw20Coin { address: "bridge".to_string(), amount: Uint128::new(1_000_000) }], mint: None, marketing: None }, snapshot: aln_auet::SnapshotMeta { chain_id: "kaiyo-1".to_string(), height: 0, merkle_root: "root".to_string() }, allowed_modules: Some(vec![]) };
+    let auet_addr = app.instantiate_contract(auet_code, Addr::unchecked("creator"), &auet_instantiate_msg, &[], "AUET", None)?;
+    let reg_addr = app.instantiate_contract(reg_code, Addr::unchecked("creator"), &registry_init_msg(gov.to_string()), &[], "REG", None)?;
+    let oracle = app.instantiate_contract(oracle_code, Addr::unchecked("creator"), &(), &[], "UBS", None)?;
+
+    let s_user = aln_bridge::SnapshotEntry { chain_id: "kaiyo-1".to_string(), height: 0, denom: "ibc/sim".to_string(), address: "user".to_string(), balance: "100".to_string() };
+    let origin = aln_bridge::OriginLockEvent { origin_chain_id: "kaiyo-1".to_string(), tx_hash: "0xtx".to_string(), nonce: 1, denom: "ibc/sim".to_string(), origin_address: "user2".to_string(), amount: "40".to_string(), height: Some(0) };
+    let s_origin = aln_bridge::SnapshotEntry { chain_id: "kaiyo-1".to_string(), height: 0, denom: "ibc/sim".to_string(), address: "user2".to_string(), balance: "40".to_string() };
+    let h_user = compute_snapshot_hash(&s_user);
+    let leaves: Vec<[u8; 32]> = [&h_user, &compute_snapshot_hash(&s_origin)].iter().map(|h| { let mut a = [0u8; 32]; a.copy_from_slice(&hex::decode(h.trim_start_matches("0x")).unwrap()); a }).collect();
+    let (root, proofs) = build_merkle_and_proofs(&leaves);
+    let steps = |i: usize| -> Vec<aln_bridge::ProofStep> { proofs[i].iter().map(|(b, is_left)| aln_bridge::ProofStep { sibling: Binary(b.clone()), is_left: *is_left }).collect() };
+    let asset = aln_registry::RegisteredAsset { id: "s1".to_string(), source_chain: "kaiyo-1".to_string(), source_denom: "ibc/sim".to_string(), snapshot_height: 0, merkle_root: root, ubs_report_hash: Some("hs".to_string()), scaling_profile_id: "clean".to_string(), activation_height: 50, sanitized_approved: true, merkle_version: aln_registry::MerkleVersion::V1, claim_deadline: None, total_supply: None, lifecycle: Default::default(), epochs: vec![] };
+    app.execute_contract(gov.clone(), reg_addr.clone(), &aln_registry::ExecuteMsg::RegisterAsset { asset }, &[])?;
+    let bridge = app.instantiate_contract(bridge_code, Addr::unchecked("creator"), &bridge_init_msg(auet_addr.to_string(), reg_addr.to_string(), gov.to_string()), &[], "BRIDGE", None)?;
+
+    let claim = |proof: Vec<aln_bridge::ProofStep>| aln_bridge::ExecuteMsg::Claim { asset_id: "s1".to_string(), epoch: None, snapshot: s_user.clone(), snapshot_hash: h_user.clone(), merkle_proof: proof, amount_auet: None, amount_csp: None, origin_tx_hash: None, origin_nonce: None, ubs_report_hash: None };
+    let claim_origin = aln_bridge::ExecuteMsg::ClaimWithOrigin { asset_id: "s1".to_string(), epoch: None, origin_event: origin, merkle_proof: steps(1), ubs_report_hash: None, amount_auet: None, amount_csp: None, origin_proof: None };
+    let simulate = |app: &App, sender: &str, msg: &aln_bridge::ExecuteMsg| -> aln_bridge::SimulateClaimResponse { app.wrap().query_wasm_smart(bridge.clone(), &aln_bridge::QueryMsg::SimulateClaim { sender: sender.to_string(), claim: msg.clone() }).unwrap() };
+    let checks = |r: &aln_bridge::SimulateClaimResponse| -> Vec<String> { r.failures.iter().map(|f| f.check.clone()).collect() };
+
+    // every failing check is listed, not just the first
+    app.update_block(|b| b.height = 10);
+    let res = simulate(&app, "mallory", &claim(steps(1)));
+    assert_eq!(checks(&res), vec!["ownership", "activation_height", "merkle_proof"]);
+    assert_eq!(res.failures[1].error, "asset claim not activated yet");
+    assert!(res.energy.is_none() && res.destination.is_none());
+
+    // a passing simulation matches the execution and writes nothing
+    app.update_block(|b| b.height = 100);
+    let res = simulate(&app, "user", &claim(steps(0)));
+    assert!(res.failures.is_empty());
+    assert_eq!(res.destination, Some(aln_bridge::ClaimDestination::Recipient { address: Addr::unchecked("user") }));
+    assert_eq!(res.ubs_decision.as_deref(), Some("approved"));
+    let energy = res.energy.unwrap();
+    assert_eq!((energy.auet, energy.csp), (Uint128::new(150), Uint128::new(75)));
+    let claimed: bool = app.wrap().query_wasm_smart(bridge.clone(), &aln_bridge::QueryMsg::IsClaimed { address: "user".to_string(), asset_id: "s1".to_string(), epoch: None, snapshot_hash: h_user.clone() })?;
+    assert!(!claimed);
+    app.execute_contract(Addr::unchecked("user"), bridge.clone(), &claim(steps(0)), &[])?;
+    let bal: aln_bridge::EnergyVector = app.wrap().query_wasm_smart(bridge.clone(), &aln_bridge::QueryMsg::EnergyBalance { address: "user".to_string() })?;
+    assert_eq!(bal, energy);
+    assert_eq!(checks(&simulate(&app, "user", &claim(steps(0)))), vec!["already_claimed"]);
+
+    // over the anomaly threshold the claim is quarantined
+    let update = |anomaly: Option<Uint128>, sink: Option<&str>, oracle: Option<String>| aln_bridge::ExecuteMsg::UpdateConfig { toxic_sink: sink.map(|s| s.to_string()), anomaly_threshold_amount: anomaly, toxic_cap_percent: None, ubs_oracle_contract: oracle, csp_contract: None, registry_contract: None, clear: None };
+    app.execute_contract(gov.clone(), bridge.clone(), &update(Some(Uint128::new(10)), None, None), &[])?;
+    assert_eq!(checks(&simulate(&app, "user2", &claim_origin)), vec!["anomaly_threshold"]);
+    app.execute_contract(gov.clone(), bridge.clone(), &update(None, Some("sink"), None), &[])?;
+    let res = simulate(&app, "user2", &claim_origin);
+    assert_eq!(res.destination, Some(aln_bridge::ClaimDestination::Quarantine { reason: aln_bridge::QuarantineReason::AnomalyThreshold { threshold: Uint128::new(10) } }));
+    assert_eq!(res.energy.map(|e| e.auet), Some(Uint128::new(60)));
+
+    // below the threshold an oracle without a report for the origin event fails the UBS step
+    app.execute_contract(gov.clone(), bridge.clone(), &update(Some(Uint128::new(1_000)), None, Some(oracle.to_string())), &[])?;
+    assert_eq!(checks(&simulate(&app, "user2", &claim_origin)), vec!["ubs_oracle"]);
+
+    // only claim messages can be simulated
+    let bad: Result<aln_bridge::SimulateClaimResponse, _> = app.wrap().query_wasm_smart(bridge.clone(), &aln_bridge::QueryMsg::SimulateClaim { sender: "user".to_string(), claim: aln_bridge::ExecuteMsg::MigrateBatch { limit: None } });
+    assert!(bad.is_err());
+    Ok(())
+}
+
+#[test]
+fn listing_queries_paginate_bridge_state() -> Result<()> {
+    let mut app = App::default();
+    let auet_code = app.store_code(Box::new(ContractWrapper::new(auet_instantiate, auet_execute, auet_query)));
+    let reg_code = app.store_code(Box::new(ContractWrapper::new(reg_instantiate, reg_execute, reg_query)));
+    let bridge_code = app.store_code(Box::new(ContractWrapper::new(bridge_instantiate, bridge_execute, bridge_query)));
+
+    let gov = Addr::unchecked("gov");
+    let auet_instantiate_msg = aln_auet::InstantiateMsg { cw20: Cw20InstantiateMsg { name: "AU.ET".to_string(), symbol: "AUET".to_string(), decimals: 6, initial_balances: vec![Cw20Coin { address: "bridge".to_string(), amount: Uint128::new(1_000_000) }], mint: None, marketing: None }, snapshot: aln_auet::SnapshotMeta { chain_id: "kaiyo-1".to_string(), height: 0, merkle_root: "root".to_string() }, allowed_modules: Some(vec![]) };
+    let auet_addr = app.instantiate_contract(auet_code, Addr::unchecked("creator"), &auet_instantiate_msg, &[], "AUET", None)?;
+    let reg_addr = app.instantiate_contract(reg_code, Addr::unchecked("creator"), &registry_init_msg(gov.to_string()), &[], "REG", None)?;
+    let bridge_msg = aln_bridge::InstantiateMsg { system_whitelist: Some(vec!["sys1".to_string(), "sys2".to_string(), "sys3".to_string()]), ..bridge_init_msg(auet_addr.to_string(), reg_addr.to_string(), gov.to_string()) };
+    let bridge = app.instantiate_contract(bridge_code, Addr::unchecked("creator"), &bridge_msg, &[], "BRIDGE", None)?;
+
+    // two assets, each with a leaf for user and user2
+    let mut leaves_of = std::collections::HashMap::new();
+    for (id, denom) in [("l1", "ibc/one"), ("l2", "ibc/two")] {
+        let snaps: Vec<aln_bridge::SnapshotEntry> = ["user", "user2"].iter().map(|a| aln_bridge::SnapshotEntry { chain_id: "kaiyo-1".to_string(), height: 0, denom: denom.to_string(), address: a.to_string(), balance: "10".to_string() }).collect();
+        let hashes: Vec<String> = snaps.iter().map(compute_snapshot_hash).collect();
+        let leaves: Vec<[u8; 32]> = hashes.iter().map(|h| { let mut a = [0u8; 32]; a.copy_from_slice(&hex::decode(h.trim_start_matches("0x")).unwrap()); a }).collect();
+        let (root, proofs) = build_merkle_and_proofs(&leaves);
+        let asset = aln_registry::RegisteredAsset { id: id.to_string(), source_chain: "kaiyo-1".to_string(), source_denom: denom.to_string(), snapshot_height: 0, merkle_root: root, ubs_report_hash: Some("hl".to_string()), scaling_profile_id: "clean".to_string(), activation_height: 0, sanitized_approved: true, merkle_version: aln_registry::MerkleVersion::V1, claim_deadline: None, total_supply: None, lifecycle: Default::default(), epochs: vec![] };
+        app.execute_contract(gov.clone(), reg_addr.clone(), &aln_registry::ExecuteMsg::RegisterAsset { asset }, &[])?;
+        leaves_of.insert(id, (snaps, hashes, proofs));
+    }
+    let claim = |id: &str, i: usize, nonce: u64| {
+        let (snaps, hashes, proofs) = &leaves_of[id];
+        let merkle_proof = proofs[i].iter().map(|(b, is_left)| aln_bridge::ProofStep { sibling: Binary(b.clone()), is_left: *is_left }).collect();
+        aln_bridge::ExecuteMsg::Claim { asset_id: id.to_string(), epoch: None, snapshot: snaps[i].clone(), snapshot_hash: hashes[i].clone(), merkle_proof, amount_auet: None, amount_csp: None, origin_tx_hash: Some(format!("0xtx{}", nonce)), origin_nonce: Some(nonce), ubs_report_hash: None }
+    };
+    app.execute_contract(Addr::unchecked("user"), bridge.clone(), &claim("l2", 0, 1), &[])?;
+    app.execute_contract(Addr::unchecked("user"), bridge.clone(), &claim("l1", 0, 2), &[])?;
+    app.execute_contract(Addr::unchecked("user2"), bridge.clone(), &claim("l1", 1, 3), &[])?;
+
+    // claims by address, one page at a time
+    let by_address = |start_after: Option<(String, u32, String)>| -> Vec<aln_bridge::ClaimRecord> { app.wrap().query_wasm_smart(bridge.clone(), &aln_bridge::QueryMsg::ClaimsByAddress { address: "user".to_string(), start_after, limit: Some(1) }).unwrap() };
+    let first = by_address(None);
+    assert_eq!((first.len(), first[0].asset_id.as_str()), (1, "l1"));
+    let second = by_address(Some((first[0].asset_id.clone(), first[0].epoch, first[0].snapshot_hash.clone())));
+    assert_eq!((second.len(), second[0].asset_id.as_str(), second[0].snapshot_hash.as_str()), (1, "l2", leaves_of["l2"].1[0].as_str()));
+    assert!(by_address(Some((second[0].asset_id.clone(), second[0].epoch, second[0].snapshot_hash.clone()))).is_empty());
+
+    // claims by asset
+    let by_asset: Vec<aln_bridge::ClaimRecord> = app.wrap().query_wasm_smart(bridge.clone(), &aln_bridge::QueryMsg::ClaimsByAsset { asset_id: "l1".to_string(), start_after: None, limit: None })?;
+    let mut recipients: Vec<&str> = by_asset.iter().map(|c| c.recipient.as_str()).collect();
+    recipients.sort();
+    assert_eq!(recipients, vec!["user", "user2"]);
+    let rest: Vec<aln_bridge::ClaimRecord> = app.wrap().query_wasm_smart(bridge.clone(), &aln_bridge::QueryMsg::ClaimsByAsset { asset_id: "l1".to_string(), start_after: Some((by_asset[0].epoch, by_asset[0].snapshot_hash.clone())), limit: None })?;
+    assert_eq!(rest, by_asset[1..].to_vec());
+
+    // ledger holders ordered by address
+    let holders: Vec<aln_bridge::EnergyHolder> = app.wrap().query_wasm_smart(bridge.clone(), &aln_bridge::QueryMsg::EnergyHolders { start_after: None, limit: None })?;
+    assert_eq!(holders.iter().map(|h| h.address.as_str()).collect::<Vec<_>>(), vec!["user", "user2"]);
+    assert_eq!(holders[0].energy.auet, Uint128::new(30));
+    let holders: Vec<aln_bridge::EnergyHolder> = app.wrap().query_wasm_smart(bridge.clone(), &aln_bridge::QueryMsg::EnergyHolders { start_after: Some("user".to_string()), limit: None })?;
+    assert_eq!(holders.len(), 1);
+
+    // removed system contracts are not listed
+    app.execute_contract(gov.clone(), bridge.clone(), &aln_bridge::ExecuteMsg::RemoveSystemWhitelist { addr: "sys2".to_string() }, &[])?;
+    let list: Vec<Addr> = app.wrap().query_wasm_smart(bridge.clone(), &aln_bridge::QueryMsg::SystemWhitelist { start_after: None, limit: None })?;
+    assert_eq!(list, vec![Addr::unchecked("sys1"), Addr::unchecked("sys3")]);
+    let list: Vec<Addr> = app.wrap().query_wasm_smart(bridge.clone(), &aln_bridge::QueryMsg::SystemWhitelist { start_after: Some("sys1".to_string()), limit: Some(1) })?;
+    assert_eq!(list, vec![Addr::unchecked("sys3")]);
+
+    // refactor audits of the origin chain
+    let audits: Vec<aln_bridge::RefactorAuditEntry> = app.wrap().query_wasm_smart(bridge.clone(), &aln_bridge::QueryMsg::RefactorAudits { origin_chain: "kaiyo-1".to_string(), start_after: None, limit: Some(2) })?;
+    assert_eq!(audits.iter().map(|a| (a.tx_hash.as_str(), a.nonce)).collect::<Vec<_>>(), vec![("0xtx1", 1), ("0xtx2", 2)]);
+    let audits: Vec<aln_bridge::RefactorAuditEntry> = app.wrap().query_wasm_smart(bridge.clone(), &aln_bridge::QueryMsg::RefactorAudits { origin_chain: "kaiyo-1".to_string(), start_after: Some(("0xtx2".to_string(), 2)), limit: None })?;
+    assert_eq!(audits.len(), 1);
+    assert_eq!(audits[0].nonce, 3);
+    let none: Vec<aln_bridge::RefactorAuditEntry> = app.wrap().query_wasm_smart(bridge.clone(), &aln_bridge::QueryMsg::RefactorAudits { origin_chain: "osmosis-1".to_string(), start_after: None, limit: None })?;
+    assert!(none.is_empty());
+    Ok(())
+}
+
+#[test]
+fn ledger_checkpoint_matches_recomputed_commitment() -> Result<()> {
+    let mut app = App::default();
+    let auet_code = app.store_code(Box::new(ContractWrapper::new(auet_instantiate, auet_execute, auet_query)));
+    let reg_code = app.store_code(Box::new(ContractWrapper::new(reg_instantiate, reg_execute, reg_query)));
+    let bridge_code = app.store_code(Box::new(ContractWrapper::new(bridge_instantiate, bridge_execute, bridge_query)));
+
+    let gov = Addr::unchecked("gov");
+    let auet_instantiate_msg = aln_auet::InstantiateMsg { cw20: Cw20InstantiateMsg { name: "AU.ET".to_string(), symbol: "AUET".to_string(), decimals: 6, initial_balances: vec![Cw20Coin { address: "bridge".to_string(), amount: Uint128::new(1_000_000) }], mint: None, marketing: None }, snapshot: aln_auet::SnapshotMeta { chain_id: "kaiyo-1".to_string(), height: 0, merkle_root: "root".to_string() }, allowed_modules: Some(vec![]) };
+    let auet_addr = app.instantiate_contract(auet_code, Addr::unchecked("creator"), &auet_instantiate_msg, &[], "AUET", None)?;
+    let reg_addr = app.instantiate_contract(reg_code, Addr::unchecked("creator"), &registry_init_msg(gov.to_string()), &[], "REG", None)?;
+
+    let snaps: Vec<aln_bridge::SnapshotEntry> = [("user", "100"), ("user2", "40"), ("user3", "20")].iter().map(|(a, b)| aln_bridge::SnapshotEntry { chain_id: "kaiyo-1".to_string(), height: 0, denom: "ibc/led".to_string(), address: a.to_string(), balance: b.to_string() }).collect();
+    let hashes: Vec<String> = snaps.iter().map(compute_snapshot_hash).collect();
+    let leaves: Vec<[u8; 32]> = hashes.iter().map(|h| { let mut a = [0u8; 32]; a.copy_from_slice(&hex::decode(h.trim_start_matches("0x")).unwrap()); a }).collect();
+    let (root, proofs) = build_merkle_and_proofs(&leaves);
+    let asset = aln_registry::RegisteredAsset { id: "lg".to_string(), source_chain: "kaiyo-1".to_string(), source_denom: "ibc/led".to_string(), snapshot_height: 0, merkle_root: root, ubs_report_hash: Some("hl".to_string()), scaling_profile_id: "clean".to_string(), activation_height: 0, sanitized_approved: true, merkle_version: aln_registry::MerkleVersion::V1, claim_deadline: None, total_supply: None, lifecycle: Default::default(), epochs: vec![] };
+    app.execute_contract(gov.clone(), reg_addr.clone(), &aln_registry::ExecuteMsg::RegisterAsset { asset }, &[])?;
+    let bridge = app.instantiate_contract(bridge_code, Addr::unchecked("creator"), &bridge_init_msg(auet_addr.to_string(), reg_addr.to_string(), gov.to_string()), &[], "BRIDGE", None)?;
+    let checkpoint = |app: &App, height: Option<u64>| -> Option<aln_bridge::LedgerCheckpoint> { app.wrap().query_wasm_smart(bridge.clone(), &aln_bridge::QueryMsg::LedgerCheckpoint { height }).unwrap() };
+
+    // an empty ledger commits to the zero root
+    assert!(checkpoint(&app, None).is_none());
+    assert!(app.execute_contract(Addr::unchecked("user"), bridge.clone(), &aln_bridge::ExecuteMsg::CheckpointLedger { limit: None }, &[]).is_err());
+    app.execute_contract(gov.clone(), bridge.clone(), &aln_bridge::ExecuteMsg::CheckpointLedger { limit: None }, &[])?;
+    let empty_height = app.block_info().height;
+    assert_eq!(checkpoint(&app, None).unwrap().root, format!("0x{}", "00".repeat(32)));
+
+    let claim = |app: &mut App, i: usize| -> Result<()> {
+        let merkle_proof = proofs[i].iter().map(|(b, is_left)| aln_bridge::ProofStep { sibling: Binary(b.clone()), is_left: *is_left }).collect();
+        app.execute_contract(Addr::unchecked(snaps[i].address.as_str()), bridge.clone(), &aln_bridge::ExecuteMsg::Claim { asset_id: "lg".to_string(), epoch: None, snapshot: snaps[i].clone(), snapshot_hash: hashes[i].clone(), merkle_proof, amount_auet: None, amount_csp: None, origin_tx_hash: None, origin_nonce: None, ubs_report_hash: None }, &[])?;
+        Ok(())
+    };
+    claim(&mut app, 0)?;
+    claim(&mut app, 1)?;
+    app.update_block(|b| b.height += 1);
+    let holders: Vec<aln_bridge::EnergyHolder> = app.wrap().query_wasm_smart(bridge.clone(), &aln_bridge::QueryMsg::EnergyHolders { start_after: None, limit: None })?;
+
+    // one entry per batch: the checkpoint stays pending and refuses a second start
+    app.execute_contract(gov.clone(), bridge.clone(), &aln_bridge::ExecuteMsg::CheckpointLedger { limit: Some(1) }, &[])?;
+    let started = app.block_info().height;
+    let pending: Option<aln_bridge::PendingCheckpoint> = app.wrap().query_wasm_smart(bridge.clone(), &aln_bridge::QueryMsg::PendingCheckpoint {})?;
+    assert_eq!(pending.unwrap().entries, 1);
+    assert!(app.execute_contract(gov.clone(), bridge.clone(), &aln_bridge::ExecuteMsg::CheckpointLedger { limit: None }, &[]).is_err());
+    assert_eq!(checkpoint(&app, None).unwrap().height, empty_height);
+
+    // a ledger change after the start is not part of the commitment
+    app.update_block(|b| b.height += 1);
+    claim(&mut app, 2)?;
+    app.execute_contract(Addr::unchecked("anyone"), bridge.clone(), &aln_bridge::ExecuteMsg::ContinueCheckpoint { limit: None }, &[])?;
+    assert!(app.execute_contract(Addr::unchecked("anyone"), bridge.clone(), &aln_bridge::ExecuteMsg::ContinueCheckpoint { limit: None }, &[]).is_err());
+    let latest = checkpoint(&app, None).unwrap();
+    assert_eq!((latest.height, latest.entries), (started, 2));
+    assert_eq!(latest.total.auet, Uint128::new(210));
+
+    // auditors recompute the root from the EnergyHolders dump at the checkpoint height
+    let ledger_leaves: Vec<[u8; 32]> = holders.iter().map(|h| aln_merkle::ledger_leaf_hash(h.address.as_str(), h.energy.auet.u128(), h.energy.csp.u128(), h.energy.erp.u128())).collect();
+    assert_eq!(latest.root, format!("0x{}", hex::encode(aln_merkle::ledger_root(&ledger_leaves))));
+    // earlier checkpoints stay queryable by height
+    assert_eq!(checkpoint(&app, Some(empty_height)).unwrap().entries, 0);
+    Ok(())
+}
+
+#[test]
+fn claim_deadline_closes_claims_and_sweep_records_unclaimed() -> Result<()> {
+    let mut app = App::default();
+    let auet_code = app.store_code(Box::new(ContractWrapper::new(auet_instantiate, auet_execute, auet_query)));
+    let reg_code = app.store_code(Box::new(ContractWrapper::new(reg_instantiate, reg_execute, reg_query)));
+    let bridge_code = app.store_code(Box::new(ContractWrapper::new(bridge_instantiate, bridge_execute, bridge_query)));
+
+    let gov = Addr::unchecked("gov");
+    let auet_instantiate_msg = aln_auet::InstantiateMsg { cw20: Cw20InstantiateMsg { name: "AU.ET".to_string(), symbol: "AUET".to_string(), decimals: 6, initial_balances: vec![Cw20Coin { address: "bridge".to_string(), amount: Uint128::new(1_000_000) }], mint: None, marketing: None }, snapshot: aln_auet::SnapshotMeta { chain_id: "kaiyo-1".to_string(), height: 0, merkle_root: "root".to_string() }, allowed_modules: Some(vec![]) };
+    let auet_addr = app.instantiate_contract(auet_code, Addr::unchecked("creator"), &auet_instantiate_msg, &[], "AUET", None)?;
+    let reg_addr = app.instantiate_contract(reg_code, Addr::unchecked("creator"), &registry_init_msg(gov.to_string()), &[], "REG", None)?;
+    let bridge = app.instantiate_contract(bridge_code, Addr::unchecked("creator"), &bridge_init_msg(auet_addr.to_string(), reg_addr.to_string(), gov.to_string()), &[], "BRIDGE", None)?;
+
+    // 30 registered: user holds 10, user2 holds 20
+    let snaps: Vec<aln_bridge::SnapshotEntry> = [("user", "10"), ("user2", "20")].iter().map(|(a, b)| aln_bridge::SnapshotEntry { chain_id: "kaiyo-1".to_string(), height: 0, denom: "ibc/ddd".to_string(), address: a.to_string(), balance: b.to_string() }).collect();
+    let hashes: Vec<String> = snaps.iter().map(compute_snapshot_hash).collect();
+    let leaves: Vec<[u8; 32]> = hashes.iter().map(|h| { let mut a = [0u8; 32]; a.copy_from_slice(&hex::decode(h.trim_start_matches("0x")).unwrap()); a }).collect();
+    let (root, proofs) = build_merkle_and_proofs(&leaves);
+    let deadline = app.block_info().height + 10;
+    let asset = aln_registry::RegisteredAsset { id: "d1".to_string(), source_chain: "kaiyo-1".to_string(), source_denom: "ibc/ddd".to_string(), snapshot_height: 0, merkle_root: root, ubs_report_hash: Some("hd".to_string()), scaling_profile_id: "clean".to_string(), activation_height: 0, sanitized_approved: true, merkle_version: aln_registry::MerkleVersion::V1, claim_deadline: Some(deadline), total_supply: Some(Uint128::new(30)), lifecycle: Default::default(), epochs: vec![] };
+    app.execute_contract(gov.clone(), reg_addr.clone(), &aln_registry::ExecuteMsg::RegisterAsset { asset }, &[])?;
+    let claim = |i: usize| {
+        let merkle_proof = proofs[i].iter().map(|(b, is_left)| aln_bridge::ProofStep { sibling: Binary(b.clone()), is_left: *is_left }).collect();
+        aln_bridge::ExecuteMsg::Claim { asset_id: "d1".to_string(), epoch: None, snapshot: snaps[i].clone(), snapshot_hash: hashes[i].clone(), merkle_proof, amount_auet: None, amount_csp: None, origin_tx_hash: None, origin_nonce: None, ubs_report_hash: None }
+    };
+    let sweep = aln_bridge::ExecuteMsg::SweepUnclaimed { asset_id: "d1".to_string() };
+    let downcast = |r: Result<AppResponse>| r.unwrap_err().downcast::<aln_bridge::ContractError>().unwrap();
+
+    app.execute_contract(Addr::unchecked("user"), bridge.clone(), &claim(0), &[])?;
+    let stats: aln_bridge::AssetClaimStatsResponse = app.wrap().query_wasm_smart(bridge.clone(), &aln_bridge::QueryMsg::AssetClaimStats { asset_id: "d1".to_string() })?;
+    assert_eq!((stats.claims, stats.claimed, stats.unclaimed, stats.sweep), (1, Uint128::new(10), Some(Uint128::new(20)), None));
+    assert_eq!(downcast(app.execute_contract(gov.clone(), bridge.clone(), &sweep, &[])), aln_bridge::ContractError::ClaimDeadlineNotPassed);
+
+    // at the deadline claims close and governance can sweep, once
+    app.update_block(|b| b.height = deadline);
+    assert_eq!(downcast(app.execute_contract(Addr::unchecked("user2"), bridge.clone(), &claim(1), &[])), aln_bridge::ContractError::ClaimDeadlinePassed);
+    assert_eq!(downcast(app.execute_contract(Addr::unchecked("user"), bridge.clone(), &sweep, &[])), aln_bridge::ContractError::Unauthorized { action: "sweep_unclaimed".to_string() });
+    app.execute_contract(gov.clone(), bridge.clone(), &sweep, &[])?;
+    assert_eq!(downcast(app.execute_contract(gov.clone(), bridge.clone(), &sweep, &[])), aln_bridge::ContractError::AlreadySwept { asset_id: "d1".to_string() });
+    let sweeps: Vec<aln_bridge::TreasurySweep> = app.wrap().query_wasm_smart(bridge.clone(), &aln_bridge::QueryMsg::TreasurySweeps { start_after: None, limit: None })?;
+    assert_eq!(sweeps.iter().map(|s| (s.asset_id.as_str(), s.first_epoch, s.last_epoch, s.height, s.claimed, s.unclaimed)).collect::<Vec<_>>(), vec![("d1", 0, 0, deadline, Uint128::new(10), Uint128::new(20))]);
+    let stats: aln_bridge::AssetClaimStatsResponse = app.wrap().query_wasm_smart(bridge.clone(), &aln_bridge::QueryMsg::AssetClaimStats { asset_id: "d1".to_string() })?;
+    assert_eq!(stats.sweep, Some(sweeps[0].clone()));
+
+    // an epoch added after the sweep is swept on its own once it closes
+    let late = aln_registry::SnapshotEpoch { epoch: 1, merkle_root: "root1".to_string(), snapshot_height: 1, activation_height: deadline, claim_deadline: Some(deadline + 10), total_supply: Some(Uint128::new(5)), ..Default::default() };
+    app.execute_contract(gov.clone(), reg_addr.clone(), &aln_registry::ExecuteMsg::AddSnapshotEpoch { id: "d1".to_string(), epoch: late }, &[])?;
+    assert_eq!(downcast(app.execute_contract(gov.clone(), bridge.clone(), &sweep, &[])), aln_bridge::ContractError::ClaimDeadlineNotPassed);
+    app.update_block(|b| b.height = deadline + 10);
+    app.execute_contract(gov.clone(), bridge.clone(), &sweep, &[])?;
+    let sweeps: Vec<aln_bridge::TreasurySweep> = app.wrap().query_wasm_smart(bridge.clone(), &aln_bridge::QueryMsg::TreasurySweeps { start_after: Some(("d1".to_string(), 0)), limit: None })?;
+    assert_eq!(sweeps.iter().map(|s| (s.first_epoch, s.last_epoch, s.claimed, s.unclaimed)).collect::<Vec<_>>(), vec![(1, 1, Uint128::zero(), Uint128::new(5))]);
+    assert_eq!(downcast(app.execute_contract(gov.clone(), bridge.clone(), &sweep, &[])), aln_bridge::ContractError::AlreadySwept { asset_id: "d1".to_string() });
+    Ok(())
+}
+
+#[test]
+fn anomalous_claims_are_quarantined_until_governance_decides() -> Result<()> {
+    let mut app = App::default();
+    // plain cw20 stands in for AU.ET
+    let cw20_code = app.store_code(Box::new(ContractWrapper::new(cw20_base::contract::instantiate, cw20_base::contract::execute, cw20_base::contract::query)));
+    let reg_code = app.store_code(Box::new(ContractWrapper::new(reg_instantiate, reg_execute, reg_query)));
+    let bridge_code = app.store_code(Box::new(ContractWrapper::new(bridge_instantiate, bridge_execute, bridge_query)));
+
+    let gov = Addr::unchecked("gov");
+    let cw20_msg = Cw20InstantiateMsg { name: "AU.ET".to_string(), symbol: "AUET".to_string(), decimals: 6, initial_balances: vec![Cw20Coin { address: "contract2".to_string(), amount: Uint128::new(1_000_000) }], mint: None, marketing: None };
+    let auet_addr = app.instantiate_contract(cw20_code, Addr::unchecked("creator"), &cw20_msg, &[], "AUET", None)?;
+    let reg_addr = app.instantiate_contract(reg_code, Addr::unchecked("creator"), &registry_init_msg(gov.to_string()), &[], "REG", None)?;
+    let bridge_msg = aln_bridge::InstantiateMsg { toxic_sink: Some("sink".to_string()), anomaly_threshold_amount: Some(Uint128::new(50)), ..bridge_init_msg(auet_addr.to_string(), reg_addr.to_string(), gov.to_string()) };
+    let bridge = app.instantiate_contract(bridge_code, Addr::unchecked("creator"), &bridge_msg, &[], "BRIDGE", None)?;
+    assert_eq!(bridge.as_str(), "contract2");
+
+    let snaps: Vec<aln_bridge::SnapshotEntry> = [("user", "100"), ("user2", "200")].iter().map(|(a, b)| aln_bridge::SnapshotEntry { chain_id: "kaiyo-1".to_string(), height: 0, denom: "ibc/qqq".to_string(), address: a.to_string(), balance: b.to_string() }).collect();
+    let hashes: Vec<String> = snaps.iter().map(compute_snapshot_hash).collect();
+    let leaves: Vec<[u8; 32]> = hashes.iter().map(|h| { let mut a = [0u8; 32]; a.copy_from_slice(&hex::decode(h.trim_start_matches("0x")).unwrap()); a }).collect();
+    let (root, proofs) = build_merkle_and_proofs(&leaves);
+    let asset = aln_registry::RegisteredAsset { id: "q1".to_string(), source_chain: "kaiyo-1".to_string(), source_denom: "ibc/qqq".to_string(), snapshot_height: 0, merkle_root: root, ubs_report_hash: Some("hq".to_string()), scaling_profile_id: "clean".to_string(), activation_height: 0, sanitized_approved: true, merkle_version: aln_registry::MerkleVersion::V1, claim_deadline: None, total_supply: None, lifecycle: Default::default(), epochs: vec![] };
+    app.execute_contract(gov.clone(), reg_addr.clone(), &aln_registry::ExecuteMsg::RegisterAsset { asset }, &[])?;
+    let claim = |i: usize| {
+        let merkle_proof = proofs[i].iter().map(|(b, is_left)| aln_bridge::ProofStep { sibling: Binary(b.clone()), is_left: *is_left }).collect();
+        aln_bridge::ExecuteMsg::Claim { asset_id: "q1".to_string(), epoch: None, snapshot: snaps[i].clone(), snapshot_hash: hashes[i].clone(), merkle_proof, amount_auet: None, amount_csp: None, origin_tx_hash: Some(format!("0xq{}", i)), origin_nonce: Some(i as u64), ubs_report_hash: None }
+    };
+    let downcast = |r: Result<AppResponse>| r.unwrap_err().downcast::<aln_bridge::ContractError>().unwrap();
+    let open_cases = |app: &App| -> Vec<aln_bridge::QuarantineCase> { app.wrap().query_wasm_smart(bridge.clone(), &aln_bridge::QueryMsg::OpenQuarantineCases { start_after: None, limit: None }).unwrap() };
+    let balance = |app: &App, who: &str| -> aln_bridge::EnergyVector { app.wrap().query_wasm_smart(bridge.clone(), &aln_bridge::QueryMsg::EnergyBalance { address: who.to_string() }).unwrap() };
+
+    // both claims are over the threshold: each opens a case holding its sanitized energy, and
+    // nothing reaches the claimant or the sink
+    for (i, who) in ["user", "user2"].iter().enumerate() {
+        let res = app.execute_contract(Addr::unchecked(*who), bridge.clone(), &claim(i), &[])?;
+        assert!(res.events.iter().any(|e| e.ty == "wasm-aln_quarantine" && e.attributes.iter().any(|a| a.key == "action" && a.value == "opened")));
+    }
+    let sink_balance: cw20::BalanceResponse = app.wrap().query_wasm_smart(auet_addr.clone(), &Cw20QueryMsg::Balance { address: "sink".to_string() })?;
+    assert_eq!(sink_balance.balance, Uint128::zero());
+    assert_eq!(balance(&app, "user").auet, Uint128::zero());
+    let cases = open_cases(&app);
+    assert_eq!(cases.iter().map(|c| (c.id, c.claimant.as_str(), c.amount, c.entitled)).collect::<Vec<_>>(), vec![(1, "user", Uint128::new(100), Uint128::new(150)), (2, "user2", Uint128::new(200), Uint128::new(300))]);
+    assert_eq!((cases[0].reason.clone(), cases[0].ubs_report_hash.as_deref(), cases[0].origin_nonce), (aln_bridge::QuarantineReason::AnomalyThreshold { threshold: Uint128::new(50) }, Some("hq"), Some(0)));
+    assert_eq!((cases[0].energy.auet, cases[0].energy.csp), (Uint128::new(150), Uint128::new(75)));
+    let page: Vec<aln_bridge::QuarantineCase> = app.wrap().query_wasm_smart(bridge.clone(), &aln_bridge::QueryMsg::OpenQuarantineCases { start_after: Some(1), limit: Some(1) })?;
+    assert_eq!(page.iter().map(|c| c.id).collect::<Vec<_>>(), vec![2]);
+    let total_energy = |app: &App| -> Uint128 { app.wrap().query_wasm_smart(bridge.clone(), &aln_bridge::QueryMsg::TotalEnergy {}).unwrap() };
+    assert_eq!(total_energy(&app), Uint128::new(450));
+
+    // governance releases one to the claimant's ledger and burns the other out of the totals
+    let release = aln_bridge::ExecuteMsg::ReleaseQuarantine { id: 1, note: Some("reviewed: legitimate holder".to_string()) };
+    assert_eq!(downcast(app.execute_contract(Addr::unchecked("user"), bridge.clone(), &release, &[])), aln_bridge::ContractError::Unauthorized { action: "release_quarantine".to_string() });
+    app.execute_contract(gov.clone(), bridge.clone(), &release, &[])?;
+    assert_eq!(balance(&app, "user"), cases[0].energy);
+    assert_eq!(downcast(app.execute_contract(gov.clone(), bridge.clone(), &release, &[])), aln_bridge::ContractError::QuarantineCaseClosed { id: 1 });
+    app.execute_contract(gov.clone(), bridge.clone(), &aln_bridge::ExecuteMsg::BurnQuarantine { id: 2, note: None }, &[])?;
+    assert_eq!(balance(&app, "user2").auet, Uint128::zero());
+    assert_eq!(total_energy(&app), Uint128::new(150));
+    assert!(open_cases(&app).is_empty());
+    assert_eq!(downcast(app.execute_contract(gov.clone(), bridge.clone(), &aln_bridge::ExecuteMsg::BurnQuarantine { id: 3, note: None }, &[])), aln_bridge::ContractError::QuarantineCaseNotFound { id: 3 });
+
+    // toxic-profile claims are quarantined whatever their size; a release meets the rate limits
+    let s_tox = aln_bridge::SnapshotEntry { chain_id: "kaiyo-1".to_string(), height: 0, denom: "ibc/tox".to_string(), address: "user3".to_string(), balance: "10".to_string() };
+    let h_tox = compute_snapshot_hash(&s_tox);
+    let asset = aln_registry::RegisteredAsset { id: "t1".to_string(), source_chain: "kaiyo-1".to_string(), source_denom: "ibc/tox".to_string(), snapshot_height: 0, merkle_root: h_tox.clone(), ubs_report_hash: Some("ht".to_string()), scaling_profile_id: "malicious_cleanup".to_string(), activation_height: 0, sanitized_approved: true, merkle_version: aln_registry::MerkleVersion::V1, claim_deadline: None, total_supply: None, lifecycle: Default::default(), epochs: vec![] };
+    app.execute_contract(gov.clone(), reg_addr.clone(), &aln_registry::ExecuteMsg::RegisterAsset { asset }, &[])?;
+    let claim_tox = aln_bridge::ExecuteMsg::Claim { asset_id: "t1".to_string(), epoch: None, snapshot: s_tox, snapshot_hash: h_tox, merkle_proof: vec![], amount_auet: None, amount_csp: None, origin_tx_hash: None, origin_nonce: None, ubs_report_hash: None };
+    let res = app.execute_contract(Addr::unchecked("user3"), bridge.clone(), &claim_tox, &[])?;
+    assert!(res.events.iter().flat_map(|e| e.attributes.iter()).any(|a| a.key == "action" && a.value == "claim_quarantined"));
+    let cases = open_cases(&app);
+    assert_eq!(cases.iter().map(|c| (c.id, c.reason.clone(), c.toxic)).collect::<Vec<_>>(), vec![(3, aln_bridge::QuarantineReason::ToxicProfile, true)]);
+    let limit = aln_bridge::RateLimit { window: aln_bridge::RateWindow::Height(100), max_per_asset: Some(Uint128::new(1)), max_per_recipient: None };
+    app.execute_contract(gov.clone(), bridge.clone(), &aln_bridge::ExecuteMsg::SetRateLimit { asset_id: "t1".to_string(), limit: Some(limit) }, &[])?;
+    app.execute_contract(gov.clone(), bridge.clone(), &aln_bridge::ExecuteMsg::ReleaseQuarantine { id: 3, note: None }, &[])?;
+    assert_eq!(balance(&app, "user3").auet, Uint128::zero());
+    let queued: Vec<aln_bridge::QueuedClaim> = app.wrap().query_wasm_smart(bridge.clone(), &aln_bridge::QueryMsg::QueuedClaims { start_after: None, limit: None })?;
+    assert_eq!(queued.iter().map(|q| (q.recipient.as_str(), q.amount, q.toxic)).collect::<Vec<_>>(), vec![("user3", Uint128::new(15), true)]);
+    app.execute_contract(gov.clone(), bridge.clone(), &aln_bridge::ExecuteMsg::ReleaseQueuedClaim { id: queued[0].id }, &[])?;
+    assert_eq!(balance(&app, "user3"), cases[0].energy);
+
+    // closed cases keep their audit trail
+    let case: Option<aln_bridge::QuarantineCase> = app.wrap().query_wasm_smart(bridge.clone(), &aln_bridge::QueryMsg::QuarantineCase { id: 1 })?;
+    let case = case.unwrap();
+    assert_eq!(case.status, aln_bridge::QuarantineStatus::Released);
+    assert_eq!(case.history.iter().map(|a| (a.action.as_str(), a.actor.as_str())).collect::<Vec<_>>(), vec![("opened", "contract2"), ("released", "gov")]);
+    assert_eq!(case.history[1].note.as_deref(), Some("reviewed: legitimate holder"));
+    Ok(())
+}
+
+#[test]
+fn light_client_proves_origin_lock_events() -> Result<()> {
+    // header chain and lock proof recorded by `cargo run --example record_fixtures` in crates/aln_light_client
+    let fixture: serde_json::Value = serde_json::from_str(include_str!("../../../crates/aln_light_client/tests/fixtures/aln-origin-1.json"))?;
+    let field = |name: &str| fixture[name].clone();
+    let trusted: aln_light_client::TrustedState = serde_json::from_value(field("trusted"))?;
+    let options: aln_light_client::TrustOptions = serde_json::from_value(field("options"))?;
+    let now: aln_light_client::Timestamp = serde_json::from_value(field("now"))?;
+    let update = |name: &str| -> aln_light_client::HeaderUpdate { serde_json::from_value(field(name)).unwrap() };
+    let lock: aln_light_client::LockEvent = serde_json::from_value(field("lock_event"))?;
+    let proofs: Vec<aln_light_client::ics23::ExistenceProof> = serde_json::from_value(field("lock_proofs"))?;
+
+    let mut app = App::default();
+    app.update_block(|b| b.time = cosmwasm_std::Timestamp::from_seconds(now.seconds as u64));
+    let auet_code = app.store_code(Box::new(ContractWrapper::new(auet_instantiate, auet_execute, auet_query)));
+    let reg_code = app.store_code(Box::new(ContractWrapper::new(reg_instantiate, reg_execute, reg_query)));
+    let bridge_code = app.store_code(Box::new(ContractWrapper::new(bridge_instantiate, bridge_execute, bridge_query)));
+
+    let gov = Addr::unchecked("gov");
+    let auet_instantiate_msg = aln_auet::InstantiateMsg { cw20: Cw20InstantiateMsg { name: "AU.ET".to_string(), symbol: "AUET".to_string(), decimals: 6, initial_balances: vec![Cw20Coin { address: "bridge".to_string(), amount: Uint128::new(1_000_000) }], mint: None, marketing: None }, snapshot: aln_auet::SnapshotMeta { chain_id: "aln-origin-1".to_string(), height: 0, merkle_root: "root".to_string() }, allowed_modules: Some(vec![]) };
+    let auet_addr = app.instantiate_contract(auet_code, Addr::unchecked("creator"), &auet_instantiate_msg, &[], "AUET", None)?;
+    let reg_addr = app.instantiate_contract(reg_code, Addr::unchecked("creator"), &registry_init_msg(gov.to_string()), &[], "REG", None)?;
+    let bridge = app.instantiate_contract(bridge_code, Addr::unchecked("creator"), &bridge_init_msg(auet_addr.to_string(), reg_addr.to_string(), gov.to_string()), &[], "BRIDGE", None)?;
+
+    // the asset's snapshot tree holds the locked event and one other holder
+    let origin = aln_bridge::OriginLockEvent { origin_chain_id: "aln-origin-1".to_string(), tx_hash: lock.tx_hash.clone(), nonce: lock.nonce, denom: lock.denom.clone(), origin_address: lock.origin_address.clone(), amount: lock.amount.clone(), height: Some(lock.height) };
+    let snaps = [origin.origin_address.as_str(), "kujira1other"].map(|a| aln_bridge::SnapshotEntry { chain_id: "aln-origin-1".to_string(), height: lock.height, denom: lock.denom.clone(), address: a.to_string(), balance: lock.amount.clone() });
+    let leaves: Vec<[u8; 32]> = snaps.iter().map(|s| { let mut a = [0u8; 32]; a.copy_from_slice(&hex::decode(compute_snapshot_hash(s).trim_start_matches("0x")).unwrap()); a }).collect();
+    let (root, merkle_proofs) = build_merkle_and_proofs(&leaves);
+    let asset = aln_registry::RegisteredAsset { id: "o1".to_string(), source_chain: "aln-origin-1".to_string(), source_denom: lock.denom.clone(), snapshot_height: lock.height, merkle_root: root, ubs_report_hash: Some("ho".to_string()), scaling_profile_id: "clean".to_string(), activation_height: 0, sanitized_approved: true, merkle_version: aln_registry::MerkleVersion::V1, claim_deadline: None, total_supply: None, lifecycle: Default::default(), epochs: vec![] };
+    app.execute_contract(gov.clone(), reg_addr.clone(), &aln_registry::ExecuteMsg::RegisterAsset { asset }, &[])?;
+    let claim = |origin_proof: Option<aln_bridge::OriginLockProof>| {
+        let merkle_proof = merkle_proofs[0].iter().map(|(b, is_left)| aln_bridge::ProofStep { sibling: Binary(b.clone()), is_left: *is_left }).collect();
+        aln_bridge::ExecuteMsg::ClaimWithOrigin { asset_id: "o1".to_string(), epoch: None, origin_event: origin.clone(), merkle_proof, ubs_report_hash: None, amount_auet: None, amount_csp: None, origin_proof }
+    };
+    let proof_at = |height: u64| Some(aln_bridge::OriginLockProof { height, proofs: proofs.clone() });
+    let holder = Addr::unchecked(origin.origin_address.as_str());
+    let downcast = |r: Result<AppResponse>| r.unwrap_err().downcast::<aln_bridge::ContractError>().unwrap();
+    let chain_id = || "aln-origin-1".to_string();
+
+    // a proof for a chain without a client is refused; governance alone starts one
+    assert_eq!(downcast(app.execute_contract(holder.clone(), bridge.clone(), &claim(proof_at(150)), &[])), aln_bridge::ContractError::LightClientNotFound { chain_id: chain_id() });
+    let set_client = aln_bridge::ExecuteMsg::SetLightClient { chain_id: chain_id(), client: Some(aln_bridge::LightClientInit { trusted, options, require_proofs: true }) };
+    assert_eq!(downcast(app.execute_contract(holder.clone(), bridge.clone(), &set_client, &[])), aln_bridge::ContractError::Unauthorized { action: "set_light_client".to_string() });
+    app.execute_contract(gov.clone(), bridge.clone(), &set_client, &[])?;
+    assert_eq!(downcast(app.execute_contract(holder.clone(), bridge.clone(), &claim(None), &[])), aln_bridge::ContractError::OriginProofRequired { chain_id: chain_id() });
+    assert_eq!(downcast(app.execute_contract(holder.clone(), bridge.clone(), &claim(proof_at(150)), &[])), aln_bridge::ContractError::ConsensusRootNotFound { chain_id: chain_id(), height: 150 });
+
+    // relayers: a validator set unknown to the client is rejected, the real chain is accepted
+    let relay = |name: &str| aln_bridge::ExecuteMsg::UpdateLightClient { chain_id: chain_id(), update: update(name) };
+    assert!(matches!(downcast(app.execute_contract(Addr::unchecked("relayer"), bridge.clone(), &relay("untrusted_skip"), &[])), aln_bridge::ContractError::LightClient(_)));
+    app.execute_contract(Addr::unchecked("relayer"), bridge.clone(), &relay("adjacent"), &[])?;
+    let res = app.execute_contract(Addr::unchecked("relayer"), bridge.clone(), &relay("skipping"), &[])?;
+    assert!(res.events.iter().any(|e| e.ty == "wasm-aln_light_client" && e.attributes.iter().any(|a| a.key == "height" && a.value == "150")));
+    let latest: Option<aln_bridge::ConsensusRoot> = app.wrap().query_wasm_smart(bridge.clone(), &aln_bridge::QueryMsg::ConsensusRoot { chain_id: chain_id(), height: None })?;
+    assert_eq!(latest.unwrap().app_hash, hex::encode(&update("skipping").signed_header.header.app_hash));
+    let client: Option<aln_bridge::LightClientState> = app.wrap().query_wasm_smart(bridge.clone(), &aln_bridge::QueryMsg::LightClient { chain_id: chain_id() })?;
+    assert_eq!(client.unwrap().latest.header.height, 150);
+
+    // the proof only holds against the app hash it was taken from
+    assert!(matches!(downcast(app.execute_contract(holder.clone(), bridge.clone(), &claim(proof_at(101)), &[])), aln_bridge::ContractError::LightClient(_)));
+    app.execute_contract(holder.clone(), bridge.clone(), &claim(proof_at(150)), &[])?;
+    let balance: aln_bridge::EnergyVector = app.wrap().query_wasm_smart(bridge.clone(), &aln_bridge::QueryMsg::EnergyBalance { address: holder.to_string() })?;
+    assert_eq!(balance.auet, Uint128::new(1_000_000));
+    Ok(())
+}
+
+fn hook_subscriber_execute(_deps: cosmwasm_std::DepsMut, _env: cosmwasm_std::Env, _info: cosmwasm_std::MessageInfo, msg: aln_bridge::LedgerHookMsg) -> Result<cosmwasm_std::Response, cosmwasm_std::StdError> {
+    let aln_bridge::LedgerHookMsg::LedgerChanged { owner, before, after, reason } = msg;
+    let reason = serde_json::to_string(&reason).unwrap();
+    Ok(cosmwasm_std::Response::new().add_attribute("owner", owner).add_attribute("before", before.auet.to_string()).add_attribute("after", after.auet.to_string()).add_attribute("reason", reason.trim_matches('"')))
+}
+
+fn hook_broken_execute(_deps: cosmwasm_std::DepsMut, _env: cosmwasm_std::Env, _info: cosmwasm_std::MessageInfo, _msg: aln_bridge::LedgerHookMsg) -> Result<cosmwasm_std::Response, cosmwasm_std::StdError> {
+    Err(cosmwasm_std::StdError::generic_err("subscriber down"))
+}
+
+fn hook_instantiate(_deps: cosmwasm_std::DepsMut, _env: cosmwasm_std::Env, _info: cosmwasm_std::MessageInfo, _msg: cosmwasm_std::Empty) -> Result<cosmwasm_std::Response, cosmwasm_std::StdError> {
+    Ok(cosmwasm_std::Response::new())
+}
+
+fn hook_query(_deps: cosmwasm_std::Deps, _env: cosmwasm_std::Env, _msg: cosmwasm_std::Empty) -> Result<Binary, cosmwasm_std::StdError> {
+    Ok(Binary::default())
+}
+
+#[test]
+fn ledger_hooks_notify_subscribers_and_isolate_failures() -> Result<()> {
+    let mut app = App::default();
+    let auet_code = app.store_code(Box::new(ContractWrapper::new(auet_instantiate, auet_execute, auet_query)));
+    let reg_code = app.store_code(Box::new(ContractWrapper::new(reg_instantiate, reg_execute, reg_query)));
+    let bridge_code = app.store_code(Box::new(ContractWrapper::new(bridge_instantiate, bridge_execute, bridge_query).with_reply(aln_bridge::reply)));
+    let subscriber_code = app.store_code(Box::new(ContractWrapper::new(hook_instantiate, hook_subscriber_execute, hook_query)));
+    let broken_code = app.store_code(Box::new(ContractWrapper::new(hook_instantiate, hook_broken_execute, hook_query)));
+
+    let gov = Addr::unchecked("gov");
+    let sys = Addr::unchecked("sys");
+    let auet_instantiate_msg = aln_auet::InstantiateMsg { cw20: Cw20InstantiateMsg { name: "AU.ET".to_string(), symbol: "AUET".to_string(), decimals: 6, initial_balances: vec![Cw20Coin { address: "bridge".to_string(), amount: Uint128::new(1_000_000) }], mint: None, marketing: None }, snapshot: aln_auet::SnapshotMeta { chain_id: "kaiyo-1".to_string(), height: 0, merkle_root: "root".to_string() }, allowed_modules: Some(vec![]) };
+    let auet_addr = app.instantiate_contract(auet_code, Addr::unchecked("creator"), &auet_instantiate_msg, &[], "AUET", None)?;
+    let reg_addr = app.instantiate_contract(reg_code, Addr::unchecked("creator"), &registry_init_msg(gov.to_string()), &[], "REG", None)?;
+    let bridge_msg = aln_bridge::InstantiateMsg { system_whitelist: Some(vec![sys.to_string()]), ..bridge_init_msg(auet_addr.to_string(), reg_addr.to_string(), gov.to_string()) };
+    let bridge = app.instantiate_contract(bridge_code, Addr::unchecked("creator"), &bridge_msg, &[], "BRIDGE", None)?;
+    let subscriber = app.instantiate_contract(subscriber_code, Addr::unchecked("creator"), &cosmwasm_std::Empty {}, &[], "SUB", None)?;
+    let broken = app.instantiate_contract(broken_code, Addr::unchecked("creator"), &cosmwasm_std::Empty {}, &[], "BROKEN", None)?;
+
+    let snaps: Vec<aln_bridge::SnapshotEntry> = [("user", "100"), ("user2", "50")].iter().map(|(a, b)| aln_bridge::SnapshotEntry { chain_id: "kaiyo-1".to_string(), height: 0, denom: "ibc/hhh".to_string(), address: a.to_string(), balance: b.to_string() }).collect();
+    let hashes: Vec<String> = snaps.iter().map(compute_snapshot_hash).collect();
+    let leaves: Vec<[u8; 32]> = hashes.iter().map(|h| { let mut a = [0u8; 32]; a.copy_from_slice(&hex::decode(h.trim_start_matches("0x")).unwrap()); a }).collect();
+    let (root, proofs) = build_merkle_and_proofs(&leaves);
+    let asset = aln_registry::RegisteredAsset { id: "h1".to_string(), source_chain: "kaiyo-1".to_string(), source_denom: "ibc/hhh".to_string(), snapshot_height: 0, merkle_root: root, ubs_report_hash: Some("hh".to_string()), scaling_profile_id: "clean".to_string(), activation_height: 0, sanitized_approved: true, merkle_version: aln_registry::MerkleVersion::V1, claim_deadline: None, total_supply: None, lifecycle: Default::default(), epochs: vec![] };
+    app.execute_contract(gov.clone(), reg_addr.clone(), &aln_registry::ExecuteMsg::RegisterAsset { asset }, &[])?;
+    let claim = |i: usize| {
+        let merkle_proof = proofs[i].iter().map(|(b, is_left)| aln_bridge::ProofStep { sibling: Binary(b.clone()), is_left: *is_left }).collect();
+        aln_bridge::ExecuteMsg::Claim { asset_id: "h1".to_string(), epoch: None, snapshot: snaps[i].clone(), snapshot_hash: hashes[i].clone(), merkle_proof, amount_auet: None, amount_csp: None, origin_tx_hash: None, origin_nonce: None, ubs_report_hash: None }
+    };
+    let downcast = |r: Result<AppResponse>| r.unwrap_err().downcast::<aln_bridge::ContractError>().unwrap();
+    let notified = |res: &AppResponse| -> Vec<(String, String, String, String)> {
+        res.events.iter().filter(|e| e.ty == "wasm" && e.attributes.iter().any(|a| a.key == "_contract_addr" && a.value == subscriber.as_str())).map(|e| {
+            let attr = |k: &str| e.attributes.iter().find(|a| a.key == k).map(|a| a.value.clone()).unwrap_or_default();
+            (attr("owner"), attr("before"), attr("after"), attr("reason"))
+        }).collect()
+    };
+    let hooks = |app: &App| -> Vec<aln_bridge::LedgerHook> { app.wrap().query_wasm_smart(bridge.clone(), &aln_bridge::QueryMsg::LedgerHooks { start_after: None, limit: None }).unwrap() };
+
+    // governance subscribes a working and a broken contract
+    let add = |c: &Addr| aln_bridge::ExecuteMsg::AddLedgerHook { contract_addr: c.to_string(), gas_limit: None };
+    assert_eq!(downcast(app.execute_contract(Addr::unchecked("user"), bridge.clone(), &add(&subscriber), &[])), aln_bridge::ContractError::Unauthorized { action: "add_ledger_hook".to_string() });
+    app.execute_contract(gov.clone(), bridge.clone(), &add(&subscriber), &[])?;
+    app.execute_contract(gov.clone(), bridge.clone(), &add(&broken), &[])?;
+    assert_eq!(downcast(app.execute_contract(gov.clone(), bridge.clone(), &add(&broken), &[])), aln_bridge::ContractError::LedgerHookExists { contract: broken.to_string() });
+
+    // a claim notifies the subscriber; the broken hook's failure is recorded, not propagated
+    let res = app.execute_contract(Addr::unchecked("user"), bridge.clone(), &claim(0), &[])?;
+    assert_eq!(notified(&res), vec![("user".to_string(), "0".to_string(), "150".to_string(), "claim".to_string())]);
+    let balance: aln_bridge::EnergyVector = app.wrap().query_wasm_smart(bridge.clone(), &aln_bridge::QueryMsg::EnergyBalance { address: "user".to_string() })?;
+    assert_eq!(balance.auet, Uint128::new(150));
+    let broken_hook = hooks(&app).into_iter().find(|h| h.contract == broken).unwrap();
+    assert_eq!(broken_hook.failures, 1);
+    assert!(broken_hook.last_error.unwrap().contains("subscriber down"));
+
+    // debits notify too
+    app.execute_contract(Addr::unchecked("user"), bridge.clone(), &aln_bridge::ExecuteMsg::GrantEnergyAllowance { spender: sys.to_string(), amount: aln_bridge::EnergyVector { auet: Uint128::new(30), csp: Uint128::zero(), erp: Uint128::zero() }, period_seconds: None, expires: None }, &[])?;
+    let consume = aln_bridge::ExecuteMsg::SystemConsume { owner: "user".to_string(), delta: aln_bridge::EnergyVector { auet: Uint128::new(30), csp: Uint128::zero(), erp: Uint128::zero() } };
+    let res = app.execute_contract(sys.clone(), bridge.clone(), &consume, &[])?;
+    assert_eq!(notified(&res), vec![("user".to_string(), "150".to_string(), "120".to_string(), "system_consume".to_string())]);
+    assert_eq!(hooks(&app).iter().map(|h| (h.contract.as_str(), h.failures)).collect::<Vec<_>>(), vec![(subscriber.as_str(), 0), (broken.as_str(), 2)]);
+
+    // removed hooks hear nothing
+    app.execute_contract(gov.clone(), bridge.clone(), &aln_bridge::ExecuteMsg::RemoveLedgerHook { contract_addr: subscriber.to_string() }, &[])?;
+    let res = app.execute_contract(Addr::unchecked("user2"), bridge.clone(), &claim(1), &[])?;
+    assert!(notified(&res).is_empty());
+    assert_eq!(downcast(app.execute_contract(gov.clone(), bridge.clone(), &aln_bridge::ExecuteMsg::RemoveLedgerHook { contract_addr: subscriber.to_string() }, &[])), aln_bridge::ContractError::LedgerHookNotFound { contract: subscriber.to_string() });
+    Ok(())
+}
+
+#[test]
+fn revoked_registry_assets_refuse_claims() -> Result<()> {
+    let mut app = App::default();
+    let auet_code = app.store_code(Box::new(ContractWrapper::new(auet_instantiate, auet_execute, auet_query)));
+    let reg_code = app.store_code(Box::new(ContractWrapper::new(reg_instantiate, reg_execute, reg_query)));
+    let bridge_code = app.store_code(Box::new(ContractWrapper::new(bridge_instantiate, bridge_execute, bridge_query)));
+
+    let gov = Addr::unchecked("gov");
+    let auet_instantiate_msg = aln_auet::InstantiateMsg { cw20: Cw20InstantiateMsg { name: "AU.ET".to_string(), symbol: "AUET".to_string(), decimals: 6, initial_balances: vec![Cw20Coin { address: "bridge".to_string(), amount: Uint128::new(1_000_000) }], mint: None, marketing: None }, snapshot: aln_auet::SnapshotMeta { chain_id: "kaiyo-1".to_string(), height: 0, merkle_root: "root".to_string() }, allowed_modules: Some(vec![]) };
+    let auet_addr = app.instantiate_contract(auet_code, Addr::unchecked("creator"), &auet_instantiate_msg, &[], "AUET", None)?;
+    let reg_addr = app.instantiate_contract(reg_code, Addr::unchecked("creator"), &registry_init_msg(gov.to_string()), &[], "REG", None)?;
+    let bridge = app.instantiate_contract(bridge_code, Addr::unchecked("creator"), &bridge_init_msg(auet_addr.to_string(), reg_addr.to_string(), gov.to_string()), &[], "BRIDGE", None)?;
+
+    let snaps: Vec<aln_bridge::SnapshotEntry> = ["user", "user2"].iter().map(|a| aln_bridge::SnapshotEntry { chain_id: "kaiyo-1".to_string(), height: 0, denom: "ibc/rev".to_string(), address: a.to_string(), balance: "10".to_string() }).collect();
+    let hashes: Vec<String> = snaps.iter().map(compute_snapshot_hash).collect();
+    let leaves: Vec<[u8; 32]> = hashes.iter().map(|h| { let mut a = [0u8; 32]; a.copy_from_slice(&hex::decode(h.trim_start_matches("0x")).unwrap()); a }).collect();
+    let (root, proofs) = build_merkle_and_proofs(&leaves);
+    let asset = aln_registry::RegisteredAsset { id: "r1".to_string(), source_chain: "kaiyo-1".to_string(), source_denom: "ibc/rev".to_string(), snapshot_height: 0, merkle_root: root, ubs_report_hash: Some("hr".to_string()), scaling_profile_id: "clean".to_string(), activation_height: 0, sanitized_approved: true, merkle_version: aln_registry::MerkleVersion::V1, claim_deadline: None, total_supply: None, lifecycle: Default::default(), epochs: vec![] };
+    app.execute_contract(gov.clone(), reg_addr.clone(), &aln_registry::ExecuteMsg::RegisterAsset { asset: asset.clone() }, &[])?;
+    let claim = |i: usize| {
+        let merkle_proof = proofs[i].iter().map(|(b, is_left)| aln_bridge::ProofStep { sibling: Binary(b.clone()), is_left: *is_left }).collect();
+        aln_bridge::ExecuteMsg::Claim { asset_id: "r1".to_string(), epoch: None, snapshot: snaps[i].clone(), snapshot_hash: hashes[i].clone(), merkle_proof, amount_auet: None, amount_csp: None, origin_tx_hash: None, origin_nonce: None, ubs_report_hash: None }
+    };
+
+    let registered: aln_registry::RegisteredAsset = app.wrap().query_wasm_smart(reg_addr.clone(), &aln_registry::QueryMsg::GetAsset { id: "r1".to_string() })?;
+    assert_eq!(registered.lifecycle.state, aln_registry::AssetState::Active);
+    app.execute_contract(Addr::unchecked("user"), bridge.clone(), &claim(0), &[])?;
+
+    // an accidental re-registration no longer replaces the live asset
+    let err = app.execute_contract(gov.clone(), reg_addr.clone(), &aln_registry::ExecuteMsg::RegisterAsset { asset }, &[]).unwrap_err();
+    assert_eq!(err.downcast::<aln_registry::ContractError>()?, aln_registry::ContractError::AssetExists { id: "r1".to_string() });
+
+    // once revoked, the bridge refuses the remaining claims
+    app.execute_contract(gov.clone(), reg_addr.clone(), &aln_registry::ExecuteMsg::RevokeSanitized { id: "r1".to_string(), reason: "ubs report withdrawn".to_string() }, &[])?;
+    let err = app.execute_contract(Addr::unchecked("user2"), bridge.clone(), &claim(1), &[]).unwrap_err();
+    assert_eq!(err.downcast::<aln_bridge::ContractError>()?, aln_bridge::ContractError::AssetNotSanitized);
+    let v1: Option<aln_registry::RegisteredAsset> = app.wrap().query_wasm_smart(reg_addr.clone(), &aln_registry::QueryMsg::AssetVersion { id: "r1".to_string(), version: 1 })?;
+    assert!(v1.unwrap().sanitized_approved);
+    Ok(())
+}
+
+#[test]
+fn registry_scaling_profiles_drive_entitlements_and_toxicity() -> Result<()> {
+    let mut app = App::default();
+    let auet_code = app.store_code(Box::new(ContractWrapper::new(auet_instantiate, auet_execute, auet_query)));
+    let reg_code = app.store_code(Box::new(ContractWrapper::new(reg_instantiate, reg_execute, reg_query)));
+    let bridge_code = app.store_code(Box::new(ContractWrapper::new(bridge_instantiate, bridge_execute, bridge_query)));
+
+    let gov = Addr::unchecked("gov");
+    let auet_instantiate_msg = aln_auet::InstantiateMsg { cw20: Cw20InstantiateMsg { name: "AU.ET".to_string(), symbol: "AUET".to_string(), decimals: 6, initial_balances: vec![Cw20Coin { address: "bridge".to_string(), amount: Uint128::new(1_000_000) }], mint: None, marketing: None }, snapshot: aln_auet::SnapshotMeta { chain_id: "kaiyo-1".to_string(), height: 0, merkle_root: "root".to_string() }, allowed_modules: Some(vec![]) };
+    let auet_addr = app.instantiate_contract(auet_code, Addr::unchecked("creator"), &auet_instantiate_msg, &[], "AUET", None)?;
+    let reg_addr = app.instantiate_contract(reg_code, Addr::unchecked("creator"), &registry_init_msg(gov.to_string()), &[], "REG", None)?;
+    let bridge = app.instantiate_contract(bridge_code, Addr::unchecked("creator"), &bridge_init_msg(auet_addr.to_string(), reg_addr.to_string(), gov.to_string()), &[], "BRIDGE", None)?;
+
+    let snap = aln_bridge::SnapshotEntry { chain_id: "kaiyo-1".to_string(), height: 0, denom: "ibc/qqq".to_string(), address: "user".to_string(), balance: "100".to_string() };
+    let h = compute_snapshot_hash(&snap);
+    let asset = aln_registry::RegisteredAsset { id: "q1".to_string(), source_chain: "kaiyo-1".to_string(), source_denom: "ibc/qqq".to_string(), snapshot_height: 0, merkle_root: h.clone(), ubs_report_hash: Some("hq".to_string()), scaling_profile_id: "quarantined".to_string(), activation_height: 0, sanitized_approved: true, merkle_version: aln_registry::MerkleVersion::V1, claim_deadline: None, total_supply: None, lifecycle: Default::default(), epochs: vec![] };
+    let register = aln_registry::ExecuteMsg::RegisterAsset { asset };
+    let err = app.execute_contract(gov.clone(), reg_addr.clone(), &register, &[]).unwrap_err();
+    assert_eq!(err.downcast::<aln_registry::ContractError>()?, aln_registry::ContractError::UnknownScalingProfile { id: "quarantined".to_string() });
+
+    // toxicity is the profile's flag, whatever the id says
+    let profile = aln_registry::ScalingProfile { id: "quarantined".to_string(), c_e: cosmwasm_std::Decimal::one(), c_s: cosmwasm_std::Decimal::percent(50), source_decimals: 6, aln_decimals: 6, toxic: true, haircut: Some(cosmwasm_std::Decimal::percent(10)) };
+    app.execute_contract(gov.clone(), reg_addr.clone(), &aln_registry::ExecuteMsg::SetScalingProfile { profile: profile.clone() }, &[])?;
+    app.execute_contract(gov.clone(), reg_addr.clone(), &register, &[])?;
+    let seen: Option<aln_bridge::ScalingProfile> = app.wrap().query_wasm_smart(bridge.clone(), &aln_bridge::QueryMsg::ScalingProfile { id: "quarantined".to_string() })?;
+    assert_eq!(seen, Some(profile));
+
+    let claim = aln_bridge::ExecuteMsg::Claim { asset_id: "q1".to_string(), epoch: None, snapshot: snap, snapshot_hash: h, merkle_proof: vec![], amount_auet: None, amount_csp: None, origin_tx_hash: None, origin_nonce: None, ubs_report_hash: None };
+    let err = app.execute_contract(Addr::unchecked("user"), bridge.clone(), &claim, &[]).unwrap_err();
+    assert_eq!(err.downcast::<aln_bridge::ContractError>()?, aln_bridge::ContractError::ToxicSinkRequired);
+
+    let update = aln_bridge::ExecuteMsg::UpdateConfig { toxic_sink: Some("sink".to_string()), anomaly_threshold_amount: None, toxic_cap_percent: None, ubs_oracle_contract: None, csp_contract: None, registry_contract: None, clear: None };
+    app.execute_contract(gov.clone(), bridge.clone(), &update, &[])?;
+    let res = app.execute_contract(Addr::unchecked("user"), bridge.clone(), &claim, &[])?;
+    let attr = |k: &str| res.events.iter().flat_map(|e| e.attributes.iter()).find(|a| a.key == k).map(|a| a.value.clone());
+    // 10% haircut on the scaled 100 AU.ET / 50 CSP
+    assert_eq!((attr("entitled_auet"), attr("entitled_csp")), (Some("90".to_string()), Some("45".to_string())));
+    let toxic: Uint128 = app.wrap().query_wasm_smart(bridge.clone(), &aln_bridge::QueryMsg::ToxicEnergy {})?;
+    assert_eq!(toxic, Uint128::new(135));
+    Ok(())
+}
+
+#[test]
+fn snapshot_epochs_are_claimed_separately() -> Result<()> {
+    let mut app = App::default();
+    let auet_code = app.store_code(Box::new(ContractWrapper::new(auet_instantiate, auet_execute, auet_query)));
+    let reg_code = app.store_code(Box::new(ContractWrapper::new(reg_instantiate, reg_execute, reg_query)));
+    let bridge_code = app.store_code(Box::new(ContractWrapper::new(bridge_instantiate, bridge_execute, bridge_query)));
+
+    let gov = Addr::unchecked("gov");
+    let auet_instantiate_msg = aln_auet::InstantiateMsg { cw20: Cw20InstantiateMsg { name: "AU.ET".to_string(), symbol: "AUET".to_string(), decimals: 6, initial_balances: vec![Cw20Coin { address: "bridge".to_string(), amount: Uint128::new(1_000_000) }], mint: None, marketing: None }, snapshot: aln_auet::SnapshotMeta { chain_id: "kaiyo-1".to_string(), height: 0, merkle_root: "root".to_string() }, allowed_modules: Some(vec![]) };
+    let auet_addr = app.instantiate_contract(auet_code, Addr::unchecked("creator"), &auet_instantiate_msg, &[], "AUET", None)?;
+    let reg_addr = app.instantiate_contract(reg_code, Addr::unchecked("creator"), &registry_init_msg(gov.to_string()), &[], "REG", None)?;
+    let bridge = app.instantiate_contract(bridge_code, Addr::unchecked("creator"), &bridge_init_msg(auet_addr.to_string(), reg_addr.to_string(), gov.to_string()), &[], "BRIDGE", None)?;
+    app.update_block(|b| b.height = 100);
+
+    // the same holder in the registration snapshot and in a later wave (single-leaf roots)
+    let snap = |height: u64| aln_bridge::SnapshotEntry { chain_id: "kaiyo-1".to_string(), height, denom: "ibc/ep".to_string(), address: "user".to_string(), balance: "100".to_string() };
+    let (h0, h1) = (compute_snapshot_hash(&snap(10)), compute_snapshot_hash(&snap(20)));
+    let asset = aln_registry::RegisteredAsset { id: "e1".to_string(), source_chain: "kaiyo-1".to_string(), source_denom: "ibc/ep".to_string(), snapshot_height: 10, merkle_root: h0.clone(), ubs_report_hash: Some("he".to_string()), scaling_profile_id: "clean".to_string(), activation_height: 0, sanitized_approved: true, merkle_version: aln_registry::MerkleVersion::V1, claim_deadline: None, total_supply: Some(Uint128::new(100)), lifecycle: Default::default(), epochs: vec![] };
+    app.execute_contract(gov.clone(), reg_addr.clone(), &aln_registry::ExecuteMsg::RegisterAsset { asset }, &[])?;
+    let claim = |epoch: Option<u32>, height: u64, h: &String| aln_bridge::ExecuteMsg::Claim { asset_id: "e1".to_string(), epoch, snapshot: snap(height), snapshot_hash: h.clone(), merkle_proof: vec![], amount_auet: None, amount_csp: None, origin_tx_hash: None, origin_nonce: None, ubs_report_hash: None };
+    app.execute_contract(Addr::unchecked("user"), bridge.clone(), &claim(None, 10, &h0), &[])?;
+
+    // epochs are numbered in order and never reuse a root
+    let epoch = |n: u32, root: &String| aln_registry::SnapshotEpoch { epoch: n, merkle_root: root.clone(), snapshot_height: 20, activation_height: 110, claim_deadline: Some(200), total_supply: Some(Uint128::new(100)), ..Default::default() };
+    let add = |e: aln_registry::SnapshotEpoch| aln_registry::ExecuteMsg::AddSnapshotEpoch { id: "e1".to_string(), epoch: e };
+    let err = app.execute_contract(gov.clone(), reg_addr.clone(), &add(epoch(1, &h0)), &[]).unwrap_err();
+    assert!(matches!(err.downcast::<aln_registry::ContractError>()?, aln_registry::ContractError::InvalidSnapshotEpoch(_)));
+    let err = app.execute_contract(gov.clone(), reg_addr.clone(), &add(epoch(2, &h1)), &[]).unwrap_err();
+    assert!(matches!(err.downcast::<aln_registry::ContractError>()?, aln_registry::ContractError::InvalidSnapshotEpoch(_)));
+    app.execute_contract(gov.clone(), reg_addr.clone(), &add(epoch(1, &h1)), &[])?;
+    let epochs: Vec<aln_registry::SnapshotEpoch> = app.wrap().query_wasm_smart(reg_addr.clone(), &aln_registry::QueryMsg::SnapshotEpochs { id: "e1".to_string() })?;
+    assert_eq!(epochs.iter().map(|e| (e.epoch, e.merkle_root.clone())).collect::<Vec<_>>(), vec![(0, h0.clone()), (1, h1.clone())]);
+
+    // epoch 1 needs its own sanitization approval, even though the asset is active
+    app.update_block(|b| b.height = 110);
+    let err = app.execute_contract(Addr::unchecked("user"), bridge.clone(), &claim(Some(1), 20, &h1), &[]).unwrap_err();
+    assert_eq!(err.downcast::<aln_bridge::ContractError>()?, aln_bridge::ContractError::EpochNotSanitized { asset_id: "e1".to_string(), epoch: 1 });
+    app.update_block(|b| b.height = 100);
+    app.execute_contract(gov.clone(), reg_addr.clone(), &aln_registry::ExecuteMsg::ApproveSanitized { id: "e1".to_string(), ubs_report_hash: "he1".to_string(), epoch: Some(1) }, &[])?;
+
+    // epoch 1 follows its own activation, and its leaf does not verify against epoch 0
+    let err = app.execute_contract(Addr::unchecked("user"), bridge.clone(), &claim(Some(1), 20, &h1), &[]).unwrap_err();
+    assert_eq!(err.downcast::<aln_bridge::ContractError>()?, aln_bridge::ContractError::NotActivated);
+    let err = app.execute_contract(Addr::unchecked("user"), bridge.clone(), &claim(None, 20, &h1), &[]).unwrap_err();
+    assert_eq!(err.downcast::<aln_bridge::ContractError>()?, aln_bridge::ContractError::InvalidMerkleProof);
+    app.update_block(|b| b.height = 110);
+    let res = app.execute_contract(Addr::unchecked("user"), bridge.clone(), &claim(Some(1), 20, &h1), &[])?;
+    assert!(res.events.iter().flat_map(|e| e.attributes.iter()).any(|a| a.key == "epoch" && a.value == "1"));
+    let err = app.execute_contract(Addr::unchecked("user"), bridge.clone(), &claim(Some(1), 20, &h1), &[]).unwrap_err();
+    assert_eq!(err.downcast::<aln_bridge::ContractError>()?, aln_bridge::ContractError::AlreadyClaimed);
+    let err = app.execute_contract(Addr::unchecked("user"), bridge.clone(), &claim(Some(2), 20, &h1), &[]).unwrap_err();
+    assert_eq!(err.downcast::<aln_bridge::ContractError>()?, aln_bridge::ContractError::UnknownSnapshotEpoch { asset_id: "e1".to_string(), epoch: 2 });
+
+    let claimed: bool = app.wrap().query_wasm_smart(bridge.clone(), &aln_bridge::QueryMsg::IsClaimed { address: "user".to_string(), asset_id: "e1".to_string(), epoch: Some(1), snapshot_hash: h1.clone() })?;
+    assert!(claimed);
+    let records: Vec<aln_bridge::ClaimRecord> = app.wrap().query_wasm_smart(bridge.clone(), &aln_bridge::QueryMsg::ClaimsByAddress { address: "user".to_string(), start_after: None, limit: None })?;
+    assert_eq!(records.iter().map(|r| (r.epoch, r.snapshot_hash.clone())).collect::<Vec<_>>(), vec![(0, h0), (1, h1)]);
+    // supply and deadline span both epochs; epoch 0 never closes, so neither does the asset
+    let stats: aln_bridge::AssetClaimStatsResponse = app.wrap().query_wasm_smart(bridge.clone(), &aln_bridge::QueryMsg::AssetClaimStats { asset_id: "e1".to_string() })?;
+    assert_eq!((stats.claims, stats.total_supply, stats.unclaimed, stats.claim_deadline), (2, Some(Uint128::new(200)), Some(Uint128::zero()), None));
+    Ok(())
+}
+
+#[test]
+fn committee_threshold_unlocks_claims() -> Result<()> {
+    let mut app = App::default();
+    let auet_code = app.store_code(Box::new(ContractWrapper::new(auet_instantiate, auet_execute, auet_query)));
+    let reg_code = app.store_code(Box::new(ContractWrapper::new(reg_instantiate, reg_execute, reg_query)));
+    let bridge_code = app.store_code(Box::new(ContractWrapper::new(bridge_instantiate, bridge_execute, bridge_query)));
+
+    let gov = Addr::unchecked("gov");
+    let auet_instantiate_msg = aln_auet::InstantiateMsg { cw20: Cw20InstantiateMsg { name: "AU.ET".to_string(), symbol: "AUET".to_string(), decimals: 6, initial_balances: vec![Cw20Coin { address: "bridge".to_string(), amount: Uint128::new(1_000_000) }], mint: None, marketing: None }, snapshot: aln_auet::SnapshotMeta { chain_id: "kaiyo-1".to_string(), height: 0, merkle_root: "root".to_string() }, allowed_modules: Some(vec![]) };
+    let auet_addr = app.instantiate_contract(auet_code, Addr::unchecked("creator"), &auet_instantiate_msg, &[], "AUET", None)?;
+    let reg_addr = app.instantiate_contract(reg_code, Addr::unchecked("creator"), &registry_init_msg(gov.to_string()), &[], "REG", None)?;
+    let bridge = app.instantiate_contract(bridge_code, Addr::unchecked("creator"), &bridge_init_msg(auet_addr.to_string(), reg_addr.to_string(), gov.to_string()), &[], "BRIDGE", None)?;
+
+    let committee = aln_registry::ExecuteMsg::SetSanitizationCommittee { members: vec!["member1".to_string(), "member2".to_string(), "member3".to_string()], threshold: 2 };
+    app.execute_contract(gov.clone(), reg_addr.clone(), &committee, &[])?;
+    let snap = aln_bridge::SnapshotEntry { chain_id: "kaiyo-1".to_string(), height: 0, denom: "ibc/cm".to_string(), address: "user".to_string(), balance: "100".to_string() };
+    let h = compute_snapshot_hash(&snap);
+    let asset = aln_registry::RegisteredAsset { id: "c1".to_string(), source_chain: "kaiyo-1".to_string(), source_denom: "ibc/cm".to_string(), snapshot_height: 0, merkle_root: h.clone(), ubs_report_hash: Some("hc".to_string()), scaling_profile_id: "clean".to_string(), activation_height: 0, sanitized_approved: false, merkle_version: aln_registry::MerkleVersion::V1, claim_deadline: None, total_supply: None, lifecycle: Default::default(), epochs: vec![] };
+    app.execute_contract(gov.clone(), reg_addr.clone(), &aln_registry::ExecuteMsg::RegisterAsset { asset }, &[])?;
+    let claim = aln_bridge::ExecuteMsg::Claim { asset_id: "c1".to_string(), epoch: None, snapshot: snap, snapshot_hash: h, merkle_proof: vec![], amount_auet: None, amount_csp: None, origin_tx_hash: None, origin_nonce: None, ubs_report_hash: None };
+    let approve = aln_registry::ExecuteMsg::ApproveSanitized { id: "c1".to_string(), ubs_report_hash: "hc".to_string(), epoch: None };
+
+    // governance alone no longer approves, nor does a single member
+    let err = app.execute_contract(gov.clone(), reg_addr.clone(), &approve, &[]).unwrap_err();
+    assert_eq!(err.downcast::<aln_registry::ContractError>()?, aln_registry::ContractError::NotCommitteeMember { address: "gov".to_string() });
+    app.execute_contract(Addr::unchecked("member1"), reg_addr.clone(), &approve, &[])?;
+    let err = app.execute_contract(Addr::unchecked("user"), bridge.clone(), &claim, &[]).unwrap_err();
+    assert_eq!(err.downcast::<aln_bridge::ContractError>()?, aln_bridge::ContractError::AssetNotSanitized);
+    let pending: aln_registry::PendingAttestationsResponse = app.wrap().query_wasm_smart(reg_addr.clone(), &aln_registry::QueryMsg::PendingAttestations { id: "c1".to_string(), epoch: None })?;
+    assert_eq!(pending.attestations.iter().map(|a| a.approver.to_string()).collect::<Vec<_>>(), vec!["member1".to_string()]);
+
+    app.execute_contract(Addr::unchecked("member3"), reg_addr.clone(), &approve, &[])?;
+    let res = app.execute_contract(Addr::unchecked("user"), bridge.clone(), &claim, &[])?;
+    let attr = |k: &str| res.events.iter().flat_map(|e| e.attributes.iter()).find(|a| a.key == k).map(|a| a.value.clone());
+    assert_eq!(attr("entitled_auet"), Some("100".to_string()));
+    let registered: aln_registry::RegisteredAsset = app.wrap().query_wasm_smart(reg_addr.clone(), &aln_registry::QueryMsg::GetAsset { id: "c1".to_string() })?;
+    assert_eq!(registered.lifecycle.approved_by, vec![Addr::unchecked("member1"), Addr::unchecked("member3")]);
+    Ok(())
+}
+
+#[test]
+fn committee_threshold_unlocks_each_snapshot_epoch() -> Result<()> {
+    let mut app = App::default();
+    let auet_code = app.store_code(Box::new(ContractWrapper::new(auet_instantiate, auet_execute, auet_query)));
+    let reg_code = app.store_code(Box::new(ContractWrapper::new(reg_instantiate, reg_execute, reg_query)));
+    let bridge_code = app.store_code(Box::new(ContractWrapper::new(bridge_instantiate, bridge_execute, bridge_query)));
+
+    let gov = Addr::unchecked("gov");
+    let auet_instantiate_msg = aln_auet::InstantiateMsg { cw20: Cw20InstantiateMsg { name: "AU.ET".to_string(), symbol: "AUET".to_string(), decimals: 6, initial_balances: vec![Cw20Coin { address: "bridge".to_string(), amount: Uint128::new(1_000_000) }], mint: None, marketing: None }, snapshot: aln_auet::SnapshotMeta { chain_id: "kaiyo-1".to_string(), height: 0, merkle_root: "root".to_string() }, allowed_modules: Some(vec![]) };
+    let auet_addr = app.instantiate_contract(auet_code, Addr::unchecked("creator"), &auet_instantiate_msg, &[], "AUET", None)?;
+    let reg_addr = app.instantiate_contract(reg_code, Addr::unchecked("creator"), &registry_init_msg(gov.to_string()), &[], "REG", None)?;
+    let bridge = app.instantiate_contract(bridge_code, Addr::unchecked("creator"), &bridge_init_msg(auet_addr.to_string(), reg_addr.to_string(), gov.to_string()), &[], "BRIDGE", None)?;
+
+    let committee = aln_registry::ExecuteMsg::SetSanitizationCommittee { members: vec!["member1".to_string(), "member2".to_string(), "member3".to_string()], threshold: 2 };
+    app.execute_contract(gov.clone(), reg_addr.clone(), &committee, &[])?;
+    let snap = |height: u64| aln_bridge::SnapshotEntry { chain_id: "kaiyo-1".to_string(), height, denom: "ibc/ce".to_string(), address: "user".to_string(), balance: "100".to_string() };
+    let (h0, h1) = (compute_snapshot_hash(&snap(10)), compute_snapshot_hash(&snap(20)));
+    let asset = aln_registry::RegisteredAsset { id: "ce".to_string(), source_chain: "kaiyo-1".to_string(), source_denom: "ibc/ce".to_string(), snapshot_height: 10, merkle_root: h0.clone(), ubs_report_hash: Some("hc".to_string()), scaling_profile_id: "clean".to_string(), activation_height: 0, sanitized_approved: false, merkle_version: aln_registry::MerkleVersion::V1, claim_deadline: None, total_supply: None, lifecycle: Default::default(), epochs: vec![] };
+    app.execute_contract(gov.clone(), reg_addr.clone(), &aln_registry::ExecuteMsg::RegisterAsset { asset }, &[])?;
+    let approve = |epoch: Option<u32>, hash: &str| aln_registry::ExecuteMsg::ApproveSanitized { id: "ce".to_string(), ubs_report_hash: hash.to_string(), epoch };
+    app.execute_contract(Addr::unchecked("member1"), reg_addr.clone(), &approve(None, "hc"), &[])?;
+    app.execute_contract(Addr::unchecked("member2"), reg_addr.clone(), &approve(None, "hc"), &[])?;
+    let claim = |epoch: Option<u32>, height: u64, h: &String| aln_bridge::ExecuteMsg::Claim { asset_id: "ce".to_string(), epoch, snapshot: snap(height), snapshot_hash: h.clone(), merkle_proof: vec![], amount_auet: None, amount_csp: None, origin_tx_hash: None, origin_nonce: None, ubs_report_hash: None };
+    app.execute_contract(Addr::unchecked("user"), bridge.clone(), &claim(None, 10, &h0), &[])?;
+
+    // governance adds the next wave, but the asset's approval does not carry over to it
+    let epoch = aln_registry::SnapshotEpoch { epoch: 1, merkle_root: h1.clone(), snapshot_height: 20, activation_height: 0, ..Default::default() };
+    app.execute_contract(gov.clone(), reg_addr.clone(), &aln_registry::ExecuteMsg::AddSnapshotEpoch { id: "ce".to_string(), epoch }, &[])?;
+    let not_sanitized = || aln_bridge::ContractError::EpochNotSanitized { asset_id: "ce".to_string(), epoch: 1 };
+    let err = app.execute_contract(Addr::unchecked("user"), bridge.clone(), &claim(Some(1), 20, &h1), &[]).unwrap_err();
+    assert_eq!(err.downcast::<aln_bridge::ContractError>()?, not_sanitized());
+    let err = app.execute_contract(gov.clone(), reg_addr.clone(), &approve(Some(1), "hc1"), &[]).unwrap_err();
+    assert_eq!(err.downcast::<aln_registry::ContractError>()?, aln_registry::ContractError::NotCommitteeMember { address: "gov".to_string() });
+
+    // one attestation is below the threshold; the epoch round is separate from the asset's
+    app.execute_contract(Addr::unchecked("member1"), reg_addr.clone(), &approve(Some(1), "hc1"), &[])?;
+    let err = app.execute_contract(Addr::unchecked("user"), bridge.clone(), &claim(Some(1), 20, &h1), &[]).unwrap_err();
+    assert_eq!(err.downcast::<aln_bridge::ContractError>()?, not_sanitized());
+    let pending: aln_registry::PendingAttestationsResponse = app.wrap().query_wasm_smart(reg_addr.clone(), &aln_registry::QueryMsg::PendingAttestations { id: "ce".to_string(), epoch: Some(1) })?;
+    assert_eq!((pending.epoch, pending.attestations.len()), (1, 1));
+    let err = app.execute_contract(Addr::unchecked("anyone"), reg_addr.clone(), &aln_registry::ExecuteMsg::FinalizeAttestations { id: "ce".to_string(), epoch: Some(1) }, &[]).unwrap_err();
+    assert_eq!(err.downcast::<aln_registry::ContractError>()?, aln_registry::ContractError::AttestationThresholdNotMet { id: "ce".to_string(), epoch: 1, attestations: 1, threshold: 2 });
+
+    // the second matching attestation opens epoch 1 under its own report hash
+    app.execute_contract(Addr::unchecked("member3"), reg_addr.clone(), &approve(Some(1), "hc1"), &[])?;
+    let res = app.execute_contract(Addr::unchecked("user"), bridge.clone(), &claim(Some(1), 20, &h1), &[])?;
+    assert!(res.events.iter().flat_map(|e| e.attributes.iter()).any(|a| a.key == "epoch" && a.value == "1"));
+    let epochs: Vec<aln_registry::SnapshotEpoch> = app.wrap().query_wasm_smart(reg_addr.clone(), &aln_registry::QueryMsg::SnapshotEpochs { id: "ce".to_string() })?;
+    assert_eq!(epochs[1].approved_by, vec![Addr::unchecked("member1"), Addr::unchecked("member3")]);
+    assert_eq!((epochs[0].ubs_report_hash.as_deref(), epochs[1].ubs_report_hash.as_deref()), (Some("hc"), Some("hc1")));
+    Ok(())
+}
@@ -0,0 +1,105 @@
+// Energy ledger commitment, recomputed from a dump of the bridge's `EnergyHolders` query pages.
+use aln_merkle::{ledger_leaf_hash, ledger_root};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Amounts as the contract serializes `Uint128`: decimal strings.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Energy {
+    pub auet: String,
+    pub csp: String,
+    pub erp: String,
+}
+
+/// One `EnergyHolders` row.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct LedgerEntry {
+    pub address: String,
+    pub energy: Energy,
+}
+
+#[derive(Serialize, Debug, PartialEq)]
+pub struct Divergence {
+    pub address: String,
+    pub dump: Option<Energy>,
+    pub reference: Option<Energy>,
+}
+
+fn amounts(e: &Energy) -> anyhow::Result<(u128, u128, u128)> {
+    Ok((e.auet.parse()?, e.csp.parse()?, e.erp.parse()?))
+}
+
+fn by_address(entries: &[LedgerEntry]) -> anyhow::Result<BTreeMap<&str, &Energy>> {
+    let mut map = BTreeMap::new();
+    for e in entries {
+        if map.insert(e.address.as_str(), &e.energy).is_some() {
+            anyhow::bail!("duplicate ledger entry for {}", e.address);
+        }
+    }
+    Ok(map)
+}
+
+/// `0x`-prefixed commitment the bridge stores in `LedgerCheckpoint::root`, and the total energy.
+/// Entries may come in any order; the contract commits to ascending address (byte) order.
+pub fn commitment(entries: &[LedgerEntry]) -> anyhow::Result<(String, (u128, u128, u128))> {
+    let mut leaves = vec![];
+    let mut total = (0u128, 0u128, 0u128);
+    for (address, energy) in by_address(entries)? {
+        let (a, c, e) = amounts(energy)?;
+        leaves.push(ledger_leaf_hash(address, a, c, e));
+        total = (total.0 + a, total.1 + c, total.2 + e);
+    }
+    Ok((format!("0x{}", hex::encode(ledger_root(&leaves))), total))
+}
+
+/// Addresses missing from either side or holding different amounts, in address order.
+pub fn divergences(dump: &[LedgerEntry], reference: &[LedgerEntry]) -> anyhow::Result<Vec<Divergence>> {
+    let d = by_address(dump)?;
+    let r = by_address(reference)?;
+    let mut out = vec![];
+    let mut addresses: Vec<&str> = d.keys().chain(r.keys()).copied().collect();
+    addresses.sort();
+    addresses.dedup();
+    for address in addresses {
+        let (de, re) = (d.get(address), r.get(address));
+        let same = match (de, re) {
+            (Some(x), Some(y)) => amounts(x)? == amounts(y)?,
+            _ => false,
+        };
+        if !same {
+            out.push(Divergence { address: address.to_string(), dump: de.map(|e| (*e).clone()), reference: re.map(|e| (*e).clone()) });
+        }
+    }
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(address: &str, auet: u128, csp: u128) -> LedgerEntry {
+        LedgerEntry { address: address.to_string(), energy: Energy { auet: auet.to_string(), csp: csp.to_string(), erp: "0".to_string() } }
+    }
+
+    #[test]
+    fn commitment_ignores_dump_order() {
+        let a = vec![entry("aln1b", 3, 1), entry("aln1a", 10, 5)];
+        let b = vec![entry("aln1a", 10, 5), entry("aln1b", 3, 1)];
+        let (root, total) = commitment(&a).unwrap();
+        assert_eq!(root, commitment(&b).unwrap().0);
+        assert_eq!(total, (13, 6, 0));
+        assert_ne!(root, commitment(&[entry("aln1a", 10, 5), entry("aln1b", 3, 2)]).unwrap().0);
+        assert_eq!(commitment(&[]).unwrap().0, format!("0x{}", "00".repeat(32)));
+        assert!(commitment(&[entry("aln1a", 1, 0), entry("aln1a", 1, 0)]).is_err());
+    }
+
+    #[test]
+    fn divergences_list_missing_and_changed_entries() {
+        let dump = vec![entry("aln1a", 10, 5), entry("aln1b", 3, 1)];
+        let reference = vec![entry("aln1c", 1, 0), entry("aln1b", 3, 2), entry("aln1a", 10, 5)];
+        let d = divergences(&dump, &reference).unwrap();
+        assert_eq!(d.iter().map(|x| x.address.as_str()).collect::<Vec<_>>(), vec!["aln1b", "aln1c"]);
+        assert!(d[1].dump.is_none());
+        assert!(divergences(&dump, &dump).unwrap().is_empty());
+    }
+}
@@ -1,8 +1,9 @@
 use clap::{Parser, Subcommand};
 mod merkle;
-use merkle::{build_merkle_and_proofs, Proof};
+mod ledger;
+use merkle::{build_merkle_and_proofs, build_multiproof, multiproof_root, verify_merkle_proof, verify_multiproof};
+use aln_merkle::{leaf_hash, MerkleVersion, SnapshotLeaf};
 use serde::{Deserialize, Serialize};
-use sha2::{Digest, Sha256};
 use std::fs::File;
 use std::io::Write;
 
@@ -15,8 +16,16 @@ pub struct Cli {
 
 #[derive(Subcommand)]
 enum Commands {
-    SnapshotHash { input: String, output: Option<String>, asset_id: Option<String>, artifacts: Option<String> },
+    /// Leaf hashes H_i and, with an asset id and artifacts dir, the merkle root and proofs. New
+    /// snapshots use `--merkle-version v2`; `v1` reproduces roots registered before it existed.
+    SnapshotHash { input: String, output: Option<String>, asset_id: Option<String>, artifacts: Option<String>, #[arg(long, default_value = "v2")] merkle_version: MerkleVersion },
     Allocations { input: String, output: Option<String>, profile: Option<String>, c_e: Option<f64>, c_s: Option<f64>, d_src: u32, d_aln: u32 },
+    /// Multiproof for several leaves of a `snapshot-hash` output (comma-separated leaf indices), for `BatchClaim`.
+    Multiproof { hashes: String, indices: String, output: Option<String>, #[arg(long, default_value = "v2")] merkle_version: MerkleVersion },
+    /// Recompute the bridge energy ledger commitment from a JSON dump of `EnergyHolders` rows.
+    /// Fails when it differs from `--expected-root` (a `LedgerCheckpoint` root) or when the dump
+    /// and a `--reference` ledger (e.g. the indexer's) disagree.
+    LedgerCommitment { dump: String, output: Option<String>, #[arg(long)] expected_root: Option<String>, #[arg(long)] reference: Option<String> },
 }
 
 #[derive(Deserialize)]
@@ -41,13 +50,15 @@ struct SnapshotHashOut {
 fn main() -> anyhow::Result<()> {
     let cli = Cli::parse();
     match cli.cmd {
-        Commands::SnapshotHash { input, output, asset_id, artifacts } => snapshot_hash(&input, output.as_deref(), asset_id.as_deref(), artifacts.as_deref())?,
-        Commands::Allocations { input, output, c_e, c_s, d_src, d_aln } => allocations(&input, output.as_deref(), c_e, c_s, d_src, d_aln)?,
+        Commands::SnapshotHash { input, output, asset_id, artifacts, merkle_version } => snapshot_hash(&input, output.as_deref(), asset_id.as_deref(), artifacts.as_deref(), merkle_version)?,
+        Commands::Allocations { input, output, profile, c_e, c_s, d_src, d_aln } => allocations(&input, output.as_deref(), profile, c_e, c_s, d_src, d_aln)?,
+        Commands::Multiproof { hashes, indices, output, merkle_version } => multiproof(&hashes, &indices, output.as_deref(), merkle_version)?,
+        Commands::LedgerCommitment { dump, output, expected_root, reference } => ledger_commitment(&dump, output.as_deref(), expected_root.as_deref(), reference.as_deref())?,
     }
     Ok(())
 }
 
-fn snapshot_hash(path: &str, output: Option<&str>, asset_id: Option<&str>, artifacts: Option<&str>) -> anyhow::Result<()> {
+fn snapshot_hash(path: &str, output: Option<&str>, asset_id: Option<&str>, artifacts: Option<&str>, version: MerkleVersion) -> anyhow::Result<()> {
     let data = std::fs::read_to_string(path)?;
 
     let rows: Vec<SnapshotRow> = if path.ends_with(".csv") {
@@ -65,14 +76,8 @@ fn snapshot_hash(path: &str, output: Option<&str>, asset_id: Option<&str>, artif
     let mut out: Vec<SnapshotHashOut> = Vec::new();
 
     for r in rows {
-        let mut hasher = Sha256::new();
-        hasher.update(r.chain_id.as_bytes());
-        hasher.update(&r.height.to_be_bytes());
-        hasher.update(r.denom.as_bytes());
-        hasher.update(r.address.as_bytes());
         let b: u128 = r.balance.parse()?;
-        hasher.update(&b.to_be_bytes());
-        let digest = hasher.finalize();
+        let digest = leaf_hash(version, &SnapshotLeaf { chain_id: &r.chain_id, height: r.height, denom: &r.denom, address: &r.address, balance: b });
         let h_i = format!("0x{}", hex::encode(digest));
 
         out.push(SnapshotHashOut { address: r.address.clone(), denom: r.denom.clone(), balance: r.balance.clone(), height: r.height, chain_id: r.chain_id.clone(), h_i });
@@ -84,6 +89,10 @@ fn snapshot_hash(path: &str, output: Option<&str>, asset_id: Option<&str>, artif
         let b_key = format!("{}:{}:{}", b.address, b.denom, b.balance);
         a_key.cmp(&b_key)
     });
+    // a repeated row would be a second leaf for the same claim
+    if let Some(dup) = out.windows(2).find(|w| w[0].h_i == w[1].h_i) {
+        anyhow::bail!("duplicate snapshot row for {} {}", dup[0].address, dup[0].denom);
+    }
     let json = serde_json::to_string_pretty(&out)?;
     if let Some(out_path) = output {
         let mut f = File::create(out_path)?;
@@ -107,9 +116,13 @@ fn snapshot_hash(path: &str, output: Option<&str>, asset_id: Option<&str>, artif
                 arr.copy_from_slice(&bytes);
                 leaves.push(arr);
             }
-            let (root, proofs) = build_merkle_and_proofs(&leaves);
+            let (root, proofs) = build_merkle_and_proofs(version, &leaves);
+            // sanity check before publishing: every proof must verify against the root
+            if !leaves.iter().zip(proofs.iter()).all(|(l, p)| verify_merkle_proof(version, l, p, &root)) {
+                anyhow::bail!("generated merkle proof does not verify");
+            }
             // snapshot root artifact
-            let snapshot_root = serde_json::json!({ "asset_id": asset_id.clone(), "merkle_root": root, "entries": out.iter().enumerate().map(|(i, e)| serde_json::json!({"index": i, "snapshot_hash": e.h_i })).collect::<Vec<_>>() });
+            let snapshot_root = serde_json::json!({ "asset_id": asset_id.clone(), "merkle_root": root, "merkle_version": version, "entries": out.iter().enumerate().map(|(i, e)| serde_json::json!({"index": i, "snapshot_hash": e.h_i })).collect::<Vec<_>>() });
             std::fs::create_dir_all(&artifacts_dir)?;
             let root_path = format!("{}/snapshot_root_{}.json", artifacts_dir, asset_id);
             let mut rf = File::create(root_path)?;
@@ -119,13 +132,8 @@ fn snapshot_hash(path: &str, output: Option<&str>, asset_id: Option<&str>, artif
             let proof_path = format!("{}/merkle_proofs_{}.json", artifacts_dir, asset_id);
             let mut pf = File::create(proof_path)?;
             pf.write_all(serde_json::to_string_pretty(&proofs_out)?.as_bytes())?;
-        }
     }
 
-    // If artifacts and asset_id provided, write snapshot_root and merkle proofs
-    // Use deterministic ordering by (address, denom, balance) before building merkle tree
-    Ok(())
-
     Ok(())
 }
 
@@ -189,3 +197,73 @@ fn allocations(path: &str, output: Option<&str>, profile: Option<String>, c_e: O
 
     Ok(())
 }
+
+#[derive(Deserialize)]
+struct HashRow {
+    h_i: String,
+}
+
+fn multiproof(hashes_path: &str, indices: &str, output: Option<&str>, version: MerkleVersion) -> anyhow::Result<()> {
+    // leaves in the same (sorted) order `snapshot-hash` used to build the tree
+    let rows: Vec<HashRow> = serde_json::from_str(&std::fs::read_to_string(hashes_path)?)?;
+    let mut leaves: Vec<[u8;32]> = vec![];
+    for r in &rows {
+        let bytes = hex::decode(r.h_i.trim_start_matches("0x"))?;
+        anyhow::ensure!(bytes.len() == 32, "h_i must be 32 bytes");
+        let mut arr = [0u8; 32];
+        arr.copy_from_slice(&bytes);
+        leaves.push(arr);
+    }
+    let idx: Vec<u64> = indices.split(',').map(|i| i.trim().parse()).collect::<Result<_, _>>()?;
+    let mp = build_multiproof(version, &leaves, &idx).ok_or_else(|| anyhow::anyhow!("no leaves or index out of range"))?;
+    let proven: Vec<[u8;32]> = mp.indices.iter().map(|i| leaves[*i as usize]).collect();
+    let (root, _) = build_merkle_and_proofs(version, &leaves);
+    anyhow::ensure!(verify_multiproof(version, &proven, &mp, &root), "generated multiproof does not verify");
+    let (_, hash_count) = multiproof_root(version, &proven, &mp).ok_or_else(|| anyhow::anyhow!("malformed multiproof"))?;
+    let out = serde_json::json!({
+        "merkle_root": root,
+        "merkle_version": version,
+        "snapshot_hashes": mp.indices.iter().map(|i| rows[*i as usize].h_i.clone()).collect::<Vec<_>>(),
+        "multiproof": mp,
+        "hash_count": hash_count,
+    });
+    let json = serde_json::to_string_pretty(&out)?;
+    if let Some(out_path) = output {
+        let mut f = File::create(out_path)?;
+        f.write_all(json.as_bytes())?;
+    } else {
+        println!("{}", json);
+    }
+    Ok(())
+}
+
+fn ledger_commitment(dump_path: &str, output: Option<&str>, expected_root: Option<&str>, reference_path: Option<&str>) -> anyhow::Result<()> {
+    let dump: Vec<ledger::LedgerEntry> = serde_json::from_str(&std::fs::read_to_string(dump_path)?)?;
+    let (root, (auet, csp, erp)) = ledger::commitment(&dump)?;
+    let root_matches = expected_root.map(|e| e.trim_start_matches("0x").eq_ignore_ascii_case(root.trim_start_matches("0x")));
+    let divergences = match reference_path {
+        Some(p) => {
+            let reference: Vec<ledger::LedgerEntry> = serde_json::from_str(&std::fs::read_to_string(p)?)?;
+            ledger::divergences(&dump, &reference)?
+        }
+        None => vec![],
+    };
+    let out = serde_json::json!({
+        "root": root,
+        "entries": dump.len(),
+        "total": { "auet": auet.to_string(), "csp": csp.to_string(), "erp": erp.to_string() },
+        "expected_root": expected_root,
+        "root_matches": root_matches,
+        "divergences": divergences,
+    });
+    let json = serde_json::to_string_pretty(&out)?;
+    if let Some(out_path) = output {
+        let mut f = File::create(out_path)?;
+        f.write_all(json.as_bytes())?;
+    } else {
+        println!("{}", json);
+    }
+    anyhow::ensure!(root_matches != Some(false), "ledger commitment {} does not match checkpoint root", root);
+    anyhow::ensure!(divergences.is_empty(), "{} ledger entries diverge from the reference", divergences.len());
+    Ok(())
+}
@@ -1,6 +1,5 @@
-use sha2::{Digest, Sha256};
+use aln_merkle::MerkleVersion;
 use serde::{Serialize, Deserialize};
-use hex;
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct ProofStep {
@@ -11,76 +10,123 @@ pub struct ProofStep {
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct Proof { pub proof: Vec<ProofStep> }
 
+/// Compact proof for several leaves of one tree. `indices` are the proven leaf positions in
+/// ascending order; `hashes` are the sibling nodes the verifier cannot derive itself, in the
+/// order it consumes them (level by level, left to right). Same shape as the bridge's `BatchClaim`.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct MultiProof {
+    pub leaf_count: u64,
+    pub indices: Vec<u64>,
+    pub hashes: Vec<String>, // hex
+}
+
+fn decode32(hex_str: &str) -> Option<[u8; 32]> {
+    let bytes = hex::decode(hex_str.trim_start_matches("0x")).ok()?;
+    if bytes.len() != 32 { return None; }
+    let mut arr = [0u8; 32];
+    arr.copy_from_slice(&bytes);
+    Some(arr)
+}
+
 /// Build a Merkle tree from a vector of leaves (32-byte arrays), returning root and per-index proofs.
-pub fn build_merkle_and_proofs(leaves: &Vec<[u8;32]>) -> (String, Vec<Proof>) {
+pub fn build_merkle_and_proofs(version: MerkleVersion, leaves: &[[u8;32]]) -> (String, Vec<Proof>) {
     if leaves.is_empty() { return (String::from(""), vec![]); }
-    let mut level: Vec<[u8;32]> = leaves.clone();
-    let mut tree_levels: Vec<Vec<[u8;32]>> = vec![level.clone()];
-    while level.len() > 1 {
-        let mut next: Vec<[u8;32]> = vec![];
-        for i in (0..level.len()).step_by(2) {
-            let left = level[i];
-            let right = if i + 1 < level.len() { level[i+1] } else { level[i] };
-            let mut h = Sha256::new();
-            h.update(&left);
-            h.update(&right);
-            let mut out = [0u8; 32];
-            out.copy_from_slice(&h.finalize());
-            next.push(out);
-        }
-        level = next.clone();
-        tree_levels.push(level.clone());
-    }
+    let tree_levels = aln_merkle::build_levels(version, leaves);
     let root = tree_levels.last().unwrap()[0];
+    let proofs = (0..leaves.len()).map(|idx| Proof {
+        proof: aln_merkle::proof(version, &tree_levels, idx).into_iter().map(|(sibling, is_left)| ProofStep { sibling: format!("0x{}", hex::encode(sibling)), is_left }).collect(),
+    }).collect();
+    (format!("0x{}", hex::encode(root)), proofs)
+}
 
-    // Build proofs for each original leaf index
-    let mut proofs: Vec<Proof> = Vec::new();
-    let leaf_count = leaves.len();
-    for idx in 0..leaf_count {
-        let mut proof_steps: Vec<ProofStep> = Vec::new();
-        let mut index = idx;
-        for lvl in 0..(tree_levels.len()-1) {
-            let level_nodes = &tree_levels[lvl];
-            let pair_index = if index % 2 == 0 { index + 1 } else { index - 1 };
-            let sibling = if pair_index < level_nodes.len() { level_nodes[pair_index] } else { level_nodes[index] };
-            let is_left = pair_index < index; // if sibling idx < index, sibling is on left
-            proof_steps.push(ProofStep { sibling: format!("0x{}", hex::encode(sibling)), is_left });
-            index = index / 2;
-        }
-        proofs.push(Proof { proof: proof_steps });
+/// Verify a proof given leaf and proof steps.
+pub fn verify_merkle_proof(version: MerkleVersion, leaf: &[u8;32], proof: &Proof, root_hex: &str) -> bool {
+    let steps: Option<Vec<([u8;32], bool)>> = proof.proof.iter().map(|step| decode32(&step.sibling).map(|s| (s, step.is_left))).collect();
+    match (steps, decode32(root_hex)) {
+        (Some(steps), Some(root)) => aln_merkle::verify_proof(version, leaf, &steps, &root),
+        _ => false,
     }
+}
 
-    (format!("0x{}", hex::encode(root)), proofs)
+/// Build a multiproof for the leaves at `indices` (any order, duplicates ignored).
+/// Returns `None` for an empty tree or an out-of-range index.
+pub fn build_multiproof(version: MerkleVersion, leaves: &[[u8;32]], indices: &[u64]) -> Option<MultiProof> {
+    let mut proven = indices.to_vec();
+    proven.sort_unstable();
+    proven.dedup();
+    let levels = aln_merkle::build_levels(version, leaves);
+    let hashes = aln_merkle::build_multiproof(&levels, &proven)?;
+    Some(MultiProof { leaf_count: leaves.len() as u64, indices: proven, hashes: hashes.iter().map(|h| format!("0x{}", hex::encode(h))).collect() })
 }
 
-/// Verify a proof given leaf and proof steps.
-pub fn verify_merkle_proof(leaf: &[u8;32], proof: &Proof, root_hex: &str) -> bool {
-    let mut cur = *leaf;
-    for step in proof.proof.iter() {
-        let sib_bytes = match hex::decode(step.sibling.trim_start_matches("0x")) {
-            Ok(b) => b,
-            Err(_) => return false,
-        };
-        let mut sib_arr = [0u8;32];
-        if sib_bytes.len() != 32 { return false; }
-        sib_arr.copy_from_slice(&sib_bytes);
-        let mut h = Sha256::new();
-        if step.is_left {
-            h.update(&sib_arr);
-            h.update(&cur);
-        } else {
-            h.update(&cur);
-            h.update(&sib_arr);
+/// Recompute the root from `leaves` (one per `mp.indices`, same order) and the multiproof.
+/// Returns the root and the number of node hashes computed, or `None` if the proof is malformed.
+pub fn multiproof_root(version: MerkleVersion, leaves: &[[u8;32]], mp: &MultiProof) -> Option<([u8;32], usize)> {
+    let hashes: Vec<[u8;32]> = mp.hashes.iter().map(|h| decode32(h)).collect::<Option<_>>()?;
+    aln_merkle::multiproof_root(version, mp.leaf_count, &mp.indices, leaves, &hashes)
+}
+
+pub fn verify_multiproof(version: MerkleVersion, leaves: &[[u8;32]], mp: &MultiProof, root_hex: &str) -> bool {
+    match multiproof_root(version, leaves, mp) {
+        Some((root, _)) => decode32(root_hex) == Some(root),
+        None => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sha2::{Digest, Sha256};
+
+    const V: MerkleVersion = MerkleVersion::V1;
+
+    fn leaf(i: u64) -> [u8; 32] {
+        let mut out = [0u8; 32];
+        out.copy_from_slice(&Sha256::digest(i.to_be_bytes()));
+        out
+    }
+
+    #[test]
+    fn multiproof_matches_single_proofs() {
+        for v in [MerkleVersion::V1, MerkleVersion::V2] {
+            for n in [1u64, 2, 3, 7, 8, 13, 64] {
+                let leaves: Vec<[u8; 32]> = (0..n).map(leaf).collect();
+                let (root, proofs) = build_merkle_and_proofs(v, &leaves);
+                for (i, p) in proofs.iter().enumerate() {
+                    assert!(verify_merkle_proof(v, &leaves[i], p, &root));
+                }
+                let picks: Vec<u64> = (0..n).filter(|i| i % 3 != 1).collect();
+                let mp = build_multiproof(v, &leaves, &picks).unwrap();
+                let proven: Vec<[u8; 32]> = picks.iter().map(|i| leaves[*i as usize]).collect();
+                assert!(verify_multiproof(v, &proven, &mp, &root), "{:?} n={}", v, n);
+            }
         }
-        let res = h.finalize();
-        cur.copy_from_slice(&res);
     }
-    let root_bytes = match hex::decode(root_hex.trim_start_matches("0x")) {
-        Ok(b) => b,
-        Err(_) => return false,
-    };
-    let mut root_arr = [0u8;32];
-    if root_bytes.len() != 32 { return false; }
-    root_arr.copy_from_slice(&root_bytes);
-    cur == root_arr
+
+    #[test]
+    fn multiproof_rejects_tampering() {
+        let leaves: Vec<[u8; 32]> = (0..13).map(leaf).collect();
+        let (root, _) = build_merkle_and_proofs(V, &leaves);
+        let mp = build_multiproof(V, &leaves, &[2, 3, 9]).unwrap();
+        let proven = vec![leaves[2], leaves[3], leaves[9]];
+        // wrong leaf, wrong index, extra hash
+        assert!(!verify_multiproof(V, &[leaves[2], leaves[4], leaves[9]], &mp, &root));
+        assert!(!verify_multiproof(V, &proven, &MultiProof { indices: vec![2, 3, 8], ..mp.clone() }, &root));
+        let mut padded = mp.clone();
+        padded.hashes.push(format!("0x{}", hex::encode(leaves[0])));
+        assert!(!verify_multiproof(V, &proven, &padded, &root));
+        assert!(verify_multiproof(V, &proven, &mp, &root));
+    }
+
+    #[test]
+    fn multiproof_saves_hashes() {
+        let leaves: Vec<[u8; 32]> = (0..256).map(leaf).collect();
+        let picks: Vec<u64> = (0..64).collect();
+        let mp = build_multiproof(V, &leaves, &picks).unwrap();
+        let proven: Vec<[u8; 32]> = picks.iter().map(|i| leaves[*i as usize]).collect();
+        let (_, hashes) = multiproof_root(V, &proven, &mp).unwrap();
+        // 64 single proofs of depth 8 cost 512 hashes; the shared subtree needs 63 + 2
+        assert_eq!(hashes, 65);
+        assert_eq!(mp.hashes.len(), 2);
+    }
 }